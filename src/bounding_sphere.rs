@@ -16,7 +16,7 @@ impl Welzl {
     fn bounding_sphere_recursive(points: &mut Vec<DVec3>, boundary: &mut Vec<DVec3>) -> Sphere {
         if points.is_empty() || boundary.len() == 4 {
             // base case: No other points left or maximal number of boundary points
-            return Sphere::from_boundary_points(&boundary);
+            return Sphere::from_boundary_points(boundary);
         }
 
         // Pop test point from points
@@ -90,8 +90,8 @@ impl BoundingSphereSolver for EPOS6 {
 
         // Get sphere from extremal points
         let mut extremal_points = HashSet::new();
-        extremal_points.extend(idx_min.into_iter());
-        extremal_points.extend(idx_max.into_iter());
+        extremal_points.extend(idx_min);
+        extremal_points.extend(idx_max);
         let extremal_points = extremal_points
             .into_iter()
             .map(|i| points[i])
@@ -0,0 +1,303 @@
+use std::{error::Error, fs, path::Path};
+
+use glam::DVec3;
+
+/// A set of generators loaded from a file, with optional per-generator attributes (e.g. masses or densities).
+///
+/// Use [`Generators::positions`] as input for [`crate::Voronoi::build`].
+#[derive(Debug, Clone, Default)]
+pub struct Generators {
+    positions: Vec<DVec3>,
+    attributes: Vec<(String, Vec<f64>)>,
+}
+
+impl Generators {
+    /// Create a generator set from the given positions (without attributes).
+    pub fn new(positions: Vec<DVec3>) -> Self {
+        Self {
+            positions,
+            attributes: vec![],
+        }
+    }
+
+    /// Add an attribute with one value per generator.
+    pub fn with_attribute(mut self, name: &str, values: Vec<f64>) -> Self {
+        assert_eq!(
+            values.len(),
+            self.positions.len(),
+            "Must have one attribute value per generator!"
+        );
+        self.attributes.push((name.to_string(), values));
+        self
+    }
+
+    /// Get the positions of the generators.
+    pub fn positions(&self) -> &[DVec3] {
+        &self.positions
+    }
+
+    /// Get the positions of the generators, discarding the attributes.
+    pub fn into_positions(self) -> Vec<DVec3> {
+        self.positions
+    }
+
+    /// Get the number of generators.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Whether this set contains no generators.
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Get the values of the attribute with the given name, if any.
+    pub fn attribute(&self, name: &str) -> Option<&[f64]> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| attribute == name)
+            .map(|(_, values)| values.as_slice())
+    }
+
+    /// Get the names of the attributes, in the order they were loaded.
+    pub fn attribute_names(&self) -> impl Iterator<Item = &str> {
+        self.attributes.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Load generators from a CSV file with a header row.
+    ///
+    /// The positions are read from the columns named `x`, `y` and `z` (`y` and `z` are optional and default to zero
+    /// for lower dimensional data). Every other column containing only numeric values becomes an attribute, other
+    /// columns are ignored. This reads back the output of [`crate::Voronoi::write_cells_csv`].
+    pub fn from_csv<P: AsRef<Path>>(filename: P) -> Result<Self, Box<dyn Error>> {
+        let contents = fs::read_to_string(filename)?;
+        let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+        let header = lines
+            .next()
+            .ok_or("CSV file must have a header row!")?
+            .split(',')
+            .map(|name| name.trim().to_string())
+            .collect::<Vec<_>>();
+        let mut columns: Vec<Vec<Option<f64>>> = header.iter().map(|_| vec![]).collect();
+        for (row, line) in lines.enumerate() {
+            let fields = line.split(',').collect::<Vec<_>>();
+            if fields.len() != header.len() {
+                return Err(format!(
+                    "Row {} has {} fields, but the header has {} columns!",
+                    row + 1,
+                    fields.len(),
+                    header.len()
+                )
+                .into());
+            }
+            for (column, field) in columns.iter_mut().zip(fields) {
+                column.push(field.trim().parse().ok());
+            }
+        }
+
+        let coordinate = |name: &str| -> Result<Option<Vec<f64>>, Box<dyn Error>> {
+            let Some(idx) = header.iter().position(|column| column == name) else {
+                return Ok(None);
+            };
+            columns[idx]
+                .iter()
+                .copied()
+                .collect::<Option<Vec<f64>>>()
+                .map(Some)
+                .ok_or_else(|| format!("Column {name} must only contain numbers!").into())
+        };
+        let x = coordinate("x")?.ok_or("CSV file must have an x column!")?;
+        let y = coordinate("y")?.unwrap_or_else(|| vec![0.; x.len()]);
+        let z = coordinate("z")?.unwrap_or_else(|| vec![0.; x.len()]);
+        let positions = (0..x.len()).map(|i| DVec3::new(x[i], y[i], z[i])).collect();
+
+        let attributes = header
+            .into_iter()
+            .zip(columns)
+            .filter(|(name, _)| !["x", "y", "z"].contains(&name.as_str()))
+            .filter_map(|(name, column)| Some((name, column.into_iter().collect::<Option<_>>()?)))
+            .collect();
+        Ok(Self {
+            positions,
+            attributes,
+        })
+    }
+
+    /// Load generators from a NumPy `.npy` file containing a (C-ordered) array of shape `(n, d)` with `d` at most
+    /// 3, of little endian 32 or 64 bit floats. Missing coordinates are set to zero.
+    pub fn from_npy<P: AsRef<Path>>(filename: P) -> Result<Self, Box<dyn Error>> {
+        let bytes = fs::read(filename)?;
+        if bytes.len() < 10 || &bytes[..6] != b"\x93NUMPY" {
+            return Err("Not a .npy file!".into());
+        }
+        let (header_len, header_start) = match bytes[6] {
+            1 => (u16::from_le_bytes([bytes[8], bytes[9]]) as usize, 10),
+            2 | 3 if bytes.len() >= 12 => (
+                u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize,
+                12,
+            ),
+            version => return Err(format!("Unsupported .npy version {version}!").into()),
+        };
+        let data_start = header_start + header_len;
+        let header = std::str::from_utf8(
+            bytes
+                .get(header_start..data_start)
+                .ok_or("Truncated .npy header!")?,
+        )?;
+
+        // The header is a python dict literal, e.g. {'descr': '<f8', 'fortran_order': False, 'shape': (10, 3), }
+        let value = |key: &str| {
+            let start = header.find(&format!("'{key}':"))? + key.len() + 3;
+            Some(header[start..].trim_start())
+        };
+        let descr = value("descr").ok_or("Missing descr in .npy header!")?;
+        let size = if descr.starts_with("'<f8'") {
+            8
+        } else if descr.starts_with("'<f4'") {
+            4
+        } else {
+            return Err("Only little endian f4 and f8 arrays are supported!".into());
+        };
+        if !value("fortran_order")
+            .ok_or("Missing fortran_order in .npy header!")?
+            .starts_with("False")
+        {
+            return Err("Only C-ordered arrays are supported!".into());
+        }
+        let shape = value("shape").ok_or("Missing shape in .npy header!")?;
+        let shape = shape[1..shape.find(')').ok_or("Invalid shape in .npy header!")?]
+            .split(',')
+            .map(str::trim)
+            .filter(|dim| !dim.is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<usize>, _>>()?;
+        let (n, d) = match shape[..] {
+            [n] => (n, 1),
+            [n, d] if (1..=3).contains(&d) => (n, d),
+            _ => return Err("Array must have shape (n, d) with d <= 3!".into()),
+        };
+
+        let data = bytes
+            .get(data_start..(data_start + n * d * size))
+            .ok_or("Truncated .npy data!")?;
+        let values = data
+            .chunks_exact(size)
+            .map(|chunk| match size {
+                8 => f64::from_le_bytes(chunk.try_into().expect("Chunks have size 8")),
+                _ => f32::from_le_bytes(chunk.try_into().expect("Chunks have size 4")) as f64,
+            })
+            .collect::<Vec<_>>();
+        let positions = values
+            .chunks_exact(d)
+            .map(|row| {
+                let mut position = DVec3::ZERO;
+                for (i, &v) in row.iter().enumerate() {
+                    position[i] = v;
+                }
+                position
+            })
+            .collect();
+        Ok(Self::new(positions))
+    }
+
+    /// Load generators from a hdf5 file. Requires the `hdf5` feature to be enabled.
+    ///
+    /// The positions are read from the dataset `positions` (an array of triplets, e.g. `"Cells/Generator"` in the
+    /// files written by [`crate::Voronoi::save`]) and the given `attributes` from one dimensional datasets with the
+    /// same length (e.g. `"Cells/Volume"`), which are named after their dataset.
+    #[cfg(feature = "hdf5")]
+    pub fn from_hdf5<P: AsRef<Path>>(
+        filename: P,
+        positions: &str,
+        attributes: &[&str],
+    ) -> Result<Self, Box<dyn Error>> {
+        let file = hdf5::File::open(filename)?;
+        let positions = file
+            .dataset(positions)?
+            .read_raw::<[f64; 3]>()?
+            .into_iter()
+            .map(DVec3::from_array)
+            .collect::<Vec<_>>();
+        let mut generators = Self::new(positions);
+        for &name in attributes {
+            let values = file.dataset(name)?.read_raw::<f64>()?;
+            if values.len() != generators.len() {
+                return Err(format!("Dataset {name} must have one value per generator!").into());
+            }
+            generators = generators.with_attribute(name, values);
+        }
+        Ok(generators)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Voronoi;
+
+    #[test]
+    fn test_from_csv() {
+        let generators = [
+            DVec3::new(0.25, 0.25, 0.5),
+            DVec3::new(0.75, 0.25, 0.5),
+            DVec3::new(0.5, 0.75, 0.5),
+        ];
+        let voronoi = Voronoi::build(&generators, DVec3::ZERO, DVec3::ONE, 3, false, None);
+        let filename = std::env::temp_dir().join("test_generators.csv");
+        voronoi
+            .write_cells_csv(fs::File::create(&filename).unwrap())
+            .unwrap();
+
+        let loaded = Generators::from_csv(&filename).unwrap();
+        assert_eq!(loaded.positions(), generators);
+        let volumes = loaded.attribute("volume").unwrap();
+        for (volume, cell) in volumes.iter().zip(voronoi.cells()) {
+            assert_eq!(*volume, cell.volume());
+        }
+        assert_eq!(loaded.attribute_names().count(), 6);
+        assert!(loaded.attribute("x").is_none());
+
+        // 2D data with a non-numeric column
+        fs::write(&filename, "name,x,y\na,0.5,1\nb,2,3.5\n").unwrap();
+        let loaded = Generators::from_csv(&filename).unwrap();
+        assert_eq!(
+            loaded.positions(),
+            [DVec3::new(0.5, 1., 0.), DVec3::new(2., 3.5, 0.)]
+        );
+        assert_eq!(loaded.attribute_names().count(), 0);
+
+        fs::write(&filename, "a,b\n1,2\n").unwrap();
+        assert!(Generators::from_csv(&filename).is_err());
+    }
+
+    #[test]
+    fn test_from_npy() {
+        let filename = std::env::temp_dir().join("test_generators.npy");
+        let values = [0.1f64, 0.2, 0.3, 0.4, 0.5, 0.6];
+        let mut header = "{'descr': '<f8', 'fortran_order': False, 'shape': (3, 2), }".to_string();
+        while !(10 + header.len() + 1).is_multiple_of(64) {
+            header.push(' ');
+        }
+        header.push('\n');
+        let mut bytes = b"\x93NUMPY\x01\x00".to_vec();
+        bytes.extend((header.len() as u16).to_le_bytes());
+        bytes.extend(header.as_bytes());
+        for v in values {
+            bytes.extend(v.to_le_bytes());
+        }
+        fs::write(&filename, &bytes).unwrap();
+
+        let loaded = Generators::from_npy(&filename).unwrap();
+        assert_eq!(
+            loaded.positions(),
+            [
+                DVec3::new(0.1, 0.2, 0.),
+                DVec3::new(0.3, 0.4, 0.),
+                DVec3::new(0.5, 0.6, 0.)
+            ]
+        );
+
+        fs::write(&filename, b"not a numpy file").unwrap();
+        assert!(Generators::from_npy(&filename).is_err());
+    }
+}
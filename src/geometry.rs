@@ -38,7 +38,7 @@ impl Plane {
         // distance from box center to plane
         let d = (self.p - aabb.center).dot(self.n).abs();
 
-        return d <= r * (1. + 1e-10);
+        d <= r * (1. + 1e-10)
     }
 }
 
@@ -145,6 +145,7 @@ impl Sphere {
     }
 
     /// Extend this sphere to include x if necessary
+    #[allow(dead_code)]
     pub fn extend(mut self, x: DVec3) -> Self {
         if !self.contains(x) {
             let opposite = self.center - self.radius * (x - self.center).normalize();
@@ -161,7 +162,7 @@ impl Sphere {
 }
 
 #[derive(Clone)]
-#[allow(dead_code)]
+#[allow(dead_code, clippy::upper_case_acronyms)]
 pub(crate) struct AABB {
     min: DVec3,
     max: DVec3,
@@ -1,6 +1,8 @@
-use glam::DVec3;
+use std::{any::Any, marker::PhantomData, sync::Arc};
 
-use crate::util::{signed_area_tri, signed_volume_tet};
+use glam::{DMat3, DVec3};
+
+use crate::util::{retain, signed_area_tri, signed_volume_tet};
 
 /// Trait to implement new integrators for cell integrals
 pub trait VoronoiCellIntegrator {
@@ -14,7 +16,9 @@ pub trait VoronoiCellIntegrator {
     fn finalize(&self) -> Self::Output;
 }
 
+#[allow(dead_code)]
 pub trait VectorVoronoiCellIntegrator: VoronoiCellIntegrator<Output = DVec3> {}
+#[allow(dead_code)]
 pub trait ScalarVoronoiCellIntegrator: VoronoiCellIntegrator<Output = f64> {}
 
 /// Trait to implement additional integrals over faces.
@@ -27,6 +31,10 @@ pub trait VoronoiFaceIntegrator {
 
     /// Finalize the calculation and return the result
     fn finalize(&self) -> Self::Output;
+
+    /// Called once before the first triangle of a face with the indices of the generators to the left and right
+    /// (`None` for boundary faces) of this face. Does nothing by default.
+    fn init(&mut self, _left_idx: usize, _right_idx: Option<usize>) {}
 }
 
 pub trait VectorVoronoiFaceIntegrator: VoronoiFaceIntegrator<Output = DVec3> {}
@@ -87,7 +95,7 @@ impl VoronoiFaceIntegrator for AreaCentroidIntegrator {
     fn collect(&mut self, v0: DVec3, v1: DVec3, v2: DVec3, left: DVec3, _right: DVec3) {
         let area = signed_area_tri(v0, v1, v2, left);
         self.area += area;
-        self.centroid += area * (v0 + v1 + v2 + left);
+        self.centroid += area * (v0 + v1 + v2);
     }
 
     fn finalize(&self) -> Self::Output {
@@ -99,3 +107,482 @@ impl VoronoiFaceIntegrator for AreaCentroidIntegrator {
         (self.area, normalisation * self.centroid)
     }
 }
+
+/// Built-in face integrator for the first moment `∫ x dA` of a face (i.e. its area times its centroid).
+///
+/// Register it with [`FaceIntegrators::add_vector`]. Positions are taken relative to the origin, for periodic faces
+/// in the frame of the left cell of the face.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct FirstMomentIntegrator {
+    first_moment: DVec3,
+}
+
+impl VoronoiFaceIntegrator for FirstMomentIntegrator {
+    type Output = DVec3;
+
+    fn collect(&mut self, v0: DVec3, v1: DVec3, v2: DVec3, left: DVec3, _right: DVec3) {
+        self.first_moment += signed_area_tri(v0, v1, v2, left) * (v0 + v1 + v2) / 3.;
+    }
+
+    fn finalize(&self) -> Self::Output {
+        self.first_moment
+    }
+}
+
+impl VectorVoronoiFaceIntegrator for FirstMomentIntegrator {}
+
+/// Built-in face integrator for the second moment `∫ x⊗x dA` of a face (a symmetric tensor).
+///
+/// Register it with [`FaceIntegrators::add`]. Positions are taken relative to the origin, for periodic faces in the
+/// frame of the left cell of the face.
+#[derive(Debug, Clone, Copy)]
+pub struct SecondMomentIntegrator {
+    second_moment: DMat3,
+}
+
+// Note that the default `DMat3` is the identity
+impl Default for SecondMomentIntegrator {
+    fn default() -> Self {
+        Self {
+            second_moment: DMat3::ZERO,
+        }
+    }
+}
+
+impl VoronoiFaceIntegrator for SecondMomentIntegrator {
+    type Output = DMat3;
+
+    fn collect(&mut self, v0: DVec3, v1: DVec3, v2: DVec3, left: DVec3, _right: DVec3) {
+        // Exact for a triangle: A / 12 * (sum_i v_i⊗v_i + s⊗s), with s the sum of the vertices
+        let area = signed_area_tri(v0, v1, v2, left);
+        let sum = v0 + v1 + v2;
+        self.second_moment +=
+            (area / 12.) * (outer(v0, v0) + outer(v1, v1) + outer(v2, v2) + outer(sum, sum));
+    }
+
+    fn finalize(&self) -> Self::Output {
+        self.second_moment
+    }
+}
+
+/// Built-in face integrator for the tensor `∫ x⊗n dA` of a face, with `n` the normal of the face pointing away from
+/// its left cell (so entry `(i, j)` is `∫ x_i n_j dA`).
+///
+/// Summed over the faces of a cell (with the normals pointing away from the cell), this yields the volume of the cell
+/// times the identity. Register it with [`FaceIntegrators::add`]. Positions are taken relative to the origin, for
+/// periodic faces in the frame of the left cell of the face.
+#[derive(Debug, Clone, Copy)]
+pub struct PositionNormalMomentIntegrator {
+    moment: DMat3,
+}
+
+impl Default for PositionNormalMomentIntegrator {
+    fn default() -> Self {
+        Self {
+            moment: DMat3::ZERO,
+        }
+    }
+}
+
+impl VoronoiFaceIntegrator for PositionNormalMomentIntegrator {
+    type Output = DMat3;
+
+    fn collect(&mut self, v0: DVec3, v1: DVec3, v2: DVec3, _left: DVec3, _right: DVec3) {
+        // The signed area (see `signed_area_tri`) times the normal pointing away from the left generator, which does
+        // not depend on the position of the left generator.
+        let area_vector = -0.5 * (v1 - v0).cross(v2 - v0);
+        self.moment += outer((v0 + v1 + v2) / 3., area_vector);
+    }
+
+    fn finalize(&self) -> Self::Output {
+        self.moment
+    }
+}
+
+/// The outer product `a⊗b` of two vectors (i.e. entry `(i, j)` is `a_i * b_j`).
+fn outer(a: DVec3, b: DVec3) -> DMat3 {
+    DMat3::from_cols(a * b.x, a * b.y, a * b.z)
+}
+
+/// Trait to implement additional integrals over faces that depend on user supplied data of the generators to the left
+/// and right of the faces (e.g. fluid states), see [`FaceIntegrators::add_scalar_with_data`].
+pub trait GeneratorDataIntegrator<D> {
+    type Output;
+
+    /// Update the state of a face's integrator using an oriented triangle, the position of the generators to the left
+    /// and right of this face and their data (`None` for the right generator of boundary faces).
+    #[allow(clippy::too_many_arguments)]
+    fn collect(
+        &mut self,
+        v0: DVec3,
+        v1: DVec3,
+        v2: DVec3,
+        left: DVec3,
+        right: DVec3,
+        left_data: &D,
+        right_data: Option<&D>,
+    );
+
+    /// Finalize the calculation and return the result
+    fn finalize(&self) -> Self::Output;
+}
+
+/// Adapter passing the per-generator data to a [`GeneratorDataIntegrator`].
+struct WithGeneratorData<D, I> {
+    data: Arc<[D]>,
+    left_idx: usize,
+    right_idx: Option<usize>,
+    integrator: I,
+}
+
+impl<D, I: GeneratorDataIntegrator<D>> VoronoiFaceIntegrator for WithGeneratorData<D, I> {
+    type Output = I::Output;
+
+    fn collect(&mut self, v0: DVec3, v1: DVec3, v2: DVec3, left: DVec3, right: DVec3) {
+        let right_data = self.right_idx.map(|idx| &self.data[idx]);
+        self.integrator.collect(
+            v0,
+            v1,
+            v2,
+            left,
+            right,
+            &self.data[self.left_idx],
+            right_data,
+        );
+    }
+
+    fn finalize(&self) -> Self::Output {
+        self.integrator.finalize()
+    }
+
+    fn init(&mut self, left_idx: usize, right_idx: Option<usize>) {
+        self.left_idx = left_idx;
+        self.right_idx = right_idx;
+    }
+}
+
+impl<D, I: GeneratorDataIntegrator<D, Output = DVec3>> VectorVoronoiFaceIntegrator
+    for WithGeneratorData<D, I>
+{
+}
+
+impl<D, I: GeneratorDataIntegrator<D, Output = f64>> ScalarVoronoiFaceIntegrator
+    for WithGeneratorData<D, I>
+{
+}
+
+/// A function creating a fresh vector face integrator for every face.
+type VectorFaceIntegratorFactory =
+    Box<dyn Fn() -> Box<dyn VectorVoronoiFaceIntegrator> + Send + Sync>;
+/// A function creating a fresh scalar face integrator for every face.
+type ScalarFaceIntegratorFactory =
+    Box<dyn Fn() -> Box<dyn ScalarVoronoiFaceIntegrator> + Send + Sync>;
+
+/// The storage of the results of a face integrator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum IntegratorKind {
+    Vector,
+    Scalar,
+    Generic,
+}
+
+/// A typed handle to the results of a face integrator registered in [`FaceIntegrators`], with `T` the output type of
+/// the integrator (`DVec3` for vector integrators, `f64` for scalar integrators).
+#[derive(Debug)]
+pub struct IntegratorId<T> {
+    kind: IntegratorKind,
+    index: usize,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for IntegratorId<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for IntegratorId<T> {}
+
+impl<T> IntegratorId<T> {
+    fn new(kind: IntegratorKind, index: usize) -> Self {
+        Self {
+            kind,
+            index,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Get the positional index of this integrator among the integrators of the same kind (i.e. its index into
+    /// [`crate::Voronoi::face_integrals`] for vector and scalar integrators).
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub(crate) fn kind(&self) -> IntegratorKind {
+        self.kind
+    }
+}
+
+/// Type-erased storage of the results of a face integrator with an arbitrary output type (a `Vec` of the outputs,
+/// indexed like the faces).
+pub(crate) trait FaceIntegralStore: Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    /// Move all results of `other` (which must have the same output type) to the end of this store.
+    fn append(&mut self, other: &mut dyn FaceIntegralStore);
+
+    /// Only keep the results for which `mask` is true.
+    fn retain(&mut self, mask: &[bool]);
+
+    /// Repeat the results `count` times and only keep the results for which `mask` is true.
+    fn replicate(&self, count: usize, mask: &[bool]) -> Box<dyn FaceIntegralStore>;
+
+    fn clone_box(&self) -> Box<dyn FaceIntegralStore>;
+}
+
+impl<T: Clone + Send + Sync + 'static> FaceIntegralStore for Vec<T> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn append(&mut self, other: &mut dyn FaceIntegralStore) {
+        let other = other
+            .as_any_mut()
+            .downcast_mut::<Vec<T>>()
+            .expect("Face integral stores must have the same type!");
+        Vec::append(self, other);
+    }
+
+    fn retain(&mut self, mask: &[bool]) {
+        retain(self, mask);
+    }
+
+    fn replicate(&self, count: usize, mask: &[bool]) -> Box<dyn FaceIntegralStore> {
+        let mut integrals = (0..count)
+            .flat_map(|_| self.iter().cloned())
+            .collect::<Vec<_>>();
+        retain(&mut integrals, mask);
+        Box::new(integrals)
+    }
+
+    fn clone_box(&self) -> Box<dyn FaceIntegralStore> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn FaceIntegralStore> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Object safe version of a [`VoronoiFaceIntegrator`] with an arbitrary output type, which is stored in a
+/// [`FaceIntegralStore`].
+pub(crate) trait GenericFaceIntegrator {
+    fn collect(&mut self, v0: DVec3, v1: DVec3, v2: DVec3, left: DVec3, right: DVec3);
+
+    fn init(&mut self, left_idx: usize, right_idx: Option<usize>);
+
+    /// Finalize the calculation and append the result to `store`.
+    fn finalize_into(&self, store: &mut dyn FaceIntegralStore);
+}
+
+/// Adapter to use a [`VoronoiFaceIntegrator`] as a [`GenericFaceIntegrator`].
+struct Erased<I>(I);
+
+impl<I> GenericFaceIntegrator for Erased<I>
+where
+    I: VoronoiFaceIntegrator,
+    I::Output: Clone + Send + Sync + 'static,
+{
+    fn collect(&mut self, v0: DVec3, v1: DVec3, v2: DVec3, left: DVec3, right: DVec3) {
+        self.0.collect(v0, v1, v2, left, right);
+    }
+
+    fn init(&mut self, left_idx: usize, right_idx: Option<usize>) {
+        self.0.init(left_idx, right_idx);
+    }
+
+    fn finalize_into(&self, store: &mut dyn FaceIntegralStore) {
+        store
+            .as_any_mut()
+            .downcast_mut::<Vec<I::Output>>()
+            .expect("Face integral store must match the output type of its integrator!")
+            .push(self.0.finalize());
+    }
+}
+
+/// Creates fresh face integrators with an arbitrary output type and empty stores for their results.
+pub(crate) struct GenericFaceIntegratorFactory {
+    new_integrator: Box<dyn Fn() -> Box<dyn GenericFaceIntegrator> + Send + Sync>,
+    new_store: fn() -> Box<dyn FaceIntegralStore>,
+}
+
+impl GenericFaceIntegratorFactory {
+    pub(crate) fn integrator(&self) -> Box<dyn GenericFaceIntegrator> {
+        (self.new_integrator)()
+    }
+
+    pub(crate) fn store(&self) -> Box<dyn FaceIntegralStore> {
+        (self.new_store)()
+    }
+}
+
+fn new_store<T: Clone + Send + Sync + 'static>() -> Box<dyn FaceIntegralStore> {
+    Box::new(Vec::<T>::new())
+}
+
+/// A registry of the extra face integrators of a tesselation, handing out an [`IntegratorId`] for every registered
+/// integrator to fetch its results later (see [`crate::Voronoi::face_integral`]).
+///
+/// Pass the registry to the construction of the tesselation (e.g. [`crate::Voronoi::build`]), which integrates every
+/// face with fresh instances of the registered integrators.
+#[derive(Default)]
+pub struct FaceIntegrators {
+    vector_integrators: Vec<VectorFaceIntegratorFactory>,
+    scalar_integrators: Vec<ScalarFaceIntegratorFactory>,
+    generic_integrators: Vec<GenericFaceIntegratorFactory>,
+    /// The lengths of the per-generator data of the registered integrators.
+    generator_data_lengths: Vec<usize>,
+}
+
+impl FaceIntegrators {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a vector face integrator. Every face is integrated by a fresh clone of the `prototype`, so the
+    /// construction stays parallel-safe.
+    pub fn add_vector<I>(&mut self, prototype: I) -> IntegratorId<DVec3>
+    where
+        I: VectorVoronoiFaceIntegrator + Clone + Send + Sync + 'static,
+    {
+        self.add_vector_factory(move || Box::new(prototype.clone()))
+    }
+
+    /// Register a scalar face integrator. Every face is integrated by a fresh clone of the `prototype`, so the
+    /// construction stays parallel-safe.
+    pub fn add_scalar<I>(&mut self, prototype: I) -> IntegratorId<f64>
+    where
+        I: ScalarVoronoiFaceIntegrator + Clone + Send + Sync + 'static,
+    {
+        self.add_scalar_factory(move || Box::new(prototype.clone()))
+    }
+
+    /// Register a face integrator with an arbitrary output type (e.g. a tensor or a user defined struct). Every face
+    /// is integrated by a fresh clone of the `prototype`.
+    ///
+    /// The results are only available through [`crate::Voronoi::face_integral`] and are not written to any of the
+    /// output formats.
+    pub fn add<I>(&mut self, prototype: I) -> IntegratorId<I::Output>
+    where
+        I: VoronoiFaceIntegrator + Clone + Send + Sync + 'static,
+        I::Output: Clone + Send + Sync + 'static,
+    {
+        self.generic_integrators.push(GenericFaceIntegratorFactory {
+            new_integrator: Box::new(move || Box::new(Erased(prototype.clone()))),
+            new_store: new_store::<I::Output>,
+        });
+        IntegratorId::new(IntegratorKind::Generic, self.generic_integrators.len() - 1)
+    }
+
+    /// Register a vector face integrator, created for every face by the given `factory`.
+    pub fn add_vector_factory<F>(&mut self, factory: F) -> IntegratorId<DVec3>
+    where
+        F: Fn() -> Box<dyn VectorVoronoiFaceIntegrator> + Send + Sync + 'static,
+    {
+        self.vector_integrators.push(Box::new(factory));
+        IntegratorId::new(IntegratorKind::Vector, self.vector_integrators.len() - 1)
+    }
+
+    /// Register a scalar face integrator, created for every face by the given `factory`.
+    pub fn add_scalar_factory<F>(&mut self, factory: F) -> IntegratorId<f64>
+    where
+        F: Fn() -> Box<dyn ScalarVoronoiFaceIntegrator> + Send + Sync + 'static,
+    {
+        self.scalar_integrators.push(Box::new(factory));
+        IntegratorId::new(IntegratorKind::Scalar, self.scalar_integrators.len() - 1)
+    }
+
+    /// Register a vector face integrator with access to the per-generator `data` (one item per generator). Every face
+    /// is integrated by a fresh clone of the `prototype`.
+    ///
+    /// The construction of a tesselation with this registry panics before building any cell if the length of `data`
+    /// does not match the number of generators.
+    pub fn add_vector_with_data<D, I>(
+        &mut self,
+        data: Arc<[D]>,
+        prototype: I,
+    ) -> IntegratorId<DVec3>
+    where
+        D: Send + Sync + 'static,
+        I: GeneratorDataIntegrator<D, Output = DVec3> + Clone + Send + Sync + 'static,
+    {
+        self.generator_data_lengths.push(data.len());
+        self.add_vector_factory(move || {
+            Box::new(WithGeneratorData {
+                data: Arc::clone(&data),
+                left_idx: 0,
+                right_idx: None,
+                integrator: prototype.clone(),
+            })
+        })
+    }
+
+    /// Register a scalar face integrator with access to the per-generator `data` (one item per generator). Every face
+    /// is integrated by a fresh clone of the `prototype`.
+    ///
+    /// The construction of a tesselation with this registry panics before building any cell if the length of `data`
+    /// does not match the number of generators.
+    pub fn add_scalar_with_data<D, I>(&mut self, data: Arc<[D]>, prototype: I) -> IntegratorId<f64>
+    where
+        D: Send + Sync + 'static,
+        I: GeneratorDataIntegrator<D, Output = f64> + Clone + Send + Sync + 'static,
+    {
+        self.generator_data_lengths.push(data.len());
+        self.add_scalar_factory(move || {
+            Box::new(WithGeneratorData {
+                data: Arc::clone(&data),
+                left_idx: 0,
+                right_idx: None,
+                integrator: prototype.clone(),
+            })
+        })
+    }
+
+    /// Check that the per-generator data of all registered integrators has one item per generator.
+    pub(crate) fn check_generator_count(&self, generator_count: usize) {
+        for &len in self.generator_data_lengths.iter() {
+            assert_eq!(
+                len, generator_count,
+                "The per-generator data of a face integrator must have one item per generator!"
+            );
+        }
+    }
+
+    /// Whether no face integrators are registered.
+    pub fn is_empty(&self) -> bool {
+        self.vector_integrators.is_empty()
+            && self.scalar_integrators.is_empty()
+            && self.generic_integrators.is_empty()
+    }
+
+    pub(crate) fn vector_integrators(&self) -> &[VectorFaceIntegratorFactory] {
+        &self.vector_integrators
+    }
+
+    pub(crate) fn scalar_integrators(&self) -> &[ScalarFaceIntegratorFactory] {
+        &self.scalar_integrators
+    }
+
+    pub(crate) fn generic_integrators(&self) -> &[GenericFaceIntegratorFactory] {
+        &self.generic_integrators
+    }
+}
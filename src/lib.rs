@@ -1,20 +1,60 @@
 //! An implementation of the [Meshless Voronoi algorithm](https://hal.inria.fr/hal-01927559/file/voroGPU.pdf) in rust.
 //!
 //! The algorithm is primarily aimed at generating 3D Voronoi diagrams, but can also be used to compute 1D and 2D Voronoi diagrams.
-//! Like Voro++, this algorithm is _meshless_ implying that no global geometry is constructed. Instead a cell based approach is used and we only compute integrals (cell/face volumes and centroids) and connectivity information (it is possible to determine a cell's neighbours).
+//! Like Voro++, this algorithm is _meshless_ implying that no global geometry is constructed. Instead a cell based approach is used and we only compute integrals (cell/face volumes and centroids), the vertices of the faces and connectivity information (it is possible to determine a cell's neighbours).
+//! If needed, the faces can be stitched together into a global mesh afterwards (see [`Voronoi::to_halfedge`]).
 //! The algorithm can generate Voronoi tesselations with a rectangular boundary or periodic boundary conditions and also supports computing a subset of the Voronoi tesselation.
+//!
+//! When the `"tracing"` feature is enabled, the construction of a tesselation emits [`tracing`](https://docs.rs/tracing) spans
+//! (r-tree construction, cell building per chunk of cells, face flattening and finalization), which can be collected with any subscriber.
+
+/// Enter a `tracing` span (at debug level) until the end of the current scope.
+/// This is a no-op when the `"tracing"` feature is disabled.
+macro_rules! span {
+    ($($args:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!($($args)*).entered();
+    };
+}
 
+#[allow(dead_code)]
 mod bounding_sphere;
+mod generators;
 mod geometry;
 mod integrators;
+pub mod mask;
 mod part;
+#[cfg(feature = "pyo3")]
+mod python;
 mod rtree_nn;
+pub mod sampling;
 mod simple_cycle;
 #[allow(dead_code)]
 // Space is no longer used, I left it in as a reference for the gpu implementation
 mod space;
+pub mod spatial;
 mod util;
 mod voronoi;
 
-pub use integrators::{VoronoiCellIntegrator, VoronoiFaceIntegrator};
-pub use voronoi::{Voronoi, VoronoiCell, VoronoiFace};
+pub use generators::Generators;
+pub use integrators::{
+    FaceIntegrators, FirstMomentIntegrator, GeneratorDataIntegrator, IntegratorId,
+    PositionNormalMomentIntegrator, ScalarVoronoiFaceIntegrator, SecondMomentIntegrator,
+    VectorVoronoiFaceIntegrator, VoronoiCellIntegrator, VoronoiFaceIntegrator,
+};
+#[cfg(feature = "petgraph")]
+pub use voronoi::AdjacencyEdge;
+#[cfg(feature = "geo")]
+pub use voronoi::ClippedCell2D;
+pub use voronoi::{
+    Agglomeration, Boundary, BuildOptions, BuildProfile, CellDefect, CellDifference, CellProfile,
+    CellQuality, ComparisonReport, DirectedFace, FracturePiece, GeneratorSet, GradientWeights,
+    HalfEdge, HalfEdgeFace, HalfEdgeMesh, HierarchicalVoronoi, Interpolation, Kernel,
+    LimiterGeometry, NeighbourSearch, OrbDecomposition, Polygon2D, SafetyCriterion, Skeleton,
+    SkeletonElements, SlicePlane, SphericalVoronoi, SphericalVoronoiCell, SphericalVoronoiEdge,
+    SuperCell, SuperFace, SupermeshCell, TopologyDefect, TriangleMesh, ValidationReport, Voronoi,
+    Voronoi1D, Voronoi2D, VoronoiCell, VoronoiCell1D, VoronoiCell2D, VoronoiEdge, VoronoiEdge2D,
+    VoronoiFace, Wireframe,
+};
+#[cfg(feature = "hdf5")]
+pub use voronoi::{Compression, OutputDataset, SaveOptions};
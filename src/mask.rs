@@ -0,0 +1,136 @@
+//! Utilities to convert common selections of generators into the mask expected by [`crate::Voronoi::build_partial`].
+//!
+//! Every mask contains one entry per generator, which is `true` for the generators whose cells should be constructed.
+//! See also [`crate::Voronoi::build_partial_indices`] to construct the cells of a list of generators directly, and
+//! [`crate::Voronoi::build_partial_by`] to select the cells with a predicate.
+
+use glam::DVec3;
+
+/// Select the generators with the given `indices`, out of `len` generators.
+pub fn from_indices(indices: &[usize], len: usize) -> Vec<bool> {
+    let mut mask = vec![false; len];
+    for &idx in indices {
+        mask[idx] = true;
+    }
+    mask
+}
+
+/// Get the (sorted) indices of the generators selected by `mask`.
+pub fn to_indices(mask: &[bool]) -> Vec<usize> {
+    (0..mask.len()).filter(|&idx| mask[idx]).collect()
+}
+
+/// Select the generators inside the (closed) box with the given `anchor` (lower left corner) and `width`.
+///
+/// If `period` is given, the generators are selected if any of their periodic images (for a periodic simulation
+/// volume with that width) lies inside the box.
+pub fn in_box(
+    generators: &[DVec3],
+    anchor: DVec3,
+    width: DVec3,
+    period: Option<DVec3>,
+) -> Vec<bool> {
+    generators
+        .iter()
+        .map(|&loc| {
+            let mut offset = loc - anchor;
+            if let Some(period) = period {
+                // The first periodic image above the anchor
+                offset -= (offset / period).floor() * period;
+            }
+            offset.cmpge(DVec3::ZERO).all() && offset.cmple(width).all()
+        })
+        .collect()
+}
+
+/// Select the generators inside the (closed) sphere with the given `center` and `radius`.
+///
+/// If `period` is given, the generators are selected if any of their periodic images (for a periodic simulation
+/// volume with that width) lies inside the sphere.
+pub fn in_sphere(
+    generators: &[DVec3],
+    center: DVec3,
+    radius: f64,
+    period: Option<DVec3>,
+) -> Vec<bool> {
+    generators
+        .iter()
+        .map(|&loc| {
+            let mut offset = loc - center;
+            if let Some(period) = period {
+                // The nearest periodic image
+                offset -= (offset / period).round() * period;
+            }
+            offset.length_squared() <= radius * radius
+        })
+        .collect()
+}
+
+/// Select the generators assigned to the given `rank`, e.g. by a [`crate::OrbDecomposition`] or
+/// [`crate::Voronoi::partition`].
+pub fn of_rank(ranks: &[usize], rank: usize) -> Vec<bool> {
+    ranks.iter().map(|&r| r == rank).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Voronoi;
+
+    #[test]
+    fn test_masks() {
+        let generators = (0..10)
+            .map(|i| DVec3::new(0.1 * i as f64 + 0.05, 0.5, 0.5))
+            .collect::<Vec<_>>();
+
+        let mask = from_indices(&[7, 2, 3], generators.len());
+        assert_eq!(to_indices(&mask), vec![2, 3, 7]);
+
+        let mask = in_box(
+            &generators,
+            DVec3::new(0.3, 0., 0.),
+            DVec3::new(0.3, 1., 1.),
+            None,
+        );
+        assert_eq!(to_indices(&mask), vec![3, 4, 5]);
+        // A box wrapping around the periodic boundary
+        let mask = in_box(
+            &generators,
+            DVec3::new(0.8, 0., 0.),
+            DVec3::new(0.3, 1., 1.),
+            Some(DVec3::ONE),
+        );
+        assert_eq!(to_indices(&mask), vec![0, 8, 9]);
+
+        let mask = in_sphere(&generators, DVec3::splat(0.5), 0.11, None);
+        assert_eq!(to_indices(&mask), vec![4, 5]);
+        let mask = in_sphere(&generators, DVec3::new(0., 0.5, 0.5), 0.1, None);
+        assert_eq!(to_indices(&mask), vec![0]);
+        let mask = in_sphere(&generators, DVec3::new(0., 0.5, 0.5), 0.1, Some(DVec3::ONE));
+        assert_eq!(to_indices(&mask), vec![0, 9]);
+
+        let ranks = [0, 1, 2, 0, 1, 2, 0, 1, 2, 0];
+        assert_eq!(to_indices(&of_rank(&ranks, 1)), vec![1, 4, 7]);
+
+        // Building from indices is the same as building from a mask
+        let indices = [1, 4, 7];
+        let anchor = DVec3::ZERO;
+        let width = DVec3::ONE;
+        let partial =
+            Voronoi::build_partial_indices(&generators, &indices, anchor, width, 3, false, None);
+        let expected = Voronoi::build_partial(
+            &generators,
+            &of_rank(&ranks, 1),
+            anchor,
+            width,
+            3,
+            false,
+            None,
+        );
+        assert_eq!(partial.faces().len(), expected.faces().len());
+        for (cell, expected) in partial.cells().iter().zip(expected.cells()) {
+            assert_eq!(cell.volume(), expected.volume());
+        }
+        assert!((partial.cells()[4].volume() - 0.1).abs() < 1e-12);
+    }
+}
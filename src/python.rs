@@ -0,0 +1,220 @@
+//! Python bindings, enabled with the `"pyo3"` feature.
+//!
+//! Build the Python extension module with [maturin](https://www.maturin.rs) (`maturin build --release`).
+// The code generated by `#[pymethods]` for methods returning `PyResult` triggers this lint.
+#![allow(clippy::useless_conversion)]
+
+use glam::DVec3;
+use numpy::{
+    IntoPyArray, PyArray1, PyArray2, PyArrayMethods, PyReadonlyArray1, PyReadonlyArray2,
+    PyUntypedArrayMethods,
+};
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::Voronoi;
+
+fn to_dvec3(array: [f64; 3]) -> DVec3 {
+    DVec3::from_array(array)
+}
+
+fn vectors_to_array<'py>(
+    py: Python<'py>,
+    vectors: impl ExactSizeIterator<Item = DVec3>,
+) -> PyResult<Bound<'py, PyArray2<f64>>> {
+    let n = vectors.len();
+    let flat = vectors.flat_map(|v| v.to_array()).collect::<Vec<_>>();
+    flat.into_pyarray_bound(py).reshape([n, 3])
+}
+
+/// A Voronoi tesselation.
+///
+/// * `generators` - Array of shape `(n, d)` with `d <= 3` containing the seed points of the Voronoi cells.
+/// * `anchor` - The lower left corner of the simulation volume.
+/// * `width` - The width of the simulation volume.
+/// * `dimensionality` - The dimensionality of the tesselation (defaults to `d`).
+/// * `periodic` - Whether to apply periodic boundary conditions.
+/// * `mask` - Optional boolean array of length `n`: only build the cells for which it is `True`.
+#[pyclass(name = "Voronoi", module = "meshless_voronoi", frozen)]
+pub struct PyVoronoi {
+    voronoi: Voronoi,
+}
+
+#[pymethods]
+impl PyVoronoi {
+    #[new]
+    #[pyo3(signature = (generators, anchor, width, dimensionality = None, periodic = false, mask = None))]
+    fn new(
+        py: Python<'_>,
+        generators: PyReadonlyArray2<'_, f64>,
+        anchor: [f64; 3],
+        width: [f64; 3],
+        dimensionality: Option<usize>,
+        periodic: bool,
+        mask: Option<PyReadonlyArray1<'_, bool>>,
+    ) -> PyResult<Self> {
+        let [n, d] = generators.shape() else {
+            unreachable!("Generators are a 2D array!");
+        };
+        let (n, d) = (*n, *d);
+        if !(1..=3).contains(&d) {
+            return Err(PyValueError::new_err(
+                "Generators must have shape (n, d) with 1 <= d <= 3!",
+            ));
+        }
+        let dimensionality = dimensionality.unwrap_or(d);
+        if !(1..=3).contains(&dimensionality) {
+            return Err(PyValueError::new_err("Dimensionality must be 1, 2 or 3!"));
+        }
+        let generators = generators.as_array();
+        let generators = (0..n)
+            .map(|i| {
+                let mut loc = DVec3::ZERO;
+                for j in 0..d {
+                    loc[j] = generators[[i, j]];
+                }
+                loc
+            })
+            .collect::<Vec<_>>();
+        let mask = match mask {
+            Some(mask) => {
+                let mask = mask.as_array().to_vec();
+                if mask.len() != n {
+                    return Err(PyValueError::new_err(
+                        "Mask must have the same length as the generators!",
+                    ));
+                }
+                Some(mask)
+            }
+            None => None,
+        };
+
+        let (anchor, width) = (to_dvec3(anchor), to_dvec3(width));
+        let voronoi = py.allow_threads(|| match mask {
+            Some(mask) => Voronoi::build_partial(
+                &generators,
+                &mask,
+                anchor,
+                width,
+                dimensionality,
+                periodic,
+                None,
+            ),
+            None => Voronoi::build(&generators, anchor, width, dimensionality, periodic, None),
+        });
+
+        Ok(Self { voronoi })
+    }
+
+    /// The number of cells.
+    fn __len__(&self) -> usize {
+        self.voronoi.cells().len()
+    }
+
+    /// The volumes of the cells, shape `(n,)`.
+    fn cell_volumes<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.voronoi
+            .cells()
+            .iter()
+            .map(|c| c.volume())
+            .collect::<Vec<_>>()
+            .into_pyarray_bound(py)
+    }
+
+    /// The centroids of the cells, shape `(n, 3)`.
+    fn cell_centroids<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<f64>>> {
+        vectors_to_array(py, self.voronoi.cells().iter().map(|c| c.centroid()))
+    }
+
+    /// The offsets and counts of the faces of every cell in `cell_face_connections`, both of shape `(n,)`.
+    fn cell_face_offsets_counts<'py>(
+        &self,
+        py: Python<'py>,
+    ) -> (Bound<'py, PyArray1<usize>>, Bound<'py, PyArray1<usize>>) {
+        let cells = self.voronoi.cells();
+        (
+            cells
+                .iter()
+                .map(|c| c.face_connections_offset())
+                .collect::<Vec<_>>()
+                .into_pyarray_bound(py),
+            cells
+                .iter()
+                .map(|c| c.face_count())
+                .collect::<Vec<_>>()
+                .into_pyarray_bound(py),
+        )
+    }
+
+    /// The indices of the faces of all cells (see `cell_face_offsets_counts`).
+    fn cell_face_connections<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<usize>> {
+        self.voronoi
+            .cell_face_connections()
+            .to_vec()
+            .into_pyarray_bound(py)
+    }
+
+    /// The left and right neighbours of the faces, both of shape `(m,)`. The right neighbour is `-1` for boundary faces.
+    fn face_neighbours<'py>(
+        &self,
+        py: Python<'py>,
+    ) -> (Bound<'py, PyArray1<i64>>, Bound<'py, PyArray1<i64>>) {
+        let faces = self.voronoi.faces();
+        (
+            faces
+                .iter()
+                .map(|f| f.left() as i64)
+                .collect::<Vec<_>>()
+                .into_pyarray_bound(py),
+            faces
+                .iter()
+                .map(|f| f.right().map_or(-1, |right| right as i64))
+                .collect::<Vec<_>>()
+                .into_pyarray_bound(py),
+        )
+    }
+
+    /// The areas of the faces, shape `(m,)`.
+    fn face_areas<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.voronoi
+            .faces()
+            .iter()
+            .map(|f| f.area())
+            .collect::<Vec<_>>()
+            .into_pyarray_bound(py)
+    }
+
+    /// The centroids of the faces, shape `(m, 3)`.
+    fn face_centroids<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<f64>>> {
+        vectors_to_array(py, self.voronoi.faces().iter().map(|f| f.centroid()))
+    }
+
+    /// The normals of the faces (pointing away from the left neighbour), shape `(m, 3)`.
+    fn face_normals<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<f64>>> {
+        vectors_to_array(py, self.voronoi.faces().iter().map(|f| f.normal()))
+    }
+
+    /// The periodic shifts of the faces (zero for non-periodic faces), shape `(m, 3)`.
+    fn face_shifts<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<f64>>> {
+        vectors_to_array(
+            py,
+            self.voronoi
+                .faces()
+                .iter()
+                .map(|f| f.shift().unwrap_or(DVec3::ZERO)),
+        )
+    }
+
+    /// Save the tesselation to a hdf5 file (requires the `hdf5` feature).
+    #[cfg(feature = "hdf5")]
+    fn save(&self, filename: &str) -> PyResult<()> {
+        self.voronoi
+            .save(filename)
+            .map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))
+    }
+}
+
+#[pymodule]
+fn meshless_voronoi(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyVoronoi>()?;
+    Ok(())
+}
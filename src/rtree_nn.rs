@@ -9,7 +9,7 @@ pub(crate) fn build_rtree(generators: &[Generator]) -> RTree<Generator> {
     RTree::bulk_load(generators.to_vec())
 }
 
-pub fn nn_iter<'a>(
+pub(crate) fn nn_iter<'a>(
     rtree: &'a RTree<Generator>,
     loc: DVec3,
 ) -> Box<dyn Iterator<Item = (usize, Option<DVec3>)> + 'a> {
@@ -41,17 +41,80 @@ pub(crate) fn wrapping_nn_iter<'a>(
     )
 }
 
+/// Get the (periodic images of the) generators within `radius` of `loc`, sorted by increasing distance, as
+/// `(id, shift, distance)` triplets (with the same meaning of `shift` as for [`wrapping_nn_iter`]).
+///
+/// For periodic boxes (with `width` given), the images in the neighbouring boxes (along the used dimensions) are
+/// included.
+pub(crate) fn candidates_within(
+    rtree: &RTree<Generator>,
+    loc: DVec3,
+    radius: f64,
+    width: Option<DVec3>,
+    dimensionality: Dimensionality,
+) -> Vec<(usize, Option<DVec3>, f64)> {
+    let range = |used: bool| {
+        if width.is_some() && used {
+            -1..=1
+        } else {
+            0..=0
+        }
+    };
+    let (j_range, k_range) = match dimensionality {
+        Dimensionality::Dimensionality1D => (range(false), range(false)),
+        Dimensionality::Dimensionality2D => (range(true), range(false)),
+        Dimensionality::Dimensionality3D => (range(true), range(true)),
+    };
+    let width = width.unwrap_or(DVec3::ZERO);
+    let root_envelope = rtree.root().envelope();
+    let radius_2 = radius * radius;
+    let mut candidates = vec![];
+    for i in range(true) {
+        for j in j_range.clone() {
+            for k in k_range.clone() {
+                let shift = DVec3::new(i as f64, j as f64, k as f64) * width;
+                let query_point = (loc + shift).to_array();
+                if root_envelope.distance_2(&query_point) > radius_2 {
+                    continue;
+                }
+                let shift = (shift != DVec3::ZERO).then_some(-shift);
+                candidates.extend(
+                    rtree
+                        .locate_within_distance(query_point, radius_2)
+                        .map(|g| {
+                            let image = g.loc() + shift.unwrap_or(DVec3::ZERO);
+                            (g.id(), shift, loc.distance(image))
+                        }),
+                );
+            }
+        }
+    }
+    candidates.sort_by(|a, b| a.2.partial_cmp(&b.2).expect("NaN distance encountered!"));
+    candidates
+}
+
 macro_rules! point {
     ($Self:ident) => {
         <<$Self as RTreeObject>::Envelope as Envelope>::Point
     };
 }
 
+/// A node of the r-tree in the heap of a [`RTreeWrappingNearestNeighbourIter`]. The root of the r-tree is only
+/// pushed (once for every periodic shift), such that its children are only added to the heap for the shifts whose
+/// images of the r-tree come close enough to the query point to be reached by the iteration.
+enum NodeRef<'a, T>
+where
+    T: RTreeObject + 'a,
+{
+    Root(&'a ParentNode<T>),
+    Node(&'a RTreeNode<T>),
+}
+
 struct RTreeNodeDistanceWrapper<'a, T>
 where
     T: WrappingPointDistance + 'a,
 {
-    node: &'a RTreeNode<T>,
+    node: NodeRef<'a, T>,
     distance: <point!(T) as Point>::Scalar,
     shift: point!(T),
 }
@@ -76,7 +139,7 @@ impl<'a> RTreeWrappingNearestNeighbourIter<'a, Generator> {
             query_point,
         };
 
-        // Add the children of this node to the heap and also shifted versions of it for all directions
+        // Add the root to the heap for all the periodic shifts, its children are only added when it is reached
         let j_range = match dimensionality {
             Dimensionality::Dimensionality2D | Dimensionality::Dimensionality3D => -1..=1,
             Dimensionality::Dimensionality1D => 0..=0,
@@ -93,7 +156,12 @@ impl<'a> RTreeWrappingNearestNeighbourIter<'a, Generator> {
                         j as f64 * width[1],
                         k as f64 * width[2],
                     ];
-                    result.extend_heap(root.children(), shift);
+                    let distance = root.envelope().wrapping_distance_2(&query_point, &shift);
+                    result.nodes.push(RTreeNodeDistanceWrapper {
+                        node: NodeRef::Root(root),
+                        distance,
+                        shift,
+                    });
                 }
             }
         }
@@ -114,7 +182,7 @@ impl<'a> RTreeWrappingNearestNeighbourIter<'a, Generator> {
             };
 
             RTreeNodeDistanceWrapper {
-                node: child,
+                node: NodeRef::Node(child),
                 distance,
                 shift,
             }
@@ -202,8 +270,7 @@ where
     T: WrappingPointDistance,
 {
     fn partial_cmp(&self, other: &Self) -> Option<::core::cmp::Ordering> {
-        // Inverse comparison creates a min heap
-        other.distance.partial_cmp(&self.distance)
+        Some(self.cmp(other))
     }
 }
 
@@ -214,7 +281,8 @@ where
     T: WrappingPointDistance,
 {
     fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
-        self.partial_cmp(other).unwrap()
+        // Inverse comparison creates a min heap
+        other.distance.partial_cmp(&self.distance).unwrap()
     }
 }
 
@@ -225,14 +293,14 @@ impl<'a> Iterator for RTreeWrappingNearestNeighbourIter<'a, Generator> {
         while let Some(current) = self.nodes.pop() {
             match current {
                 RTreeNodeDistanceWrapper {
-                    node: RTreeNode::Parent(ref data),
+                    node: NodeRef::Root(data) | NodeRef::Node(RTreeNode::Parent(data)),
                     shift,
                     ..
                 } => {
                     self.extend_heap(data.children(), shift);
                 }
                 RTreeNodeDistanceWrapper {
-                    node: RTreeNode::Leaf(ref t),
+                    node: NodeRef::Node(RTreeNode::Leaf(t)),
                     distance,
                     shift,
                 } => {
@@ -0,0 +1,483 @@
+//! Utilities to generate generator positions (seed points) inside a (periodic or bounded) simulation volume.
+//!
+//! All functions take the simulation volume in the same form as [`crate::Voronoi::build`] and a user supplied random
+//! number generator, so that the results are reproducible when using a seeded generator.
+//! The unused coordinates of 1D and 2D samples are set to 0.
+
+use glam::DVec3;
+use rand::Rng;
+
+use crate::{
+    rtree_nn::{nn_iter, wrapping_nn_iter},
+    spatial::RTree,
+    voronoi::{Dimensionality, Generator},
+};
+
+/// The number of candidates generated around an active sample before it is retired in [`poisson_disk`].
+const POISSON_DISK_ATTEMPTS: usize = 30;
+
+/// The maximal number of consecutive rejected candidates in [`hard_core`].
+const HARD_CORE_ATTEMPTS: usize = 10_000;
+
+/// The maximal number of consecutive rejected candidates in [`density_sampling`].
+const DENSITY_SAMPLING_ATTEMPTS: usize = 100_000;
+
+/// Mask of the used dimensions.
+fn used_dimensions(dimensionality: Dimensionality) -> DVec3 {
+    match dimensionality {
+        Dimensionality::Dimensionality1D => DVec3::X,
+        Dimensionality::Dimensionality2D => DVec3::new(1., 1., 0.),
+        Dimensionality::Dimensionality3D => DVec3::ONE,
+    }
+}
+
+/// Normalize the simulation volume: set the unused dimensions to `[0, 1]`.
+fn normalize(anchor: DVec3, width: DVec3, mask: DVec3) -> (DVec3, DVec3) {
+    (anchor * mask, width * mask + (DVec3::ONE - mask))
+}
+
+/// Draw a uniform random point inside the simulation volume.
+fn uniform_point<R: Rng + ?Sized>(rng: &mut R, anchor: DVec3, width: DVec3, mask: DVec3) -> DVec3 {
+    let u = DVec3::new(rng.gen(), rng.gen(), rng.gen());
+    (anchor + u * width) * mask
+}
+
+/// Get the offset from `a` to `b`, taking into account periodic boundary conditions if necessary.
+fn offset(a: DVec3, b: DVec3, width: DVec3, periodic: bool) -> DVec3 {
+    let dx = b - a;
+    if periodic {
+        dx - (dx / width).round() * width
+    } else {
+        dx
+    }
+}
+
+/// Wrap `point` into the (normalized) simulation volume. Returns `None` for points outside of a non-periodic
+/// simulation volume.
+fn wrap(point: DVec3, anchor: DVec3, width: DVec3, periodic: bool) -> Option<DVec3> {
+    let dx = point - anchor;
+    if periodic {
+        Some(anchor + dx - (dx / width).floor() * width)
+    } else if dx.cmpge(DVec3::ZERO).all() && dx.cmplt(width).all() {
+        Some(point)
+    } else {
+        None
+    }
+}
+
+/// A uniform background grid over the simulation volume, with at most one sample per cell.
+struct BackgroundGrid {
+    anchor: DVec3,
+    cell_width: DVec3,
+    counts: [usize; 3],
+    cells: Vec<Option<usize>>,
+}
+
+impl BackgroundGrid {
+    fn new(anchor: DVec3, width: DVec3, mask: DVec3, max_cell_width: f64) -> Self {
+        let mut counts = [1; 3];
+        for i in 0..3 {
+            if mask[i] > 0. {
+                counts[i] = ((width[i] / max_cell_width).ceil() as usize).max(1);
+            }
+        }
+        let cell_width = width / DVec3::new(counts[0] as f64, counts[1] as f64, counts[2] as f64);
+        Self {
+            anchor,
+            cell_width,
+            counts,
+            cells: vec![None; counts[0] * counts[1] * counts[2]],
+        }
+    }
+
+    fn index(&self, point: DVec3) -> [usize; 3] {
+        let idx = ((point - self.anchor) / self.cell_width).floor();
+        let mut index = [0; 3];
+        for i in 0..3 {
+            index[i] = (idx[i].max(0.) as usize).min(self.counts[i] - 1);
+        }
+        index
+    }
+
+    fn flat_index(&self, index: [usize; 3]) -> usize {
+        (index[0] * self.counts[1] + index[1]) * self.counts[2] + index[2]
+    }
+
+    fn insert(&mut self, point: DVec3, sample: usize) {
+        let idx = self.flat_index(self.index(point));
+        self.cells[idx] = Some(sample);
+    }
+
+    /// Get an `Iterator` over the samples in the cells within `radius` of the cell containing `point`.
+    fn neighbours(
+        &self,
+        point: DVec3,
+        radius: f64,
+        periodic: bool,
+    ) -> impl Iterator<Item = usize> + '_ {
+        let index = self.index(point);
+        let ranges = [0, 1, 2].map(|i| {
+            let reach = (radius / self.cell_width[i]).ceil() as isize;
+            let count = self.counts[i] as isize;
+            let (start, end) = if periodic && 2 * reach + 1 >= count {
+                (0, count - 1)
+            } else if periodic {
+                (index[i] as isize - reach, index[i] as isize + reach)
+            } else {
+                (
+                    (index[i] as isize - reach).max(0),
+                    (index[i] as isize + reach).min(count - 1),
+                )
+            };
+            (start..=end).map(move |j| j.rem_euclid(count) as usize)
+        });
+        let [range_x, range_y, range_z] = ranges;
+        range_x
+            .flat_map(move |i| {
+                let range_z = range_z.clone();
+                range_y
+                    .clone()
+                    .flat_map(move |j| range_z.clone().map(move |k| [i, j, k]))
+            })
+            .filter_map(|index| self.cells[self.flat_index(index)])
+    }
+}
+
+/// Generate well-spaced samples inside the simulation volume using Bridson's Poisson-disk sampling algorithm
+/// (Bridson 2007).
+///
+/// * `anchor` - The lower left corner of the simulation volume.
+/// * `width` - The width of the simulation volume.
+/// * `dimensionality` - The dimensionality of the samples (1, 2 or 3).
+/// * `periodic` - Whether to measure the distances between the samples with periodic boundary conditions.
+/// * `radius` - The minimal distance between any two samples.
+/// * `rng` - The random number generator to use.
+///
+/// The samples are maximal: no further sample can be added without violating the minimal distance (up to the
+/// stochastic nature of the algorithm, which tries 30 candidates around every sample).
+pub fn poisson_disk<R: Rng + ?Sized>(
+    anchor: DVec3,
+    width: DVec3,
+    dimensionality: usize,
+    periodic: bool,
+    radius: f64,
+    rng: &mut R,
+) -> Vec<DVec3> {
+    assert!(radius > 0., "Radius must be positive!");
+    let dimensionality: Dimensionality = dimensionality.into();
+    let mask = used_dimensions(dimensionality);
+    let (anchor, width) = normalize(anchor, width, mask);
+    let dim = usize::from(dimensionality) as f64;
+    let mut grid = BackgroundGrid::new(anchor, width, mask, radius / dim.sqrt());
+
+    let first = uniform_point(rng, anchor, width, mask);
+    grid.insert(first, 0);
+    let mut samples = vec![first];
+    let mut active = vec![0];
+    while !active.is_empty() {
+        let active_idx = rng.gen_range(0..active.len());
+        let center = samples[active[active_idx]];
+        let mut found = false;
+        for _ in 0..POISSON_DISK_ATTEMPTS {
+            // Draw a candidate uniformly from the spherical shell between `radius` and `2 * radius`
+            let candidate = loop {
+                let u = DVec3::new(rng.gen(), rng.gen(), rng.gen()) * 2. - 1.;
+                let d = 2. * radius * u * mask;
+                let length = d.length();
+                if length >= radius && length <= 2. * radius {
+                    break center + d;
+                }
+            };
+            let Some(candidate) = wrap(candidate, anchor, width, periodic) else {
+                continue;
+            };
+            let too_close = grid.neighbours(candidate, radius, periodic).any(|other| {
+                offset(candidate, samples[other], width, periodic).length_squared()
+                    < radius * radius
+            });
+            if !too_close {
+                grid.insert(candidate, samples.len());
+                active.push(samples.len());
+                samples.push(candidate);
+                found = true;
+                break;
+            }
+        }
+        if !found {
+            active.swap_remove(active_idx);
+        }
+    }
+
+    samples
+}
+
+/// Generate a regular grid of `counts[0] x counts[1] x counts[2]` samples inside the simulation volume, with every
+/// sample perturbed by a uniform random offset.
+///
+/// * `anchor` - The lower left corner of the simulation volume.
+/// * `width` - The width of the simulation volume.
+/// * `counts` - The number of samples along every axis.
+/// * `perturbation` - The amplitude of the perturbation in units of the grid spacing: every coordinate is offset by a
+///   uniform random value in `[-perturbation / 2, perturbation / 2]` times the grid spacing. A perturbation of 0
+///   results in a regular grid, a perturbation of 1 in one uniform random sample per grid cell.
+/// * `rng` - The random number generator to use.
+///
+/// The samples are clamped to the simulation volume and ordered by increasing x-index, then y-index, then z-index.
+pub fn jittered_grid<R: Rng + ?Sized>(
+    anchor: DVec3,
+    width: DVec3,
+    counts: [usize; 3],
+    perturbation: f64,
+    rng: &mut R,
+) -> Vec<DVec3> {
+    let spacing = width / DVec3::new(counts[0] as f64, counts[1] as f64, counts[2] as f64);
+    let mut samples = Vec::with_capacity(counts[0] * counts[1] * counts[2]);
+    for i in 0..counts[0] {
+        for j in 0..counts[1] {
+            for k in 0..counts[2] {
+                let jitter = DVec3::new(rng.gen(), rng.gen(), rng.gen()) - 0.5;
+                let pos = (DVec3::new(i as f64, j as f64, k as f64) + 0.5 + perturbation * jitter)
+                    * spacing
+                    + anchor;
+                samples.push(pos.clamp(anchor, anchor + width));
+            }
+        }
+    }
+    samples
+}
+
+/// Generate a regular 2D grid of `counts[0] x counts[1]` perturbed samples in the xy-plane of the simulation
+/// volume, see [`jittered_grid`]. The z-coordinates of the samples are set to 0.
+pub fn jittered_plane<R: Rng + ?Sized>(
+    anchor: DVec3,
+    width: DVec3,
+    counts: [usize; 2],
+    perturbation: f64,
+    rng: &mut R,
+) -> Vec<DVec3> {
+    let (anchor, width) = normalize(
+        anchor,
+        width,
+        used_dimensions(Dimensionality::Dimensionality2D),
+    );
+    jittered_grid(anchor, width, [counts[0], counts[1], 1], perturbation, rng)
+        .into_iter()
+        .map(|sample| sample.truncate().extend(0.))
+        .collect()
+}
+
+/// Generate `count` uniform random samples inside the simulation volume with a guaranteed minimal pairwise distance
+/// (a hard-core point process), by dart throwing: candidates are drawn uniformly and rejected if they lie within
+/// `min_distance` of an already accepted sample.
+///
+/// * `anchor` - The lower left corner of the simulation volume.
+/// * `width` - The width of the simulation volume.
+/// * `dimensionality` - The dimensionality of the samples (1, 2 or 3).
+/// * `periodic` - Whether to measure the distances between the samples with periodic boundary conditions.
+/// * `count` - The number of samples to generate.
+/// * `min_distance` - The minimal distance between any two samples.
+/// * `rng` - The random number generator to use.
+///
+/// Returns `None` if no new sample could be placed after 10000 consecutive attempts, which means that the requested
+/// density is too high (random sequential adsorption jams well below the densest packing).
+pub fn hard_core<R: Rng + ?Sized>(
+    anchor: DVec3,
+    width: DVec3,
+    dimensionality: usize,
+    periodic: bool,
+    count: usize,
+    min_distance: f64,
+    rng: &mut R,
+) -> Option<Vec<DVec3>> {
+    let dimensionality: Dimensionality = dimensionality.into();
+    let mask = used_dimensions(dimensionality);
+    let (anchor, width) = normalize(anchor, width, mask);
+
+    let mut rtree = RTree::new();
+    let mut samples = Vec::with_capacity(count);
+    while samples.len() < count {
+        let accepted = (0..HARD_CORE_ATTEMPTS).find_map(|_| {
+            let candidate = uniform_point(rng, anchor, width, mask);
+            let nearest = if periodic {
+                wrapping_nn_iter(&rtree, candidate, width, dimensionality).next()
+            } else {
+                nn_iter(&rtree, candidate).next()
+            };
+            match nearest {
+                Some((idx, shift)) => {
+                    let other: DVec3 = samples[idx];
+                    let distance = candidate.distance(other + shift.unwrap_or(DVec3::ZERO));
+                    (distance >= min_distance).then_some(candidate)
+                }
+                None => Some(candidate),
+            }
+        })?;
+        rtree.insert(Generator::new(samples.len(), accepted, dimensionality));
+        samples.push(accepted);
+    }
+
+    Some(samples)
+}
+
+/// Draw `count` samples from the (unnormalized) probability density `density` over the simulation volume, using
+/// rejection sampling with a uniform proposal distribution.
+///
+/// * `anchor` - The lower left corner of the simulation volume.
+/// * `width` - The width of the simulation volume.
+/// * `dimensionality` - The dimensionality of the samples (1, 2 or 3).
+/// * `count` - The number of samples to generate.
+/// * `density` - The target density (up to normalization). Must be non-negative.
+/// * `max_density` - An upper bound of `density` over the simulation volume. The closer this bound is to the actual
+///   maximum, the fewer candidates are rejected.
+/// * `rng` - The random number generator to use.
+///
+/// This is useful to draw the initial generators of a centroidal Voronoi tesselation (e.g. Lloyd relaxation) that
+/// is already close to the target density. Panics if `max_density` is not positive or if `density` exceeds
+/// `max_density` for any candidate.
+///
+/// Returns fewer than `count` samples (the ones accepted so far) if 100000 consecutive candidates are rejected, which
+/// means that `density` vanishes (almost) everywhere or that `max_density` is far above its actual maximum.
+pub fn density_sampling<R: Rng + ?Sized, F: Fn(DVec3) -> f64>(
+    anchor: DVec3,
+    width: DVec3,
+    dimensionality: usize,
+    count: usize,
+    density: F,
+    max_density: f64,
+    rng: &mut R,
+) -> Vec<DVec3> {
+    assert!(max_density > 0., "Maximal density must be positive!");
+    let mask = used_dimensions(dimensionality.into());
+    let (anchor, width) = normalize(anchor, width, mask);
+
+    let mut samples = Vec::with_capacity(count);
+    while samples.len() < count {
+        let accepted = (0..DENSITY_SAMPLING_ATTEMPTS).find_map(|_| {
+            let candidate = uniform_point(rng, anchor, width, mask);
+            let value = density(candidate);
+            assert!(
+                (0. ..=max_density).contains(&value),
+                "Density must be non-negative and cannot exceed the maximal density!"
+            );
+            (rng.gen::<f64>() * max_density < value).then_some(candidate)
+        });
+        let Some(accepted) = accepted else {
+            break;
+        };
+        samples.push(accepted);
+    }
+
+    samples
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn min_distance(samples: &[DVec3], width: DVec3, periodic: bool) -> f64 {
+        let mut min_distance = f64::INFINITY;
+        for (i, &a) in samples.iter().enumerate() {
+            for &b in samples[(i + 1)..].iter() {
+                min_distance = min_distance.min(offset(a, b, width, periodic).length());
+            }
+        }
+        min_distance
+    }
+
+    #[test]
+    fn test_jittered_grid() {
+        let anchor = DVec3::new(1., -1., 0.);
+        let width = DVec3::new(2., 1., 3.);
+        let counts = [4, 2, 3];
+        let regular = jittered_grid(anchor, width, counts, 0., &mut StdRng::seed_from_u64(0));
+        assert_eq!(regular.len(), 24);
+        assert_eq!(regular[0], anchor + 0.5 * DVec3::new(0.5, 0.5, 1.));
+        assert_eq!(regular[23], anchor + width - 0.5 * DVec3::new(0.5, 0.5, 1.));
+
+        // The same seed produces the same samples, every sample stays within its grid cell
+        let samples = jittered_grid(anchor, width, counts, 1., &mut StdRng::seed_from_u64(7));
+        let again = jittered_grid(anchor, width, counts, 1., &mut StdRng::seed_from_u64(7));
+        assert_eq!(samples, again);
+        for (sample, center) in samples.iter().zip(regular.iter()) {
+            let d = (*sample - *center).abs();
+            assert!(d.cmple(DVec3::new(0.25, 0.25, 0.5)).all());
+        }
+
+        let plane = jittered_plane(anchor, width, [3, 5], 0.5, &mut StdRng::seed_from_u64(7));
+        assert_eq!(plane.len(), 15);
+        assert!(plane.iter().all(|s| s.z == 0.));
+    }
+
+    #[test]
+    fn test_hard_core() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let anchor = DVec3::ZERO;
+        let width = DVec3::new(1., 2., 1.);
+        for dimensionality in [1, 2, 3] {
+            for periodic in [false, true] {
+                let samples =
+                    hard_core(anchor, width, dimensionality, periodic, 10, 0.05, &mut rng)
+                        .expect("Density is low enough!");
+                assert_eq!(samples.len(), 10);
+                assert!(min_distance(&samples, width, periodic) >= 0.05);
+            }
+        }
+        // Two samples at a distance of at least 0.6 cannot fit in a periodic unit interval
+        assert!(hard_core(anchor, width, 1, true, 2, 0.6, &mut rng).is_none());
+        assert!(hard_core(anchor, width, 1, false, 2, 0.6, &mut rng).is_some());
+    }
+
+    #[test]
+    fn test_density_sampling() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let anchor = DVec3::new(0., 0., -3.);
+        let width = DVec3::new(1., 1., 10.);
+        // A density proportional to x: the fraction of samples with x < 0.5 should be 1/4
+        let count = 10_000;
+        let samples = density_sampling(anchor, width, 2, count, |x| x.x, 1., &mut rng);
+        assert_eq!(samples.len(), count);
+        assert!(samples.iter().all(|s| s.z == 0.));
+        let fraction = samples.iter().filter(|s| s.x < 0.5).count() as f64 / count as f64;
+        assert!((fraction - 0.25).abs() < 0.02);
+
+        // A vanishing density terminates without samples
+        let samples = density_sampling(anchor, width, 2, count, |_| 0., 1., &mut rng);
+        assert!(samples.is_empty());
+    }
+
+    #[test]
+    fn test_poisson_disk() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let anchor = DVec3::new(-1., 0., 2.);
+        let width = DVec3::new(2., 1., 1.5);
+        let radius = 0.1;
+        for dimensionality in [1, 2, 3] {
+            for periodic in [false, true] {
+                let samples =
+                    poisson_disk(anchor, width, dimensionality, periodic, radius, &mut rng);
+                assert!(min_distance(&samples, width, periodic) >= radius);
+                let mask = used_dimensions(dimensionality.into());
+                for sample in samples.iter() {
+                    assert_eq!(*sample * mask, *sample);
+                    let dx = (*sample - anchor) * mask;
+                    assert!(dx.cmpge(DVec3::ZERO).all() && dx.cmple(width).all());
+                }
+                // The samples should be (close to) maximal: in 1D, no gap can fit a sample with some margin.
+                if dimensionality == 1 && !periodic {
+                    let mut x = samples.iter().map(|s| s.x).collect::<Vec<_>>();
+                    x.sort_by(f64::total_cmp);
+                    assert!(x.windows(2).all(|w| w[1] - w[0] < 3. * radius));
+                }
+                // A rough lower bound on the number of samples
+                let volume = {
+                    let w = width * mask + (DVec3::ONE - mask);
+                    w.x * w.y * w.z
+                };
+                let sample_volume = radius.powi(dimensionality as i32);
+                assert!(samples.len() as f64 > 0.2 * volume / sample_volume);
+            }
+        }
+    }
+}
@@ -1,6 +1,9 @@
+use smallvec::SmallVec;
+
 #[derive(Clone)]
 pub struct SimpleCycle {
-    ptrs: Vec<usize>,
+    /// Stored inline for the typical number of clipping planes of a convex cell.
+    ptrs: SmallVec<[usize; 32]>,
     start: usize,
     pub len: usize,
 }
@@ -77,7 +80,7 @@ impl SimpleCycle {
         Err(())
     }
 
-    pub fn iter(&self) -> SimpleCycle2Iterator {
+    pub fn iter(&self) -> SimpleCycle2Iterator<'_> {
         SimpleCycle2Iterator {
             simple_cycle: self,
             next: self.start,
@@ -105,6 +108,7 @@ mod test {
     use crate::simple_cycle::SimpleCycle;
 
     #[test]
+    #[allow(clippy::useless_vec)]
     fn test_extend() {
         let mut tris = vec![
             (2, 4, 1),
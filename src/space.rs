@@ -113,7 +113,7 @@ impl Space {
             .collect();
 
         // sort by cid
-        parts.sort_by(|p_a, p_b| p_a.cid().cmp(&p_b.cid()));
+        parts.sort_by_key(|p| p.cid());
 
         // add parts to space and set cell offsets and counts
         let mut offset = 0;
@@ -147,13 +147,13 @@ impl Space {
 
         impl PartialOrd for HeapEntry {
             fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-                self.d_2.partial_cmp(&other.d_2)
+                Some(self.cmp(other))
             }
         }
 
         impl Ord for HeapEntry {
             fn cmp(&self, other: &Self) -> Ordering {
-                self.partial_cmp(other).unwrap()
+                self.d_2.partial_cmp(&other.d_2).unwrap()
             }
         }
 
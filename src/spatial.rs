@@ -0,0 +1,116 @@
+//! Nearest neighbour search utilities.
+//!
+//! These are the same (periodic aware) nearest neighbour streams that are used internally to construct the Voronoi
+//! cells, so applications can reuse them for their own neighbour loops (e.g. SPH) without duplicating the wrapping logic.
+
+use glam::DVec3;
+pub use rstar::RTree;
+
+use crate::rtree_nn;
+pub use crate::voronoi::Generator;
+
+/// Build an r-tree of the given generators. The id of every [`Generator`] is its index in `generators`.
+///
+/// * `generators` - The points to insert in the r-tree.
+/// * `dimensionality` - The dimensionality of the points. The unused coordinates of the generators are set to 0.
+pub fn build_rtree(generators: &[DVec3], dimensionality: usize) -> RTree<Generator> {
+    let dimensionality = dimensionality.into();
+    let generators = generators
+        .iter()
+        .enumerate()
+        .map(|(id, &loc)| Generator::new(id, loc, dimensionality))
+        .collect::<Vec<_>>();
+    rtree_nn::build_rtree(&generators)
+}
+
+/// Get an `Iterator` over the ids of the generators in the r-tree, sorted by increasing distance to `loc`.
+///
+/// Every item is of the form `(id, None)`, to match the items of [`wrapping_nn_iter`].
+pub fn nn_iter(
+    rtree: &RTree<Generator>,
+    loc: DVec3,
+) -> Box<dyn Iterator<Item = (usize, Option<DVec3>)> + '_> {
+    rtree_nn::nn_iter(rtree, loc)
+}
+
+/// Get an `Iterator` over the ids of the generators in the r-tree, sorted by increasing _periodic_ distance to `loc`,
+/// for a periodic box with the given `width`.
+///
+/// Every item is of the form `(id, shift)`, where `shift` is `Some` if the nearest periodic image of the generator
+/// is located at `generator.loc() + shift`, and `None` if the generator itself is the nearest image.
+/// Every generator is returned at most once for each of its periodic images (in the 27 neighbouring boxes in 3D).
+pub fn wrapping_nn_iter(
+    rtree: &RTree<Generator>,
+    loc: DVec3,
+    width: DVec3,
+    dimensionality: usize,
+) -> Box<dyn Iterator<Item = (usize, Option<DVec3>)> + '_> {
+    rtree_nn::wrapping_nn_iter(rtree, loc, width, dimensionality.into())
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{distributions::Uniform, prelude::*};
+
+    use super::*;
+
+    #[test]
+    fn test_nn_iter() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let distr = Uniform::new(0., 1.);
+        let generators = (0..100)
+            .map(|_| DVec3::new(rng.sample(distr), rng.sample(distr), rng.sample(distr)))
+            .collect::<Vec<_>>();
+        let rtree = build_rtree(&generators, 3);
+        let loc = DVec3::splat(0.05);
+
+        let mut expected = (0..generators.len()).collect::<Vec<_>>();
+        expected.sort_by(|&a, &b| {
+            loc.distance_squared(generators[a])
+                .total_cmp(&loc.distance_squared(generators[b]))
+        });
+        let ids = nn_iter(&rtree, loc).map(|(id, _)| id).collect::<Vec<_>>();
+        assert_eq!(ids, expected);
+
+        // The first neighbours in a periodic box should be the periodic images closest to the query point.
+        let width = DVec3::ONE;
+        let periodic_distance = |loc: DVec3, generator: DVec3| {
+            let dx = loc - generator;
+            (dx - (dx / width).round() * width).length()
+        };
+        let mut expected = (0..generators.len()).collect::<Vec<_>>();
+        expected.sort_by(|&a, &b| {
+            periodic_distance(loc, generators[a]).total_cmp(&periodic_distance(loc, generators[b]))
+        });
+        for ((id, shift), &expected) in wrapping_nn_iter(&rtree, loc, width, 3)
+            .take(10)
+            .zip(expected.iter())
+        {
+            assert_eq!(id, expected);
+            let image = generators[id] + shift.unwrap_or(DVec3::ZERO);
+            assert!((image.distance(loc) - periodic_distance(loc, generators[id])).abs() < 1e-12);
+        }
+
+        // All periodic images are returned exactly once, in order of increasing distance
+        let images = wrapping_nn_iter(&rtree, loc, width, 3)
+            .map(|(id, shift)| {
+                (
+                    id,
+                    shift.unwrap_or(DVec3::ZERO).to_array().map(|x| x as i64),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(images.len(), 27 * generators.len());
+        let distances = images
+            .iter()
+            .map(|&(id, shift)| {
+                loc.distance(generators[id] + DVec3::from_array(shift.map(|x| x as f64)))
+            })
+            .collect::<Vec<_>>();
+        assert!(distances.windows(2).all(|w| w[0] <= w[1]));
+        let mut unique = images.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), images.len());
+    }
+}
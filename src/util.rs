@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use glam::{DMat3, DVec3};
 
 pub trait GetMutMultiple {
@@ -60,6 +62,218 @@ pub fn signed_area_tri(v0: DVec3, v1: DVec3, v2: DVec3, t: DVec3) -> f64 {
     n.length() * sign
 }
 
+/// Calculates the distance from `point` to the line segment from `a` to `b`.
+pub fn distance_to_segment(point: DVec3, a: DVec3, b: DVec3) -> f64 {
+    let ab = b - a;
+    let length_2 = ab.length_squared();
+    if length_2 == 0. {
+        return point.distance(a);
+    }
+    let t = (ab.dot(point - a) / length_2).clamp(0., 1.);
+    point.distance(a + t * ab)
+}
+
+/// Calculates the distance from `point` to the convex polygon with the given (ordered) `vertices` in the plane with
+/// the given unit `normal`.
+pub fn distance_to_polygon(point: DVec3, vertices: &[DVec3], normal: DVec3) -> f64 {
+    let n = vertices.len();
+    let edges = (0..n).map(|i| (vertices[i], vertices[(i + 1) % n]));
+    let height = normal.dot(point - vertices[0]);
+    let projected = point - height * normal;
+    // The projected point lies inside the polygon if it lies on the same side of all edges
+    let mut sides = edges
+        .clone()
+        .map(|(a, b)| (b - a).cross(projected - a).dot(normal));
+    if sides.clone().all(|side| side >= 0.) || sides.all(|side| side <= 0.) {
+        return height.abs();
+    }
+    edges
+        .map(|(a, b)| distance_to_segment(point, a, b))
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// Integrates a function of a single variable that is piecewise given by the antiderivatives `inner` (on
+/// `[-breakpoint, breakpoint]`) and `outer` (elsewhere) from `lower` to `upper`.
+fn integrate_piecewise(
+    lower: f64,
+    upper: f64,
+    breakpoint: Option<f64>,
+    inner: impl Fn(f64) -> f64,
+    outer: impl Fn(f64) -> f64,
+) -> f64 {
+    let (sign, lower, upper) = if lower <= upper {
+        (1., lower, upper)
+    } else {
+        (-1., upper, lower)
+    };
+    let mut points = vec![lower];
+    if let Some(breakpoint) = breakpoint {
+        points.extend(
+            [-breakpoint, breakpoint]
+                .into_iter()
+                .filter(|&x| x > lower && x < upper),
+        );
+    }
+    points.push(upper);
+    let integral = points
+        .windows(2)
+        .map(|w| {
+            let midpoint = 0.5 * (w[0] + w[1]);
+            if breakpoint.is_some_and(|breakpoint| midpoint.abs() < breakpoint) {
+                inner(w[1]) - inner(w[0])
+            } else {
+                outer(w[1]) - outer(w[0])
+            }
+        })
+        .sum::<f64>();
+    sign * integral
+}
+
+/// Calculates the signed area of the intersection of the disk with given `center` and `radius` with the triangle
+/// `center`, `a`, `b` in the xy-plane. The area is positive if the vertices are ordered counterclockwise as seen from
+/// the positive z-axis. Summing it over the edges of a polygon gives the area of the intersection of the polygon with
+/// the disk.
+pub fn disk_triangle_area(center: DVec3, radius: f64, a: DVec3, b: DVec3) -> f64 {
+    let Some(tangent) = (b - a).truncate().try_normalize() else {
+        return 0.;
+    };
+    let (a, b) = ((a - center).truncate(), (b - center).truncate());
+    // Signed distance of the edge to the center and positions of its endpoints along the edge
+    let h = tangent.perp_dot(-a);
+    if h == 0. || radius <= 0. {
+        return 0.;
+    }
+    let chord = (radius * radius - h * h).max(0.).sqrt();
+    // The edge lies inside the disk for |s| < chord
+    0.5 * h
+        * integrate_piecewise(
+            tangent.dot(a),
+            tangent.dot(b),
+            (chord > 0.).then_some(chord),
+            |s| s,
+            |s| radius * radius / h.abs() * (s / h.abs()).atan(),
+        )
+}
+
+/// Calculates the signed volume of the intersection of the ball with given `center` and `radius` with the cone from
+/// `center` over the convex polygon with the given `vertices` (ordered counterclockwise around the unit `normal`). The
+/// volume is positive if `normal` points away from `center`. Summing it over the faces of a polyhedron (with outward
+/// normals) gives the volume of the intersection of the polyhedron with the ball.
+pub fn ball_cone_volume(center: DVec3, radius: f64, vertices: &[DVec3], normal: DVec3) -> f64 {
+    let Some(&v0) = vertices.first() else {
+        return 0.;
+    };
+    let h = normal.dot(v0 - center);
+    if h == 0. || radius <= 0. {
+        return 0.;
+    }
+    // The ball intersects the plane of the polygon in a disk with radius `chord` around `foot`
+    let foot = center + h * normal;
+    let chord = (radius * radius - h * h).max(0.).sqrt();
+    let r3 = radius * radius * radius;
+    let n = vertices.len();
+    // Integrate h * min(d, radius)^3 / (3 d^3), with d the distance to the center, over the triangles joining the foot
+    // with the edges of the polygon, in polar coordinates around the foot
+    let integral = (0..n)
+        .map(|i| {
+            let (a, b) = (vertices[i] - foot, vertices[(i + 1) % n] - foot);
+            let edge = b - a;
+            let length_2 = edge.length_squared();
+            if length_2 == 0. {
+                return 0.;
+            }
+            // Angles are measured from the perpendicular from the foot to the edge
+            let perpendicular = a - a.dot(edge) / length_2 * edge;
+            let q = perpendicular.length();
+            if q <= 1e-14 * length_2.sqrt() {
+                return 0.;
+            }
+            let x_axis = perpendicular / q;
+            let y_axis = normal.cross(x_axis);
+            let angle = |p: DVec3| p.dot(y_axis).atan2(p.dot(x_axis));
+            let l = (h * h + q * q).sqrt();
+            integrate_piecewise(
+                angle(a),
+                angle(b),
+                (chord > q).then(|| (q / chord).acos()),
+                |phi| q * q * phi.tan() / 6.,
+                |phi| {
+                    (chord * chord / 6. + r3 / (3. * (h * h + chord * chord).sqrt())) * phi
+                        - r3 / (3. * h.abs()) * (h.abs() * phi.sin() / l).asin()
+                },
+            )
+        })
+        .sum::<f64>();
+    h * integral
+}
+
+pub fn retain<T>(v: &mut Vec<T>, mask: &[bool]) {
+    let mut iter = mask.iter();
+    v.retain(|_| *iter.next().unwrap());
+}
+
+/// Weld points that lie within `tolerance` of each other.
+///
+/// Returns the welded points and for each of the input points the index of its welded counterpart.
+pub fn weld(points: &[DVec3], tolerance: f64) -> (Vec<DVec3>, Vec<usize>) {
+    let key = |p: DVec3| {
+        let k = (p / tolerance).floor();
+        (k.x as i64, k.y as i64, k.z as i64)
+    };
+    let mut grid: HashMap<(i64, i64, i64), Vec<usize>> = HashMap::new();
+    let mut welded: Vec<DVec3> = vec![];
+    let indices = points
+        .iter()
+        .map(|&p| {
+            let (i, j, k) = key(p);
+            for di in -1..=1 {
+                for dj in -1..=1 {
+                    for dk in -1..=1 {
+                        if let Some(candidates) = grid.get(&(i + di, j + dj, k + dk)) {
+                            if let Some(&idx) = candidates.iter().find(|&&idx| {
+                                welded[idx].distance_squared(p) <= tolerance * tolerance
+                            }) {
+                                return idx;
+                            }
+                        }
+                    }
+                }
+            }
+            let idx = welded.len();
+            welded.push(p);
+            grid.entry((i, j, k)).or_default().push(idx);
+            idx
+        })
+        .collect();
+
+    (welded, indices)
+}
+
+/// A simple stopwatch.
+///
+/// `std::time::Instant` is not available on `wasm32-unknown-unknown` (it panics), on that target the elapsed time is
+/// always zero.
+pub(crate) struct Timer {
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    start: std::time::Instant,
+}
+
+impl Timer {
+    pub(crate) fn start() -> Self {
+        Self {
+            #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+            start: std::time::Instant::now(),
+        }
+    }
+
+    pub(crate) fn elapsed(&self) -> std::time::Duration {
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        return self.start.elapsed();
+        #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+        return std::time::Duration::ZERO;
+    }
+}
+
 #[cfg(test)]
 mod test {
     use glam::DVec3;
@@ -121,9 +335,18 @@ mod test {
         let area3 = signed_area_tri(v0, v1, v2, t);
         assert_eq!(area, area3)
     }
-}
 
-pub fn retain<T>(v: &mut Vec<T>, mask: &[bool]) {
-    let mut iter = mask.iter();
-    v.retain(|_| *iter.next().unwrap());
+    #[test]
+    fn test_weld() {
+        let points = [
+            DVec3::ZERO,
+            DVec3::X,
+            DVec3::splat(1e-12),
+            DVec3::X - DVec3::splat(1e-12),
+            DVec3::Y,
+        ];
+        let (welded, indices) = weld(&points, 1e-10);
+        assert_eq!(welded.len(), 3);
+        assert_eq!(indices, vec![0, 1, 0, 1, 2]);
+    }
 }
@@ -1,28 +1,129 @@
+use std::{any::Any, collections::HashSet, sync::Arc, time::Duration};
+
 use glam::DVec3;
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
 use rstar::RTree;
-#[cfg(feature = "hdf5")]
-use std::error::Error;
-#[cfg(feature = "hdf5")]
-use std::path::Path;
 
 use crate::{
-    integrators::{ScalarVoronoiFaceIntegrator, VectorVoronoiFaceIntegrator},
-    rtree_nn::{build_rtree, nn_iter, wrapping_nn_iter},
-    util::retain,
+    integrators::{
+        FaceIntegralStore, FaceIntegrators, GenericFaceIntegratorFactory, IntegratorId,
+        IntegratorKind,
+    },
+    rtree_nn::{build_rtree, candidates_within, nn_iter, wrapping_nn_iter},
+    util::{retain, Timer},
 };
 
+pub use agglomeration::{Agglomeration, SuperCell, SuperFace};
+use build_options::{bounded_k_radius, CandidateSearch};
+pub use build_options::{BuildOptions, NeighbourSearch, SafetyCriterion};
+#[cfg(feature = "geo")]
+pub use clip_polygon::ClippedCell2D;
+pub use comparison::{CellDifference, ComparisonReport};
+pub use directed_face::DirectedFace;
+pub use fracture::FracturePiece;
 pub use generator::Generator;
+pub use generator_set::GeneratorSet;
+pub use gradients::GradientWeights;
+#[cfg(feature = "petgraph")]
+pub use graph::AdjacencyEdge;
+pub use half_edge::{HalfEdge, HalfEdgeFace, HalfEdgeMesh};
+pub use hierarchy::HierarchicalVoronoi;
+pub use interpolation::Interpolation;
+pub use kernel::Kernel;
+use lattice::LatticeBins;
+pub use limiter::LimiterGeometry;
+pub use orb::OrbDecomposition;
+pub use profile::{BuildProfile, CellProfile};
+pub use quality::CellQuality;
+pub use remap::SupermeshCell;
+#[cfg(feature = "hdf5")]
+pub use save::{Compression, OutputDataset, SaveOptions};
+pub use skeleton::{Skeleton, SkeletonElements};
+pub use slice::{Polygon2D, SlicePlane};
+pub use spherical::{SphericalVoronoi, SphericalVoronoiCell, SphericalVoronoiEdge};
+pub use triangle_mesh::TriangleMesh;
+pub use validation::{CellDefect, TopologyDefect, ValidationReport};
+pub use voronoi_1d::{Voronoi1D, VoronoiCell1D};
+pub use voronoi_2d::{Voronoi2D, VoronoiCell2D, VoronoiEdge2D};
 use voronoi_cell::ConvexCell;
 pub use voronoi_cell::VoronoiCell;
-pub use voronoi_face::VoronoiFace;
+pub use voronoi_face::{Boundary, VoronoiFace};
+pub use wireframe::{VoronoiEdge, Wireframe};
 
+mod agglomeration;
+#[cfg(feature = "bevy")]
+mod bevy;
+mod build_options;
+#[cfg(feature = "geo")]
+mod clip_polygon;
+mod comparison;
+mod contour;
+mod csv_export;
+mod delaunay;
+mod directed_face;
+mod foam;
+mod fracture;
 mod generator;
+mod generator_set;
+#[cfg(feature = "geojson")]
+mod geo_json;
+mod gmsh;
+mod gradients;
+#[cfg(feature = "petgraph")]
+mod graph;
+mod half_edge;
+mod halo;
+mod hierarchy;
+mod interpolation;
+mod kernel;
+#[cfg(feature = "sprs")]
+mod laplacian;
+mod lattice;
+mod limiter;
+mod moving_mesh;
+mod orb;
+mod partition;
+mod polytope;
+mod profile;
+mod proximity;
+mod quality;
+mod raster;
+mod remap;
+#[cfg(feature = "hdf5")]
+mod save;
+mod segment;
+mod skeleton;
+mod slice;
+mod spherical;
+mod stippling;
+mod triangle_mesh;
+mod validation;
+mod voro_pp;
+mod voronoi_1d;
+mod voronoi_2d;
 mod voronoi_cell;
 mod voronoi_face;
+mod wireframe;
+
+/// The number of cells that are built together in one (parallel) task.
+const CHUNK_SIZE: usize = 256;
+
+/// The per-cell faces, face vertices and vector, scalar and generic face integrals of a chunk of cells, zipped
+/// together.
+type ChunkBuffers<'a> = (
+    (
+        (
+            (&'a mut [Vec<VoronoiFace>], &'a mut [Vec<DVec3>]),
+            &'a mut [Vec<DVec3>],
+        ),
+        &'a mut [Vec<f64>],
+    ),
+    &'a mut [Vec<Box<dyn FaceIntegralStore>>],
+);
 
 #[derive(Clone, Copy)]
+#[allow(clippy::enum_variant_names)]
 pub(crate) enum Dimensionality {
     Dimensionality1D,
     Dimensionality2D,
@@ -50,16 +151,167 @@ impl From<Dimensionality> for usize {
     }
 }
 
+/// The cells to construct in a (partial) tesselation.
+#[derive(Clone, Copy)]
+pub(crate) enum CellSelection<'a> {
+    All,
+    Mask(&'a [bool]),
+    Predicate(&'a (dyn Fn(usize) -> bool + Sync)),
+}
+
+impl CellSelection<'_> {
+    /// Whether the cell with the given index has to be constructed.
+    pub(crate) fn contains(&self, idx: usize) -> bool {
+        match self {
+            CellSelection::All => true,
+            CellSelection::Mask(mask) => mask[idx],
+            CellSelection::Predicate(predicate) => predicate(idx),
+        }
+    }
+}
+
+impl<'a> From<Option<&'a [bool]>> for CellSelection<'a> {
+    fn from(mask: Option<&'a [bool]>) -> Self {
+        match mask {
+            Some(mask) => CellSelection::Mask(mask),
+            None => CellSelection::All,
+        }
+    }
+}
+
+/// Normalize the unused components of the simulation volume, so that the lower dimensional volumes will be correct.
+fn normalize_simulation_volume(
+    anchor: &mut DVec3,
+    width: &mut DVec3,
+    dimensionality: Dimensionality,
+) {
+    if let Dimensionality::Dimensionality1D = dimensionality {
+        anchor.y = -0.5;
+        width.y = 1.;
+    };
+    if let Dimensionality::Dimensionality1D | Dimensionality::Dimensionality2D = dimensionality {
+        anchor.z = -0.5;
+        width.z = 1.;
+    }
+}
+
+/// Add the twins of the periodic faces constructed only once (see [`BuildOptions::deduplicate_periodic_faces`]) or
+/// whose right cells are not constructed (see [`BuildOptions::symmetric_partial_faces`]) to the faces of their right
+/// cells. The extra face integrals of the twins are integrated over a triangulation of the translated face.
+#[allow(clippy::too_many_arguments)]
+fn mirror_periodic_faces(
+    generators: &[Generator],
+    mask: CellSelection,
+    deduplicate_periodic_faces: bool,
+    symmetric_partial_faces: bool,
+    faces: &mut [Vec<VoronoiFace>],
+    face_vertices: &mut [Vec<DVec3>],
+    vector_face_integrals: &mut [Vec<DVec3>],
+    scalar_face_integrals: &mut [Vec<f64>],
+    generic_face_integrals: &mut [Vec<Box<dyn FaceIntegralStore>>],
+    face_integrators: &FaceIntegrators,
+) {
+    let mut twins = vec![];
+    for cell_faces in faces.iter() {
+        let mut vertex_offset = 0;
+        for face in cell_faces {
+            let vertex_range = vertex_offset..vertex_offset + face.vertex_count();
+            vertex_offset = vertex_range.end;
+            if let (Some(right), Some(shift)) = (face.right(), face.shift()) {
+                let mirror = if mask.contains(right) {
+                    deduplicate_periodic_faces && face.is_primary()
+                } else {
+                    symmetric_partial_faces
+                };
+                if mirror {
+                    twins.push((face.periodic_twin(), face.left(), vertex_range, shift));
+                }
+            }
+        }
+    }
+
+    for (twin, left, vertex_range, shift) in twins {
+        let right = twin.left();
+        // Reverse the orientation of the face, which is now seen from the other side.
+        let vertices = face_vertices[left][vertex_range]
+            .iter()
+            .rev()
+            .map(|&v| v - shift)
+            .collect::<Vec<_>>();
+        if !face_integrators.is_empty() {
+            let left_loc = generators[right].loc();
+            let right_loc = generators[left].loc() - shift;
+            let mut vector_integrators = face_integrators
+                .vector_integrators()
+                .iter()
+                .map(|get_integrator| {
+                    let mut integrator = get_integrator();
+                    integrator.init(right, Some(left));
+                    integrator
+                })
+                .collect::<Vec<_>>();
+            let mut scalar_integrators = face_integrators
+                .scalar_integrators()
+                .iter()
+                .map(|get_integrator| {
+                    let mut integrator = get_integrator();
+                    integrator.init(right, Some(left));
+                    integrator
+                })
+                .collect::<Vec<_>>();
+            let mut generic_integrators = face_integrators
+                .generic_integrators()
+                .iter()
+                .map(|factory| {
+                    let mut integrator = factory.integrator();
+                    integrator.init(right, Some(left));
+                    integrator
+                })
+                .collect::<Vec<_>>();
+            for i in 1..vertices.len().saturating_sub(1) {
+                let (v0, mut v1, mut v2) = (vertices[0], vertices[i], vertices[i + 1]);
+                // Orient the triangles as in the construction: with positive area as seen from the left generator
+                if (left_loc - v0).dot((v1 - v0).cross(v2 - v0)) < 0. {
+                    (v1, v2) = (v2, v1);
+                }
+                for integrator in vector_integrators.iter_mut() {
+                    integrator.collect(v0, v1, v2, left_loc, right_loc);
+                }
+                for integrator in scalar_integrators.iter_mut() {
+                    integrator.collect(v0, v1, v2, left_loc, right_loc);
+                }
+                for integrator in generic_integrators.iter_mut() {
+                    integrator.collect(v0, v1, v2, left_loc, right_loc);
+                }
+            }
+            vector_face_integrals[right].extend(vector_integrators.iter().map(|i| i.finalize()));
+            scalar_face_integrals[right].extend(scalar_integrators.iter().map(|i| i.finalize()));
+            for (integrator, store) in generic_integrators
+                .iter()
+                .zip(generic_face_integrals[right].iter_mut())
+            {
+                integrator.finalize_into(store.as_mut());
+            }
+        }
+        face_vertices[right].extend(vertices);
+        faces[right].push(twin);
+    }
+}
+
 /// The main Voronoi struct
 pub struct Voronoi {
     anchor: DVec3,
     width: DVec3,
+    periodic: bool,
     cells: Vec<VoronoiCell>,
     faces: Vec<VoronoiFace>,
+    face_vertices: Vec<DVec3>,
     vector_face_integrals: Vec<Vec<DVec3>>,
     scalar_face_integrals: Vec<Vec<f64>>,
+    generic_face_integrals: Vec<Box<dyn FaceIntegralStore>>,
     cell_face_connections: Vec<usize>,
     dimensionality: Dimensionality,
+    rtree: Arc<RTree<Generator>>,
 }
 
 impl Voronoi {
@@ -74,29 +326,26 @@ impl Voronoi {
     /// * `width` - The width of the simulation volume. Also determines the period of periodic Voronoi tesselations.
     /// * `dimensionality` - The dimensionality of the Voronoi tesselation. The algorithm is mainly aimed at constructiong 3D Voronoi tesselations, but can be used for 1 or 2D as well.
     /// * `periodic` - Whether to apply periodic boundary conditions to the Voronoi tesselation.
+    /// * `face_integrators` - If `Some`: The extra face integrators to evaluate on every face (see [`FaceIntegrators`]).
     pub fn build(
         generators: &[DVec3],
         anchor: DVec3,
         width: DVec3,
         dimensionality: usize,
         periodic: bool,
-        vector_face_integrators: Option<
-            &[Box<dyn Fn() -> Box<dyn VectorVoronoiFaceIntegrator> + Send + Sync>],
-        >,
-        scalar_face_integrators: Option<
-            &[Box<dyn Fn() -> Box<dyn ScalarVoronoiFaceIntegrator> + Send + Sync>],
-        >,
+        face_integrators: Option<&FaceIntegrators>,
     ) -> Self {
         Self::build_internal(
             generators,
-            None,
+            CellSelection::All,
             anchor,
             width,
             dimensionality,
             periodic,
-            vector_face_integrators,
-            scalar_face_integrators,
+            face_integrators,
+            &BuildOptions::default(),
         )
+        .0
     }
 
     /// Same as `build`, but now, only a subset of the voronoi cells is fully constructed.
@@ -116,170 +365,504 @@ impl Voronoi {
         width: DVec3,
         dimensionality: usize,
         periodic: bool,
-        vector_face_integrators: Option<
-            &[Box<dyn Fn() -> Box<dyn VectorVoronoiFaceIntegrator> + Send + Sync>],
-        >,
-        scalar_face_integrators: Option<
-            &[Box<dyn Fn() -> Box<dyn ScalarVoronoiFaceIntegrator> + Send + Sync>],
-        >,
+        face_integrators: Option<&FaceIntegrators>,
     ) -> Self {
         Self::build_internal(
             generators,
-            Some(mask),
+            CellSelection::Mask(mask),
             anchor,
             width,
             dimensionality,
             periodic,
-            vector_face_integrators,
-            scalar_face_integrators,
+            face_integrators,
+            &BuildOptions::default(),
         )
+        .0
     }
 
-    fn build_internal(
+    /// Same as [`Voronoi::build_partial`], but only the cells of the generators with the given `indices` are fully
+    /// constructed, without the need to build a mask (see also [`crate::mask`]).
+    pub fn build_partial_indices(
         generators: &[DVec3],
-        mask: Option<&[bool]>,
-        mut anchor: DVec3,
-        mut width: DVec3,
+        indices: &[usize],
+        anchor: DVec3,
+        width: DVec3,
+        dimensionality: usize,
+        periodic: bool,
+        face_integrators: Option<&FaceIntegrators>,
+    ) -> Self {
+        let mut indices = indices.to_vec();
+        indices.sort_unstable();
+        Self::build_partial_by(
+            generators,
+            |idx, _| indices.binary_search(&idx).is_ok(),
+            anchor,
+            width,
+            dimensionality,
+            periodic,
+            face_integrators,
+        )
+    }
+
+    /// Same as [`Voronoi::build_partial`], but the cells to construct are selected by a `predicate`, which is called
+    /// with the index and position of a generator and returns `true` if its cell has to be fully constructed.
+    ///
+    /// This avoids materializing a mask for very large sets of generators when the selection is e.g. spatial. The
+    /// predicate may be called multiple times for the same generator.
+    pub fn build_partial_by(
+        generators: &[DVec3],
+        predicate: impl Fn(usize, DVec3) -> bool + Sync,
+        anchor: DVec3,
+        width: DVec3,
         dimensionality: usize,
         periodic: bool,
-        vector_face_integrators: Option<
-            &[Box<dyn Fn() -> Box<dyn VectorVoronoiFaceIntegrator> + Send + Sync>],
-        >,
-        scalar_face_integrators: Option<
-            &[Box<dyn Fn() -> Box<dyn ScalarVoronoiFaceIntegrator> + Send + Sync>],
-        >,
+        face_integrators: Option<&FaceIntegrators>,
     ) -> Self {
-        let dimensionality = dimensionality.into();
-        let vector_face_integrators = vector_face_integrators.unwrap_or_default();
-        let scalar_face_integrators = scalar_face_integrators.unwrap_or_default();
-
-        // Normalize the unused components of the simulation volume, so that the lower dimensional volumes will be correct.
-        if let Dimensionality::Dimensionality1D = dimensionality {
-            anchor.y = -0.5;
-            width.y = 1.;
+        let selected = |idx: usize| predicate(idx, generators[idx]);
+        Self::build_internal(
+            generators,
+            CellSelection::Predicate(&selected),
+            anchor,
+            width,
+            dimensionality,
+            periodic,
+            face_integrators,
+            &BuildOptions::default(),
+        )
+        .0
+    }
+
+    /// Same as `build` (or `build_partial` if a `mask` is given), but also return a [`BuildProfile`] with performance
+    /// counters of the construction (time per phase, nearest neighbour candidates and clipping planes tested per cell).
+    ///
+    /// This is useful for tuning the safety criterion and nearest neighbour search on new datasets.
+    pub fn build_profiled(
+        generators: &[DVec3],
+        mask: Option<&[bool]>,
+        anchor: DVec3,
+        width: DVec3,
+        dimensionality: usize,
+        periodic: bool,
+        face_integrators: Option<&FaceIntegrators>,
+    ) -> (Self, BuildProfile) {
+        Self::build_internal(
+            generators,
+            mask.into(),
+            anchor,
+            width,
+            dimensionality,
+            periodic,
+            face_integrators,
+            &BuildOptions::default(),
+        )
+    }
+
+    /// Same as [`Voronoi::build_profiled`], but with the given [`BuildOptions`] to tune the construction.
+    #[allow(clippy::too_many_arguments)]
+    pub fn build_with_options(
+        generators: &[DVec3],
+        mask: Option<&[bool]>,
+        anchor: DVec3,
+        width: DVec3,
+        dimensionality: usize,
+        periodic: bool,
+        face_integrators: Option<&FaceIntegrators>,
+        options: &BuildOptions,
+    ) -> (Self, BuildProfile) {
+        Self::build_internal(
+            generators,
+            mask.into(),
+            anchor,
+            width,
+            dimensionality,
+            periodic,
+            face_integrators,
+            options,
+        )
+    }
+
+    /// Same as [`Voronoi::build_with_options`], but reuse the generators and r-tree of the given [`GeneratorSet`]
+    /// (e.g. to rebuild the tesselation of a slowly moving point set after [`GeneratorSet::update_positions`]). The
+    /// dimensionality of the tesselation is the one of the set.
+    ///
+    /// The r-tree is shared with the returned tesselation, so the [`BuildProfile::rtree_time`] is zero.
+    pub fn build_from(
+        generators: &GeneratorSet,
+        mask: Option<&[bool]>,
+        anchor: DVec3,
+        width: DVec3,
+        periodic: bool,
+        face_integrators: Option<&FaceIntegrators>,
+        options: &BuildOptions,
+    ) -> (Self, BuildProfile) {
+        Self::build_from_set(
+            generators,
+            mask.into(),
+            anchor,
+            width,
+            periodic,
+            face_integrators,
+            options,
+            Duration::ZERO,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn build_internal(
+        generators: &[DVec3],
+        mask: CellSelection,
+        anchor: DVec3,
+        width: DVec3,
+        dimensionality: usize,
+        periodic: bool,
+        face_integrators: Option<&FaceIntegrators>,
+        options: &BuildOptions,
+    ) -> (Self, BuildProfile) {
+        let timer = Timer::start();
+        let generators = {
+            span!("build_rtree");
+            GeneratorSet::new(generators, dimensionality)
         };
-        if let Dimensionality::Dimensionality1D | Dimensionality::Dimensionality2D = dimensionality
-        {
-            anchor.z = -0.5;
-            width.z = 1.;
-        }
+        let rtree_time = timer.elapsed();
+        Self::build_from_set(
+            &generators,
+            mask,
+            anchor,
+            width,
+            periodic,
+            face_integrators,
+            options,
+            rtree_time,
+        )
+    }
 
-        let generators: Vec<Generator> = generators
-            .iter()
-            .enumerate()
-            .map(|(id, &loc)| Generator::new(id, loc, dimensionality))
-            .collect();
+    #[allow(clippy::too_many_arguments)]
+    fn build_from_set(
+        generator_set: &GeneratorSet,
+        mask: CellSelection,
+        mut anchor: DVec3,
+        mut width: DVec3,
+        periodic: bool,
+        face_integrators: Option<&FaceIntegrators>,
+        options: &BuildOptions,
+        rtree_time: Duration,
+    ) -> (Self, BuildProfile) {
+        let generators = generator_set.generators();
+        let rtree = generator_set.rtree();
+        let dimensionality = generator_set.dimensionality();
+        span!("build_voronoi", n_generators = generators.len());
+        let no_face_integrators = FaceIntegrators::default();
+        let face_integrators = face_integrators.unwrap_or(&no_face_integrators);
+        face_integrators.check_generator_count(generators.len());
+
+        normalize_simulation_volume(&mut anchor, &mut width, dimensionality);
 
-        let rtree = build_rtree(&generators);
         let simulation_volume =
-            ConvexCell::init_simulation_volume(anchor, width, periodic, dimensionality);
-
-        fn maybe_build_cell(
-            idx: usize,
-            generators: &[Generator],
-            mask: Option<&[bool]>,
-            faces: &mut Vec<VoronoiFace>,
-            vector_face_integrals: &mut Vec<DVec3>,
-            scalar_face_integrals: &mut Vec<f64>,
-            rtree: &RTree<Generator>,
-            simulation_volume: &ConvexCell,
+            ConvexCell::init_simulation_volume(anchor, width, periodic, dimensionality)
+                .with_single_precision_prechecks(options.single_precision_prechecks);
+        let lattice = match options.neighbour_search {
+            NeighbourSearch::Lattice { shape } => {
+                LatticeBins::new(generators, shape, anchor, width, periodic, dimensionality)
+            }
+            _ => None,
+        };
+        let candidate_search = match options.neighbour_search {
+            NeighbourSearch::Incremental => CandidateSearch::Incremental,
+            NeighbourSearch::BoundedK { k } => CandidateSearch::BoundedK {
+                k,
+                radius: bounded_k_radius(k, generators.len(), width, dimensionality),
+            },
+            NeighbourSearch::Lattice { .. } => match lattice.as_ref() {
+                Some(lattice) => CandidateSearch::Lattice(lattice),
+                // Not a lattice
+                None => CandidateSearch::Incremental,
+            },
+        };
+
+        /// The state shared by the construction of all cells.
+        struct CellBuilder<'a> {
+            generators: &'a [Generator],
+            mask: CellSelection<'a>,
+            rtree: &'a RTree<Generator>,
+            simulation_volume: &'a ConvexCell,
             width: DVec3,
             dimensionality: Dimensionality,
             periodic: bool,
-            vector_face_integrators: &[Box<
-                dyn Fn() -> Box<dyn VectorVoronoiFaceIntegrator> + Send + Sync,
-            >],
-            scalar_face_integrators: &[Box<
-                dyn Fn() -> Box<dyn ScalarVoronoiFaceIntegrator> + Send + Sync,
-            >],
-        ) -> VoronoiCell {
-            if mask.map_or(true, |mask| mask[idx]) {
-                let loc = generators[idx].loc();
-                debug_assert_eq!(generators[idx].id(), idx);
-                let mut convex_cell = ConvexCell::init(loc, idx, simulation_volume, dimensionality);
-                let nearest_neighbours = if periodic {
-                    wrapping_nn_iter(&rtree, loc, width, dimensionality)
-                } else {
-                    nn_iter(&rtree, loc)
-                };
-                convex_cell.build(&generators, nearest_neighbours, dimensionality);
-                VoronoiCell::from_convex_cell(
-                    &convex_cell,
-                    faces,
-                    vector_face_integrals,
-                    scalar_face_integrals,
+            candidate_search: CandidateSearch<'a>,
+            safety_criterion: SafetyCriterion,
+            deduplicate_periodic_faces: bool,
+            face_integrators: &'a FaceIntegrators,
+        }
+
+        impl CellBuilder<'_> {
+            fn maybe_build_cell(
+                &self,
+                idx: usize,
+                faces: &mut Vec<VoronoiFace>,
+                face_vertices: &mut Vec<DVec3>,
+                vector_face_integrals: &mut Vec<DVec3>,
+                scalar_face_integrals: &mut Vec<f64>,
+                generic_face_integrals: &mut [Box<dyn FaceIntegralStore>],
+            ) -> (VoronoiCell, CellProfile) {
+                let CellBuilder {
+                    generators,
                     mask,
-                    vector_face_integrators,
-                    scalar_face_integrators,
-                )
-            } else {
-                VoronoiCell::default()
+                    rtree,
+                    simulation_volume,
+                    width,
+                    dimensionality,
+                    periodic,
+                    candidate_search,
+                    safety_criterion,
+                    deduplicate_periodic_faces,
+                    face_integrators,
+                } = *self;
+                if mask.contains(idx) {
+                    let loc = generators[idx].loc();
+                    debug_assert_eq!(generators[idx].id(), idx);
+                    let mut convex_cell =
+                        ConvexCell::init(loc, idx, simulation_volume, dimensionality);
+                    let nearest_neighbours = || {
+                        if periodic {
+                            wrapping_nn_iter(rtree, loc, width, dimensionality)
+                        } else {
+                            nn_iter(rtree, loc)
+                        }
+                    };
+                    let mut profile = match candidate_search {
+                        CandidateSearch::Incremental => convex_cell.build(
+                            generators,
+                            nearest_neighbours(),
+                            safety_criterion,
+                            dimensionality,
+                        ),
+                        CandidateSearch::BoundedK { k, radius } => {
+                            let mut candidates = candidates_within(
+                                rtree,
+                                loc,
+                                radius,
+                                periodic.then_some(width),
+                                dimensionality,
+                            );
+                            // The candidates include the generator itself
+                            let bound = if candidates.len() > k + 1 {
+                                let bound = candidates[k + 1].2;
+                                candidates.truncate(k + 1);
+                                bound
+                            } else {
+                                radius
+                            };
+                            convex_cell.build_bounded(
+                                generators,
+                                &candidates,
+                                bound,
+                                nearest_neighbours,
+                                safety_criterion,
+                                dimensionality,
+                            )
+                        }
+                        CandidateSearch::Lattice(lattice) => {
+                            let (candidates, bound) = lattice.candidates(generators, loc);
+                            convex_cell.build_bounded(
+                                generators,
+                                &candidates,
+                                bound,
+                                nearest_neighbours,
+                                safety_criterion,
+                                dimensionality,
+                            )
+                        }
+                    };
+                    profile.safety_radius = convex_cell.safety_radius();
+                    let cell = VoronoiCell::from_convex_cell(
+                        &convex_cell,
+                        faces,
+                        face_vertices,
+                        vector_face_integrals,
+                        scalar_face_integrals,
+                        generic_face_integrals,
+                        mask,
+                        deduplicate_periodic_faces,
+                        face_integrators,
+                    );
+                    (cell, profile)
+                } else {
+                    // Keep the position of the generator, e.g. to resolve periodic neighbours
+                    (
+                        VoronoiCell::default().with_loc(generators[idx].loc()),
+                        CellProfile::default(),
+                    )
+                }
             }
         }
 
+        let cell_builder = CellBuilder {
+            generators,
+            mask,
+            rtree,
+            simulation_volume: &simulation_volume,
+            width,
+            dimensionality,
+            periodic,
+            candidate_search,
+            safety_criterion: options.safety_criterion,
+            deduplicate_periodic_faces: options.deduplicate_periodic_faces,
+            face_integrators,
+        };
+        let timer = Timer::start();
         let mut faces: Vec<Vec<VoronoiFace>> = generators.iter().map(|_| vec![]).collect();
+        let mut face_vertices: Vec<Vec<DVec3>> = generators.iter().map(|_| vec![]).collect();
         let mut vector_face_integrals: Vec<Vec<DVec3>> =
             generators.iter().map(|_| vec![]).collect();
         let mut scalar_face_integrals: Vec<Vec<f64>> = generators.iter().map(|_| vec![]).collect();
+        let mut generic_face_integrals: Vec<Vec<Box<dyn FaceIntegralStore>>> = generators
+            .iter()
+            .map(|_| {
+                face_integrators
+                    .generic_integrators()
+                    .iter()
+                    .map(|factory| factory.store())
+                    .collect()
+            })
+            .collect();
+        let build_chunk = |(
+            chunk_idx,
+            (
+                (((faces, face_vertices), vector_face_integrals), scalar_face_integrals),
+                generic_face_integrals,
+            ),
+        ): (usize, ChunkBuffers)| {
+            span!("build_chunk", chunk_idx);
+            faces
+                .iter_mut()
+                .zip(face_vertices.iter_mut())
+                .zip(vector_face_integrals.iter_mut())
+                .zip(scalar_face_integrals.iter_mut())
+                .zip(generic_face_integrals.iter_mut())
+                .enumerate()
+                .map(
+                    |(
+                        idx,
+                        (
+                            (
+                                ((faces, face_vertices), vector_face_integrals),
+                                scalar_face_integrals,
+                            ),
+                            generic_face_integrals,
+                        ),
+                    )| {
+                        cell_builder.maybe_build_cell(
+                            chunk_idx * CHUNK_SIZE + idx,
+                            faces,
+                            face_vertices,
+                            vector_face_integrals,
+                            scalar_face_integrals,
+                            generic_face_integrals,
+                        )
+                    },
+                )
+                .collect::<Vec<_>>()
+        };
         #[cfg(feature = "rayon")]
-        let cells = faces
-            .par_iter_mut()
-            .zip(vector_face_integrals.par_iter_mut())
-            .zip(scalar_face_integrals.par_iter_mut())
+        let (cells, cell_profiles): (Vec<_>, Vec<_>) = faces
+            .par_chunks_mut(CHUNK_SIZE)
+            .zip(face_vertices.par_chunks_mut(CHUNK_SIZE))
+            .zip(vector_face_integrals.par_chunks_mut(CHUNK_SIZE))
+            .zip(scalar_face_integrals.par_chunks_mut(CHUNK_SIZE))
+            .zip(generic_face_integrals.par_chunks_mut(CHUNK_SIZE))
             .enumerate()
-            .map(
-                |(idx, ((faces, vector_face_integrals), scalar_face_integrals))| {
-                    maybe_build_cell(
-                        idx,
-                        &generators,
-                        mask,
-                        faces,
-                        vector_face_integrals,
-                        scalar_face_integrals,
-                        &rtree,
-                        &simulation_volume,
-                        width,
-                        dimensionality,
-                        periodic,
-                        vector_face_integrators,
-                        scalar_face_integrators,
-                    )
-                },
-            )
-            .collect();
+            .flat_map_iter(build_chunk)
+            .unzip();
         #[cfg(not(feature = "rayon"))]
-        let cells = faces
-            .iter_mut()
-            .zip(vector_face_integrals.iter_mut())
-            .zip(scalar_face_integrals.iter_mut())
+        let (cells, cell_profiles): (Vec<_>, Vec<_>) = faces
+            .chunks_mut(CHUNK_SIZE)
+            .zip(face_vertices.chunks_mut(CHUNK_SIZE))
+            .zip(vector_face_integrals.chunks_mut(CHUNK_SIZE))
+            .zip(scalar_face_integrals.chunks_mut(CHUNK_SIZE))
+            .zip(generic_face_integrals.chunks_mut(CHUNK_SIZE))
             .enumerate()
-            .map(
-                |(idx, ((faces, vector_face_integrals), scalar_face_integrals))| {
-                    maybe_build_cell(
-                        idx,
-                        &generators,
-                        mask,
-                        faces,
-                        vector_face_integrals,
-                        scalar_face_integrals,
-                        &rtree,
-                        &simulation_volume,
-                        width,
-                        dimensionality,
-                        periodic,
-                        vector_face_integrators,
-                        scalar_face_integrators,
-                    )
-                },
-            )
-            .collect();
+            .flat_map(build_chunk)
+            .unzip();
+        if periodic && (options.deduplicate_periodic_faces || options.symmetric_partial_faces) {
+            span!("mirror_periodic_faces");
+            mirror_periodic_faces(
+                generators,
+                mask,
+                options.deduplicate_periodic_faces,
+                options.symmetric_partial_faces,
+                &mut faces,
+                &mut face_vertices,
+                &mut vector_face_integrals,
+                &mut scalar_face_integrals,
+                &mut generic_face_integrals,
+                face_integrators,
+            );
+        }
+        let cells_time = timer.elapsed();
+
+        let timer = Timer::start();
+        let voronoi = Self::assemble(
+            anchor,
+            width,
+            periodic,
+            dimensionality,
+            cells,
+            faces,
+            face_vertices,
+            vector_face_integrals,
+            scalar_face_integrals,
+            generic_face_integrals,
+            face_integrators.vector_integrators().len(),
+            face_integrators.scalar_integrators().len(),
+            face_integrators.generic_integrators(),
+            Some(Arc::clone(rtree)),
+        );
+        let profile = BuildProfile {
+            cells: cell_profiles,
+            rtree_time,
+            cells_time,
+            assemble_time: timer.elapsed(),
+        };
+
+        (voronoi, profile)
+    }
 
+    /// Flatten the per-cell faces, face vertices and face integrals, filter out the faces that
+    /// do not match the dimensionality and link the faces to their cells.
+    #[allow(clippy::too_many_arguments)]
+    fn assemble(
+        anchor: DVec3,
+        width: DVec3,
+        periodic: bool,
+        dimensionality: Dimensionality,
+        cells: Vec<VoronoiCell>,
+        faces: Vec<Vec<VoronoiFace>>,
+        face_vertices: Vec<Vec<DVec3>>,
+        vector_face_integrals: Vec<Vec<DVec3>>,
+        scalar_face_integrals: Vec<Vec<f64>>,
+        generic_face_integrals: Vec<Vec<Box<dyn FaceIntegralStore>>>,
+        vector_face_integrator_count: usize,
+        scalar_face_integrator_count: usize,
+        generic_face_integrators: &[GenericFaceIntegratorFactory],
+        rtree: Option<Arc<RTree<Generator>>>,
+    ) -> Self {
+        span!("assemble");
         // flatten faces and filter on dimensionality
         let mut faces = faces.into_iter().flatten().collect::<Vec<_>>();
         let face_mask = faces
             .iter()
             .map(|f| f.has_valid_dimensionality(dimensionality))
             .collect::<Vec<_>>();
+
+        // Flatten and filter face vertices (this has to happen before the faces themselves are filtered)
+        let mut face_vertices = face_vertices.into_iter().flatten().collect::<Vec<_>>();
+        let vertex_mask = faces
+            .iter()
+            .zip(face_mask.iter())
+            .flat_map(|(face, &keep)| std::iter::repeat_n(keep, face.vertex_count()))
+            .collect::<Vec<_>>();
+        debug_assert_eq!(vertex_mask.len(), face_vertices.len());
+        retain(&mut face_vertices, &vertex_mask);
         retain(&mut faces, &face_mask);
 
         // Flatten and filter face integrals
@@ -287,7 +870,7 @@ impl Voronoi {
             .into_iter()
             .flatten()
             .collect::<Vec<_>>();
-        let n = vector_face_integrators.len();
+        let n = vector_face_integrator_count;
         let mut vector_face_integrals = (0..n)
             .map(|i| {
                 vector_face_integrals
@@ -298,12 +881,14 @@ impl Voronoi {
                     .collect::<Vec<_>>()
             })
             .collect::<Vec<_>>();
-        retain(&mut vector_face_integrals, &face_mask);
+        for integrals in vector_face_integrals.iter_mut() {
+            retain(integrals, &face_mask);
+        }
         let scalar_face_integrals = scalar_face_integrals
             .into_iter()
             .flatten()
             .collect::<Vec<_>>();
-        let n = scalar_face_integrators.len();
+        let n = scalar_face_integrator_count;
         let mut scalar_face_integrals = (0..n)
             .map(|i| {
                 scalar_face_integrals
@@ -314,26 +899,69 @@ impl Voronoi {
                     .collect::<Vec<_>>()
             })
             .collect::<Vec<_>>();
-        retain(&mut scalar_face_integrals, &face_mask);
+        for integrals in scalar_face_integrals.iter_mut() {
+            retain(integrals, &face_mask);
+        }
+        let mut flat_generic_face_integrals = generic_face_integrators
+            .iter()
+            .map(|factory| factory.store())
+            .collect::<Vec<_>>();
+        for mut cell_integrals in generic_face_integrals {
+            for (integrals, cell_integrals) in flat_generic_face_integrals
+                .iter_mut()
+                .zip(cell_integrals.iter_mut())
+            {
+                integrals.append(cell_integrals.as_mut());
+            }
+        }
+        for integrals in flat_generic_face_integrals.iter_mut() {
+            integrals.retain(&face_mask);
+        }
 
+        let rtree = rtree.unwrap_or_else(|| Self::generator_rtree(&cells, dimensionality));
         Voronoi {
             anchor,
             width,
+            periodic,
             cells,
             faces,
+            face_vertices,
             vector_face_integrals,
             scalar_face_integrals,
+            generic_face_integrals: flat_generic_face_integrals,
             cell_face_connections: vec![],
             dimensionality,
+            rtree,
         }
         .finalize()
     }
 
+    /// Build an r-tree of the generators of the constructed cells (i.e. with nonzero volume).
+    fn generator_rtree(
+        cells: &[VoronoiCell],
+        dimensionality: Dimensionality,
+    ) -> Arc<RTree<Generator>> {
+        let generators = cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| cell.volume() > 0.)
+            .map(|(idx, cell)| Generator::new(idx, cell.loc(), dimensionality))
+            .collect::<Vec<_>>();
+        Arc::new(build_rtree(&generators))
+    }
+
     /// Link the Voronoi faces to their respective cells.
     fn finalize(mut self) -> Self {
+        span!("finalize");
         let mut cell_face_connections: Vec<Vec<usize>> =
             (0..self.cells.len()).map(|_| vec![]).collect();
 
+        let mut vertex_offset = 0;
+        for face in self.faces.iter_mut() {
+            face.finalize(vertex_offset);
+            vertex_offset += face.vertex_count();
+        }
+
         for (i, face) in self.faces.iter().enumerate() {
             cell_face_connections[face.left()].push(i);
             if let (Some(right_idx), None) = (face.right(), face.shift()) {
@@ -363,6 +991,11 @@ impl Voronoi {
         self.width
     }
 
+    /// Whether this Voronoi tesselation was built with periodic boundary conditions.
+    pub fn periodic(&self) -> bool {
+        self.periodic
+    }
+
     /// Get the voronoi cells.
     pub fn cells(&self) -> &[VoronoiCell] {
         self.cells.as_ref()
@@ -373,11 +1006,70 @@ impl Voronoi {
         self.faces.as_ref()
     }
 
+    /// Get the vertices of all the faces. See [`VoronoiFace::vertices`] to get the vertices of a single face.
+    pub fn face_vertices(&self) -> &[DVec3] {
+        self.face_vertices.as_ref()
+    }
+
     /// Get the additional integrals that were calculated for the faces
     pub fn face_integrals(&self) -> (&[Vec<DVec3>], &[Vec<f64>]) {
         (&self.vector_face_integrals, &self.scalar_face_integrals)
     }
 
+    /// Get an `Iterator` over the extra vector face integral with given `id` (the index of its integrator) of the faces
+    /// of the cell with index `cell_idx`, in the same order as [`VoronoiCell::faces`].
+    pub fn cell_vector_face_integrals(
+        &self,
+        cell_idx: usize,
+        id: usize,
+    ) -> impl Iterator<Item = DVec3> + '_ {
+        self.cells[cell_idx].vector_face_integrals(id, self)
+    }
+
+    /// Get an `Iterator` over the extra scalar face integral with given `id` (the index of its integrator) of the faces
+    /// of the cell with index `cell_idx`, in the same order as [`VoronoiCell::faces`].
+    pub fn cell_scalar_face_integrals(
+        &self,
+        cell_idx: usize,
+        id: usize,
+    ) -> impl Iterator<Item = f64> + '_ {
+        self.cells[cell_idx].scalar_face_integrals(id, self)
+    }
+
+    /// Get the extra face integrals of the integrator with the given handle (see [`crate::FaceIntegrators`]), indexed
+    /// like [`Voronoi::faces`].
+    ///
+    /// This panics if the tesselation was constructed without the [`FaceIntegrators`] registry of the handle.
+    pub fn face_integral<T: 'static>(&self, id: IntegratorId<T>) -> &[T] {
+        let integrals: &dyn Any = match id.kind() {
+            IntegratorKind::Vector => &self.vector_face_integrals[id.index()],
+            IntegratorKind::Scalar => &self.scalar_face_integrals[id.index()],
+            IntegratorKind::Generic => self
+                .generic_face_integrals
+                .get(id.index())
+                .expect("Face integrator was not used in the construction of this tesselation!")
+                .as_any(),
+        };
+        integrals
+            .downcast_ref::<Vec<T>>()
+            .expect("Handle must match the output type of its integrator!")
+    }
+
+    /// Get an `Iterator` over the extra face integrals of the integrator with the given handle (see
+    /// [`crate::FaceIntegrators`]) of the faces of the cell with index `cell_idx`, in the same order as
+    /// [`VoronoiCell::faces`].
+    pub fn cell_face_integrals<T: Clone + 'static>(
+        &self,
+        cell_idx: usize,
+        id: IntegratorId<T>,
+    ) -> impl Iterator<Item = T> + '_ {
+        let integrals = self.face_integral(id);
+        self.cells[cell_idx]
+            .face_indices(self)
+            .iter()
+            .map(move |&face_idx| integrals[face_idx].clone())
+    }
+
     /// Get a vector of the Voronoi faces by consuming the Voronoi struct.
     pub fn into_faces(self) -> Vec<VoronoiFace> {
         self.faces
@@ -388,171 +1080,515 @@ impl Voronoi {
         self.cell_face_connections.as_ref()
     }
 
+    /// Whether the cells with indices `a` and `b` share a face across the periodic boundary (i.e. a face with a
+    /// periodic shift). Cells can be both direct and periodic neighbours in small periodic tesselations.
+    pub fn are_periodic_neighbours(&self, a: usize, b: usize) -> bool {
+        let is_periodic_face = |cell: usize, other: usize| {
+            self.cells[cell].faces(self).any(|face| {
+                face.shift().is_some() && face.left() == cell && face.right() == Some(other)
+            })
+        };
+        // In partial tesselations, only the constructed cell might have a copy of the face
+        is_periodic_face(a, b) || is_periodic_face(b, a)
+    }
+
     pub fn dimensionality(&self) -> usize {
         self.dimensionality.into()
     }
 
-    /// Save the Voronoi tesselation to a hdf5 file. Requires the `hdf5` feature to be enabled.
-    #[cfg(feature = "hdf5")]
-    pub fn save<P: AsRef<Path>>(&self, filename: P) -> Result<(), Box<dyn Error>> {
-        // Create the file to write the data to
-        let file = hdf5::File::create(filename)?;
-
-        // Write cell info
-        let group = file.create_group("Cells")?;
-        let data = self.cells.iter().map(|c| c.volume()).collect::<Vec<_>>();
-        group
-            .new_dataset_builder()
-            .with_data(&data)
-            .create("Volume")?;
-        let data = self
-            .cells
-            .iter()
-            .map(|c| c.face_connections_offset())
-            .collect::<Vec<_>>();
-        group
-            .new_dataset_builder()
-            .with_data(&data)
-            .create("FaceConnectionsOffset")?;
-        let data = self
-            .cells
-            .iter()
-            .map(|c| c.face_count())
-            .collect::<Vec<_>>();
-        group
-            .new_dataset_builder()
-            .with_data(&data)
-            .create("FaceCount")?;
-        let data = self
-            .cells
-            .iter()
-            .map(|c| c.centroid().to_array())
-            .collect::<Vec<_>>();
-        group
-            .new_dataset_builder()
-            .with_data(&data)
-            .create("Centroid")?;
-        let data = self
-            .cells
-            .iter()
-            .map(|c| c.loc().to_array())
+    /// The total volume of the cells of this tesselation. This method runs in parallel if the `"rayon"` feature is
+    /// enabled.
+    ///
+    /// For partial tesselations (see [`Voronoi::build_partial`]), only the constructed cells contribute.
+    pub fn total_volume(&self) -> f64 {
+        #[cfg(feature = "rayon")]
+        return self.cells.par_iter().map(|cell| cell.volume()).sum();
+        #[cfg(not(feature = "rayon"))]
+        return self.cells.iter().map(|cell| cell.volume()).sum();
+    }
+
+    /// The volume of the simulation volume, i.e. its length or area for 1D or 2D tesselations.
+    pub fn domain_volume(&self) -> f64 {
+        // The unused dimensions of the simulation volume are normalized to unit width
+        self.width.x * self.width.y * self.width.z
+    }
+
+    /// The relative difference between the [`Voronoi::total_volume`] and the [`Voronoi::domain_volume`]. This should
+    /// be zero (up to round-off errors) for complete tesselations.
+    pub fn volume_error(&self) -> f64 {
+        let domain_volume = self.domain_volume();
+        (self.total_volume() - domain_volume).abs() / domain_volume
+    }
+
+    /// Extract the sub-tesselation consisting of the cells for which `mask` is `true`.
+    ///
+    /// The extracted tesselation contains the selected cells and all of their faces. Faces between a selected and an
+    /// unselected cell become boundary faces of the selected cell (their orientation is reversed if necessary, the extra
+    /// face integrals are copied unchanged).
+    ///
+    /// Returns the extracted tesselation and for each cell of `self` its index in the extracted tesselation (if any).
+    pub fn extract(&self, mask: &[bool]) -> (Voronoi, Vec<Option<usize>>) {
+        assert_eq!(
+            mask.len(),
+            self.cells.len(),
+            "Mask must have the same length as the cells!"
+        );
+
+        let mut index_map = vec![None; self.cells.len()];
+        let mut cells = vec![];
+        for (idx, cell) in self.cells.iter().enumerate() {
+            if mask[idx] {
+                index_map[idx] = Some(cells.len());
+                cells.push(*cell);
+            }
+        }
+
+        let mut faces = vec![];
+        let mut face_vertices = vec![];
+        let mut face_mask = vec![false; self.faces.len()];
+        for (face_idx, face) in self.faces.iter().enumerate() {
+            let left = index_map[face.left()];
+            let right = face.right().and_then(|right| index_map[right]);
+            match (left, right) {
+                (Some(left), right) => {
+                    faces.push(face.remapped(left, right, false));
+                    face_vertices.extend_from_slice(face.vertices(self));
+                }
+                // Periodic faces are also stored separately for their right cell
+                (None, Some(right)) if face.shift().is_none() => {
+                    faces.push(face.remapped(right, None, true));
+                    face_vertices.extend(face.vertices(self).iter().rev());
+                }
+                _ => continue,
+            }
+            face_mask[face_idx] = true;
+        }
+
+        let mut vector_face_integrals = self.vector_face_integrals.clone();
+        for integrals in vector_face_integrals.iter_mut() {
+            retain(integrals, &face_mask);
+        }
+        let mut scalar_face_integrals = self.scalar_face_integrals.clone();
+        for integrals in scalar_face_integrals.iter_mut() {
+            retain(integrals, &face_mask);
+        }
+        let mut generic_face_integrals = self.generic_face_integrals.clone();
+        for integrals in generic_face_integrals.iter_mut() {
+            integrals.retain(&face_mask);
+        }
+
+        let rtree = Self::generator_rtree(&cells, self.dimensionality);
+        let voronoi = Voronoi {
+            anchor: self.anchor,
+            width: self.width,
+            periodic: self.periodic,
+            cells,
+            faces,
+            face_vertices,
+            vector_face_integrals,
+            scalar_face_integrals,
+            generic_face_integrals,
+            cell_face_connections: vec![],
+            dimensionality: self.dimensionality,
+            rtree,
+        }
+        .finalize();
+
+        (voronoi, index_map)
+    }
+
+    /// Crop this Voronoi tesselation to the box with the given `anchor` and `width`.
+    ///
+    /// The cells intersecting the new box are clipped by it (recomputing their volumes, centroids and faces), the
+    /// other cells are dropped. This is a lot cheaper than rebuilding the tesselation, since the neighbours of every
+    /// cell are already known. Cells that were not constructed in a partial tesselation are dropped as well.
+    /// The resulting tesselation is no longer periodic and does not contain any extra face integrals.
+    ///
+    /// The cells keep the position of their generator, unless it lies outside of the new box (and hence outside of
+    /// the cropped cell). Those cells get the centroid of the cropped cell as position instead, so that the position
+    /// of every cell lies inside of it.
+    ///
+    /// Returns the cropped tesselation and for each cell of `self` its index in the cropped tesselation (if any).
+    pub fn crop(&self, mut anchor: DVec3, mut width: DVec3) -> (Voronoi, Vec<Option<usize>>) {
+        normalize_simulation_volume(&mut anchor, &mut width, self.dimensionality);
+        if !self.periodic {
+            // Also clip by the original simulation volume
+            let upper = (anchor + width).min(self.anchor + self.width);
+            anchor = anchor.max(self.anchor);
+            width = (upper - anchor).max(DVec3::ZERO);
+        }
+        let simulation_volume =
+            ConvexCell::init_simulation_volume(anchor, width, false, self.dimensionality);
+
+        // Quickly discard cells whose bounding box does not overlap the new simulation volume
+        let overlaps = |cell: &VoronoiCell| {
+            let mut min = DVec3::splat(f64::INFINITY);
+            let mut max = DVec3::splat(f64::NEG_INFINITY);
+            for vertex in cell.faces(self).flat_map(|face| face.vertices(self)) {
+                min = min.min(*vertex);
+                max = max.max(*vertex);
+            }
+            min.cmple(anchor + width).all() && max.cmpge(anchor).all()
+        };
+        let convex_cells = self
+            .cells
+            .iter()
+            .enumerate()
+            .map(|(idx, cell)| {
+                if cell.volume() > 0. && overlaps(cell) {
+                    ConvexCell::reconstruct(self, idx, &simulation_volume, self.dimensionality)
+                } else {
+                    None
+                }
+            })
             .collect::<Vec<_>>();
-        group
-            .new_dataset_builder()
-            .with_data(&data)
-            .create("Generator")?;
-
-        // Write face info
-        let group = file.create_group("Faces")?;
-        let data = self.faces.iter().map(|f| f.area()).collect::<Vec<_>>();
-        group
-            .new_dataset_builder()
-            .with_data(&data)
-            .create("Area")?;
-        let data = self
-            .faces
+        let mask = convex_cells.iter().map(|c| c.is_some()).collect::<Vec<_>>();
+
+        let mut index_map = vec![None; self.cells.len()];
+        let mut cells = vec![];
+        let mut faces = vec![];
+        let mut face_vertices = vec![];
+        for (idx, convex_cell) in convex_cells.into_iter().enumerate() {
+            let Some(mut convex_cell) = convex_cell else {
+                continue;
+            };
+            // The generator might lie outside of the cropped cell, use a point inside the cell to compute the integrals.
+            convex_cell.loc = convex_cell.vertices.iter().map(|v| v.loc).sum::<DVec3>()
+                / convex_cell.vertices.len() as f64;
+            let mut cell_faces = vec![];
+            let mut cell_face_vertices = vec![];
+            let cell = VoronoiCell::from_convex_cell(
+                &convex_cell,
+                &mut cell_faces,
+                &mut cell_face_vertices,
+                &mut vec![],
+                &mut vec![],
+                &mut [],
+                CellSelection::Mask(&mask),
+                false,
+                &FaceIntegrators::default(),
+            );
+            if cell.volume() == 0. {
+                // Only touches the new simulation volume
+                continue;
+            }
+            index_map[idx] = Some(cells.len());
+            let loc = self.cells[idx].loc();
+            if loc.cmpge(anchor).all() && loc.cmple(anchor + width).all() {
+                cells.push(cell.with_loc(loc));
+            } else {
+                let centroid = cell.centroid();
+                cells.push(cell.with_loc(centroid));
+            }
+            faces.push(cell_faces);
+            face_vertices.push(cell_face_vertices);
+        }
+
+        // Renumber the neighbours of the faces
+        for face in faces.iter_mut().flatten() {
+            let left = index_map[face.left()].expect("Left cell of face must be kept!");
+            let right = face.right().and_then(|right| index_map[right]);
+            *face = face.remapped(left, right, false);
+        }
+
+        let voronoi = Self::assemble(
+            anchor,
+            width,
+            false,
+            self.dimensionality,
+            cells,
+            faces,
+            face_vertices,
+            vec![],
+            vec![],
+            vec![],
+            0,
+            0,
+            &[],
+            None,
+        );
+
+        (voronoi, index_map)
+    }
+
+    /// Apply the affine transformation `x -> x * scale + translation` to this Voronoi tesselation.
+    /// Use `DVec3::splat` for a uniform scaling.
+    ///
+    /// All geometric quantities are updated consistently: cell volumes are scaled by the Jacobian of the transformation
+    /// and face areas and normals are transformed accordingly. The unused dimensions of 1D and 2D tesselations are not
+    /// transformed. Note that the extra face integrals cannot be transformed and are left unchanged.
+    pub fn transform(&mut self, mut translation: DVec3, mut scale: DVec3) {
+        if let Dimensionality::Dimensionality1D = self.dimensionality {
+            translation.y = 0.;
+            scale.y = 1.;
+        }
+        if let Dimensionality::Dimensionality1D | Dimensionality::Dimensionality2D =
+            self.dimensionality
+        {
+            translation.z = 0.;
+            scale.z = 1.;
+        }
+        assert!(
+            scale.cmpgt(DVec3::ZERO).all(),
+            "Only positive scale factors are supported!"
+        );
+        let jacobian = scale.x * scale.y * scale.z;
+
+        self.anchor = self.anchor * scale + translation;
+        self.width *= scale;
+        for cell in self.cells.iter_mut() {
+            cell.transform(translation, scale, jacobian);
+        }
+        for face in self.faces.iter_mut() {
+            face.transform(translation, scale, jacobian);
+        }
+        for vertex in self.face_vertices.iter_mut() {
+            *vertex = *vertex * scale + translation;
+        }
+        let generators = self
+            .rtree
             .iter()
-            .map(|f| f.centroid().to_array())
+            .map(|g| Generator::new(g.id(), g.loc() * scale + translation, self.dimensionality))
             .collect::<Vec<_>>();
-        group
-            .new_dataset_builder()
-            .with_data(&data)
-            .create("Centroid")?;
-        let data = self
+        self.rtree = Arc::new(build_rtree(&generators));
+    }
+
+    /// Replicate this periodic Voronoi tesselation `nx` x `ny` x `nz` times.
+    ///
+    /// The result is the explicitly unrolled periodic tesselation with width `width * (nx, ny, nz)`, which is again
+    /// periodic. The cell with index `c` of the replica `(i, j, k)` gets index `((i * ny + j) * nz + k) * n + c`, where
+    /// `n` is the number of cells of this tesselation. Periodic neighbours are remapped to the appropriate replica.
+    /// The extra face integrals are copied unchanged. For 1D and 2D tesselations, the replication counts of the unused
+    /// dimensions are ignored. Partial tesselations are replicated as well, including their faces towards the cells
+    /// that were not constructed.
+    pub fn replicate(&self, nx: usize, ny: usize, nz: usize) -> Voronoi {
+        assert!(
+            self.periodic,
+            "Only periodic tesselations can be replicated!"
+        );
+        let ny = match self.dimensionality {
+            Dimensionality::Dimensionality1D => 1,
+            _ => ny,
+        };
+        let nz = match self.dimensionality {
+            Dimensionality::Dimensionality3D => nz,
+            _ => 1,
+        };
+        assert!(
+            nx > 0 && ny > 0 && nz > 0,
+            "Replication counts must be positive!"
+        );
+        let counts = [nx as i64, ny as i64, nz as i64];
+        let n = self.cells.len();
+        // The periodic shift of a face as multiples of the width of the simulation volume
+        let shift_multiples = |shift: DVec3| {
+            let m = (shift / self.width).round();
+            [m.x as i64, m.y as i64, m.z as i64]
+        };
+        // The periodic faces, to find out whether the twin of a face exists (it does not for faces towards cells that
+        // were not constructed in a partial tesselation, unless they were mirrored)
+        let periodic_faces = self
             .faces
             .iter()
-            .map(|f| f.normal().to_array())
+            .filter_map(|face| Some((face.left(), face.right()?, shift_multiples(face.shift()?))))
+            .collect::<HashSet<_>>();
+        let replica_idx = |replica: [i64; 3]| {
+            ((replica[0] * counts[1] + replica[1]) * counts[2] + replica[2]) as usize
+        };
+
+        let mut cells = Vec::with_capacity(n * nx * ny * nz);
+        let mut faces = Vec::with_capacity(self.faces.len() * nx * ny * nz);
+        let mut face_vertices = Vec::with_capacity(self.face_vertices.len() * nx * ny * nz);
+        let mut face_mask = vec![];
+        for i in 0..counts[0] {
+            for j in 0..counts[1] {
+                for k in 0..counts[2] {
+                    let replica = [i, j, k];
+                    let offset = DVec3::new(i as f64, j as f64, k as f64) * self.width;
+                    let cell_offset = replica_idx(replica) * n;
+                    cells.extend(self.cells.iter().map(|cell| {
+                        let mut cell = *cell;
+                        cell.transform(offset, DVec3::ONE, 1.);
+                        cell
+                    }));
+                    for face in self.faces.iter() {
+                        let mut face = *face;
+                        face.transform(offset, DVec3::ONE, 1.);
+                        let left = face.left() + cell_offset;
+                        let (face, keep) = match (face.right(), face.shift()) {
+                            (None, _) => (face.remapped(left, None, false), true),
+                            (Some(right), None) => {
+                                (face.remapped(left, Some(right + cell_offset), false), true)
+                            }
+                            (Some(right), Some(shift)) => {
+                                // Determine the replica the right neighbour lies in.
+                                let multiples = shift_multiples(shift);
+                                let mut right_replica = replica;
+                                let mut new_shift = DVec3::ZERO;
+                                for d in 0..3 {
+                                    let m = multiples[d];
+                                    right_replica[d] = (replica[d] + m).rem_euclid(counts[d]);
+                                    new_shift[d] =
+                                        (replica[d] + m - right_replica[d]) as f64 * self.width[d];
+                                }
+                                let has_twin = periodic_faces.contains(&(
+                                    right,
+                                    face.left(),
+                                    multiples.map(|m| -m),
+                                ));
+                                let right = right + replica_idx(right_replica) * n;
+                                let face = face.remapped(left, Some(right), false);
+                                if new_shift == DVec3::ZERO {
+                                    // No longer a periodic face. Periodic faces are stored for both of their cells
+                                    // (if both exist), so only keep one of them.
+                                    (face.with_shift(None), left < right || !has_twin)
+                                } else {
+                                    (face.with_shift(Some(new_shift)), true)
+                                }
+                            }
+                        };
+                        face_mask.push(keep);
+                        face_vertices.extend(face.vertices(self).iter().map(|v| *v + offset));
+                        faces.push(face);
+                    }
+                }
+            }
+        }
+
+        // Remove the duplicate faces
+        let vertex_mask = faces
+            .iter()
+            .zip(face_mask.iter())
+            .flat_map(|(face, &keep)| std::iter::repeat_n(keep, face.vertex_count()))
             .collect::<Vec<_>>();
-        group
-            .new_dataset_builder()
-            .with_data(&data)
-            .create("Normal")?;
-        if let Dimensionality::Dimensionality2D = self.dimensionality {
-            // Also write face start and end points
-            let face_directions = self
-                .faces
-                .iter()
-                .map(|f| f.area() * f.normal().cross(DVec3::Z))
-                .collect::<Vec<_>>();
-            let face_start = self
-                .faces
-                .iter()
-                .zip(face_directions.iter())
-                .map(|(f, &d)| (f.centroid() - 0.5 * d).to_array())
-                .collect::<Vec<_>>();
-            let face_end = self
-                .faces
-                .iter()
-                .zip(face_directions.iter())
-                .map(|(f, &d)| (f.centroid() + 0.5 * d).to_array())
+        retain(&mut face_vertices, &vertex_mask);
+        retain(&mut faces, &face_mask);
+        fn replicate_integrals<T: Copy>(
+            integrals: &[T],
+            count: usize,
+            face_mask: &[bool],
+        ) -> Vec<T> {
+            let mut integrals = (0..count)
+                .flat_map(|_| integrals.iter().copied())
                 .collect::<Vec<_>>();
-            group
-                .new_dataset_builder()
-                .with_data(&face_start)
-                .create("Start")?;
-            group
-                .new_dataset_builder()
-                .with_data(&face_end)
-                .create("End")?;
+            retain(&mut integrals, face_mask);
+            integrals
         }
+        let vector_face_integrals = self
+            .vector_face_integrals
+            .iter()
+            .map(|integrals| replicate_integrals(integrals, nx * ny * nz, &face_mask))
+            .collect();
+        let scalar_face_integrals = self
+            .scalar_face_integrals
+            .iter()
+            .map(|integrals| replicate_integrals(integrals, nx * ny * nz, &face_mask))
+            .collect();
+        let generic_face_integrals = self
+            .generic_face_integrals
+            .iter()
+            .map(|integrals| integrals.replicate(nx * ny * nz, &face_mask))
+            .collect();
 
-        // Write cell face connections
-        file.new_dataset_builder()
-            .with_data(self.cell_face_connections())
-            .create("CellFaceConnections")?;
+        let rtree = Self::generator_rtree(&cells, self.dimensionality);
+        Voronoi {
+            anchor: self.anchor,
+            width: self.width * DVec3::new(nx as f64, ny as f64, nz as f64),
+            periodic: true,
+            cells,
+            faces,
+            face_vertices,
+            vector_face_integrals,
+            scalar_face_integrals,
+            generic_face_integrals,
+            cell_face_connections: vec![],
+            dimensionality: self.dimensionality,
+            rtree,
+        }
+        .finalize()
+    }
 
-        Ok(())
+    /// Find the indices of the cells containing the given `points`. This method runs in parallel if the `"rayon"`
+    /// feature is enabled.
+    ///
+    /// For periodic tesselations, the points are first wrapped into the simulation volume.
+    /// Points outside the simulation volume of a non-periodic tesselation are assigned to the nearest cell.
+    /// For partial tesselations (see [`Voronoi::build_partial`]), the points are assigned to the nearest generator,
+    /// which might be the generator of a cell that was not constructed.
+    pub fn cells_at(&self, points: &[DVec3]) -> Vec<usize> {
+        assert!(self.rtree.size() > 0, "Tesselation has no generators!");
+        let cell_at = |&point: &DVec3| {
+            if self.periodic {
+                let dx = point - self.anchor;
+                let point = point - (dx / self.width).floor() * self.width;
+                wrapping_nn_iter(&self.rtree, point, self.width, self.dimensionality)
+                    .next()
+                    .expect("R-tree cannot be empty!")
+                    .0
+            } else {
+                nn_iter(&self.rtree, point)
+                    .next()
+                    .expect("R-tree cannot be empty!")
+                    .0
+            }
+        };
+        #[cfg(feature = "rayon")]
+        return points.par_iter().map(cell_at).collect();
+        #[cfg(not(feature = "rayon"))]
+        return points.iter().map(cell_at).collect();
+    }
+
+    /// Convert this Voronoi tesselation to a half-edge mesh.
+    ///
+    /// The vertices are welded across faces and cells. Every cell's boundary becomes a closed surface
+    /// and the faces between neighbouring cells are linked to each other (also across periodic boundaries).
+    pub fn to_halfedge(&self) -> HalfEdgeMesh {
+        HalfEdgeMesh::from_voronoi(self)
     }
 }
 
 #[cfg(test)]
 mod test {
+    use std::collections::HashMap;
+
     use super::*;
+    use crate::sampling;
     use float_cmp::assert_approx_eq;
-    use rand::{distributions::Uniform, prelude::*};
+    use rand::prelude::*;
 
     const DIM2D: usize = 2;
     const DIM3D: usize = 3;
+    /// The seed of the random perturbations of the test generators, so that the tests are reproducible.
+    const SEED: u64 = 42;
 
-    fn perturbed_grid(anchor: DVec3, width: DVec3, count: usize, pert: f64) -> Vec<DVec3> {
-        let mut generators = vec![];
-        let mut rng = thread_rng();
-        let distr = Uniform::new(-0.5, 0.5);
-        for n in 0..count.pow(3) {
-            let i = n / count.pow(2);
-            let j = (n % count.pow(2)) / count;
-            let k = n % count;
-            let pos = DVec3 {
-                x: i as f64 + 0.5 + pert * rng.sample(distr),
-                y: j as f64 + 0.5 + pert * rng.sample(distr),
-                z: k as f64 + 0.5 + pert * rng.sample(distr),
-            } * width
-                / count as f64
-                + anchor;
-            generators.push(pos.clamp(anchor, anchor + width));
-        }
-
-        generators
+    pub(super) fn perturbed_grid(
+        anchor: DVec3,
+        width: DVec3,
+        count: usize,
+        pert: f64,
+    ) -> Vec<DVec3> {
+        sampling::jittered_grid(
+            anchor,
+            width,
+            [count; 3],
+            pert,
+            &mut StdRng::seed_from_u64(SEED),
+        )
     }
 
-    fn perturbed_plane(anchor: DVec3, width: DVec3, count: usize, pert: f64) -> Vec<DVec3> {
-        let mut generators = vec![];
-        let mut rng = thread_rng();
-        let distr = Uniform::new(-0.5, 0.5);
-        for n in 0..count.pow(2) {
-            let i = n / count;
-            let j = n % count;
-            let pos = DVec3 {
-                x: i as f64 + 0.5 + pert * rng.sample(distr),
-                y: j as f64 + 0.5 + pert * rng.sample(distr),
-                z: 0.5 * count as f64,
-            } * width
-                / count as f64
-                + anchor;
-            generators.push(pos.clamp(anchor, anchor + width));
-        }
-
-        generators
+    pub(super) fn perturbed_plane(
+        anchor: DVec3,
+        width: DVec3,
+        count: usize,
+        pert: f64,
+    ) -> Vec<DVec3> {
+        sampling::jittered_plane(
+            anchor,
+            width,
+            [count; 2],
+            pert,
+            &mut StdRng::seed_from_u64(SEED),
+        )
     }
 
     #[test]
@@ -560,7 +1596,7 @@ mod test {
         let generators = vec![DVec3::splat(0.5)];
         let anchor = DVec3::ZERO;
         let width = DVec3::splat(1.);
-        let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, false, None, None);
+        let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, false, None);
         assert_approx_eq!(f64, voronoi.cells[0].volume(), 1.);
     }
 
@@ -580,11 +1616,44 @@ mod test {
         ];
         let anchor = DVec3::ZERO;
         let width = DVec3::splat(1.);
-        let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, false, None, None);
+        let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, false, None);
         assert_approx_eq!(f64, voronoi.cells[0].volume(), 0.5);
         assert_approx_eq!(f64, voronoi.cells[1].volume(), 0.5);
     }
 
+    #[test]
+    fn test_cell_sizes() {
+        let generators = vec![DVec3::new(0.3, 0.4, 0.5), DVec3::new(0.7, 0.4, 0.5)];
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let mut voronoi = Voronoi::build(&generators, anchor, width, DIM3D, false, None);
+        let radius = (0.75 * 0.5 / std::f64::consts::PI).powf(1. / 3.);
+        assert_approx_eq!(
+            f64,
+            voronoi.cells[0].characteristic_radius(&voronoi),
+            radius
+        );
+        assert_approx_eq!(f64, voronoi.cells[0].min_face_distance(&voronoi), 0.2);
+
+        voronoi.transform(DVec3::ZERO, DVec3::splat(2.));
+        assert_approx_eq!(
+            f64,
+            voronoi.cells[1].characteristic_radius(&voronoi),
+            2. * radius
+        );
+        assert_approx_eq!(f64, voronoi.cells[1].min_face_distance(&voronoi), 0.4);
+
+        let voronoi = Voronoi::build(&generators, anchor, width, DIM2D, false, None);
+        let radius = (0.5 / std::f64::consts::PI).sqrt();
+        assert_approx_eq!(
+            f64,
+            voronoi.cells[0].characteristic_radius(&voronoi),
+            radius
+        );
+        // The faces of the unused dimension are not taken into account
+        assert_approx_eq!(f64, voronoi.cells[0].min_face_distance(&voronoi), 0.2);
+    }
+
     #[test]
     fn test_4_cells() {
         let generators = vec![
@@ -615,7 +1684,7 @@ mod test {
             y: 1.,
             z: 1.,
         };
-        let voronoi = Voronoi::build(&generators, anchor, width, DIM2D, true, None, None);
+        let voronoi = Voronoi::build(&generators, anchor, width, DIM2D, true, None);
         #[cfg(feature = "hdf5")]
         voronoi.save("test_4_cells.hdf5").unwrap();
         assert_approx_eq!(f64, voronoi.cells.iter().map(|c| c.volume()).sum(), 2.);
@@ -653,7 +1722,7 @@ mod test {
         ];
         let anchor = DVec3::ZERO;
         let width = DVec3::splat(1.);
-        let voronoi = Voronoi::build(&generators, anchor, width, DIM2D, false, None, None);
+        let voronoi = Voronoi::build(&generators, anchor, width, DIM2D, false, None);
         assert_approx_eq!(f64, voronoi.cells[0].volume(), 0.2);
         assert_approx_eq!(f64, voronoi.cells[1].volume(), 0.2);
         assert_approx_eq!(f64, voronoi.cells[2].volume(), 0.2);
@@ -666,7 +1735,7 @@ mod test {
         let anchor = DVec3::ZERO;
         let width = DVec3::splat(1.);
         let generators = perturbed_grid(anchor, width, 2, 0.);
-        let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, false, None, None);
+        let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, false, None);
         for cell in &voronoi.cells {
             assert_approx_eq!(f64, cell.volume(), 0.125);
         }
@@ -677,7 +1746,7 @@ mod test {
         let anchor = DVec3::ZERO;
         let width = DVec3::splat(1.);
         let generators = perturbed_grid(anchor, width, 3, 0.);
-        let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, false, None, None);
+        let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, false, None);
         for cell in &voronoi.cells {
             assert_approx_eq!(f64, cell.volume(), 1. / 27.);
         }
@@ -688,7 +1757,7 @@ mod test {
         let anchor = DVec3::ZERO;
         let width = DVec3::splat(1.);
         let generators = perturbed_grid(anchor, width, 4, 0.);
-        let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, false, None, None);
+        let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, false, None);
         for cell in &voronoi.cells {
             assert_approx_eq!(f64, cell.volume(), 1. / 64.);
         }
@@ -700,7 +1769,7 @@ mod test {
         let anchor = DVec3::ZERO;
         let width = DVec3::splat(1.);
         let generators = perturbed_grid(anchor, width, 5, pert);
-        let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, false, None, None);
+        let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, false, None);
         let mut total_volume = 0.;
         for cell in &voronoi.cells {
             total_volume += cell.volume();
@@ -708,18 +1777,455 @@ mod test {
         assert_approx_eq!(f64, total_volume, 1., epsilon = 1e-10, ulps = 8)
     }
 
+    #[test]
+    fn test_face_vertices() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 3, 0.5);
+        let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, true, None);
+        for face in voronoi.faces() {
+            let vertices = face.vertices(&voronoi);
+            assert!(vertices.len() >= 3);
+            // Vector area of the vertex loop
+            let mut area = DVec3::ZERO;
+            for (i, v) in vertices.iter().enumerate() {
+                area += 0.5 * v.cross(vertices[(i + 1) % vertices.len()]);
+            }
+            assert_approx_eq!(f64, area.length(), face.area(), epsilon = 1e-12);
+            assert_approx_eq!(f64, area.dot(face.normal()), face.area(), epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_face_centroids() {
+        let generators = vec![DVec3::new(0.25, 0.5, 0.5), DVec3::new(0.75, 0.5, 0.5)];
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, false, None);
+        let centroid = |left: usize, right: Option<usize>, normal: DVec3| {
+            voronoi
+                .faces()
+                .iter()
+                .find(|f| f.left() == left && f.right() == right && f.normal().dot(normal) > 0.5)
+                .expect("Face must exist!")
+                .centroid()
+        };
+        // The centroids do not depend on the position of the generators
+        let expected = [
+            (0, Some(1), DVec3::X, DVec3::new(0.5, 0.5, 0.5)),
+            (0, None, DVec3::NEG_X, DVec3::new(0., 0.5, 0.5)),
+            (0, None, DVec3::NEG_Y, DVec3::new(0.25, 0., 0.5)),
+            (1, None, DVec3::Z, DVec3::new(0.75, 0.5, 1.)),
+        ];
+        for (left, right, normal, expected) in expected {
+            assert!(centroid(left, right, normal).distance(expected) < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_face_getters() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 3, 0.5);
+        for periodic in [false, true] {
+            let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, periodic, None);
+            for face in voronoi.faces() {
+                assert_approx_eq!(
+                    f64,
+                    face.area_vector().length(),
+                    face.area(),
+                    epsilon = 1e-12
+                );
+                let Some(distance) = face.generator_distance(&voronoi) else {
+                    assert!(!periodic);
+                    continue;
+                };
+                let right = face.right().unwrap();
+                let right_loc = generators[right] + face.shift().unwrap_or(DVec3::ZERO);
+                assert_approx_eq!(
+                    f64,
+                    distance,
+                    generators[face.left()].distance(right_loc),
+                    epsilon = 1e-10
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_periodic_neighbours() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        let mask = (0..generators.len())
+            .map(|i| i % 2 == 0)
+            .collect::<Vec<_>>();
+        for periodic in [false, true] {
+            let voronoi =
+                Voronoi::build_partial(&generators, &mask, anchor, width, DIM3D, periodic, None);
+            let mut periodic_pairs = vec![];
+            for face in voronoi.faces() {
+                let Some(right_loc) = face.right_loc(&voronoi) else {
+                    continue;
+                };
+                let right = face.right().unwrap();
+                assert_eq!(
+                    right_loc,
+                    generators[right] + face.shift().unwrap_or(DVec3::ZERO)
+                );
+                // The face lies halfway between both generators
+                assert_approx_eq!(
+                    f64,
+                    face.centroid().distance(generators[face.left()]),
+                    face.centroid().distance(right_loc),
+                    epsilon = 1e-10
+                );
+                if face.shift().is_some() {
+                    periodic_pairs.push((face.left(), right));
+                }
+            }
+            assert_eq!(periodic, !periodic_pairs.is_empty());
+            for a in 0..generators.len() {
+                for b in 0..generators.len() {
+                    assert_eq!(
+                        voronoi.are_periodic_neighbours(a, b),
+                        periodic_pairs.contains(&(a, b)) || periodic_pairs.contains(&(b, a))
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_face_boundaries() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 3, 0.5);
+        let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, false, None);
+        let mut counts = HashMap::new();
+        for face in voronoi.faces() {
+            let Some(boundary) = face.boundary() else {
+                assert!(face.right().is_some());
+                continue;
+            };
+            assert!(face.right().is_none());
+            let expected_normal = match boundary {
+                Boundary::XMin => DVec3::NEG_X,
+                Boundary::XMax => DVec3::X,
+                Boundary::YMin => DVec3::NEG_Y,
+                Boundary::YMax => DVec3::Y,
+                Boundary::ZMin => DVec3::NEG_Z,
+                Boundary::ZMax => DVec3::Z,
+                Boundary::Cut => panic!("No cells were removed!"),
+            };
+            assert_eq!(face.normal(), expected_normal);
+            *counts.entry(boundary).or_insert(0.) += face.area();
+        }
+        assert_eq!(counts.len(), 6);
+        for area in counts.values() {
+            assert_approx_eq!(f64, *area, 1., epsilon = 1e-10);
+        }
+
+        // Periodic tesselations have no boundary faces
+        let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, true, None);
+        assert!(voronoi.faces().iter().all(|f| f.boundary().is_none()));
+
+        // Faces to cells that are removed become cut faces
+        let mask = generators.iter().map(|g| g.x < 0.5).collect::<Vec<_>>();
+        let (extracted, _) = voronoi.extract(&mask);
+        assert!(extracted.faces().iter().any(|f| f.right().is_none()));
+        for face in extracted.faces() {
+            assert_eq!(
+                face.right().is_none(),
+                face.boundary() == Some(Boundary::Cut)
+            );
+        }
+    }
+
+    #[test]
+    fn test_extract() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        for periodic in [false, true] {
+            let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, periodic, None);
+            let mask = generators.iter().map(|g| g.x < 0.5).collect::<Vec<_>>();
+            let (extracted, index_map) = voronoi.extract(&mask);
+            assert_eq!(extracted.cells().len(), mask.iter().filter(|&&m| m).count());
+            for (old_idx, new_idx) in index_map.iter().enumerate() {
+                let Some(new_idx) = *new_idx else {
+                    assert!(!mask[old_idx]);
+                    continue;
+                };
+                let old_cell = &voronoi.cells()[old_idx];
+                let new_cell = &extracted.cells()[new_idx];
+                assert_eq!(old_cell.volume(), new_cell.volume());
+                assert_eq!(old_cell.face_count(), new_cell.face_count());
+                let old_area = old_cell.faces(&voronoi).map(|f| f.area()).sum::<f64>();
+                let new_area = new_cell.faces(&extracted).map(|f| f.area()).sum::<f64>();
+                assert_approx_eq!(f64, old_area, new_area);
+            }
+            for face in extracted.faces() {
+                assert!(face.left() < extracted.cells().len());
+            }
+            assert_eq!(
+                extracted.face_vertices().len(),
+                extracted
+                    .faces()
+                    .iter()
+                    .map(|f| f.vertices(&extracted).len())
+                    .sum::<usize>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_crop() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 5, 0.5);
+        let crop_anchor = DVec3::splat(0.25);
+        let crop_width = DVec3::new(0.5, 0.3, 0.6);
+        for periodic in [false, true] {
+            let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, periodic, None);
+            let (cropped, index_map) = voronoi.crop(crop_anchor, crop_width);
+            let total_volume = cropped.cells().iter().map(|c| c.volume()).sum::<f64>();
+            assert_approx_eq!(f64, total_volume, 0.09, epsilon = 1e-10);
+            for (old_idx, new_idx) in index_map.iter().enumerate() {
+                let Some(new_idx) = *new_idx else {
+                    continue;
+                };
+                let old_cell = &voronoi.cells()[old_idx];
+                let new_cell = &cropped.cells()[new_idx];
+                assert!(new_cell.volume() <= old_cell.volume() * (1. + 1e-10));
+                let loc = old_cell.loc();
+                if loc.cmpge(crop_anchor + 0.2).all()
+                    && loc.cmple(crop_anchor + crop_width - 0.2).all()
+                {
+                    // Cells far away from the new boundary are unchanged
+                    assert_approx_eq!(f64, new_cell.volume(), old_cell.volume(), epsilon = 1e-12);
+                }
+            }
+            // Boundary faces of the cropped tesselation cover the surface of the new box
+            let boundary_area = cropped
+                .faces()
+                .iter()
+                .filter(|f| f.right().is_none())
+                .map(|f| f.area())
+                .sum::<f64>();
+            assert_approx_eq!(
+                f64,
+                boundary_area,
+                2. * (0.15 + 0.3 + 0.18),
+                epsilon = 1e-10
+            );
+        }
+    }
+
+    #[test]
+    fn test_crop_generators_outside() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        let crop_anchor = DVec3::splat(0.3);
+        let crop_width = DVec3::splat(0.4);
+        let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, false, None);
+        let (cropped, index_map) = voronoi.crop(crop_anchor, crop_width);
+        let is_inside =
+            |loc: DVec3| loc.cmpge(crop_anchor).all() && loc.cmple(crop_anchor + crop_width).all();
+        let mut outside_count = 0;
+        for (old_idx, new_idx) in index_map.iter().enumerate() {
+            let Some(new_idx) = *new_idx else {
+                continue;
+            };
+            let cell = &cropped.cells()[new_idx];
+            let generator = voronoi.cells()[old_idx].loc();
+            if is_inside(generator) {
+                assert_eq!(cell.loc(), generator);
+            } else {
+                outside_count += 1;
+                assert_eq!(cell.loc(), cell.centroid());
+            }
+            // The position of the cell lies inside of it
+            assert!(cell.signed_distance(&cropped, cell.loc()) < 0.);
+            assert!(cell.min_face_distance(&cropped) > 0.);
+            let mesh = cell.triangle_mesh(&cropped);
+            assert_approx_eq!(f64, mesh.volume(), cell.volume(), epsilon = 1e-10);
+        }
+        assert!(outside_count > 0);
+    }
+
+    #[test]
+    fn test_transform() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        let translation = DVec3::new(1., -2., 3.);
+
+        // A uniform scaling commutes with the construction of the Voronoi tesselation
+        let scale = DVec3::splat(2.5);
+        let mut voronoi = Voronoi::build(&generators, anchor, width, DIM3D, true, None);
+        voronoi.transform(translation, scale);
+        let transformed = generators
+            .iter()
+            .map(|&g| g * scale + translation)
+            .collect::<Vec<_>>();
+        let reference = Voronoi::build(
+            &transformed,
+            anchor * scale + translation,
+            width * scale,
+            DIM3D,
+            true,
+            None,
+        );
+        for (cell, ref_cell) in voronoi.cells().iter().zip(reference.cells()) {
+            assert_approx_eq!(f64, cell.volume(), ref_cell.volume(), epsilon = 1e-10);
+            assert!(cell.centroid().distance(ref_cell.centroid()) < 1e-10);
+            assert!(cell.loc().distance(ref_cell.loc()) < 1e-10);
+        }
+
+        // Non-uniform scaling
+        let scale = DVec3::new(0.5, 2., 3.);
+        let mut voronoi = Voronoi::build(&generators, anchor, width, DIM3D, false, None);
+        voronoi.transform(translation, scale);
+        assert_approx_eq!(
+            f64,
+            voronoi.cells().iter().map(|c| c.volume()).sum(),
+            3.,
+            epsilon = 1e-10
+        );
+        for face in voronoi.faces() {
+            let vertices = face.vertices(&voronoi);
+            let mut area = DVec3::ZERO;
+            for (i, v) in vertices.iter().enumerate() {
+                area += 0.5 * v.cross(vertices[(i + 1) % vertices.len()]);
+            }
+            assert_approx_eq!(f64, area.length(), face.area(), epsilon = 1e-10);
+            assert!(area.normalize().distance(face.normal()) < 1e-8);
+        }
+    }
+
+    #[test]
+    fn test_cells_at() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        for periodic in [false, true] {
+            let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, periodic, None);
+            // The centroid of a convex cell lies inside that cell
+            let centroids = voronoi
+                .cells()
+                .iter()
+                .map(|c| c.centroid())
+                .collect::<Vec<_>>();
+            let expected = (0..generators.len()).collect::<Vec<_>>();
+            assert_eq!(voronoi.cells_at(&centroids), expected);
+            if periodic {
+                let shifted = centroids
+                    .iter()
+                    .map(|&c| c + DVec3::new(2., -1., 0.))
+                    .collect::<Vec<_>>();
+                assert_eq!(voronoi.cells_at(&shifted), expected);
+            }
+        }
+
+        // Transformed and extracted tesselations can still be queried
+        let mut voronoi = Voronoi::build(&generators, anchor, width, DIM3D, false, None);
+        voronoi.transform(DVec3::splat(1.), DVec3::splat(2.));
+        let centroids = voronoi
+            .cells()
+            .iter()
+            .map(|c| c.centroid())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            voronoi.cells_at(&centroids),
+            (0..generators.len()).collect::<Vec<_>>()
+        );
+        let mask = (0..generators.len())
+            .map(|i| i % 3 == 0)
+            .collect::<Vec<_>>();
+        let (extracted, index_map) = voronoi.extract(&mask);
+        let query = (0..generators.len())
+            .filter(|&i| mask[i])
+            .map(|i| centroids[i])
+            .collect::<Vec<_>>();
+        let expected = (0..generators.len())
+            .filter_map(|i| index_map[i])
+            .collect::<Vec<_>>();
+        assert_eq!(extracted.cells_at(&query), expected);
+    }
+
+    #[test]
+    fn test_replicate() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 3, 0.5);
+        let mut replicated_generators = vec![];
+        for i in 0..2 {
+            for j in 0..1 {
+                for k in 0..3 {
+                    let offset = DVec3::new(i as f64, j as f64, k as f64);
+                    replicated_generators.extend(generators.iter().map(|&g| g + offset));
+                }
+            }
+        }
+
+        // Full and partial tesselations (with faces towards unconstructed cells that have no twin)
+        let mask = (0..generators.len())
+            .map(|i| i % 4 != 0)
+            .collect::<Vec<_>>();
+        for mask in [None, Some(mask)] {
+            let voronoi = match &mask {
+                Some(mask) => {
+                    Voronoi::build_partial(&generators, mask, anchor, width, DIM3D, true, None)
+                }
+                None => Voronoi::build(&generators, anchor, width, DIM3D, true, None),
+            };
+            let replicated = voronoi.replicate(2, 1, 3);
+
+            let replicated_mask = mask.map(|mask| mask.repeat(6));
+            let reference = Voronoi::build_profiled(
+                &replicated_generators,
+                replicated_mask.as_deref(),
+                anchor,
+                DVec3::new(2., 1., 3.),
+                DIM3D,
+                true,
+                None,
+            )
+            .0;
+            assert_eq!(replicated.cells().len(), reference.cells().len());
+            assert_eq!(replicated.faces().len(), reference.faces().len());
+            for (cell, ref_cell) in replicated.cells().iter().zip(reference.cells()) {
+                assert_approx_eq!(f64, cell.volume(), ref_cell.volume(), epsilon = 1e-12);
+                assert_eq!(cell.face_count(), ref_cell.face_count());
+                let mut neighbours = cell
+                    .faces(&replicated)
+                    .map(|f| (f.left(), f.right(), f.shift().is_some()))
+                    .collect::<Vec<_>>();
+                let mut ref_neighbours = ref_cell
+                    .faces(&reference)
+                    .map(|f| (f.left(), f.right(), f.shift().is_some()))
+                    .collect::<Vec<_>>();
+                neighbours.sort();
+                ref_neighbours.sort();
+                assert_eq!(neighbours, ref_neighbours);
+            }
+        }
+    }
+
     #[test]
     fn test_partial() {
         let pert = 0.9;
         let anchor = DVec3::ZERO;
         let width = DVec3::splat(1.);
         let generators = perturbed_grid(anchor, width, 3, pert);
-        let voronoi_all = Voronoi::build(&generators, anchor, width, DIM3D, false, None, None);
+        let voronoi_all = Voronoi::build(&generators, anchor, width, DIM3D, false, None);
         for i in 0..27 {
             let mut mask = vec![false; 27];
             mask[i] = true;
             let voronoi_partial =
-                Voronoi::build_partial(&generators, &mask, anchor, width, DIM3D, false, None, None);
+                Voronoi::build_partial(&generators, &mask, anchor, width, DIM3D, false, None);
             for j in 0..27 {
                 if j == i {
                     assert_approx_eq!(
@@ -738,6 +2244,107 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_symmetric_partial_faces() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        let mask = (0..generators.len())
+            .map(|i| generators[i].x < 0.5)
+            .collect::<Vec<_>>();
+        let full = Voronoi::build(&generators, anchor, width, DIM3D, true, None);
+        for deduplicate_periodic_faces in [false, true] {
+            let options = BuildOptions::new()
+                .deduplicate_periodic_faces(deduplicate_periodic_faces)
+                .symmetric_partial_faces(true);
+            let (partial, _) = Voronoi::build_with_options(
+                &generators,
+                Some(&mask),
+                anchor,
+                width,
+                DIM3D,
+                true,
+                None,
+                &options,
+            );
+            let (asymmetric, _) = Voronoi::build_with_options(
+                &generators,
+                Some(&mask),
+                anchor,
+                width,
+                DIM3D,
+                true,
+                None,
+                &options.clone().symmetric_partial_faces(false),
+            );
+            let mut missing_area = 0.;
+            for (idx, cell) in partial.cells.iter().enumerate() {
+                if mask[idx] {
+                    assert_approx_eq!(
+                        f64,
+                        cell.volume(),
+                        full.cells[idx].volume(),
+                        epsilon = 1e-12
+                    );
+                    continue;
+                }
+                // The unconstructed cells see all their faces with constructed cells
+                let expected = full.cells[idx]
+                    .faces(&full)
+                    .filter(|face| {
+                        let other = if face.left() == idx {
+                            face.right()
+                        } else {
+                            Some(face.left())
+                        };
+                        other.is_some_and(|other| mask[other])
+                    })
+                    .map(|face| face.area())
+                    .sum::<f64>();
+                let area = cell.faces(&partial).map(|face| face.area()).sum::<f64>();
+                assert_approx_eq!(f64, area, expected, epsilon = 1e-10);
+                missing_area += area
+                    - asymmetric.cells[idx]
+                        .faces(&asymmetric)
+                        .map(|face| face.area())
+                        .sum::<f64>();
+                // The mirrored faces are oriented away from the unconstructed cell
+                for face in cell.faces(&partial).filter(|face| face.left() == idx) {
+                    assert!((face.centroid() - generators[idx]).dot(face.normal()) > 0.);
+                }
+            }
+            assert!(missing_area > 0.);
+        }
+    }
+
+    #[test]
+    fn test_partial_by() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 6, 0.5);
+        let center = DVec3::splat(0.5);
+        for periodic in [false, true] {
+            let inside = |_, loc: DVec3| loc.distance(center) < 0.3;
+            let voronoi = Voronoi::build_partial_by(
+                &generators,
+                inside,
+                anchor,
+                width,
+                DIM3D,
+                periodic,
+                None,
+            );
+            let mask = crate::mask::in_sphere(&generators, center, 0.3, None);
+            let expected =
+                Voronoi::build_partial(&generators, &mask, anchor, width, DIM3D, periodic, None);
+            assert_eq!(voronoi.faces.len(), expected.faces.len());
+            for (cell, expected) in voronoi.cells.iter().zip(expected.cells.iter()) {
+                assert_eq!(cell.volume(), expected.volume());
+                assert_eq!(cell.face_count(), expected.face_count());
+            }
+        }
+    }
+
     #[test]
     fn test_2_d() {
         let pert = 0.95;
@@ -749,18 +2356,12 @@ mod test {
             z: 1.,
         };
         let generators = perturbed_plane(anchor, width, count, pert);
-        let voronoi = Voronoi::build(&generators, anchor, width, DIM2D, true, None, None);
+        let voronoi = Voronoi::build(&generators, anchor, width, DIM2D, true, None);
 
         #[cfg(feature = "hdf5")]
         voronoi.save("test_2_d.hdf5").unwrap();
 
-        assert_approx_eq!(
-            f64,
-            voronoi.cells.iter().map(|c| c.volume()).sum(),
-            4.,
-            epsilon = 1e-10,
-            ulps = 8
-        );
+        assert_approx_eq!(f64, voronoi.total_volume(), 4., epsilon = 1e-10, ulps = 8);
     }
 
     #[test]
@@ -770,13 +2371,13 @@ mod test {
         let anchor = DVec3::ZERO;
         let width = DVec3::splat(2.);
         let generators = perturbed_grid(anchor, width, count, pert);
-        let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, false, None, None);
-        let total_volume: f64 = voronoi.cells.iter().map(|c| c.volume()).sum();
+        let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, false, None);
         assert_eq!(voronoi.cells.len(), generators.len());
-        assert_approx_eq!(f64, total_volume, 8., epsilon = 1e-10, ulps = 8);
+        assert_approx_eq!(f64, voronoi.total_volume(), 8., epsilon = 1e-10, ulps = 8);
     }
 
     #[test]
+    #[allow(clippy::needless_range_loop)]
     fn test_density_grad_2_d() {
         let pert = 1.;
         let counts = [10, 40, 20, 80];
@@ -801,12 +2402,382 @@ mod test {
                 pert,
             ));
         }
-        let voronoi = Voronoi::build(&plane, anchor, width, DIM2D, true, None, None);
+        let voronoi = Voronoi::build(&plane, anchor, width, DIM2D, true, None);
         #[cfg(feature = "hdf5")]
         voronoi.save("test_density_grad_2_d.hdf5").unwrap();
 
-        let total_volume: f64 = voronoi.cells.iter().map(|c| c.volume()).sum();
         assert_eq!(voronoi.cells.len(), plane.len());
-        assert_approx_eq!(f64, total_volume, 1., epsilon = 1e-10, ulps = 8);
+        assert_approx_eq!(f64, voronoi.total_volume(), 1., epsilon = 1e-10, ulps = 8);
+    }
+
+    #[test]
+    fn test_volume_error() {
+        let anchor = DVec3::splat(1.);
+        let width = DVec3::new(2., 3., 4.);
+        for (generators, dimensionality, domain_volume) in [
+            (perturbed_grid(anchor, width, 6, 0.5), DIM3D, 24.),
+            (perturbed_plane(anchor, width, 10, 0.5), DIM2D, 6.),
+        ] {
+            for periodic in [false, true] {
+                let voronoi =
+                    Voronoi::build(&generators, anchor, width, dimensionality, periodic, None);
+                assert_approx_eq!(f64, voronoi.domain_volume(), domain_volume);
+                assert!(voronoi.volume_error() < 1e-10);
+
+                // Partial tesselations only cover part of the simulation volume
+                let mask = (0..generators.len())
+                    .map(|i| i % 2 == 0)
+                    .collect::<Vec<_>>();
+                let partial = Voronoi::build_partial(
+                    &generators,
+                    &mask,
+                    anchor,
+                    width,
+                    dimensionality,
+                    periodic,
+                    None,
+                );
+                let volume = (0..generators.len())
+                    .filter(|&i| mask[i])
+                    .map(|i| voronoi.cells()[i].volume())
+                    .sum::<f64>();
+                assert_approx_eq!(f64, partial.total_volume(), volume, epsilon = 1e-10);
+                assert_approx_eq!(
+                    f64,
+                    partial.volume_error(),
+                    1. - volume / domain_volume,
+                    epsilon = 1e-10
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_cell_face_integrals() {
+        use crate::integrators::{
+            FaceIntegrators, ScalarVoronoiFaceIntegrator, VectorVoronoiFaceIntegrator,
+            VoronoiFaceIntegrator,
+        };
+        use crate::util::signed_area_tri;
+
+        #[derive(Default, Clone)]
+        struct Area(f64);
+        impl VoronoiFaceIntegrator for Area {
+            type Output = f64;
+            fn collect(&mut self, v0: DVec3, v1: DVec3, v2: DVec3, left: DVec3, _right: DVec3) {
+                self.0 += signed_area_tri(v0, v1, v2, left);
+            }
+            fn finalize(&self) -> f64 {
+                self.0
+            }
+        }
+        impl ScalarVoronoiFaceIntegrator for Area {}
+
+        #[derive(Default, Clone)]
+        struct FirstMoment(DVec3);
+        impl VoronoiFaceIntegrator for FirstMoment {
+            type Output = DVec3;
+            fn collect(&mut self, v0: DVec3, v1: DVec3, v2: DVec3, left: DVec3, _right: DVec3) {
+                self.0 += signed_area_tri(v0, v1, v2, left) * (v0 + v1 + v2) / 3.;
+            }
+            fn finalize(&self) -> DVec3 {
+                self.0
+            }
+        }
+        impl VectorVoronoiFaceIntegrator for FirstMoment {}
+
+        let mut integrators = FaceIntegrators::new();
+        let first_moment = integrators.add_vector(FirstMoment::default());
+        let area = integrators.add_scalar(Area::default());
+        // A second integrator of the same type, created by a factory
+        let other_area = integrators.add_scalar_factory(|| Box::new(Area(0.)));
+        assert_eq!((area.index(), other_area.index()), (0, 1));
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        for periodic in [false, true] {
+            let voronoi = Voronoi::build(
+                &generators,
+                anchor,
+                width,
+                DIM3D,
+                periodic,
+                Some(&integrators),
+            );
+            assert_eq!(voronoi.face_integral(area), voronoi.face_integrals().1[0]);
+            assert_eq!(
+                voronoi.face_integral(first_moment).len(),
+                voronoi.faces().len()
+            );
+            for (cell_idx, cell) in voronoi.cells().iter().enumerate() {
+                let faces = cell.faces(&voronoi).collect::<Vec<_>>();
+                let areas = voronoi
+                    .cell_scalar_face_integrals(cell_idx, 0)
+                    .collect::<Vec<_>>();
+                let moments = voronoi
+                    .cell_vector_face_integrals(cell_idx, 0)
+                    .collect::<Vec<_>>();
+                assert_eq!(areas.len(), faces.len());
+                assert_eq!(moments.len(), faces.len());
+                for ((face, area), moment) in faces.iter().zip(areas).zip(moments) {
+                    assert_approx_eq!(f64, area, face.area(), epsilon = 1e-12);
+                    assert!(moment.distance(face.area() * face.centroid()) < 1e-12);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_generic_face_integrators() {
+        use crate::integrators::{FaceIntegrators, VoronoiFaceIntegrator};
+        use crate::util::signed_area_tri;
+
+        /// An integrator with a user defined output type.
+        #[derive(Default, Clone, Copy, Debug)]
+        struct Moments {
+            area: f64,
+            first: DVec3,
+        }
+        impl VoronoiFaceIntegrator for Moments {
+            type Output = Self;
+            fn collect(&mut self, v0: DVec3, v1: DVec3, v2: DVec3, left: DVec3, _right: DVec3) {
+                let area = signed_area_tri(v0, v1, v2, left);
+                self.area += area;
+                self.first += area * (v0 + v1 + v2) / 3.;
+            }
+            fn finalize(&self) -> Self {
+                *self
+            }
+        }
+
+        let mut integrators = FaceIntegrators::new();
+        let moments = integrators.add(Moments::default());
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        for (periodic, deduplicate_periodic_faces) in [(false, false), (true, false), (true, true)]
+        {
+            let (voronoi, _) = Voronoi::build_with_options(
+                &generators,
+                None,
+                anchor,
+                width,
+                DIM3D,
+                periodic,
+                Some(&integrators),
+                &BuildOptions::new().deduplicate_periodic_faces(deduplicate_periodic_faces),
+            );
+            assert_eq!(voronoi.face_integral(moments).len(), voronoi.faces().len());
+            for (face, moments) in voronoi.faces().iter().zip(voronoi.face_integral(moments)) {
+                assert_approx_eq!(f64, moments.area, face.area(), epsilon = 1e-12);
+                assert!(moments.first.distance(face.area() * face.centroid()) < 1e-12);
+            }
+
+            // The results are carried over to extracted tesselations
+            let mask = (0..generators.len())
+                .map(|i| i % 2 == 0)
+                .collect::<Vec<_>>();
+            let (extracted, _) = voronoi.extract(&mask);
+            for (cell_idx, cell) in extracted.cells().iter().enumerate() {
+                for (face, moments) in cell
+                    .faces(&extracted)
+                    .zip(extracted.cell_face_integrals(cell_idx, moments))
+                {
+                    assert_approx_eq!(f64, moments.area, face.area(), epsilon = 1e-12);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_moment_integrators() {
+        use glam::DMat3;
+
+        use crate::integrators::{
+            FaceIntegrators, FirstMomentIntegrator, PositionNormalMomentIntegrator,
+            SecondMomentIntegrator,
+        };
+
+        let mut integrators = FaceIntegrators::new();
+        let first_moment = integrators.add_vector(FirstMomentIntegrator::default());
+        let second_moment = integrators.add(SecondMomentIntegrator::default());
+        let position_normal = integrators.add(PositionNormalMomentIntegrator::default());
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+
+        // A single cell: the faces are the faces of the unit cube
+        let (voronoi, _) = Voronoi::build_with_options(
+            &[DVec3::new(0.3, 0.4, 0.6)],
+            None,
+            anchor,
+            width,
+            DIM3D,
+            false,
+            Some(&integrators),
+            &BuildOptions::new(),
+        );
+        assert_eq!(voronoi.faces().len(), 6);
+        for (face, moment) in voronoi
+            .faces()
+            .iter()
+            .zip(voronoi.face_integral(second_moment))
+        {
+            let axis = (0..3)
+                .find(|&i| face.normal()[i].abs() > 0.5)
+                .expect("Faces of the unit cube are axis aligned!");
+            let c = face.centroid()[axis];
+            for i in 0..3 {
+                for j in 0..3 {
+                    let expected = match (i == axis, j == axis) {
+                        (true, true) => c * c,
+                        (true, false) | (false, true) => 0.5 * c,
+                        (false, false) if i == j => 1. / 3.,
+                        (false, false) => 0.25,
+                    };
+                    assert_approx_eq!(f64, moment.col(j)[i], expected, epsilon = 1e-12);
+                }
+            }
+        }
+
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        for (periodic, deduplicate_periodic_faces) in [(false, false), (true, false), (true, true)]
+        {
+            let (voronoi, _) = Voronoi::build_with_options(
+                &generators,
+                None,
+                anchor,
+                width,
+                DIM3D,
+                periodic,
+                Some(&integrators),
+                &BuildOptions::new().deduplicate_periodic_faces(deduplicate_periodic_faces),
+            );
+            for (face, moment) in voronoi
+                .faces()
+                .iter()
+                .zip(voronoi.face_integral(first_moment))
+            {
+                assert!(moment.distance(face.area() * face.centroid()) < 1e-12);
+            }
+            for (face, moment) in voronoi
+                .faces()
+                .iter()
+                .zip(voronoi.face_integral(second_moment))
+            {
+                assert!(moment.abs_diff_eq(moment.transpose(), 1e-12));
+                let trace = moment.col(0).x + moment.col(1).y + moment.col(2).z;
+                assert!(trace >= face.area() * face.centroid().length_squared() - 1e-12);
+            }
+
+            // Divergence theorem: the moments of the faces of a cell, with the normals pointing away from the cell,
+            // sum to the volume of the cell times the identity.
+            for (cell_idx, cell) in voronoi.cells().iter().enumerate() {
+                let total = cell
+                    .faces(&voronoi)
+                    .zip(voronoi.cell_face_integrals(cell_idx, position_normal))
+                    .map(|(face, moment)| {
+                        if face.left() == cell_idx {
+                            moment
+                        } else {
+                            -moment
+                        }
+                    })
+                    .fold(DMat3::ZERO, |total, moment| total + moment);
+                assert!(total.abs_diff_eq(cell.volume() * DMat3::IDENTITY, 1e-10));
+            }
+        }
+    }
+
+    #[test]
+    fn test_generator_data_integrators() {
+        use std::sync::Arc;
+
+        use crate::integrators::{FaceIntegrators, GeneratorDataIntegrator};
+        use crate::util::signed_area_tri;
+
+        /// The area-weighted mean of the data of the generators of a face (zero on the right for boundary faces).
+        #[derive(Default, Clone)]
+        struct MeanFlux(f64);
+        impl GeneratorDataIntegrator<f64> for MeanFlux {
+            type Output = f64;
+            fn collect(
+                &mut self,
+                v0: DVec3,
+                v1: DVec3,
+                v2: DVec3,
+                left: DVec3,
+                _right: DVec3,
+                left_data: &f64,
+                right_data: Option<&f64>,
+            ) {
+                let mean = 0.5 * (left_data + right_data.unwrap_or(&0.));
+                self.0 += signed_area_tri(v0, v1, v2, left) * mean;
+            }
+            fn finalize(&self) -> f64 {
+                self.0
+            }
+        }
+
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        let data: Arc<[f64]> = (0..generators.len()).map(|i| i as f64).collect();
+        let mut integrators = FaceIntegrators::new();
+        let flux = integrators.add_scalar_with_data(Arc::clone(&data), MeanFlux::default());
+        for (periodic, deduplicate_periodic_faces) in [(false, false), (true, false), (true, true)]
+        {
+            let (voronoi, _) = Voronoi::build_with_options(
+                &generators,
+                None,
+                anchor,
+                width,
+                DIM3D,
+                periodic,
+                Some(&integrators),
+                &BuildOptions::new().deduplicate_periodic_faces(deduplicate_periodic_faces),
+            );
+            for (face, integral) in voronoi.faces().iter().zip(voronoi.face_integral(flux)) {
+                let right_data = face.right().map_or(0., |right| data[right]);
+                let expected = face.area() * 0.5 * (data[face.left()] + right_data);
+                assert_approx_eq!(f64, *integral, expected, epsilon = 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "one item per generator")]
+    fn test_generator_data_length() {
+        use std::sync::Arc;
+
+        use crate::integrators::{FaceIntegrators, GeneratorDataIntegrator};
+
+        #[derive(Default, Clone)]
+        struct LeftData(f64);
+        impl GeneratorDataIntegrator<f64> for LeftData {
+            type Output = f64;
+            fn collect(
+                &mut self,
+                _v0: DVec3,
+                _v1: DVec3,
+                _v2: DVec3,
+                _left: DVec3,
+                _right: DVec3,
+                left_data: &f64,
+                _right_data: Option<&f64>,
+            ) {
+                self.0 = *left_data;
+            }
+            fn finalize(&self) -> f64 {
+                self.0
+            }
+        }
+
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 3, 0.5);
+        let data: Arc<[f64]> = vec![0.; generators.len() - 1].into();
+        let mut integrators = FaceIntegrators::new();
+        integrators.add_scalar_with_data(data, LeftData::default());
+        Voronoi::build(&generators, anchor, width, DIM3D, false, Some(&integrators));
     }
 }
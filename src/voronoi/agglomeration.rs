@@ -0,0 +1,291 @@
+use std::collections::VecDeque;
+
+use glam::DVec3;
+
+use super::Voronoi;
+
+/// A group of cells of a Voronoi tesselation merged into a single cell, see [`Voronoi::agglomerate`].
+#[derive(Debug, Clone)]
+pub struct SuperCell {
+    label: usize,
+    cells: Vec<usize>,
+    volume: f64,
+    centroid: DVec3,
+    face_indices: Vec<usize>,
+}
+
+impl SuperCell {
+    /// Get the label of the cells in this super-cell.
+    pub fn label(&self) -> usize {
+        self.label
+    }
+
+    /// Get the (sorted) indices of the Voronoi cells merged into this super-cell.
+    pub fn cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    /// Get the total volume of the merged cells.
+    pub fn volume(&self) -> f64 {
+        self.volume
+    }
+
+    /// Get the volume weighted centroid of the merged cells.
+    ///
+    /// For periodic tesselations, the cells are unwrapped to the frame of the first cell of this super-cell (as long
+    /// as they are connected through interior faces).
+    pub fn centroid(&self) -> DVec3 {
+        self.centroid
+    }
+
+    /// Get the indices (into [`Agglomeration::faces`]) of the faces that have this super-cell as their left or right
+    /// neighbour.
+    pub fn face_indices(&self) -> &[usize] {
+        &self.face_indices
+    }
+}
+
+/// An exterior face of a super-cell, i.e. a face of a Voronoi tesselation separating two different super-cells or
+/// a boundary face, see [`Voronoi::agglomerate`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SuperFace {
+    face: usize,
+    left: usize,
+    right: Option<usize>,
+}
+
+impl SuperFace {
+    /// Get the index of the original face (into [`Voronoi::faces`]). Its area, centroid, normal and shift are those of
+    /// this face.
+    pub fn face(&self) -> usize {
+        self.face
+    }
+
+    /// Get the index of the super-cell to the left of this face.
+    pub fn left(&self) -> usize {
+        self.left
+    }
+
+    /// Get the index of the super-cell to the right of this face, if any (i.e. `None` for boundary faces).
+    pub fn right(&self) -> Option<usize> {
+        self.right
+    }
+}
+
+/// The super-cells and their exterior faces of an agglomerated Voronoi tesselation, see [`Voronoi::agglomerate`].
+#[derive(Debug, Clone)]
+pub struct Agglomeration {
+    super_cells: Vec<usize>,
+    cells: Vec<SuperCell>,
+    faces: Vec<SuperFace>,
+}
+
+impl Agglomeration {
+    /// Get the super-cells, ordered by their label.
+    pub fn cells(&self) -> &[SuperCell] {
+        &self.cells
+    }
+
+    /// Get the exterior faces of the super-cells, in the order of the original faces.
+    pub fn faces(&self) -> &[SuperFace] {
+        &self.faces
+    }
+
+    /// Get the index of the super-cell containing the Voronoi cell with the given index.
+    pub fn super_cell(&self, cell_idx: usize) -> usize {
+        self.super_cells[cell_idx]
+    }
+}
+
+impl Voronoi {
+    /// Merge the cells of this tesselation with the same label into super-cells.
+    ///
+    /// `labels` contains one (arbitrary) label per cell. Every distinct label produces one super-cell, with the
+    /// aggregated volume and centroid of its cells. The faces between two cells of the same super-cell are dropped,
+    /// and the left and right neighbours of the remaining faces are reindexed to super-cells. Cells that were not
+    /// constructed (in a partial tesselation) do not contribute to their super-cell.
+    ///
+    /// Cells with the same label are meant to form connected groups, but this is not enforced.
+    pub fn agglomerate(&self, labels: &[usize]) -> Agglomeration {
+        assert_eq!(
+            labels.len(),
+            self.cells.len(),
+            "Must have one label per cell!"
+        );
+        let mut distinct_labels = labels.to_vec();
+        distinct_labels.sort_unstable();
+        distinct_labels.dedup();
+        let super_cells = labels
+            .iter()
+            .map(|label| distinct_labels.binary_search(label).unwrap())
+            .collect::<Vec<_>>();
+
+        let mut cells = distinct_labels
+            .iter()
+            .map(|&label| SuperCell {
+                label,
+                cells: vec![],
+                volume: 0.,
+                centroid: DVec3::ZERO,
+                face_indices: vec![],
+            })
+            .collect::<Vec<_>>();
+        for (cell_idx, &super_cell) in super_cells.iter().enumerate() {
+            cells[super_cell].cells.push(cell_idx);
+        }
+
+        let mut faces = vec![];
+        for (face_idx, face) in self.faces.iter().enumerate() {
+            let left = super_cells[face.left()];
+            let right = face.right().map(|right| super_cells[right]);
+            if right == Some(left) {
+                continue;
+            }
+            cells[left].face_indices.push(faces.len());
+            if let (Some(right), None) = (right, face.shift()) {
+                cells[right].face_indices.push(faces.len());
+            }
+            faces.push(SuperFace {
+                face: face_idx,
+                left,
+                right,
+            });
+        }
+
+        // Offsets of the cells to the frame of the first cell of their super-cell (reached through interior faces)
+        let mut offsets = vec![None; self.cells.len()];
+        let mut queue = VecDeque::new();
+        for super_cell in cells.iter_mut() {
+            let mut moment = DVec3::ZERO;
+            for &start in super_cell.cells.iter() {
+                if offsets[start].is_some() {
+                    continue;
+                }
+                offsets[start] = Some(DVec3::ZERO);
+                queue.push_back(start);
+                while let Some(cell_idx) = queue.pop_front() {
+                    let offset = offsets[cell_idx].unwrap();
+                    let cell = &self.cells[cell_idx];
+                    super_cell.volume += cell.volume();
+                    moment += cell.volume() * (cell.centroid() + offset);
+                    for face in cell.faces(self) {
+                        let Some(right) = face.right() else {
+                            continue;
+                        };
+                        let shift = face.shift().unwrap_or(DVec3::ZERO);
+                        let (neighbour, neighbour_offset) = if face.left() == cell_idx {
+                            (right, offset + shift)
+                        } else {
+                            (face.left(), offset - shift)
+                        };
+                        if super_cells[neighbour] == super_cells[cell_idx]
+                            && offsets[neighbour].is_none()
+                        {
+                            offsets[neighbour] = Some(neighbour_offset);
+                            queue.push_back(neighbour);
+                        }
+                    }
+                }
+            }
+            if super_cell.volume > 0. {
+                super_cell.centroid = moment / super_cell.volume;
+            }
+        }
+
+        Agglomeration {
+            super_cells,
+            cells,
+            faces,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+    use crate::voronoi::test::perturbed_grid;
+
+    #[test]
+    fn test_agglomerate() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 6, 0.5);
+        for periodic in [false, true] {
+            let voronoi = Voronoi::build(&generators, anchor, width, 3, periodic, None);
+            // Group the cells per octant
+            let labels = voronoi
+                .cells()
+                .iter()
+                .map(|cell| {
+                    let octant = cell.loc().cmpge(DVec3::splat(0.5));
+                    10 * octant.bitmask() as usize
+                })
+                .collect::<Vec<_>>();
+            let agglomeration = voronoi.agglomerate(&labels);
+            assert_eq!(agglomeration.cells().len(), 8);
+
+            let total_volume = agglomeration
+                .cells()
+                .iter()
+                .map(|c| c.volume())
+                .sum::<f64>();
+            assert_approx_eq!(f64, total_volume, 1., epsilon = 1e-10);
+            for (super_idx, super_cell) in agglomeration.cells().iter().enumerate() {
+                assert_eq!(super_cell.label() % 10, 0);
+                let volume = super_cell
+                    .cells()
+                    .iter()
+                    .map(|&i| voronoi.cells()[i].volume())
+                    .sum::<f64>();
+                assert_approx_eq!(f64, super_cell.volume(), volume, epsilon = 1e-10);
+                for &cell_idx in super_cell.cells() {
+                    assert_eq!(agglomeration.super_cell(cell_idx), super_idx);
+                }
+                if !periodic {
+                    let centroid = super_cell
+                        .cells()
+                        .iter()
+                        .map(|&i| voronoi.cells()[i].volume() * voronoi.cells()[i].centroid())
+                        .sum::<DVec3>()
+                        / volume;
+                    assert!(centroid.distance(super_cell.centroid()) < 1e-10);
+                }
+
+                // The exterior faces form a closed surface enclosing the volume of the super-cell
+                let mut area_vector = DVec3::ZERO;
+                let mut divergence = 0.;
+                for &face_idx in super_cell.face_indices() {
+                    let super_face = &agglomeration.faces()[face_idx];
+                    assert_ne!(super_face.right(), Some(super_face.left()));
+                    let face = &voronoi.faces()[super_face.face()];
+                    let sign = if super_face.left() == super_idx {
+                        1.
+                    } else {
+                        assert_eq!(super_face.right(), Some(super_idx));
+                        -1.
+                    };
+                    area_vector += sign * face.area_vector();
+                    divergence += sign * face.centroid().dot(face.area_vector());
+                }
+                assert!(area_vector.length() < 1e-10);
+                if !periodic {
+                    assert_approx_eq!(f64, divergence / 3., volume, epsilon = 1e-10);
+                }
+            }
+        }
+
+        // A single label merges everything into the simulation volume
+        let voronoi = Voronoi::build(&generators, anchor, width, 3, false, None);
+        let agglomeration = voronoi.agglomerate(&vec![0; generators.len()]);
+        assert_eq!(agglomeration.cells().len(), 1);
+        assert!(agglomeration.faces().iter().all(|f| f.right().is_none()));
+        assert!(
+            agglomeration.cells()[0]
+                .centroid()
+                .distance(DVec3::splat(0.5))
+                < 1e-10
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use bevy_render::{
+    mesh::{Indices, Mesh},
+    render_asset::RenderAssetUsages,
+    render_resource::PrimitiveTopology,
+};
+
+use super::Voronoi;
+
+impl Voronoi {
+    /// Convert the cell with the given index into a flat shaded bevy [`Mesh`] with positions, normals and indices.
+    /// Requires the `bevy` feature to be enabled.
+    ///
+    /// Every face of the cell is fan-triangulated separately (such that every vertex has the normal of its face) and
+    /// the triangles are oriented counterclockwise when seen from outside the cell.
+    pub fn cell_to_bevy_mesh(&self, cell_idx: usize) -> Mesh {
+        let mut mesh_data = MeshData::default();
+        mesh_data.push_cell(self, cell_idx);
+        mesh_data.into()
+    }
+
+    /// Convert the constructed cells (i.e. with nonzero volume) of this tesselation into a single flat shaded bevy
+    /// [`Mesh`], see [`Voronoi::cell_to_bevy_mesh`]. The cells do not share any vertices.
+    /// Requires the `bevy` feature to be enabled.
+    pub fn to_bevy_mesh(&self) -> Mesh {
+        let mut mesh_data = MeshData::default();
+        for (cell_idx, cell) in self.cells.iter().enumerate() {
+            if cell.volume() > 0. {
+                mesh_data.push_cell(self, cell_idx);
+            }
+        }
+        mesh_data.into()
+    }
+}
+
+#[derive(Default)]
+struct MeshData {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    indices: Vec<u32>,
+}
+
+impl MeshData {
+    fn push_cell(&mut self, voronoi: &Voronoi, cell_idx: usize) {
+        let cell = &voronoi.cells[cell_idx];
+        for face in cell.faces(voronoi) {
+            let vertices = face.vertices(voronoi);
+            if vertices.len() < 3 {
+                continue;
+            }
+            // Faces are oriented outwards w.r.t. their left cell.
+            let (normal, reversed) = if face.left() == cell_idx {
+                (face.normal(), false)
+            } else {
+                (-face.normal(), true)
+            };
+            let offset = self.positions.len() as u32;
+            self.positions
+                .extend(vertices.iter().map(|v| v.as_vec3().to_array()));
+            self.normals.extend(std::iter::repeat_n(
+                normal.as_vec3().to_array(),
+                vertices.len(),
+            ));
+            for i in 1..vertices.len() as u32 - 1 {
+                if reversed {
+                    self.indices.extend([offset, offset + i + 1, offset + i]);
+                } else {
+                    self.indices.extend([offset, offset + i, offset + i + 1]);
+                }
+            }
+        }
+    }
+}
+
+impl From<MeshData> for Mesh {
+    fn from(mesh_data: MeshData) -> Self {
+        let mut mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, mesh_data.positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, mesh_data.normals);
+        mesh.insert_indices(Indices::U32(mesh_data.indices));
+        mesh
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy_render::mesh::VertexAttributeValues;
+    use glam::DVec3;
+
+    use super::*;
+    use crate::voronoi::test::perturbed_grid;
+
+    #[test]
+    fn test_bevy_mesh() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 3, 0.5);
+        let voronoi = Voronoi::build(&generators, anchor, width, 3, false, None);
+
+        let mesh = voronoi.cell_to_bevy_mesh(0);
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("Mesh should have positions!");
+        };
+        let Some(Indices::U32(indices)) = mesh.indices() else {
+            panic!("Mesh should have indices!");
+        };
+        // The enclosed volume should match the volume of the cell
+        let volume = indices
+            .chunks(3)
+            .map(|t| {
+                let [a, b, c] = [t[0], t[1], t[2]]
+                    .map(|i| glam::Vec3::from_array(positions[i as usize]).as_dvec3());
+                a.dot(b.cross(c)) / 6.
+            })
+            .sum::<f64>();
+        assert!((volume - voronoi.cells()[0].volume()).abs() < 1e-6);
+
+        let mesh = voronoi.to_bevy_mesh();
+        let face_vertex_count = voronoi
+            .cells()
+            .iter()
+            .flat_map(|cell| cell.faces(&voronoi))
+            .map(|face| face.vertices(&voronoi).len())
+            .sum::<usize>();
+        assert_eq!(mesh.count_vertices(), face_vertex_count);
+    }
+}
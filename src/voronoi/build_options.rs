@@ -0,0 +1,460 @@
+use std::f64::consts::PI;
+
+use glam::DVec3;
+
+use super::{lattice::LatticeBins, Dimensionality};
+
+/// The strategy used to find the candidate neighbours that clip every Voronoi cell, see
+/// [`BuildOptions::neighbour_search`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NeighbourSearch {
+    /// Iterate over the nearest neighbours of every generator (in order of increasing distance) until the safety
+    /// criterion is met. This is the default.
+    #[default]
+    Incremental,
+    /// First clip every cell by (at most) its `k` nearest neighbours, found with a single range query in the r-tree
+    /// (with a radius estimated from the mean density of generators). Only the cells that do not meet the safety
+    /// criterion afterwards fall back to the incremental search.
+    ///
+    /// For quasi-uniform point sets, this significantly reduces the number of r-tree traversals. The result is
+    /// identical to the incremental search.
+    BoundedK {
+        /// The maximal number of neighbours used in the first pass, typically 30 to 40 in 3D.
+        k: usize,
+    },
+    /// Assume that the generators form a (perturbed) regular lattice with `shape[i]` generators along the `i`-th axis
+    /// (only the used dimensions matter), and use a fixed stencil of the generators in the neighbouring lattice cells
+    /// as candidates, instead of searching the r-tree. Only the cells that do not meet the safety criterion with these
+    /// candidates fall back to the incremental search, so the result is identical to the incremental search, also if
+    /// the assumption does not hold.
+    ///
+    /// If no `shape` is given, it is detected from the generators: the lattice is assumed to have (roughly) cubic
+    /// cells, and the incremental search is used for all cells if not every lattice cell contains exactly one
+    /// generator.
+    Lattice {
+        /// The number of generators along every axis of the lattice.
+        shape: Option<[usize; 3]>,
+    },
+}
+
+/// The resolved neighbour search strategy used during the construction.
+#[derive(Clone, Copy)]
+pub(super) enum CandidateSearch<'a> {
+    Incremental,
+    BoundedK { k: usize, radius: f64 },
+    Lattice(&'a LatticeBins),
+}
+
+/// The criterion deciding when the construction of a Voronoi cell is finished, see
+/// [`BuildOptions::safety_criterion`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SafetyCriterion {
+    /// Clip every cell until no other generator can possibly clip it anymore, i.e. until the next nearest neighbour
+    /// lies beyond twice the maximal distance from the generator to the vertices of the cell. This is the default and
+    /// the only criterion that guarantees exact Voronoi cells.
+    #[default]
+    Strict,
+    /// Same as [`SafetyCriterion::Strict`], but never consider neighbours farther than `radius` from the generator.
+    /// Cells that are not complete within this radius are over-estimated (i.e. too large).
+    MaxSearchRadius {
+        /// The maximal distance from a generator to the neighbours clipping its cell.
+        radius: f64,
+    },
+    /// Same as [`SafetyCriterion::Strict`], but clip every cell by at most `max_neighbours` neighbours. Cells that
+    /// are not complete after this many neighbours are over-estimated (i.e. too large).
+    NeighbourBudget {
+        /// The maximal number of neighbours clipping each cell.
+        max_neighbours: usize,
+    },
+}
+
+/// Configuration of the construction of a Voronoi tesselation, see [`crate::Voronoi::build_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct BuildOptions {
+    pub(super) neighbour_search: NeighbourSearch,
+    pub(super) safety_criterion: SafetyCriterion,
+    pub(super) deduplicate_periodic_faces: bool,
+    pub(super) symmetric_partial_faces: bool,
+    pub(super) single_precision_prechecks: bool,
+}
+
+impl BuildOptions {
+    /// Create the default options (equivalent to [`crate::Voronoi::build`]).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use the given strategy to find the neighbours clipping every cell. Defaults to
+    /// [`NeighbourSearch::Incremental`].
+    pub fn neighbour_search(mut self, neighbour_search: NeighbourSearch) -> Self {
+        match neighbour_search {
+            NeighbourSearch::BoundedK { k } => {
+                assert!(k > 0, "The neighbour budget must be positive!")
+            }
+            NeighbourSearch::Lattice { shape: Some(shape) } => assert!(
+                shape.iter().all(|&n| n > 0),
+                "The shape of the lattice must be positive!"
+            ),
+            _ => (),
+        }
+        self.neighbour_search = neighbour_search;
+        self
+    }
+
+    /// Use the given criterion to decide when the construction of a cell is finished. Defaults to
+    /// [`SafetyCriterion::Strict`].
+    ///
+    /// With a relaxed criterion, cells may be reported before they are complete. Whether every cell is complete and
+    /// how much of the required search radius was covered for the incomplete ones is reported in the
+    /// [`crate::CellProfile`]s returned by [`crate::Voronoi::build_with_options`].
+    pub fn safety_criterion(mut self, safety_criterion: SafetyCriterion) -> Self {
+        match safety_criterion {
+            SafetyCriterion::MaxSearchRadius { radius } => {
+                assert!(radius > 0., "The maximal search radius must be positive!")
+            }
+            SafetyCriterion::NeighbourBudget { max_neighbours } => {
+                assert!(max_neighbours > 0, "The neighbour budget must be positive!")
+            }
+            SafetyCriterion::Strict => (),
+        }
+        self.safety_criterion = safety_criterion;
+        self
+    }
+
+    /// Whether to construct every periodic face only once. Defaults to `false`.
+    ///
+    /// Faces between non-periodic neighbours are always constructed only once (by the cell with the lower index).
+    /// Periodic faces however are stored for both of their cells, and are by default constructed in both of them.
+    /// With this option, a periodic face is only constructed by the cell with the lower index and the copy of the
+    /// other cell is mirrored from it (translated and with reversed orientation). The extra face integrals of the
+    /// copies are integrated over a triangulation of the mirrored face.
+    ///
+    /// The resulting tesselation is the same up to round-off errors and the order of the faces.
+    pub fn deduplicate_periodic_faces(mut self, deduplicate_periodic_faces: bool) -> Self {
+        self.deduplicate_periodic_faces = deduplicate_periodic_faces;
+        self
+    }
+
+    /// Whether to also store the periodic faces between a constructed and an unconstructed cell of a partial
+    /// tesselation for the unconstructed cell. Defaults to `false`.
+    ///
+    /// Faces between non-periodic neighbours are stored once and always linked to both of their cells. Periodic
+    /// faces are stored for both of their cells (in the frame of that cell), but in a partial tesselation, the copy
+    /// of a cell that is not constructed is missing. With this option, that copy is mirrored from the copy of the
+    /// constructed cell (as with [`BuildOptions::deduplicate_periodic_faces`]), without constructing the other cell,
+    /// so that fluxes across the boundary of the constructed region can be assembled symmetrically.
+    pub fn symmetric_partial_faces(mut self, symmetric_partial_faces: bool) -> Self {
+        self.symmetric_partial_faces = symmetric_partial_faces;
+        self
+    }
+
+    /// Whether to first test which vertices of a cell are clipped by a new plane in single precision (with
+    /// [`glam::Vec3A`], relative to the generator of the cell). Defaults to `false`.
+    ///
+    /// Only the vertices lying too close to the plane for the single precision test to be conclusive are tested again
+    /// in double precision, so the resulting tesselation is identical.
+    ///
+    /// This option is experimental: it is not backed by a benchmark, and any speedup depends on the target (it needs
+    /// SIMD support to pay off) and the point set, so measure it with [`crate::BuildProfile::cells_time`] on
+    /// representative data before enabling it.
+    pub fn single_precision_prechecks(mut self, single_precision_prechecks: bool) -> Self {
+        self.single_precision_prechecks = single_precision_prechecks;
+        self
+    }
+}
+
+/// Estimate the radius of the ball around a generator containing `k` other generators on average, for `n`
+/// generators uniformly distributed in the simulation volume with the given `width`.
+pub(super) fn bounded_k_radius(
+    k: usize,
+    n: usize,
+    width: DVec3,
+    dimensionality: Dimensionality,
+) -> f64 {
+    let n = n.max(1) as f64;
+    match dimensionality {
+        Dimensionality::Dimensionality1D => (k as f64 * width.x / n) / 2.,
+        Dimensionality::Dimensionality2D => (k as f64 * width.x * width.y / (n * PI)).sqrt(),
+        Dimensionality::Dimensionality3D => {
+            (3. * k as f64 * width.x * width.y * width.z / (4. * n * PI)).cbrt()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+    use crate::integrators::{FaceIntegrators, FirstMomentIntegrator};
+    use crate::voronoi::test::{perturbed_grid, perturbed_plane};
+    use crate::Voronoi;
+
+    #[test]
+    fn test_bounded_k() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let mut generators_3d = perturbed_grid(anchor, width, 5, 0.5);
+        // A dense cluster, to trigger the fallback
+        generators_3d.extend(perturbed_grid(
+            DVec3::splat(0.41),
+            DVec3::splat(0.08),
+            3,
+            0.5,
+        ));
+        let generators_2d = perturbed_plane(anchor, width, 6, 0.5);
+        for (generators, dimensionality) in [(generators_3d, 3), (generators_2d, 2)] {
+            for periodic in [false, true] {
+                let build = |neighbour_search| {
+                    Voronoi::build_with_options(
+                        &generators,
+                        None,
+                        anchor,
+                        width,
+                        dimensionality,
+                        periodic,
+                        None,
+                        &BuildOptions::new().neighbour_search(neighbour_search),
+                    )
+                };
+                let (expected, _) = build(NeighbourSearch::Incremental);
+                for k in [1, 8, 40] {
+                    let (voronoi, profile) = build(NeighbourSearch::BoundedK { k });
+                    if k == 1 {
+                        assert!(profile.fallback_count() > 0);
+                    }
+                    assert_eq!(voronoi.faces().len(), expected.faces().len());
+                    for (cell, expected) in voronoi.cells().iter().zip(expected.cells()) {
+                        assert_eq!(cell.face_count(), expected.face_count());
+                        assert_approx_eq!(f64, cell.volume(), expected.volume(), epsilon = 1e-12);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_safety_criterion() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 5, 0.5);
+        let build = |safety_criterion| {
+            Voronoi::build_with_options(
+                &generators,
+                None,
+                anchor,
+                width,
+                3,
+                false,
+                None,
+                &BuildOptions::new().safety_criterion(safety_criterion),
+            )
+        };
+        let (expected, profile) = build(SafetyCriterion::Strict);
+        assert_eq!(profile.incomplete_count(), 0);
+
+        // Generous limits give exact cells
+        for criterion in [
+            SafetyCriterion::MaxSearchRadius { radius: 2. },
+            SafetyCriterion::NeighbourBudget {
+                max_neighbours: 1000,
+            },
+        ] {
+            let (voronoi, profile) = build(criterion);
+            assert_eq!(profile.incomplete_count(), 0);
+            for (cell, expected) in voronoi.cells().iter().zip(expected.cells()) {
+                assert_approx_eq!(f64, cell.volume(), expected.volume(), epsilon = 1e-12);
+            }
+        }
+
+        // Tight limits over-estimate the incomplete cells
+        for criterion in [
+            SafetyCriterion::MaxSearchRadius { radius: 0.2 },
+            SafetyCriterion::NeighbourBudget { max_neighbours: 6 },
+        ] {
+            let (voronoi, profile) = build(criterion);
+            assert!(profile.incomplete_count() > 0);
+            for ((cell, expected), cell_profile) in voronoi
+                .cells()
+                .iter()
+                .zip(expected.cells())
+                .zip(profile.cells())
+            {
+                if cell_profile.is_complete() {
+                    assert_eq!(cell_profile.confidence(), 1.);
+                    assert_approx_eq!(f64, cell.volume(), expected.volume(), epsilon = 1e-12);
+                } else {
+                    assert!(cell_profile.confidence() > 0. && cell_profile.confidence() <= 1.);
+                    assert!(cell.volume() >= expected.volume() - 1e-12);
+                }
+                if let SafetyCriterion::NeighbourBudget { max_neighbours } = criterion {
+                    assert!(cell_profile.clipping_planes_tested() <= max_neighbours);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_lattice() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let lattice_3d = perturbed_grid(anchor, width, 6, 0.5);
+        let lattice_2d = perturbed_plane(anchor, width, 8, 0.5);
+        // Not a lattice
+        let mut clustered = perturbed_grid(anchor, width, 4, 0.5);
+        clustered.extend(perturbed_grid(
+            DVec3::splat(0.41),
+            DVec3::splat(0.08),
+            3,
+            0.5,
+        ));
+        for (generators, dimensionality, is_lattice) in [
+            (lattice_3d, 3, true),
+            (lattice_2d, 2, true),
+            (clustered, 3, false),
+        ] {
+            for periodic in [false, true] {
+                let build = |neighbour_search| {
+                    Voronoi::build_with_options(
+                        &generators,
+                        None,
+                        anchor,
+                        width,
+                        dimensionality,
+                        periodic,
+                        None,
+                        &BuildOptions::new().neighbour_search(neighbour_search),
+                    )
+                };
+                let (expected, _) = build(NeighbourSearch::Incremental);
+                for shape in [None, Some([6, 6, 6]), Some([2, 3, 1])] {
+                    let (voronoi, profile) = build(NeighbourSearch::Lattice { shape });
+                    if shape.is_none() && is_lattice {
+                        // Detected lattices rarely need the fallback
+                        assert!(profile.fallback_count() * 10 <= generators.len());
+                    }
+                    assert_eq!(voronoi.faces().len(), expected.faces().len());
+                    for (cell, expected) in voronoi.cells().iter().zip(expected.cells()) {
+                        assert_eq!(cell.face_count(), expected.face_count());
+                        assert_approx_eq!(f64, cell.volume(), expected.volume(), epsilon = 1e-12);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_single_precision_prechecks() {
+        let width = DVec3::splat(1.);
+        // Far from the origin, where single precision coordinates would be too coarse
+        for anchor in [DVec3::ZERO, DVec3::splat(1e6)] {
+            for (generators, dimensionality) in [
+                (perturbed_grid(anchor, width, 6, 0.5), 3),
+                (perturbed_plane(anchor, width, 10, 0.5), 2),
+            ] {
+                for periodic in [false, true] {
+                    let build = |single_precision_prechecks| {
+                        Voronoi::build_with_options(
+                            &generators,
+                            None,
+                            anchor,
+                            width,
+                            dimensionality,
+                            periodic,
+                            None,
+                            &BuildOptions::new()
+                                .single_precision_prechecks(single_precision_prechecks),
+                        )
+                    };
+                    let (expected, expected_profile) = build(false);
+                    let (voronoi, profile) = build(true);
+                    // The same vertices are clipped, so the tesselations are identical
+                    assert_eq!(voronoi.faces().len(), expected.faces().len());
+                    assert_eq!(
+                        profile.total_clipping_planes_tested(),
+                        expected_profile.total_clipping_planes_tested()
+                    );
+                    for (cell, expected) in voronoi.cells().iter().zip(expected.cells()) {
+                        assert_eq!(cell.face_count(), expected.face_count());
+                        assert_eq!(cell.volume(), expected.volume());
+                        assert_eq!(cell.centroid(), expected.centroid());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_deduplicate_periodic_faces() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let mut integrators = FaceIntegrators::new();
+        integrators.add_vector(FirstMomentIntegrator::default());
+        // Includes cells that are their own periodic neighbour
+        for (generators, dimensionality) in [
+            (perturbed_grid(anchor, width, 4, 0.5), 3),
+            (perturbed_grid(anchor, width, 2, 0.5), 3),
+            (perturbed_plane(anchor, width, 5, 0.5), 2),
+        ] {
+            let build = |deduplicate_periodic_faces| {
+                Voronoi::build_with_options(
+                    &generators,
+                    None,
+                    anchor,
+                    width,
+                    dimensionality,
+                    true,
+                    Some(&integrators),
+                    &BuildOptions::new().deduplicate_periodic_faces(deduplicate_periodic_faces),
+                )
+                .0
+            };
+            let expected = build(false);
+            let voronoi = build(true);
+            assert_eq!(voronoi.faces().len(), expected.faces().len());
+            assert_eq!(voronoi.face_vertices.len(), expected.face_vertices.len());
+            for (idx, (cell, expected_cell)) in
+                voronoi.cells().iter().zip(expected.cells()).enumerate()
+            {
+                assert_eq!(cell.face_count(), expected_cell.face_count());
+                assert_approx_eq!(f64, cell.volume(), expected_cell.volume(), epsilon = 1e-12);
+                // Match the faces of the cell by their neighbour and centroid
+                let expected_faces = expected_cell
+                    .face_indices(&expected)
+                    .iter()
+                    .map(|&i| (&expected.faces()[i], expected.vector_face_integrals[0][i]))
+                    .collect::<Vec<_>>();
+                for (face, integral) in cell
+                    .faces(&voronoi)
+                    .zip(cell.vector_face_integrals(0, &voronoi))
+                {
+                    if face.shift().is_some() {
+                        assert_eq!(face.left(), idx);
+                    }
+                    let (expected_face, expected_integral) = expected_faces
+                        .iter()
+                        .find(|(f, _)| {
+                            f.right() == face.right()
+                                && f.centroid().distance(face.centroid()) < 1e-10
+                        })
+                        .expect("Face must exist in both tesselations!");
+                    assert_eq!(face.left(), expected_face.left());
+                    assert_approx_eq!(f64, face.area(), expected_face.area(), epsilon = 1e-12);
+                    assert!(face.normal().distance(expected_face.normal()) < 1e-10);
+                    assert!(integral.distance(*expected_integral) < 1e-10);
+                    let vertices = face.vertices(&voronoi);
+                    let expected_vertices = expected_face.vertices(&expected);
+                    assert_eq!(vertices.len(), expected_vertices.len());
+                    // Same vertices in the same (cyclic) order
+                    let n = vertices.len();
+                    let start = (0..n)
+                        .find(|&i| vertices[i].distance(expected_vertices[0]) < 1e-10)
+                        .expect("Faces must have the same vertices!");
+                    for (i, expected_vertex) in expected_vertices.iter().enumerate() {
+                        assert!(vertices[(start + i) % n].distance(*expected_vertex) < 1e-10);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use geo::{
+    Area, BooleanOps, Centroid, Contains, Coord, Intersects, LineString, MultiPolygon, Polygon,
+};
+use glam::DVec2;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use super::Voronoi2D;
+
+/// A cell of a [`Voronoi2D`] tesselation clipped to a polygonal domain, see [`Voronoi2D::clip_to_polygon`].
+#[derive(Debug, Clone)]
+pub struct ClippedCell2D {
+    polygon: MultiPolygon<f64>,
+    area: f64,
+    centroid: DVec2,
+    clipped: bool,
+}
+
+impl ClippedCell2D {
+    /// Get the polygon(s) of this cell. A cell can be split into multiple pieces by a concave domain and is empty
+    /// when it lies entirely outside of the domain.
+    pub fn polygon(&self) -> &MultiPolygon<f64> {
+        &self.polygon
+    }
+
+    /// Get the area of this (clipped) cell.
+    pub fn area(&self) -> f64 {
+        self.area
+    }
+
+    /// Get the centroid of this (clipped) cell (the generator of the original cell if the clipped cell is empty).
+    pub fn centroid(&self) -> DVec2 {
+        self.centroid
+    }
+
+    /// Whether this cell was changed by the clipping, i.e. whether it intersected the boundary of the domain or lies
+    /// entirely outside of it (and is hence empty). Cells that were not changed keep the area of the original cell.
+    pub fn clipped(&self) -> bool {
+        self.clipped
+    }
+}
+
+impl Voronoi2D {
+    /// Convert the cell with the given index to a `geo` polygon (see [`Voronoi2D::cell_polygon`]).
+    pub fn cell_to_geo_polygon(&self, cell_idx: usize) -> Polygon<f64> {
+        let exterior = self
+            .cell_polygon(cell_idx)
+            .into_iter()
+            .map(|v| Coord { x: v.x, y: v.y })
+            .collect::<Vec<_>>();
+        Polygon::new(LineString::new(exterior), vec![])
+    }
+
+    /// Clip the cells of this tesselation to the given (possibly concave, with holes) polygonal `domain`.
+    ///
+    /// The tesselation must cover the domain, e.g. by using the bounding box of the domain as simulation volume.
+    /// Cells that do not intersect the boundary of the domain are kept as is (or become empty when they lie entirely
+    /// outside of it), the other cells are intersected with the domain and their area and centroid are recomputed.
+    /// The clipped geometry is only accurate up to the (fixed) precision of the boolean operations of `geo`.
+    /// Periodic tesselations are not supported, as their cells are not wrapped back into the simulation volume.
+    pub fn clip_to_polygon(&self, domain: &MultiPolygon<f64>) -> Vec<ClippedCell2D> {
+        assert!(
+            !self.as_voronoi().periodic,
+            "Cannot clip a periodic tesselation!"
+        );
+        let boundary = domain
+            .iter()
+            .flat_map(|polygon| std::iter::once(polygon.exterior()).chain(polygon.interiors()))
+            .collect::<Vec<_>>();
+
+        let clip = |cell_idx: usize| {
+            let cell = self.cell(cell_idx);
+            let empty = ClippedCell2D {
+                polygon: MultiPolygon::new(vec![]),
+                area: 0.,
+                centroid: cell.loc(),
+                clipped: false,
+            };
+            if cell.area() == 0. {
+                return empty;
+            }
+            let polygon = self.cell_to_geo_polygon(cell_idx);
+            if !boundary.iter().any(|ring| ring.intersects(&polygon)) {
+                let centroid = cell.centroid();
+                return if domain.contains(&Coord {
+                    x: centroid.x,
+                    y: centroid.y,
+                }) {
+                    ClippedCell2D {
+                        polygon: MultiPolygon::new(vec![polygon]),
+                        area: cell.area(),
+                        centroid,
+                        clipped: false,
+                    }
+                } else {
+                    ClippedCell2D {
+                        clipped: true,
+                        ..empty
+                    }
+                };
+            }
+
+            let polygon = MultiPolygon::new(vec![polygon]).intersection(domain);
+            let area = polygon.unsigned_area();
+            let centroid = polygon
+                .centroid()
+                .map_or(cell.loc(), |c| DVec2::new(c.x(), c.y()));
+            ClippedCell2D {
+                polygon,
+                area,
+                centroid,
+                clipped: true,
+            }
+        };
+
+        #[cfg(feature = "rayon")]
+        return (0..self.cell_count()).into_par_iter().map(clip).collect();
+        #[cfg(not(feature = "rayon"))]
+        return (0..self.cell_count()).map(clip).collect();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+    use geo::polygon;
+    use rand::{distributions::Uniform, prelude::*};
+
+    use super::*;
+
+    #[test]
+    fn test_clip_to_polygon() {
+        // An L-shaped domain with a square hole
+        let domain: MultiPolygon<f64> = polygon![
+            exterior: [
+                (x: 0., y: 0.),
+                (x: 1., y: 0.),
+                (x: 1., y: 0.5),
+                (x: 0.5, y: 0.5),
+                (x: 0.5, y: 1.),
+                (x: 0., y: 1.),
+            ],
+            interiors: [[
+                (x: 0.1, y: 0.1),
+                (x: 0.1, y: 0.3),
+                (x: 0.3, y: 0.3),
+                (x: 0.3, y: 0.1),
+            ]],
+        ]
+        .into();
+        let domain_area = 0.75 - 0.04;
+
+        let mut rng = thread_rng();
+        let distr = Uniform::new(0., 1.);
+        let generators = (0..100)
+            .map(|_| DVec2::new(rng.sample(distr), rng.sample(distr)))
+            .collect::<Vec<_>>();
+        let voronoi = Voronoi2D::build(&generators, DVec2::ZERO, DVec2::ONE, false);
+        let cells = voronoi.clip_to_polygon(&domain);
+        assert_eq!(cells.len(), generators.len());
+
+        let total_area = cells.iter().map(|c| c.area()).sum::<f64>();
+        // The boolean operations of `geo` snap the coordinates to a fixed precision grid
+        assert_approx_eq!(f64, total_area, domain_area, epsilon = 1e-8);
+        for (i, cell) in cells.iter().enumerate() {
+            assert_approx_eq!(
+                f64,
+                cell.area(),
+                cell.polygon().unsigned_area(),
+                epsilon = 1e-12
+            );
+            if !cell.clipped() {
+                assert_approx_eq!(f64, cell.area(), voronoi.cell(i).area(), epsilon = 1e-12);
+            }
+            assert!(cell.area() <= voronoi.cell(i).area() + 1e-8);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use super::Voronoi;
+
+/// The differences between two matching cells of two Voronoi tesselations.
+#[derive(Debug, Clone, Copy)]
+pub struct CellDifference {
+    idx: usize,
+    volume_difference: f64,
+    centroid_difference: f64,
+    face_count_difference: i64,
+}
+
+impl CellDifference {
+    /// Get the index (generator id) of the cells being compared.
+    pub fn idx(&self) -> usize {
+        self.idx
+    }
+
+    /// Get the difference in volume (`other - self`).
+    pub fn volume_difference(&self) -> f64 {
+        self.volume_difference
+    }
+
+    /// Get the distance between the centroids of both cells.
+    pub fn centroid_difference(&self) -> f64 {
+        self.centroid_difference
+    }
+
+    /// Get the difference in face count (`other - self`).
+    pub fn face_count_difference(&self) -> i64 {
+        self.face_count_difference
+    }
+}
+
+/// A report of the differences between two Voronoi tesselations, see [`Voronoi::compare`].
+#[derive(Debug, Clone)]
+pub struct ComparisonReport {
+    cell_counts: (usize, usize),
+    max_volume_difference: f64,
+    max_centroid_difference: f64,
+    differences: Vec<CellDifference>,
+}
+
+impl ComparisonReport {
+    /// Whether both tesselations match within the tolerance.
+    pub fn is_match(&self) -> bool {
+        self.cell_counts.0 == self.cell_counts.1 && self.differences.is_empty()
+    }
+
+    /// Get the number of cells of both tesselations.
+    pub fn cell_counts(&self) -> (usize, usize) {
+        self.cell_counts
+    }
+
+    /// Get the maximal absolute volume difference over all compared cells.
+    pub fn max_volume_difference(&self) -> f64 {
+        self.max_volume_difference
+    }
+
+    /// Get the maximal distance between centroids over all compared cells.
+    pub fn max_centroid_difference(&self) -> f64 {
+        self.max_centroid_difference
+    }
+
+    /// Get the cells that differ by more than the tolerance (or have a different face count).
+    pub fn differences(&self) -> &[CellDifference] {
+        self.differences.as_ref()
+    }
+}
+
+impl Voronoi {
+    /// Compare this Voronoi tesselation with another one.
+    ///
+    /// Cells are matched by generator id (i.e. index). A cell is reported as different when its volume or centroid
+    /// differ by more than `tolerance` (absolute) or when its face count differs. Only the first `min(n, m)` cells are
+    /// compared if the number of cells differs.
+    pub fn compare(&self, other: &Voronoi, tolerance: f64) -> ComparisonReport {
+        let mut max_volume_difference = 0f64;
+        let mut max_centroid_difference = 0f64;
+        let mut differences = vec![];
+        for (idx, (cell, other_cell)) in self.cells.iter().zip(other.cells.iter()).enumerate() {
+            let volume_difference = other_cell.volume() - cell.volume();
+            let centroid_difference = other_cell.centroid().distance(cell.centroid());
+            let face_count_difference = other_cell.face_count() as i64 - cell.face_count() as i64;
+            max_volume_difference = max_volume_difference.max(volume_difference.abs());
+            max_centroid_difference = max_centroid_difference.max(centroid_difference);
+            if volume_difference.abs() > tolerance
+                || centroid_difference > tolerance
+                || face_count_difference != 0
+            {
+                differences.push(CellDifference {
+                    idx,
+                    volume_difference,
+                    centroid_difference,
+                    face_count_difference,
+                });
+            }
+        }
+
+        ComparisonReport {
+            cell_counts: (self.cells.len(), other.cells.len()),
+            max_volume_difference,
+            max_centroid_difference,
+            differences,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use glam::DVec3;
+
+    use super::*;
+    use crate::voronoi::test::perturbed_grid;
+
+    #[test]
+    fn test_compare() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let mut generators = perturbed_grid(anchor, width, 4, 0.5);
+        let voronoi = Voronoi::build(&generators, anchor, width, 3, false, None);
+        let report = voronoi.compare(&voronoi, 1e-12);
+        assert!(report.is_match());
+        assert_eq!(report.max_volume_difference(), 0.);
+
+        generators[5] += DVec3::splat(0.01);
+        let other = Voronoi::build(&generators, anchor, width, 3, false, None);
+        let report = voronoi.compare(&other, 1e-12);
+        assert!(!report.is_match());
+        assert!(report.differences().iter().any(|d| d.idx() == 5));
+        assert!(report.max_volume_difference() > 0.);
+        let total_difference = report
+            .differences()
+            .iter()
+            .map(|d| d.volume_difference())
+            .sum::<f64>();
+        assert!(total_difference.abs() < 1e-10);
+    }
+}
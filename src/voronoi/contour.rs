@@ -0,0 +1,224 @@
+use glam::{DVec2, DVec3};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::util::weld;
+
+use super::{voronoi_cell::ConvexCell, Boundary, Voronoi2D};
+
+impl Voronoi2D {
+    /// Get the triangles of the Delaunay triangulation dual to this tesselation, as the (cell index, position) pairs of
+    /// their corners.
+    ///
+    /// Every vertex of the tesselation corresponds to a triangle, reported once, by the lowest-index cell seeing that
+    /// vertex inside the simulation volume. For periodic tesselations, the corners of triangles connecting periodic
+    /// images of generators are the shifted positions of the generators.
+    fn dual_triangles(&self) -> Vec<[(usize, DVec2); 3]> {
+        let voronoi = self.as_voronoi();
+        let simulation_volume = ConvexCell::init_simulation_volume(
+            voronoi.anchor,
+            voronoi.width,
+            voronoi.periodic,
+            voronoi.dimensionality,
+        );
+        let (anchor, upper) = (
+            voronoi.anchor.truncate(),
+            (voronoi.anchor + voronoi.width).truncate(),
+        );
+
+        let cell_triangles = |cell_idx: usize| {
+            if voronoi.cells[cell_idx].volume() == 0. {
+                return vec![];
+            }
+            let Some(convex_cell) = ConvexCell::reconstruct(
+                voronoi,
+                cell_idx,
+                &simulation_volume,
+                voronoi.dimensionality,
+            ) else {
+                return vec![];
+            };
+            let loc = convex_cell.loc.truncate();
+            convex_cell
+                .vertices
+                .iter()
+                .filter_map(|vertex| {
+                    // Every vertex of the 2D tesselation is shared by the top and bottom of the (prismatic) cell
+                    let (a, b, c) = vertex.dual;
+                    let planes = [a, b, c].map(|idx| &convex_cell.clipping_planes[idx]);
+                    let bottom = planes
+                        .iter()
+                        .position(|plane| plane.boundary == Some(Boundary::ZMin))?;
+                    let mut neighbours =
+                        planes.iter().enumerate().filter(|&(i, _)| i != bottom).map(
+                            |(_, plane)| {
+                                let idx = plane.right_idx?;
+                                let shift = plane.shift.unwrap_or(DVec3::ZERO);
+                                Some((
+                                    idx,
+                                    shift,
+                                    voronoi.cells[idx].loc().truncate() + shift.truncate(),
+                                ))
+                            },
+                        );
+                    let (ngb_1, ngb_2) = (neighbours.next()??, neighbours.next()??);
+
+                    // Only the lowest-index cell seeing the vertex inside the simulation volume reports it.
+                    let circumcenter = vertex.loc.truncate();
+                    if voronoi.periodic
+                        && !(circumcenter.cmpge(anchor).all() && circumcenter.cmplt(upper).all())
+                    {
+                        return None;
+                    }
+                    if [ngb_1, ngb_2]
+                        .iter()
+                        .any(|&(idx, shift, _)| shift == DVec3::ZERO && idx <= cell_idx)
+                    {
+                        return None;
+                    }
+                    Some([(cell_idx, loc), (ngb_1.0, ngb_1.2), (ngb_2.0, ngb_2.2)])
+                })
+                .collect::<Vec<_>>()
+        };
+
+        #[cfg(feature = "rayon")]
+        return (0..voronoi.cells.len())
+            .into_par_iter()
+            .flat_map_iter(cell_triangles)
+            .collect();
+        #[cfg(not(feature = "rayon"))]
+        return (0..voronoi.cells.len()).flat_map(cell_triangles).collect();
+    }
+
+    /// Extract the iso-contour at `level` of the cell-centred scalar field `values` (one value per cell).
+    ///
+    /// The field is linearly interpolated over the triangles of the Delaunay triangulation dual to this tesselation
+    /// (with a value at every generator) and the contour is traced with marching triangles. Returns the polylines of the
+    /// contour: closed contours start and end with the same point. Contours end where the triangulation does not cover
+    /// the simulation volume (near its boundary, for non-periodic tesselations). For periodic tesselations, contours
+    /// crossing the boundary of the simulation volume continue on the periodic images of the cells, so they are not
+    /// wrapped.
+    pub fn contour(&self, values: &[f64], level: f64) -> Vec<Vec<DVec2>> {
+        assert_eq!(
+            values.len(),
+            self.cell_count(),
+            "Must have one value per cell!"
+        );
+        // The segments of the contour in every triangle it crosses
+        let mut segments = vec![];
+        for triangle in self.dual_triangles() {
+            let above = triangle.map(|(idx, _)| values[idx] >= level);
+            let crossings = (0..3)
+                .filter(|&i| above[i] != above[(i + 1) % 3])
+                .map(|i| {
+                    // Interpolate in a fixed order along the edge to get identical crossings for both triangles
+                    let (mut p, mut q) = (triangle[i], triangle[(i + 1) % 3]);
+                    if p.0 > q.0 {
+                        (p, q) = (q, p);
+                    }
+                    let t = (level - values[p.0]) / (values[q.0] - values[p.0]);
+                    p.1 + t * (q.1 - p.1)
+                })
+                .collect::<Vec<_>>();
+            if let [start, end] = crossings[..] {
+                segments.push((start, end));
+            }
+        }
+
+        // Weld the crossings and chain the segments into polylines
+        let points = segments
+            .iter()
+            .flat_map(|&(start, end)| [start.extend(0.), end.extend(0.)])
+            .collect::<Vec<_>>();
+        let (points, indices) = weld(&points, 1e-10 * self.as_voronoi().width.max_element());
+        let mut neighbours = vec![vec![]; points.len()];
+        for (segment, ends) in indices.chunks_exact(2).enumerate() {
+            if ends[0] != ends[1] {
+                neighbours[ends[0]].push(segment);
+                neighbours[ends[1]].push(segment);
+            }
+        }
+        let other_end = |segment: usize, point: usize| {
+            if indices[2 * segment] == point {
+                indices[2 * segment + 1]
+            } else {
+                indices[2 * segment]
+            }
+        };
+        let mut used = vec![false; segments.len()];
+        let trace = |start: usize, used: &mut [bool]| {
+            let mut polyline = vec![start];
+            let mut current = start;
+            while let Some(&segment) = neighbours[current].iter().find(|&&s| !used[s]) {
+                used[segment] = true;
+                current = other_end(segment, current);
+                polyline.push(current);
+            }
+            polyline
+                .into_iter()
+                .map(|idx| points[idx].truncate())
+                .collect::<Vec<_>>()
+        };
+        // Open polylines start at points with a single segment, the remaining segments form closed loops
+        let mut polylines = vec![];
+        for (point, segments) in neighbours.iter().enumerate() {
+            if segments.len() == 1 && !used[segments[0]] {
+                polylines.push(trace(point, &mut used));
+            }
+        }
+        for (point, segments) in neighbours.iter().enumerate() {
+            if segments.iter().any(|&s| !used[s]) {
+                polylines.push(trace(point, &mut used));
+            }
+        }
+        polylines
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f64::consts::TAU;
+
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+    use crate::voronoi::test::perturbed_plane;
+
+    #[test]
+    fn test_contour() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_plane(anchor, width, 20, 0.5)
+            .into_iter()
+            .map(|g| g.truncate())
+            .collect::<Vec<_>>();
+        for periodic in [false, true] {
+            let voronoi = Voronoi2D::build(&generators, DVec2::ZERO, DVec2::ONE, periodic);
+
+            // A circle: a single closed contour, with all points on the contour of the linear interpolant
+            let center = DVec2::splat(0.5);
+            let values = voronoi
+                .cells()
+                .map(|cell| cell.loc().distance(center))
+                .collect::<Vec<_>>();
+            let contours = voronoi.contour(&values, 0.3);
+            assert_eq!(contours.len(), 1);
+            let contour = &contours[0];
+            assert_eq!(contour.first(), contour.last());
+            for p in contour.iter() {
+                assert!((p.distance(center) - 0.3).abs() < 0.02);
+            }
+            let length = contour.windows(2).map(|w| w[0].distance(w[1])).sum::<f64>();
+            assert_approx_eq!(f64, length, 0.3 * TAU, epsilon = 0.02);
+        }
+
+        // A linear field: a single straight contour
+        let voronoi = Voronoi2D::build(&generators, DVec2::ZERO, DVec2::ONE, false);
+        let values = voronoi.cells().map(|cell| cell.loc().x).collect::<Vec<_>>();
+        let contours = voronoi.contour(&values, 0.37);
+        assert_eq!(contours.len(), 1);
+        for p in contours[0].iter() {
+            assert_approx_eq!(f64, p.x, 0.37, epsilon = 1e-10);
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use std::io::{self, Write};
+
+use glam::DVec3;
+
+use super::Voronoi;
+
+/// Format a vector as three comma separated values.
+fn vector(v: DVec3) -> String {
+    format!("{},{},{}", v.x, v.y, v.z)
+}
+
+impl Voronoi {
+    /// Write a CSV table of the cells of this tesselation to `writer`, with a header row and one row per cell.
+    ///
+    /// The columns are `id`, the position of the generator (`x`, `y`, `z`), `volume`, the centroid (`centroid_x`,
+    /// `centroid_y`, `centroid_z`) and the number of faces (`face_count`).
+    pub fn write_cells_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(
+            writer,
+            "id,x,y,z,volume,centroid_x,centroid_y,centroid_z,face_count"
+        )?;
+        for (i, cell) in self.cells.iter().enumerate() {
+            let (loc, centroid) = (cell.loc(), cell.centroid());
+            writeln!(
+                writer,
+                "{i},{},{},{},{},{},{},{},{}",
+                loc.x,
+                loc.y,
+                loc.z,
+                cell.volume(),
+                centroid.x,
+                centroid.y,
+                centroid.z,
+                cell.face_count()
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Write a CSV table of the faces of this tesselation to `writer`, with a header row and one row per face.
+    ///
+    /// The columns are `id`, `left`, `right`, `area`, the normal (`normal_x`, `normal_y`, `normal_z`), the centroid
+    /// (`centroid_x`, `centroid_y`, `centroid_z`), the periodic shift (`shift_x`, `shift_y`, `shift_z`) and `boundary`.
+    /// `right` and the shift are empty for boundary faces resp. non-periodic faces, `boundary` is empty for faces
+    /// between two cells (see [`crate::VoronoiFace::boundary`]).
+    pub fn write_faces_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(
+            writer,
+            "id,left,right,area,normal_x,normal_y,normal_z,centroid_x,centroid_y,centroid_z,shift_x,shift_y,shift_z,boundary"
+        )?;
+        for (i, face) in self.faces.iter().enumerate() {
+            writeln!(
+                writer,
+                "{i},{},{},{},{},{},{},{}",
+                face.left(),
+                face.right()
+                    .map_or(String::new(), |right| right.to_string()),
+                face.area(),
+                vector(face.normal()),
+                vector(face.centroid()),
+                face.shift().map_or(",,".to_string(), vector),
+                face.boundary()
+                    .map_or(String::new(), |boundary| format!("{boundary:?}"))
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::voronoi::test::perturbed_grid;
+
+    #[test]
+    fn test_csv() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 3, 0.5);
+        for periodic in [false, true] {
+            let voronoi = Voronoi::build(&generators, anchor, width, 3, periodic, None);
+
+            let mut cells = vec![];
+            voronoi.write_cells_csv(&mut cells).unwrap();
+            let cells = String::from_utf8(cells).unwrap();
+            assert_eq!(cells.lines().count(), generators.len() + 1);
+            for (line, cell) in cells.lines().skip(1).zip(voronoi.cells()) {
+                let fields = line.split(',').collect::<Vec<_>>();
+                assert_eq!(fields.len(), 9);
+                assert_eq!(fields[4].parse::<f64>().unwrap(), cell.volume());
+            }
+
+            let mut faces = vec![];
+            voronoi.write_faces_csv(&mut faces).unwrap();
+            let faces = String::from_utf8(faces).unwrap();
+            assert_eq!(faces.lines().count(), voronoi.faces().len() + 1);
+            for (line, face) in faces.lines().skip(1).zip(voronoi.faces()) {
+                let fields = line.split(',').collect::<Vec<_>>();
+                assert_eq!(fields.len(), 14);
+                assert_eq!(fields[2].is_empty(), face.right().is_none());
+                assert_eq!(fields[10].is_empty(), face.shift().is_none());
+                assert_eq!(
+                    fields[13] == "XMin",
+                    face.boundary() == Some(crate::Boundary::XMin)
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use std::{
+    error::Error,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use glam::DVec3;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::util::signed_volume_tet;
+
+use super::{voronoi_cell::ConvexCell, Dimensionality, Voronoi};
+
+impl Voronoi {
+    /// Get the tetrahedra of the Delaunay tetrahedralization dual to this (3D) tesselation, as quadruples of
+    /// generator (cell) indices. All tetrahedra are positively oriented.
+    ///
+    /// Every vertex of the tesselation that is shared by four cells corresponds to a tetrahedron, so only tetrahedra
+    /// whose circumcenter lies inside the simulation volume are found: near the boundary, the tetrahedralization does
+    /// not necessarily cover the convex hull of the generators. For periodic tesselations, tetrahedra connecting
+    /// periodic images of generators are omitted. Tetrahedra are only found once the cell of their lowest-index generator
+    /// is constructed (in a partial tesselation). The generators are assumed to be in general position (no five
+    /// generators on a common sphere), otherwise the tetrahedra of a degenerate vertex may be inconsistent.
+    pub fn delaunay_tetrahedra(&self) -> Vec<[usize; 4]> {
+        assert!(
+            matches!(self.dimensionality, Dimensionality::Dimensionality3D),
+            "Delaunay tetrahedra are only defined for 3D tesselations!"
+        );
+        let simulation_volume = ConvexCell::init_simulation_volume(
+            self.anchor,
+            self.width,
+            self.periodic,
+            self.dimensionality,
+        );
+
+        let cell_tetrahedra = |cell_idx: usize| {
+            if self.cells[cell_idx].volume() == 0. {
+                return vec![];
+            }
+            let Some(convex_cell) =
+                ConvexCell::reconstruct(self, cell_idx, &simulation_volume, self.dimensionality)
+            else {
+                return vec![];
+            };
+            let neighbour = |plane_idx: usize| {
+                let plane = &convex_cell.clipping_planes[plane_idx];
+                plane.right_idx.filter(|_| plane.shift.is_none())
+            };
+            convex_cell
+                .vertices
+                .iter()
+                .filter_map(|vertex| {
+                    let (a, b, c) = vertex.dual;
+                    let mut tet = [cell_idx, neighbour(a)?, neighbour(b)?, neighbour(c)?];
+                    // Only the cell with the lowest index reports the tetrahedron
+                    if tet[1..].iter().any(|&idx| idx <= cell_idx) {
+                        return None;
+                    }
+                    let loc = |idx: usize| self.cells[idx].loc();
+                    if signed_volume_tet(loc(tet[0]), loc(tet[1]), loc(tet[2]), loc(tet[3])) < 0. {
+                        tet.swap(2, 3);
+                    }
+                    Some(tet)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        #[cfg(feature = "rayon")]
+        return (0..self.cells.len())
+            .into_par_iter()
+            .flat_map_iter(cell_tetrahedra)
+            .collect();
+        #[cfg(not(feature = "rayon"))]
+        return (0..self.cells.len()).flat_map(cell_tetrahedra).collect();
+    }
+
+    /// Write the Delaunay tetrahedralization dual to this tesselation (see [`Voronoi::delaunay_tetrahedra`]) in the
+    /// TetGen format, i.e. to a `.node` file containing the generators and an `.ele` file containing the tetrahedra.
+    ///
+    /// The files are named after `filename`, with its extension replaced. Nodes are numbered from zero, in the order of
+    /// the cells of this tesselation.
+    pub fn save_tetgen<P: AsRef<Path>>(&self, filename: P) -> Result<(), Box<dyn Error>> {
+        let filename = filename.as_ref();
+        let tetrahedra = self.delaunay_tetrahedra();
+
+        let mut nodes = BufWriter::new(File::create(filename.with_extension("node"))?);
+        writeln!(nodes, "{} 3 0 0", self.cells.len())?;
+        for (i, cell) in self.cells.iter().enumerate() {
+            let DVec3 { x, y, z } = cell.loc();
+            writeln!(nodes, "{i} {x} {y} {z}")?;
+        }
+        nodes.flush()?;
+
+        let mut elements = BufWriter::new(File::create(filename.with_extension("ele"))?);
+        writeln!(elements, "{} 4 0", tetrahedra.len())?;
+        for (i, [a, b, c, d]) in tetrahedra.iter().enumerate() {
+            writeln!(elements, "{i} {a} {b} {c} {d}")?;
+        }
+        elements.flush()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::voronoi::test::perturbed_grid;
+
+    #[test]
+    fn test_delaunay_tetrahedra() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 5, 0.9);
+        for periodic in [false, true] {
+            let voronoi = Voronoi::build(&generators, anchor, width, 3, periodic, None);
+            let tetrahedra = voronoi.delaunay_tetrahedra();
+            assert!(!tetrahedra.is_empty());
+            for tet in tetrahedra.iter() {
+                let [a, b, c, d] = tet.map(|idx| generators[idx]);
+                assert!(signed_volume_tet(a, b, c, d) > 0.);
+
+                // The circumsphere is empty
+                let rhs = DVec3::new(
+                    (b - a).length_squared(),
+                    (c - a).length_squared(),
+                    (d - a).length_squared(),
+                );
+                let m = glam::DMat3::from_cols(b - a, c - a, d - a).transpose();
+                let circumcenter = a + 0.5 * m.inverse() * rhs;
+                let radius = circumcenter.distance(a);
+                for g in generators.iter() {
+                    assert!(g.distance(circumcenter) >= radius * (1. - 1e-9));
+                }
+            }
+
+            // Every tetrahedron is reported once
+            let mut sorted = tetrahedra
+                .iter()
+                .map(|tet| {
+                    let mut tet = *tet;
+                    tet.sort();
+                    tet
+                })
+                .collect::<Vec<_>>();
+            sorted.sort();
+            sorted.dedup();
+            assert_eq!(sorted.len(), tetrahedra.len());
+        }
+    }
+
+    #[test]
+    fn test_save_tetgen() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 3, 0.9);
+        let voronoi = Voronoi::build(&generators, anchor, width, 3, false, None);
+        let filename = std::env::temp_dir().join("test_save_tetgen");
+        voronoi.save_tetgen(&filename).unwrap();
+
+        let nodes = std::fs::read_to_string(filename.with_extension("node")).unwrap();
+        assert_eq!(nodes.lines().count(), generators.len() + 1);
+        let elements = std::fs::read_to_string(filename.with_extension("ele")).unwrap();
+        let tet_count = voronoi.delaunay_tetrahedra().len();
+        assert_eq!(
+            elements.lines().next(),
+            Some(format!("{tet_count} 4 0").as_str())
+        );
+        assert_eq!(elements.lines().count(), tet_count + 1);
+    }
+}
@@ -0,0 +1,188 @@
+use glam::DVec3;
+
+use super::{Boundary, Voronoi, VoronoiFace};
+
+/// A Voronoi face as seen from one of its cells, see [`Voronoi::directed_faces`].
+///
+/// Every face between two cells results in two directed faces, one for each of its cells, and every boundary face in
+/// one. The normal always points away from the cell and the geometry is given in the frame of the cell.
+#[derive(Debug, Clone, Copy)]
+pub struct DirectedFace {
+    cell: usize,
+    neighbour: Option<usize>,
+    face_idx: usize,
+    area: f64,
+    centroid: DVec3,
+    normal: DVec3,
+    shift: Option<DVec3>,
+    boundary: Option<Boundary>,
+}
+
+impl DirectedFace {
+    fn new(cell: usize, face_idx: usize, face: &VoronoiFace) -> Self {
+        // Faces between non-periodic neighbours are stored once, possibly with the cell on the right. Periodic faces
+        // are stored for both of their cells, but would have their left generator at `-shift` otherwise.
+        let reversed = face.left() != cell;
+        let shift = face.shift().unwrap_or(DVec3::ZERO);
+        Self {
+            cell,
+            neighbour: if reversed {
+                Some(face.left())
+            } else {
+                face.right()
+            },
+            face_idx,
+            area: face.area(),
+            centroid: if reversed {
+                face.centroid() - shift
+            } else {
+                face.centroid()
+            },
+            normal: if reversed {
+                -face.normal()
+            } else {
+                face.normal()
+            },
+            shift: face
+                .shift()
+                .map(|shift| if reversed { -shift } else { shift }),
+            boundary: face.boundary(),
+        }
+    }
+
+    /// Get the index of the cell this face belongs to.
+    pub fn cell(&self) -> usize {
+        self.cell
+    }
+
+    /// Get the index of the neighbouring cell on the other side of this face.
+    /// Returns `None` for boundary faces.
+    pub fn neighbour(&self) -> Option<usize> {
+        self.neighbour
+    }
+
+    /// Get the index of the underlying (undirected) face in [`Voronoi::faces`].
+    pub fn face_idx(&self) -> usize {
+        self.face_idx
+    }
+
+    /// Get the area of this face.
+    pub fn area(&self) -> f64 {
+        self.area
+    }
+
+    /// Get the position of the centroid of this face, in the frame of its cell.
+    pub fn centroid(&self) -> DVec3 {
+        self.centroid
+    }
+
+    /// Get the normal vector of this face, pointing away from its cell.
+    pub fn normal(&self) -> DVec3 {
+        self.normal
+    }
+
+    /// Get the area vector of this face (i.e. its area times its outward normal).
+    pub fn area_vector(&self) -> DVec3 {
+        self.area * self.normal
+    }
+
+    /// Get the shift vector (if any) to apply to the generator of the neighbouring cell to bring it to the frame of
+    /// the cell of this face. Can only be `Some` for periodic Voronoi tesselations.
+    pub fn shift(&self) -> Option<DVec3> {
+        self.shift
+    }
+
+    /// Get the boundary of the simulation volume that created this face.
+    /// Returns `None` for interior faces.
+    pub fn boundary(&self) -> Option<Boundary> {
+        self.boundary
+    }
+}
+
+impl Voronoi {
+    /// Get an `Iterator` over the faces of the cell with index `cell_idx` as [`DirectedFace`]s, i.e. with their normal
+    /// pointing away from the cell and their neighbour and shift relative to the cell.
+    pub fn cell_directed_faces(&self, cell_idx: usize) -> impl Iterator<Item = DirectedFace> + '_ {
+        self.cells[cell_idx]
+            .face_indices(self)
+            .iter()
+            .map(move |&face_idx| DirectedFace::new(cell_idx, face_idx, &self.faces[face_idx]))
+    }
+
+    /// Get the faces of all cells as [`DirectedFace`]s, i.e. one record per (cell, neighbour) pair with the normal
+    /// pointing away from the cell, ordered by cell.
+    pub fn directed_faces(&self) -> Vec<DirectedFace> {
+        (0..self.cells.len())
+            .flat_map(|cell_idx| self.cell_directed_faces(cell_idx))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+    use crate::voronoi::test::perturbed_grid;
+    use crate::BuildOptions;
+
+    #[test]
+    fn test_directed_faces() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        for (periodic, deduplicate_periodic_faces) in [(false, false), (true, false), (true, true)]
+        {
+            let (voronoi, _) = Voronoi::build_with_options(
+                &generators,
+                None,
+                anchor,
+                width,
+                3,
+                periodic,
+                None,
+                &BuildOptions::new().deduplicate_periodic_faces(deduplicate_periodic_faces),
+            );
+            let directed_faces = voronoi.directed_faces();
+            let total_face_count = voronoi
+                .cells()
+                .iter()
+                .map(|c| c.face_count())
+                .sum::<usize>();
+            assert_eq!(directed_faces.len(), total_face_count);
+
+            for face in directed_faces.iter() {
+                let loc = voronoi.cells()[face.cell()].loc();
+                assert!(face.normal().dot(face.centroid() - loc) > 0.);
+                let Some(neighbour) = face.neighbour() else {
+                    assert!(face.boundary().is_some());
+                    continue;
+                };
+                assert!(periodic || face.shift().is_none());
+                // The twin of the face, as seen from the neighbour
+                let neighbour_loc =
+                    voronoi.cells()[neighbour].loc() + face.shift().unwrap_or(DVec3::ZERO);
+                let twin = voronoi
+                    .cell_directed_faces(neighbour)
+                    .find(|twin| {
+                        twin.neighbour() == Some(face.cell())
+                            && twin.shift().map(|s| -s) == face.shift()
+                    })
+                    .expect("Directed faces must come in pairs!");
+                assert_approx_eq!(f64, twin.area(), face.area(), epsilon = 1e-12);
+                assert!((twin.normal() + face.normal()).length() < 1e-10);
+                let offset = neighbour_loc - voronoi.cells()[neighbour].loc();
+                assert!((twin.centroid() + offset).distance(face.centroid()) < 1e-10);
+            }
+
+            // The faces of every cell are closed
+            for cell_idx in 0..voronoi.cells().len() {
+                let area_vector = voronoi
+                    .cell_directed_faces(cell_idx)
+                    .map(|face| face.area_vector())
+                    .sum::<DVec3>();
+                assert!(area_vector.length() < 1e-10);
+            }
+        }
+    }
+}
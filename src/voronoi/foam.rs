@@ -0,0 +1,184 @@
+use std::f64::consts::TAU;
+
+use glam::DVec3;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use super::{voronoi_cell::ConvexCell, Dimensionality, TriangleMesh, Voronoi};
+
+impl Voronoi {
+    /// Shrink every cell of this (3D) tesselation by the given `margin` and collect the surfaces of the shrunken cells
+    /// in a single triangle mesh (oriented outwards).
+    ///
+    /// Every face of a cell (including the faces on the boundary of the simulation volume) is moved inwards by
+    /// `margin`, so neighbouring shrunken cells are separated by walls of thickness `2 * margin`. Cells that vanish
+    /// entirely are skipped, as are cells that were not constructed (in a partial tesselation). The resulting closed
+    /// shells are the cavities of a closed-cell foam filling the simulation volume. Cells of periodic tesselations are
+    /// not wrapped.
+    pub fn shrunken_cells(&self, margin: f64) -> TriangleMesh {
+        assert!(
+            matches!(self.dimensionality, Dimensionality::Dimensionality3D),
+            "Foam generation is only supported for 3D tesselations!"
+        );
+        let simulation_volume = ConvexCell::init_simulation_volume(
+            self.anchor,
+            self.width,
+            self.periodic,
+            self.dimensionality,
+        );
+
+        let shrink = |cell_idx: usize| {
+            if self.cells[cell_idx].volume() == 0. {
+                return vec![];
+            }
+            let Some(convex_cell) =
+                ConvexCell::reconstruct(self, cell_idx, &simulation_volume, self.dimensionality)
+            else {
+                return vec![];
+            };
+            // The offset of a convex polyhedron is the intersection of its offset half spaces.
+            let mut shrunken = convex_cell.clone();
+            for (plane, vertex_loop) in convex_cell
+                .clipping_planes
+                .iter()
+                .zip(convex_cell.face_vertex_loops())
+            {
+                if let Some(&v) = vertex_loop.first() {
+                    let normal = plane.normal();
+                    let point = convex_cell.vertices[v].loc + margin * normal;
+                    shrunken.clip_by_half_space(normal, point, self.dimensionality);
+                }
+            }
+            shrunken
+                .face_vertex_loops()
+                .into_iter()
+                .filter(|vertex_loop| !vertex_loop.is_empty())
+                .map(|vertex_loop| {
+                    vertex_loop
+                        .into_iter()
+                        .map(|v| shrunken.vertices[v].loc)
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        #[cfg(feature = "rayon")]
+        let polygons = (0..self.cells.len())
+            .into_par_iter()
+            .flat_map_iter(shrink)
+            .collect::<Vec<_>>();
+        #[cfg(not(feature = "rayon"))]
+        let polygons = (0..self.cells.len()).flat_map(shrink).collect::<Vec<_>>();
+
+        TriangleMesh::from_polygons(
+            polygons.iter().map(|polygon| (polygon.as_slice(), false)),
+            1e-10 * self.width.max_element(),
+        )
+    }
+
+    /// Get the unique edges of the faces of this tesselation, as pairs of points.
+    ///
+    /// Edges shared by several faces are only returned once. For periodic tesselations, the edges of faces with a
+    /// periodic neighbour are returned at both sides of the simulation volume. See [`Voronoi::edges`] for the faces
+    /// and cells incident to the edges.
+    pub fn unique_face_edges(&self) -> Vec<(DVec3, DVec3)> {
+        let wireframe = self.edges();
+        (0..wireframe.edges().len())
+            .map(|edge_idx| wireframe.edge_points(edge_idx))
+            .collect()
+    }
+
+    /// Generate a strut lattice from the edges of the faces of this tesselation, as a triangle mesh (oriented
+    /// outwards).
+    ///
+    /// Every unique edge (see [`Voronoi::unique_face_edges`]) is replaced by a closed prism with a regular polygonal
+    /// cross-section with `segments` sides, inscribed in a circle of the given `radius`. The struts are not merged, so
+    /// they overlap around the vertices of the tesselation.
+    pub fn strut_lattice(&self, radius: f64, segments: usize) -> TriangleMesh {
+        assert!(segments >= 3, "A strut needs at least 3 sides!");
+        let polygons = self
+            .unique_face_edges()
+            .into_iter()
+            .flat_map(|(a, b)| {
+                let axis = (b - a).normalize();
+                let u = axis.any_orthonormal_vector();
+                let w = axis.cross(u);
+                let ring = |center: DVec3| {
+                    (0..segments)
+                        .map(|i| {
+                            let angle = TAU * i as f64 / segments as f64;
+                            center + radius * (angle.cos() * u + angle.sin() * w)
+                        })
+                        .collect::<Vec<_>>()
+                };
+                let (bottom, top) = (ring(a), ring(b));
+                let mut polygons = (0..segments)
+                    .map(|i| {
+                        let j = (i + 1) % segments;
+                        vec![bottom[i], bottom[j], top[j], top[i]]
+                    })
+                    .collect::<Vec<_>>();
+                polygons.push(top);
+                polygons.push(bottom.into_iter().rev().collect());
+                polygons
+            })
+            .collect::<Vec<_>>();
+
+        TriangleMesh::from_polygons(
+            polygons.iter().map(|polygon| (polygon.as_slice(), false)),
+            1e-3 * radius * (TAU / segments as f64).sin(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+    use crate::voronoi::test::perturbed_grid;
+
+    #[test]
+    fn test_shrunken_cells() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.);
+        let voronoi = Voronoi::build(&generators, anchor, width, 3, false, None);
+
+        let mesh = voronoi.shrunken_cells(0.05);
+        assert_eq!(mesh.vertices().len(), 8 * generators.len());
+        assert_approx_eq!(
+            f64,
+            mesh.volume(),
+            generators.len() as f64 * 0.15f64.powi(3),
+            epsilon = 1e-10
+        );
+
+        // Cells vanish when the margin exceeds their inradius
+        assert!(voronoi.shrunken_cells(0.2).triangles().is_empty());
+    }
+
+    #[test]
+    fn test_strut_lattice() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 2, 0.);
+        let voronoi = Voronoi::build(&generators, anchor, width, 3, false, None);
+
+        // The edges of 2x2x2 cubes
+        let edges = voronoi.unique_face_edges();
+        assert_eq!(edges.len(), 54);
+        let total_length = edges.iter().map(|(a, b)| a.distance(*b)).sum::<f64>();
+        assert_approx_eq!(f64, total_length, 27., epsilon = 1e-10);
+
+        let (radius, segments) = (0.01, 8);
+        let mesh = voronoi.strut_lattice(radius, segments);
+        let cross_section = 0.5 * segments as f64 * radius * radius * (TAU / segments as f64).sin();
+        assert_approx_eq!(
+            f64,
+            mesh.volume(),
+            total_length * cross_section,
+            epsilon = 1e-10
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use glam::DVec3;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use super::{voronoi_cell::ConvexCell, TriangleMesh, Voronoi};
+
+/// A convex piece of a shape fractured along the cells of a Voronoi tesselation, see [`Voronoi::fracture`].
+#[derive(Debug, Clone)]
+pub struct FracturePiece {
+    cell: usize,
+    mesh: TriangleMesh,
+    volume: f64,
+    centroid: DVec3,
+}
+
+impl FracturePiece {
+    /// Get the index of the Voronoi cell this piece was cut from.
+    pub fn cell(&self) -> usize {
+        self.cell
+    }
+
+    /// Get the closed, convex triangle mesh of this piece (oriented outwards).
+    pub fn mesh(&self) -> &TriangleMesh {
+        &self.mesh
+    }
+
+    /// Get the volume of this piece.
+    pub fn volume(&self) -> f64 {
+        self.volume
+    }
+
+    /// Get the centroid (center of mass for a uniform density) of this piece.
+    pub fn centroid(&self) -> DVec3 {
+        self.centroid
+    }
+}
+
+impl Voronoi {
+    /// Fracture a convex shape along the cells of this (3D) tesselation.
+    ///
+    /// The shape is the intersection of the half spaces `normal . (x - point) <= 0` for the given `(point, normal)`
+    /// pairs, i.e. the normals point outwards. Every cell intersecting the shape produces one piece, which is the
+    /// intersection of the cell with the shape (and the simulation volume, for non-periodic tesselations) as a closed
+    /// triangle mesh. Cells of periodic tesselations are not wrapped, so periodic images of the shape are not taken
+    /// into account. Cells that were not constructed (in a partial tesselation) are skipped.
+    ///
+    /// See [`Voronoi::fracture_box`] to fracture a box.
+    pub fn fracture(&self, shape: &[(DVec3, DVec3)]) -> Vec<FracturePiece> {
+        let simulation_volume = ConvexCell::init_simulation_volume(
+            self.anchor,
+            self.width,
+            self.periodic,
+            self.dimensionality,
+        );
+        let tolerance = 1e-10 * self.width.max_element();
+
+        let fracture = |cell_idx: usize| {
+            if self.cells[cell_idx].volume() == 0. {
+                return None;
+            }
+            let mut convex_cell =
+                ConvexCell::reconstruct(self, cell_idx, &simulation_volume, self.dimensionality)?;
+            for &(point, normal) in shape {
+                convex_cell.clip_by_half_space(-normal, point, self.dimensionality);
+            }
+            if convex_cell.vertices.is_empty() {
+                return None;
+            }
+
+            let vertex_loops = convex_cell
+                .face_vertex_loops()
+                .into_iter()
+                .filter(|vertex_loop| !vertex_loop.is_empty())
+                .map(|vertex_loop| {
+                    vertex_loop
+                        .into_iter()
+                        .map(|v| convex_cell.vertices[v].loc)
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            let mesh = TriangleMesh::from_polygons(
+                vertex_loops
+                    .iter()
+                    .map(|polygon| (polygon.as_slice(), false)),
+                tolerance,
+            );
+
+            let (volume, centroid) = convex_cell.volume_centroid();
+            (volume > 0.).then_some(FracturePiece {
+                cell: cell_idx,
+                mesh,
+                volume,
+                centroid,
+            })
+        };
+
+        #[cfg(feature = "rayon")]
+        return (0..self.cells.len())
+            .into_par_iter()
+            .filter_map(fracture)
+            .collect();
+        #[cfg(not(feature = "rayon"))]
+        return (0..self.cells.len()).filter_map(fracture).collect();
+    }
+
+    /// Fracture the box with the given `anchor` and `width` along the cells of this tesselation, see
+    /// [`Voronoi::fracture`].
+    pub fn fracture_box(&self, anchor: DVec3, width: DVec3) -> Vec<FracturePiece> {
+        let upper = anchor + width;
+        self.fracture(&[
+            (anchor, DVec3::NEG_X),
+            (upper, DVec3::X),
+            (anchor, DVec3::NEG_Y),
+            (upper, DVec3::Y),
+            (anchor, DVec3::NEG_Z),
+            (upper, DVec3::Z),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+    use crate::voronoi::test::perturbed_grid;
+
+    #[test]
+    fn test_fracture() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        for periodic in [false, true] {
+            let voronoi = Voronoi::build(&generators, anchor, width, 3, periodic, None);
+
+            // Fracturing the whole simulation volume reproduces the cells. Periodic cells are cut by the box, without
+            // adding the pieces of their periodic images.
+            let pieces = voronoi.fracture_box(anchor, width);
+            assert_eq!(pieces.len(), generators.len());
+            let total_volume = pieces.iter().map(|p| p.volume()).sum::<f64>();
+            if periodic {
+                assert!(total_volume < 1.);
+            } else {
+                assert_approx_eq!(f64, total_volume, 1., epsilon = 1e-10);
+            }
+            for piece in pieces.iter() {
+                assert_approx_eq!(f64, piece.mesh().volume(), piece.volume(), epsilon = 1e-10);
+                let cell = &voronoi.cells()[piece.cell()];
+                if !periodic {
+                    assert_approx_eq!(f64, piece.volume(), cell.volume(), epsilon = 1e-10);
+                    assert!(piece.centroid().distance(cell.centroid()) < 1e-10);
+                }
+            }
+
+            if periodic {
+                continue;
+            }
+            // A tetrahedron (corner of the unit cube)
+            let normal = DVec3::ONE.normalize();
+            let shape = [
+                (DVec3::ZERO, DVec3::NEG_X),
+                (DVec3::ZERO, DVec3::NEG_Y),
+                (DVec3::ZERO, DVec3::NEG_Z),
+                (DVec3::X, normal),
+            ];
+            let pieces = voronoi.fracture(&shape);
+            let total_volume = pieces.iter().map(|p| p.volume()).sum::<f64>();
+            assert_approx_eq!(f64, total_volume, 1. / 6., epsilon = 1e-10);
+            let centroid = pieces
+                .iter()
+                .map(|p| p.volume() * p.centroid())
+                .sum::<DVec3>()
+                / total_volume;
+            assert!(centroid.distance(DVec3::splat(0.25)) < 1e-10);
+            for piece in pieces.iter() {
+                for v in piece.mesh().vertices() {
+                    assert!(v.cmpge(DVec3::splat(-1e-12)).all());
+                    assert!(v.dot(normal) <= normal.x + 1e-12);
+                }
+            }
+        }
+    }
+}
@@ -10,7 +10,7 @@ pub struct Generator {
 }
 
 impl Generator {
-    pub(super) fn new(id: usize, loc: DVec3, dimensionality: Dimensionality) -> Self {
+    pub(crate) fn new(id: usize, loc: DVec3, dimensionality: Dimensionality) -> Self {
         let mut loc = loc;
         match dimensionality {
             Dimensionality::Dimensionality1D => {
@@ -0,0 +1,186 @@
+use std::sync::Arc;
+
+use glam::DVec3;
+use rstar::{Envelope, RTree, SelectionFunction, AABB};
+
+use crate::rtree_nn::build_rtree;
+
+use super::{Dimensionality, Generator};
+
+/// The generators of a tesselation together with the r-tree used to find their nearest neighbours, to reuse them
+/// across multiple constructions (see [`crate::Voronoi::build_from`]).
+///
+/// For (mostly) static point sets, e.g. the generators of a simulation that only move slightly every time step, this
+/// avoids re-allocating the generators and re-indexing them from scratch every time the tesselation is rebuilt. The
+/// r-tree is shared (not copied) with the tesselations built from this set.
+#[derive(Clone)]
+pub struct GeneratorSet {
+    generators: Vec<Generator>,
+    rtree: Arc<RTree<Generator>>,
+    dimensionality: Dimensionality,
+}
+
+/// Select a single generator (by id) at a given position in the r-tree.
+struct SelectGenerator {
+    id: usize,
+    point: [f64; 3],
+}
+
+impl SelectionFunction<Generator> for SelectGenerator {
+    fn should_unpack_parent(&self, envelope: &AABB<[f64; 3]>) -> bool {
+        envelope.contains_point(&self.point)
+    }
+
+    fn should_unpack_leaf(&self, leaf: &Generator) -> bool {
+        leaf.id() == self.id
+    }
+}
+
+impl GeneratorSet {
+    /// The maximal fraction of the generators that can move in [`GeneratorSet::update_positions`] for the r-tree to be
+    /// updated incrementally instead of being rebuilt.
+    const MAX_INCREMENTAL_FRACTION: f64 = 0.1;
+
+    /// Create a new set from the given `positions` for a tesselation with the given `dimensionality`.
+    pub fn new(positions: &[DVec3], dimensionality: usize) -> Self {
+        let dimensionality = dimensionality.into();
+        let generators: Vec<Generator> = positions
+            .iter()
+            .enumerate()
+            .map(|(id, &loc)| Generator::new(id, loc, dimensionality))
+            .collect();
+        let rtree = Arc::new(build_rtree(&generators));
+        Self {
+            generators,
+            rtree,
+            dimensionality,
+        }
+    }
+
+    /// Move the generators to the given `positions` (one per generator, in the same order), reusing the existing
+    /// allocations.
+    ///
+    /// Only the generators that actually moved are re-indexed, unless a large fraction of them moved, in which case
+    /// the r-tree is rebuilt. If the r-tree is still shared with a tesselation built from this set, it is copied
+    /// first (the tesselation is not affected).
+    pub fn update_positions(&mut self, positions: &[DVec3]) {
+        assert_eq!(
+            positions.len(),
+            self.generators.len(),
+            "Must have one position per generator!"
+        );
+        let mut moved = vec![];
+        for (generator, &loc) in self.generators.iter_mut().zip(positions) {
+            let updated = Generator::new(generator.id(), loc, self.dimensionality);
+            if updated.loc() != generator.loc() {
+                moved.push((generator.loc(), updated));
+                *generator = updated;
+            }
+        }
+        if moved.is_empty() {
+            return;
+        }
+
+        if moved.len() as f64 > Self::MAX_INCREMENTAL_FRACTION * self.generators.len() as f64 {
+            self.rtree = Arc::new(build_rtree(&self.generators));
+        } else {
+            let rtree = Arc::make_mut(&mut self.rtree);
+            for (old_loc, generator) in moved {
+                rtree
+                    .remove_with_selection_function(SelectGenerator {
+                        id: generator.id(),
+                        point: old_loc.to_array(),
+                    })
+                    .expect("Generator must be present in the r-tree!");
+                rtree.insert(generator);
+            }
+        }
+    }
+
+    /// Get the number of generators in this set.
+    pub fn len(&self) -> usize {
+        self.generators.len()
+    }
+
+    /// Whether this set contains no generators.
+    pub fn is_empty(&self) -> bool {
+        self.generators.is_empty()
+    }
+
+    pub(super) fn generators(&self) -> &[Generator] {
+        &self.generators
+    }
+
+    pub(super) fn rtree(&self) -> &Arc<RTree<Generator>> {
+        &self.rtree
+    }
+
+    pub(super) fn dimensionality(&self) -> Dimensionality {
+        self.dimensionality
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+    use crate::voronoi::test::{perturbed_grid, perturbed_plane};
+    use crate::{BuildOptions, Voronoi};
+
+    fn assert_same(voronoi: &Voronoi, expected: &Voronoi) {
+        assert_eq!(voronoi.faces().len(), expected.faces().len());
+        for (cell, expected) in voronoi.cells().iter().zip(expected.cells()) {
+            assert_eq!(cell.face_count(), expected.face_count());
+            assert_approx_eq!(f64, cell.volume(), expected.volume(), epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_generator_set() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        for (mut positions, dimensionality) in [
+            (perturbed_grid(anchor, width, 5, 0.5), 3),
+            (perturbed_plane(anchor, width, 7, 0.5), 2),
+        ] {
+            for periodic in [false, true] {
+                let build = |set: &GeneratorSet| {
+                    Voronoi::build_from(
+                        set,
+                        None,
+                        anchor,
+                        width,
+                        periodic,
+                        None,
+                        &BuildOptions::new(),
+                    )
+                    .0
+                };
+                let mut set = GeneratorSet::new(&positions, dimensionality);
+                let voronoi = build(&set);
+                let expected =
+                    Voronoi::build(&positions, anchor, width, dimensionality, periodic, None);
+                assert_same(&voronoi, &expected);
+
+                // Move a few generators (incremental update), then all of them (rebuild)
+                for step in [20, 1] {
+                    for position in positions.iter_mut().step_by(step) {
+                        *position += DVec3::new(0.01, -0.02, 0.015);
+                        *position = position.clamp(anchor, anchor + width);
+                    }
+                    set.update_positions(&positions);
+                    assert_eq!(set.rtree().size(), positions.len());
+                    let updated = build(&set);
+                    let expected =
+                        Voronoi::build(&positions, anchor, width, dimensionality, periodic, None);
+                    assert_same(&updated, &expected);
+                }
+
+                // The tesselation built before the update still uses the original generators
+                let locs = voronoi.cells().iter().map(|c| c.loc()).collect::<Vec<_>>();
+                assert_eq!(voronoi.cells_at(&locs), (0..locs.len()).collect::<Vec<_>>());
+            }
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use geojson::{feature::Id, Feature, FeatureCollection, Geometry, JsonObject, JsonValue, Value};
+
+use super::Voronoi2D;
+
+impl Voronoi2D {
+    /// Convert the cell with the given index to a GeoJSON `Feature` with a `Polygon` geometry.
+    ///
+    /// The exterior ring is closed and ordered counterclockwise (as required by RFC 7946). Cells crossing a periodic
+    /// boundary are not wrapped: their polygon is given in the reference frame of their generator.
+    /// The feature's `id` is the cell index and its properties are the `id`, the `area` and the `generator`
+    /// coordinates of the cell.
+    pub fn cell_to_geojson(&self, cell_idx: usize) -> Feature {
+        let cell = self.cell(cell_idx);
+        let mut ring = self
+            .cell_polygon(cell_idx)
+            .into_iter()
+            .map(|v| vec![v.x, v.y])
+            .collect::<Vec<_>>();
+        if let Some(first) = ring.first().cloned() {
+            ring.push(first);
+        }
+
+        let mut properties = JsonObject::new();
+        properties.insert("id".to_string(), JsonValue::from(cell_idx));
+        properties.insert("area".to_string(), JsonValue::from(cell.area()));
+        properties.insert(
+            "generator".to_string(),
+            JsonValue::from(cell.loc().to_array().to_vec()),
+        );
+
+        Feature {
+            bbox: None,
+            geometry: Some(Geometry::new(Value::Polygon(vec![ring]))),
+            id: Some(Id::Number(cell_idx.into())),
+            properties: Some(properties),
+            foreign_members: None,
+        }
+    }
+
+    /// Convert this tesselation to a GeoJSON `FeatureCollection` of its cells (see
+    /// [`Voronoi2D::cell_to_geojson`]).
+    ///
+    /// Cells that were not constructed (in a partial tesselation) are skipped.
+    pub fn to_geojson(&self) -> FeatureCollection {
+        FeatureCollection {
+            bbox: None,
+            features: (0..self.cell_count())
+                .filter(|&i| self.cell(i).area() > 0.)
+                .map(|i| self.cell_to_geojson(i))
+                .collect(),
+            foreign_members: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+    use glam::DVec2;
+
+    use super::*;
+
+    #[test]
+    fn test_to_geojson() {
+        let generators = (0..16)
+            .map(|i| DVec2::new((i % 4) as f64 + 0.5, (i / 4) as f64 + 0.5) * 0.25)
+            .map(|g| g + DVec2::new(0.01, -0.02))
+            .collect::<Vec<_>>();
+        let mask = (0..generators.len()).map(|i| i != 5).collect::<Vec<_>>();
+        let voronoi = Voronoi2D::build_partial(&generators, &mask, DVec2::ZERO, DVec2::ONE, true);
+        let collection = voronoi.to_geojson();
+        assert_eq!(collection.features.len(), generators.len() - 1);
+
+        for feature in collection.features {
+            let properties = feature.properties.as_ref().unwrap();
+            let idx = properties["id"].as_u64().unwrap() as usize;
+            assert_eq!(feature.id, Some(Id::Number(idx.into())));
+            let area = properties["area"].as_f64().unwrap();
+            assert_approx_eq!(f64, area, voronoi.cell(idx).area(), epsilon = 1e-12);
+
+            let Some(Value::Polygon(rings)) = feature.geometry.map(|g| g.value) else {
+                panic!("Cells must be polygons!");
+            };
+            let ring = &rings[0];
+            assert_eq!(ring.first(), ring.last());
+            // Unwrapped periodic cells are not split: the shoelace area matches the cell area
+            let shoelace = 0.5
+                * ring
+                    .windows(2)
+                    .map(|w| w[0][0] * w[1][1] - w[1][0] * w[0][1])
+                    .sum::<f64>();
+            assert_approx_eq!(f64, shoelace, area, epsilon = 1e-12);
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use glam::DVec3;
+
+use crate::util::weld;
+
+use super::{Boundary, Dimensionality, Voronoi};
+
+const BOUNDARIES: [(Boundary, &str); 7] = [
+    (Boundary::XMin, "XMin"),
+    (Boundary::XMax, "XMax"),
+    (Boundary::YMin, "YMin"),
+    (Boundary::YMax, "YMax"),
+    (Boundary::ZMin, "ZMin"),
+    (Boundary::ZMax, "ZMax"),
+    (Boundary::Cut, "Cut"),
+];
+
+/// The bounding box of a set of points, as written in the `$Entities` section.
+fn bounding_box<'a>(points: impl Iterator<Item = &'a DVec3>) -> String {
+    let (min, max) = points.fold(
+        (DVec3::splat(f64::INFINITY), DVec3::splat(f64::NEG_INFINITY)),
+        |(min, max), p| (min.min(*p), max.max(*p)),
+    );
+    format!(
+        "{} {} {} {} {} {}",
+        min.x, min.y, min.z, max.x, max.y, max.z
+    )
+}
+
+impl Voronoi {
+    /// Save this (3D) tesselation to a Gmsh mesh file, in the (ASCII) MSH 4.1 format.
+    ///
+    /// Every cell is decomposed into tetrahedra (joining its generator with the fan-triangulated faces) and stored as a
+    /// separate volume entity, with tag `cell_idx + 1`, in the physical group `"Cells"`. The faces on the boundary of
+    /// the tesselation are stored as triangles, grouped in one surface entity and physical group per [`Boundary`]
+    /// (named after it, e.g. `"XMin"`). The face vertices are welded, followed by the generators, as nodes.
+    ///
+    /// Cells that were not constructed (in a partial tesselation) are skipped. Cells of periodic tesselations are not
+    /// wrapped.
+    pub fn save_gmsh<P: AsRef<Path>>(&self, filename: P) -> Result<(), Box<dyn Error>> {
+        assert!(
+            matches!(self.dimensionality, Dimensionality::Dimensionality3D),
+            "Gmsh export is only supported for 3D tesselations!"
+        );
+        let (vertices, vertex_indices) =
+            weld(&self.face_vertices, 1e-10 * self.width.max_element());
+        let vertex_tag = |face_vertex_idx: usize| vertex_indices[face_vertex_idx] + 1;
+        let generator_tag = |cell_idx: usize| vertices.len() + cell_idx + 1;
+        // Fan triangulation of a face, oriented counterclockwise around its normal (i.e. seen from the right)
+        let triangles = |face_idx: usize| {
+            let face = &self.faces[face_idx];
+            let offset = face.vertex_offset();
+            (2..face.vertex_count()).map(move |i| {
+                [
+                    vertex_tag(offset),
+                    vertex_tag(offset + i - 1),
+                    vertex_tag(offset + i),
+                ]
+            })
+        };
+
+        // Collect the tetrahedra of each cell and the boundary triangles
+        let mut tetrahedra: Vec<Vec<[usize; 4]>> = self.cells.iter().map(|_| vec![]).collect();
+        let mut boundary_triangles: BTreeMap<usize, Vec<[usize; 3]>> = BTreeMap::new();
+        for (face_idx, face) in self.faces.iter().enumerate() {
+            let left = face.left();
+            for [a, b, c] in triangles(face_idx) {
+                tetrahedra[left].push([a, c, b, generator_tag(left)]);
+            }
+            match (face.right(), face.shift()) {
+                // Periodic faces are stored for both of their cells
+                (Some(_), Some(_)) => (),
+                (Some(right), None) => {
+                    for [a, b, c] in triangles(face_idx) {
+                        tetrahedra[right].push([a, b, c, generator_tag(right)]);
+                    }
+                }
+                (None, _) => {
+                    let boundary = face.boundary().unwrap_or(Boundary::Cut);
+                    let surface = BOUNDARIES
+                        .iter()
+                        .position(|(b, _)| *b == boundary)
+                        .expect("All boundaries are listed!")
+                        + 1;
+                    boundary_triangles
+                        .entry(surface)
+                        .or_default()
+                        .extend(triangles(face_idx));
+                }
+            }
+        }
+        let cells = (0..self.cells.len())
+            .filter(|&i| self.cells[i].volume() > 0. && !tetrahedra[i].is_empty())
+            .collect::<Vec<_>>();
+
+        let node_loc = |tag: usize| {
+            if tag > vertices.len() {
+                self.cells[tag - vertices.len() - 1].loc()
+            } else {
+                vertices[tag - 1]
+            }
+        };
+
+        let mut file = BufWriter::new(File::create(filename)?);
+        writeln!(file, "$MeshFormat\n4.1 0 8\n$EndMeshFormat")?;
+
+        writeln!(file, "$PhysicalNames\n{}", boundary_triangles.len() + 1)?;
+        writeln!(file, "3 1 \"Cells\"")?;
+        for &surface in boundary_triangles.keys() {
+            writeln!(file, "2 {surface} \"{}\"", BOUNDARIES[surface - 1].1)?;
+        }
+        writeln!(file, "$EndPhysicalNames")?;
+
+        writeln!(file, "$Entities")?;
+        writeln!(file, "0 0 {} {}", boundary_triangles.len(), cells.len())?;
+        for (&surface, triangles) in boundary_triangles.iter() {
+            let points = triangles
+                .iter()
+                .flatten()
+                .map(|&tag| node_loc(tag))
+                .collect::<Vec<_>>();
+            writeln!(
+                file,
+                "{surface} {} 1 {surface} 0",
+                bounding_box(points.iter())
+            )?;
+        }
+        for &cell_idx in cells.iter() {
+            let points = tetrahedra[cell_idx]
+                .iter()
+                .flatten()
+                .map(|&tag| node_loc(tag))
+                .collect::<Vec<_>>();
+            writeln!(
+                file,
+                "{} {} 1 1 0",
+                cell_idx + 1,
+                bounding_box(points.iter())
+            )?;
+        }
+        writeln!(file, "$EndEntities")?;
+
+        // All nodes are stored in the entity of the first cell
+        let node_count = vertices.len() + self.cells.len();
+        writeln!(file, "$Nodes\n1 {node_count} 1 {node_count}")?;
+        writeln!(
+            file,
+            "3 {} 0 {node_count}",
+            cells.first().map_or(1, |i| i + 1)
+        )?;
+        for tag in 1..=node_count {
+            writeln!(file, "{tag}")?;
+        }
+        for tag in 1..=node_count {
+            let DVec3 { x, y, z } = node_loc(tag);
+            writeln!(file, "{x} {y} {z}")?;
+        }
+        writeln!(file, "$EndNodes")?;
+
+        let element_count = cells.iter().map(|&i| tetrahedra[i].len()).sum::<usize>()
+            + boundary_triangles.values().map(Vec::len).sum::<usize>();
+        writeln!(
+            file,
+            "$Elements\n{} {element_count} 1 {element_count}",
+            cells.len() + boundary_triangles.len()
+        )?;
+        let mut element_tag = 0;
+        for &cell_idx in cells.iter() {
+            writeln!(file, "3 {} 4 {}", cell_idx + 1, tetrahedra[cell_idx].len())?;
+            for [a, b, c, d] in tetrahedra[cell_idx].iter() {
+                element_tag += 1;
+                writeln!(file, "{element_tag} {a} {b} {c} {d}")?;
+            }
+        }
+        for (&surface, triangles) in boundary_triangles.iter() {
+            writeln!(file, "2 {surface} 2 {}", triangles.len())?;
+            for [a, b, c] in triangles.iter() {
+                element_tag += 1;
+                writeln!(file, "{element_tag} {a} {b} {c}")?;
+            }
+        }
+        writeln!(file, "$EndElements")?;
+        file.flush()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+    use crate::{util::signed_volume_tet, voronoi::test::perturbed_grid};
+
+    #[test]
+    fn test_save_gmsh() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 3, 0.9);
+        let voronoi = Voronoi::build(&generators, anchor, width, 3, false, None);
+        let filename = std::env::temp_dir().join("test_save_gmsh.msh");
+        voronoi.save_gmsh(&filename).unwrap();
+
+        let contents = std::fs::read_to_string(filename).unwrap();
+        let lines = contents.lines().collect::<Vec<_>>();
+        assert_eq!(lines[..3], ["$MeshFormat", "4.1 0 8", "$EndMeshFormat"]);
+        assert_eq!(lines[3..5], ["$PhysicalNames", "7"]);
+
+        // Read back the nodes
+        let section = |name: &str| lines.iter().position(|l| *l == name).unwrap() + 1;
+        let start = section("$Nodes");
+        let node_count: usize = lines[start].split(' ').nth(1).unwrap().parse().unwrap();
+        let nodes = lines[(start + 2 + node_count)..(start + 2 + 2 * node_count)]
+            .iter()
+            .map(|l| {
+                let c = l
+                    .split(' ')
+                    .map(|c| c.parse().unwrap())
+                    .collect::<Vec<f64>>();
+                DVec3::new(c[0], c[1], c[2])
+            })
+            .collect::<Vec<_>>();
+
+        // The positively oriented tetrahedra fill the simulation volume, the triangles cover its boundary
+        let mut line = section("$Elements");
+        let block_count: usize = lines[line].split(' ').next().unwrap().parse().unwrap();
+        let (mut volume, mut area) = (0., 0.);
+        for _ in 0..block_count {
+            line += 1;
+            let header = lines[line]
+                .split(' ')
+                .map(|c| c.parse().unwrap())
+                .collect::<Vec<usize>>();
+            for _ in 0..header[3] {
+                line += 1;
+                let element = lines[line]
+                    .split(' ')
+                    .skip(1)
+                    .map(|c| nodes[c.parse::<usize>().unwrap() - 1])
+                    .collect::<Vec<_>>();
+                if header[0] == 3 {
+                    let tet_volume =
+                        signed_volume_tet(element[0], element[1], element[2], element[3]);
+                    assert!(tet_volume >= 0.);
+                    volume += tet_volume;
+                } else {
+                    area += 0.5
+                        * (element[1] - element[0])
+                            .cross(element[2] - element[0])
+                            .length();
+                }
+            }
+        }
+        assert_eq!(lines[line + 1], "$EndElements");
+        assert_approx_eq!(f64, volume, 1., epsilon = 1e-10);
+        assert_approx_eq!(f64, area, 6., epsilon = 1e-10);
+    }
+}
@@ -0,0 +1,143 @@
+use glam::{DMat3, DVec3};
+
+use super::{Dimensionality, Voronoi};
+
+/// Precomputed geometric weights for weighted least-squares gradient estimation, see
+/// [`Voronoi::gradient_weights`].
+///
+/// For every cell `i`, the gradient of a field `phi` is estimated as `sum_j g_ij * (phi_j - phi_i)`, where the sum
+/// runs over the neighbours `j` of `i` and `g_ij` are the precomputed weights.
+#[derive(Default, Debug, Clone)]
+pub struct GradientWeights {
+    offsets: Vec<usize>,
+    neighbours: Vec<usize>,
+    weights: Vec<DVec3>,
+}
+
+impl GradientWeights {
+    /// Get an `Iterator` over the `(neighbour_idx, weight)` pairs of the cell with index `cell_idx`.
+    ///
+    /// A neighbour appears multiple times if it borders the cell through several faces (e.g. across different
+    /// periodic boundaries).
+    pub fn cell_weights(&self, cell_idx: usize) -> impl Iterator<Item = (usize, DVec3)> + '_ {
+        let range = self.offsets[cell_idx]..self.offsets[cell_idx + 1];
+        self.neighbours[range.clone()]
+            .iter()
+            .copied()
+            .zip(self.weights[range].iter().copied())
+    }
+
+    /// Estimate the gradients of a field with the given per-cell `values`.
+    pub fn gradients(&self, values: &[f64]) -> Vec<DVec3> {
+        assert_eq!(
+            values.len() + 1,
+            self.offsets.len(),
+            "Must have one value per cell!"
+        );
+        (0..values.len())
+            .map(|i| {
+                self.cell_weights(i)
+                    .map(|(j, weight)| (values[j] - values[i]) * weight)
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+impl Voronoi {
+    /// Precompute the geometric weights for weighted least-squares gradient estimation from the values of
+    /// neighbouring cells.
+    ///
+    /// For every cell `i` with neighbours `j` (including periodic images), let `d_ij` be the offset between both
+    /// generators and `w_ij = A_ij / |d_ij|^2` with `A_ij` the area of their shared face. The gradient weights are
+    /// then `g_ij = E_i^-1 w_ij d_ij` with `E_i = sum_j w_ij d_ij d_ij^T`, such that linear fields are reproduced exactly.
+    /// Boundary faces are ignored. Cells that do not have enough neighbours to determine a gradient get zero weights.
+    pub fn gradient_weights(&self) -> GradientWeights {
+        let mut offsets = vec![0];
+        let mut neighbours = vec![];
+        let mut weights = vec![];
+
+        for (cell_idx, cell) in self.cells.iter().enumerate() {
+            let start = neighbours.len();
+            let mut matrix = DMat3::ZERO;
+            for face in cell.faces(self) {
+                let Some(right) = face.right() else {
+                    continue;
+                };
+                let (neighbour, d) = if face.left() == cell_idx {
+                    let right_loc = self.cells[right].loc() + face.shift().unwrap_or(DVec3::ZERO);
+                    (right, right_loc - cell.loc())
+                } else {
+                    (face.left(), self.cells[face.left()].loc() - cell.loc())
+                };
+                let w = face.area() / d.length_squared();
+                matrix += w * DMat3::from_cols(d.x * d, d.y * d, d.z * d);
+                neighbours.push(neighbour);
+                weights.push(w * d);
+            }
+
+            // Regularize the unused dimensions
+            match self.dimensionality {
+                Dimensionality::Dimensionality1D => {
+                    matrix.y_axis.y = 1.;
+                    matrix.z_axis.z = 1.;
+                }
+                Dimensionality::Dimensionality2D => matrix.z_axis.z = 1.,
+                Dimensionality::Dimensionality3D => (),
+            }
+
+            let trace = matrix.x_axis.x + matrix.y_axis.y + matrix.z_axis.z;
+            let determinant = matrix.determinant();
+            if determinant.abs() > 1e-12 * trace.powi(3) {
+                let inverse = matrix.inverse();
+                for weight in weights[start..].iter_mut() {
+                    *weight = inverse * *weight;
+                }
+            } else {
+                weights[start..].fill(DVec3::ZERO);
+            }
+            offsets.push(neighbours.len());
+        }
+
+        GradientWeights {
+            offsets,
+            neighbours,
+            weights,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::voronoi::test::perturbed_grid;
+
+    #[test]
+    fn test_linear_gradients() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        let gradient = DVec3::new(1., -2., 3.);
+        let values = generators
+            .iter()
+            .map(|g| g.dot(gradient))
+            .collect::<Vec<_>>();
+
+        let voronoi = Voronoi::build(&generators, anchor, width, 3, false, None);
+        for estimate in voronoi.gradient_weights().gradients(&values) {
+            assert!(estimate.distance(gradient) < 1e-10);
+        }
+
+        // Only keep one layer of the generators in 2D
+        let generators = generators.iter().step_by(4).copied().collect::<Vec<_>>();
+        let voronoi = Voronoi::build(&generators, anchor, width, 2, false, None);
+        let gradient_2d = DVec3::new(gradient.x, gradient.y, 0.);
+        let values = generators
+            .iter()
+            .map(|g| g.dot(gradient_2d))
+            .collect::<Vec<_>>();
+        for estimate in voronoi.gradient_weights().gradients(&values) {
+            assert!(estimate.distance(gradient_2d) < 1e-10);
+        }
+    }
+}
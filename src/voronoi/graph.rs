@@ -0,0 +1,125 @@
+use petgraph::graph::{NodeIndex, UnGraph};
+
+use super::Voronoi;
+
+/// The data attached to an edge of the adjacency graph of a Voronoi tesselation, see [`Voronoi::adjacency_graph`].
+#[derive(Debug, Clone, Copy)]
+pub struct AdjacencyEdge {
+    face: usize,
+    area: f64,
+    distance: f64,
+}
+
+impl AdjacencyEdge {
+    /// Get the index of the face between both cells in [`Voronoi::faces`].
+    pub fn face(&self) -> usize {
+        self.face
+    }
+
+    /// Get the area of the face between both cells.
+    pub fn area(&self) -> f64 {
+        self.area
+    }
+
+    /// Get the distance between the generators of both cells (taking into account the periodic shift).
+    pub fn distance(&self) -> f64 {
+        self.distance
+    }
+}
+
+impl Voronoi {
+    /// Get the adjacency graph of this Voronoi tesselation. Requires the `petgraph` feature to be enabled.
+    ///
+    /// The node with index `i` corresponds to the cell with index `i` (and has `i` as weight). Every face between two
+    /// cells corresponds to an edge, hence neighbouring cells can be connected by multiple edges for periodic
+    /// tesselations (and a cell can be connected to itself).
+    pub fn adjacency_graph(&self) -> UnGraph<usize, AdjacencyEdge> {
+        self.proximity_graph((0..self.faces.len()).filter(|&i| self.faces[i].right().is_some()))
+    }
+
+    /// Get the Gabriel graph of the generators of this Voronoi tesselation (see [`Voronoi::gabriel_faces`]), as a
+    /// subgraph of the adjacency graph. Requires the `petgraph` feature to be enabled.
+    pub fn gabriel_graph(&self) -> UnGraph<usize, AdjacencyEdge> {
+        self.proximity_graph(self.gabriel_faces().into_iter())
+    }
+
+    /// Get the β-skeleton of the generators of this Voronoi tesselation (see [`Voronoi::beta_skeleton_faces`]), as a
+    /// subgraph of the adjacency graph. Requires the `petgraph` feature to be enabled.
+    pub fn beta_skeleton_graph(&self, beta: f64) -> UnGraph<usize, AdjacencyEdge> {
+        self.proximity_graph(self.beta_skeleton_faces(beta).into_iter())
+    }
+
+    /// Get the relative neighbourhood graph of the generators of this Voronoi tesselation (see
+    /// [`Voronoi::relative_neighbourhood_faces`]), as a subgraph of the adjacency graph. Requires the `petgraph`
+    /// feature to be enabled.
+    pub fn relative_neighbourhood_graph(&self) -> UnGraph<usize, AdjacencyEdge> {
+        self.proximity_graph(self.relative_neighbourhood_faces().into_iter())
+    }
+
+    /// Build a graph with a node per cell and an edge for every primary face among the given faces.
+    fn proximity_graph(&self, faces: impl Iterator<Item = usize>) -> UnGraph<usize, AdjacencyEdge> {
+        let mut graph = UnGraph::with_capacity(self.cells.len(), self.faces.len());
+        for i in 0..self.cells.len() {
+            graph.add_node(i);
+        }
+        for face_idx in faces {
+            let face = &self.faces[face_idx];
+            // Periodic faces are stored for both of their cells, only keep one of them.
+            if !face.is_primary() {
+                continue;
+            }
+            let right = face.right().expect("Primary faces have a right neighbour!");
+            let distance = face
+                .generator_distance(self)
+                .expect("Face between two cells must have a generator distance!");
+            graph.add_edge(
+                NodeIndex::new(face.left()),
+                NodeIndex::new(right),
+                AdjacencyEdge {
+                    face: face_idx,
+                    area: face.area(),
+                    distance,
+                },
+            );
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use glam::DVec3;
+    use petgraph::visit::EdgeRef;
+
+    use super::*;
+    use crate::voronoi::test::perturbed_grid;
+
+    #[test]
+    fn test_adjacency_graph() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        for periodic in [false, true] {
+            let voronoi = Voronoi::build(&generators, anchor, width, 3, periodic, None);
+            let graph = voronoi.adjacency_graph();
+            assert_eq!(graph.node_count(), generators.len());
+            for (i, cell) in voronoi.cells().iter().enumerate() {
+                let interior_faces = cell.faces(&voronoi).filter(|f| f.right().is_some()).count();
+                let degree = graph
+                    .edges(NodeIndex::new(i))
+                    .map(|e| if e.source() == e.target() { 2 } else { 1 })
+                    .sum::<usize>();
+                assert_eq!(degree, interior_faces);
+            }
+
+            let gabriel_graph = voronoi.gabriel_graph();
+            assert_eq!(gabriel_graph.node_count(), generators.len());
+            assert_eq!(gabriel_graph.edge_count(), voronoi.gabriel_faces().len());
+            assert!(gabriel_graph.edge_count() <= graph.edge_count());
+            assert_eq!(
+                voronoi.relative_neighbourhood_graph().edge_count(),
+                voronoi.beta_skeleton_graph(2.).edge_count()
+            );
+        }
+    }
+}
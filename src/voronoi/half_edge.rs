@@ -0,0 +1,340 @@
+use std::collections::HashMap;
+
+use glam::DVec3;
+
+use crate::util::weld;
+
+use super::Voronoi;
+
+/// A half-edge of a [`HalfEdgeMesh`].
+#[derive(Debug, Clone, Copy)]
+pub struct HalfEdge {
+    origin: usize,
+    next: usize,
+    prev: usize,
+    opposite: Option<usize>,
+    twin: Option<usize>,
+    face: usize,
+}
+
+impl HalfEdge {
+    /// Get the index of the vertex this half-edge starts from.
+    pub fn origin(&self) -> usize {
+        self.origin
+    }
+
+    /// Get the index of the next half-edge around the same face.
+    pub fn next(&self) -> usize {
+        self.next
+    }
+
+    /// Get the index of the previous half-edge around the same face.
+    pub fn prev(&self) -> usize {
+        self.prev
+    }
+
+    /// Get the index of the twin of this half-edge across cells: the oppositely oriented half-edge on the same face,
+    /// as seen from the neighbouring cell. `None` for half-edges of boundary faces.
+    pub fn twin(&self) -> Option<usize> {
+        self.twin
+    }
+
+    /// Get the index of the oppositely oriented half-edge on the adjacent face _of the same cell_.
+    /// Can only be `None` if the boundary of the cell is not closed (e.g. for 1D and 2D tesselations or for
+    /// cells that were not constructed in a partial tesselation).
+    pub fn opposite(&self) -> Option<usize> {
+        self.opposite
+    }
+
+    /// Get the index of the face this half-edge belongs to.
+    pub fn face(&self) -> usize {
+        self.face
+    }
+}
+
+/// A face of a [`HalfEdgeMesh`] bounding a single cell.
+///
+/// Every Voronoi face between two cells is represented by two of these faces (one for each cell)
+/// with opposite orientations.
+#[derive(Debug, Clone, Copy)]
+pub struct HalfEdgeFace {
+    half_edge: usize,
+    cell: usize,
+    voronoi_face: usize,
+    twin: Option<usize>,
+}
+
+impl HalfEdgeFace {
+    /// Get the index of one of the half-edges around this face.
+    pub fn half_edge(&self) -> usize {
+        self.half_edge
+    }
+
+    /// Get the index of the cell this face bounds.
+    pub fn cell(&self) -> usize {
+        self.cell
+    }
+
+    /// Get the index of the corresponding Voronoi face in [`Voronoi::faces`].
+    pub fn voronoi_face(&self) -> usize {
+        self.voronoi_face
+    }
+
+    /// Get the index of the same face as seen from the neighbouring cell (if any).
+    pub fn twin(&self) -> Option<usize> {
+        self.twin
+    }
+}
+
+/// A half-edge representation of a Voronoi tesselation.
+///
+/// The vertices are welded across faces and cells. The boundary of every cell is a closed
+/// surface of [`HalfEdgeFace`]s oriented counterclockwise as seen from outside the cell.
+pub struct HalfEdgeMesh {
+    vertices: Vec<DVec3>,
+    half_edges: Vec<HalfEdge>,
+    faces: Vec<HalfEdgeFace>,
+}
+
+impl HalfEdgeMesh {
+    pub(super) fn from_voronoi(voronoi: &Voronoi) -> Self {
+        let tolerance = 1e-10 * voronoi.width.max_element();
+        let (vertices, welded) = weld(&voronoi.face_vertices, tolerance);
+
+        // Welded vertex loops of the Voronoi faces, with consecutive duplicates removed.
+        let face_loops = voronoi
+            .faces
+            .iter()
+            .map(|face| {
+                let mut face_loop = welded
+                    [face.vertex_offset()..face.vertex_offset() + face.vertex_count()]
+                    .to_vec();
+                face_loop.dedup();
+                while face_loop.len() > 1 && face_loop.first() == face_loop.last() {
+                    face_loop.pop();
+                }
+                face_loop
+            })
+            .collect::<Vec<_>>();
+
+        let mut half_edges = vec![];
+        let mut faces = vec![];
+        // Index of the half-edge face created for a given (cell, voronoi face) pair
+        let mut face_lookup = HashMap::new();
+        for (cell_idx, cell) in voronoi.cells.iter().enumerate() {
+            let mut cell_half_edges = HashMap::new();
+            for &face_idx in cell.face_indices(voronoi) {
+                let face_loop = &face_loops[face_idx];
+                if face_loop.len() < 3 {
+                    continue;
+                }
+                let voronoi_face = &voronoi.faces[face_idx];
+                let reversed = voronoi_face.left() != cell_idx;
+                let he_face_idx = faces.len();
+                let offset = half_edges.len();
+                let n = face_loop.len();
+                for i in 0..n {
+                    let (origin, dest) = if reversed {
+                        (face_loop[n - 1 - i], face_loop[(2 * n - 2 - i) % n])
+                    } else {
+                        (face_loop[i], face_loop[(i + 1) % n])
+                    };
+                    cell_half_edges.insert((origin, dest), offset + i);
+                    half_edges.push(HalfEdge {
+                        origin,
+                        next: offset + (i + 1) % n,
+                        prev: offset + (i + n - 1) % n,
+                        opposite: None,
+                        twin: None,
+                        face: he_face_idx,
+                    });
+                }
+                face_lookup.insert((cell_idx, face_idx), he_face_idx);
+                faces.push(HalfEdgeFace {
+                    half_edge: offset,
+                    cell: cell_idx,
+                    voronoi_face: face_idx,
+                    twin: None,
+                });
+            }
+
+            // Link the opposite half-edges within this cell
+            for (&(origin, dest), &he_idx) in cell_half_edges.iter() {
+                half_edges[he_idx].opposite = cell_half_edges.get(&(dest, origin)).copied();
+            }
+        }
+
+        // Link the faces (and their half-edges) to their counterparts in the neighbouring cells
+        for he_face_idx in 0..faces.len() {
+            let face = faces[he_face_idx];
+            let voronoi_face = &voronoi.faces[face.voronoi_face];
+            let (twin, shift) = match (voronoi_face.right(), voronoi_face.shift()) {
+                (None, _) => continue,
+                (Some(right_idx), None) => {
+                    // Same voronoi face, other cell
+                    let other = if face.cell == voronoi_face.left() {
+                        right_idx
+                    } else {
+                        voronoi_face.left()
+                    };
+                    (face_lookup.get(&(other, face.voronoi_face)), DVec3::ZERO)
+                }
+                (Some(right_idx), Some(shift)) => {
+                    // Periodic face: look for the face of the right cell pointing back to this cell
+                    let twin = voronoi.cells[right_idx]
+                        .face_indices(voronoi)
+                        .iter()
+                        .find(|&&i| {
+                            let other = &voronoi.faces[i];
+                            other.left() == right_idx
+                                && other.right() == Some(face.cell)
+                                && other.shift().is_some_and(|s| {
+                                    s.distance_squared(-shift) <= tolerance * tolerance
+                                })
+                        })
+                        .and_then(|&i| face_lookup.get(&(right_idx, i)));
+                    (twin, shift)
+                }
+            };
+            let Some(&twin) = twin else {
+                continue;
+            };
+            faces[he_face_idx].twin = Some(twin);
+
+            // Match the half-edges of both faces by position
+            let half_edge_ids = Self::face_half_edges(&half_edges, face.half_edge);
+            let twin_half_edge_ids = Self::face_half_edges(&half_edges, faces[twin].half_edge);
+            for &he_idx in half_edge_ids.iter() {
+                let he = half_edges[he_idx];
+                let origin = vertices[he.origin];
+                let dest = vertices[half_edges[he.next].origin];
+                half_edges[he_idx].twin = twin_half_edge_ids.iter().copied().find(|&other_idx| {
+                    let other = half_edges[other_idx];
+                    let other_origin = vertices[other.origin] + shift;
+                    let other_dest = vertices[half_edges[other.next].origin] + shift;
+                    other_origin.distance_squared(dest) <= tolerance * tolerance
+                        && other_dest.distance_squared(origin) <= tolerance * tolerance
+                });
+            }
+        }
+
+        Self {
+            vertices,
+            half_edges,
+            faces,
+        }
+    }
+
+    fn face_half_edges(half_edges: &[HalfEdge], start: usize) -> Vec<usize> {
+        let mut result = vec![start];
+        let mut cur = half_edges[start].next;
+        while cur != start {
+            result.push(cur);
+            cur = half_edges[cur].next;
+        }
+        result
+    }
+
+    /// Get the (welded) vertices of this mesh.
+    pub fn vertices(&self) -> &[DVec3] {
+        self.vertices.as_ref()
+    }
+
+    /// Get the half-edges of this mesh.
+    pub fn half_edges(&self) -> &[HalfEdge] {
+        self.half_edges.as_ref()
+    }
+
+    /// Get the faces of this mesh.
+    pub fn faces(&self) -> &[HalfEdgeFace] {
+        self.faces.as_ref()
+    }
+
+    /// Get an `Iterator` over the indices of the half-edges around the face with index `face_idx`.
+    pub fn face_half_edges_iter(&self, face_idx: usize) -> impl Iterator<Item = usize> + '_ {
+        let start = self.faces[face_idx].half_edge;
+        let mut cur = Some(start);
+        std::iter::from_fn(move || {
+            let result = cur?;
+            let next = self.half_edges[result].next;
+            cur = if next == start { None } else { Some(next) };
+            Some(result)
+        })
+    }
+
+    /// Get the index of the vertex a half-edge points to.
+    pub fn destination(&self, half_edge_idx: usize) -> usize {
+        self.half_edges[self.half_edges[half_edge_idx].next].origin
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::voronoi::test::perturbed_grid;
+
+    fn check_mesh(voronoi: &Voronoi, mesh: &HalfEdgeMesh) {
+        for (i, he) in mesh.half_edges().iter().enumerate() {
+            let opposite = he.opposite().expect("Cell boundaries should be closed!");
+            assert_eq!(mesh.half_edges()[opposite].opposite(), Some(i));
+            assert_eq!(mesh.half_edges()[opposite].origin(), mesh.destination(i));
+            let opposite_face = mesh.half_edges()[opposite].face();
+            assert_eq!(
+                mesh.faces()[opposite_face].cell(),
+                mesh.faces()[he.face()].cell()
+            );
+            if let Some(twin) = he.twin() {
+                assert_eq!(mesh.half_edges()[twin].twin(), Some(i));
+            }
+        }
+        for (i, face) in mesh.faces().iter().enumerate() {
+            let is_interior = voronoi.faces()[face.voronoi_face()].right().is_some();
+            assert_eq!(face.twin().is_some(), is_interior);
+            for he in mesh.face_half_edges_iter(i) {
+                assert_eq!(mesh.half_edges()[he].face(), i);
+                assert_eq!(mesh.half_edges()[he].twin().is_some(), is_interior);
+            }
+        }
+    }
+
+    #[test]
+    fn test_half_edge() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 3, 0.5);
+        let voronoi = Voronoi::build(&generators, anchor, width, 3, false, None);
+        let mesh = voronoi.to_halfedge();
+        check_mesh(&voronoi, &mesh);
+
+        // The Euler characteristic of every cell should be 2
+        let mut vertices_per_cell = vec![vec![]; voronoi.cells().len()];
+        let mut half_edges_per_cell = vec![0; voronoi.cells().len()];
+        let mut faces_per_cell = vec![0; voronoi.cells().len()];
+        for he in mesh.half_edges() {
+            let cell = mesh.faces()[he.face()].cell();
+            vertices_per_cell[cell].push(he.origin());
+            half_edges_per_cell[cell] += 1;
+        }
+        for face in mesh.faces() {
+            faces_per_cell[face.cell()] += 1;
+        }
+        for (cell, vertices) in vertices_per_cell.iter_mut().enumerate() {
+            vertices.sort();
+            vertices.dedup();
+            let euler =
+                vertices.len() as i64 - half_edges_per_cell[cell] / 2 + faces_per_cell[cell];
+            assert_eq!(euler, 2);
+        }
+    }
+
+    #[test]
+    fn test_half_edge_periodic() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 3, 0.5);
+        let voronoi = Voronoi::build(&generators, anchor, width, 3, true, None);
+        let mesh = voronoi.to_halfedge();
+        check_mesh(&voronoi, &mesh);
+        assert!(mesh.faces().iter().all(|f| f.twin().is_some()));
+    }
+}
@@ -0,0 +1,173 @@
+use glam::DVec3;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::rtree_nn::{build_rtree, nn_iter, wrapping_nn_iter};
+
+use super::{normalize_simulation_volume, ConvexCell, Generator, SafetyCriterion, Voronoi};
+
+impl Voronoi {
+    /// Determine the halo of every rank of a domain decomposition, i.e. the foreign generators a rank needs to import
+    /// to correctly construct the Voronoi cells of its own generators.
+    /// This method runs in parallel over the ranks if the `"rayon"` feature is enabled.
+    ///
+    /// The halos are determined iteratively: every rank constructs its cells using its own generators and the
+    /// generators it has imported so far, and then imports all foreign generators within the safety radius of its
+    /// cells, until no new generators are found.
+    ///
+    /// * `generators` - The seed points of the Voronoi cells.
+    /// * `ranks` - The rank each generator belongs to.
+    /// * `anchor` - The lower left corner of the simulation volume.
+    /// * `width` - The width of the simulation volume. Also determines the period of periodic Voronoi tesselations.
+    /// * `dimensionality` - The dimensionality of the Voronoi tesselation.
+    /// * `periodic` - Whether to apply periodic boundary conditions to the Voronoi tesselation.
+    ///
+    /// Returns the sorted indices of the imported generators for every rank (`0..=max(ranks)`). Building a partial
+    /// Voronoi tesselation (see [`Voronoi::build_partial`]) of the local and imported generators of a rank, with the
+    /// local generators masked in, yields the same cells as the global tesselation.
+    pub fn determine_halos(
+        generators: &[DVec3],
+        ranks: &[usize],
+        mut anchor: DVec3,
+        mut width: DVec3,
+        dimensionality: usize,
+        periodic: bool,
+    ) -> Vec<Vec<usize>> {
+        assert_eq!(
+            generators.len(),
+            ranks.len(),
+            "Must have one rank per generator!"
+        );
+        let dimensionality = dimensionality.into();
+        normalize_simulation_volume(&mut anchor, &mut width, dimensionality);
+
+        let generators: Vec<Generator> = generators
+            .iter()
+            .enumerate()
+            .map(|(id, &loc)| Generator::new(id, loc, dimensionality))
+            .collect();
+        let rtree = build_rtree(&generators);
+        let simulation_volume =
+            ConvexCell::init_simulation_volume(anchor, width, periodic, dimensionality);
+        let rank_count = ranks.iter().max().map_or(0, |&rank| rank + 1);
+
+        let determine_halo = |rank: usize| {
+            let local = (0..generators.len())
+                .filter(|&idx| ranks[idx] == rank)
+                .collect::<Vec<_>>();
+            let mut imported = vec![false; generators.len()];
+            let mut halo = vec![];
+            loop {
+                let known = local
+                    .iter()
+                    .chain(halo.iter())
+                    .map(|&idx| generators[idx])
+                    .collect::<Vec<_>>();
+                let known_rtree = build_rtree(&known);
+
+                let mut new_imports = vec![];
+                for &idx in local.iter() {
+                    let loc = generators[idx].loc();
+                    let mut convex_cell =
+                        ConvexCell::init(loc, idx, &simulation_volume, dimensionality);
+                    let nearest_neighbours = if periodic {
+                        wrapping_nn_iter(&known_rtree, loc, width, dimensionality)
+                    } else {
+                        nn_iter(&known_rtree, loc)
+                    };
+                    convex_cell.build(
+                        &generators,
+                        nearest_neighbours,
+                        SafetyCriterion::Strict,
+                        dimensionality,
+                    );
+
+                    // Import all foreign generators that might still clip this cell
+                    let safety_radius = convex_cell.safety_radius();
+                    let candidates = if periodic {
+                        wrapping_nn_iter(&rtree, loc, width, dimensionality)
+                    } else {
+                        nn_iter(&rtree, loc)
+                    };
+                    for (ngb_idx, shift) in candidates {
+                        let ngb_loc = generators[ngb_idx].loc() + shift.unwrap_or(DVec3::ZERO);
+                        if loc.distance(ngb_loc) > safety_radius {
+                            break;
+                        }
+                        if ranks[ngb_idx] != rank && !imported[ngb_idx] {
+                            imported[ngb_idx] = true;
+                            new_imports.push(ngb_idx);
+                        }
+                    }
+                }
+
+                if new_imports.is_empty() {
+                    break;
+                }
+                halo.extend(new_imports);
+            }
+            halo.sort();
+            halo
+        };
+
+        #[cfg(feature = "rayon")]
+        return (0..rank_count)
+            .into_par_iter()
+            .map(determine_halo)
+            .collect();
+        #[cfg(not(feature = "rayon"))]
+        return (0..rank_count).map(determine_halo).collect();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+    use crate::voronoi::test::perturbed_grid;
+
+    #[test]
+    fn test_determine_halos() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 5, 0.5);
+        let ranks = generators
+            .iter()
+            .map(|g| (g.x > 0.5) as usize + 2 * (g.y > 0.5) as usize)
+            .collect::<Vec<_>>();
+        for periodic in [false, true] {
+            let reference = Voronoi::build(&generators, anchor, width, 3, periodic, None);
+            let halos = Voronoi::determine_halos(&generators, &ranks, anchor, width, 3, periodic);
+            assert_eq!(halos.len(), 4);
+            for (rank, halo) in halos.iter().enumerate() {
+                assert!(halo.iter().all(|&idx| ranks[idx] != rank));
+                let local = (0..generators.len())
+                    .filter(|&idx| ranks[idx] == rank)
+                    .collect::<Vec<_>>();
+                let subset = local
+                    .iter()
+                    .chain(halo.iter())
+                    .map(|&idx| generators[idx])
+                    .collect::<Vec<_>>();
+                let mask = (0..subset.len())
+                    .map(|i| i < local.len())
+                    .collect::<Vec<_>>();
+                let voronoi =
+                    Voronoi::build_partial(&subset, &mask, anchor, width, 3, periodic, None);
+                for (i, &idx) in local.iter().enumerate() {
+                    assert_approx_eq!(
+                        f64,
+                        voronoi.cells()[i].volume(),
+                        reference.cells()[idx].volume(),
+                        epsilon = 1e-10
+                    );
+                    assert_eq!(
+                        voronoi.cells()[i].face_count(),
+                        reference.cells()[idx].face_count()
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use glam::DVec3;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{
+    integrators::FaceIntegrators,
+    rtree_nn::{build_rtree, nn_iter},
+};
+
+use super::{
+    voronoi_cell::ConvexCell, CellSelection, Dimensionality, Generator, SafetyCriterion, Voronoi,
+    VoronoiCell,
+};
+
+/// A two-level Voronoi tesselation: a coarse tesselation of a subset of the generators, of which selected cells are
+/// refined into the Voronoi tesselation of the generators they contain (clipped to the coarse cell).
+///
+/// See [`HierarchicalVoronoi::build`].
+pub struct HierarchicalVoronoi {
+    coarse: Voronoi,
+    coarse_generators: Vec<usize>,
+    parents: Vec<usize>,
+    children: Vec<Vec<usize>>,
+    refinements: Vec<Option<Voronoi>>,
+}
+
+impl HierarchicalVoronoi {
+    /// Build the coarse tesselation of the generators with the given indices `coarse_generators` and refine the coarse
+    /// cells for which `refine` is `true` (one entry per coarse cell) with all generators they contain.
+    ///
+    /// Every generator is assigned to the coarse cell containing it (i.e. the coarse generator nearest to it), and the
+    /// refinement of a coarse cell is the Voronoi tesselation of its generators, clipped to the coarse cell. The faces
+    /// of a refinement on the boundary of the coarse cell are boundary faces, with a [`crate::Boundary`] only if they
+    /// lie on the boundary of the simulation volume. For periodic tesselations, the generators are taken at their
+    /// periodic image nearest to the coarse generator and the refinements are not periodic.
+    pub fn build(
+        generators: &[DVec3],
+        coarse_generators: &[usize],
+        refine: &[bool],
+        anchor: DVec3,
+        width: DVec3,
+        dimensionality: usize,
+        periodic: bool,
+    ) -> Self {
+        assert_eq!(
+            refine.len(),
+            coarse_generators.len(),
+            "Must have one refinement flag per coarse generator!"
+        );
+        let coarse_locs = coarse_generators
+            .iter()
+            .map(|&idx| generators[idx])
+            .collect::<Vec<_>>();
+        let coarse = Voronoi::build(&coarse_locs, anchor, width, dimensionality, periodic, None);
+
+        let parents = coarse.cells_at(generators);
+        let mut children = vec![vec![]; coarse_generators.len()];
+        for (idx, &parent) in parents.iter().enumerate() {
+            children[parent].push(idx);
+        }
+
+        let simulation_volume = ConvexCell::init_simulation_volume(
+            coarse.anchor,
+            coarse.width,
+            periodic,
+            coarse.dimensionality,
+        );
+        let refine_cell = |coarse_idx: usize| {
+            if !refine[coarse_idx] {
+                return None;
+            }
+            let coarse_cell = ConvexCell::reconstruct(
+                &coarse,
+                coarse_idx,
+                &simulation_volume,
+                coarse.dimensionality,
+            )?;
+            let coarse_loc = coarse_cell.loc;
+            let locs = children[coarse_idx]
+                .iter()
+                .map(|&idx| {
+                    let loc = generators[idx];
+                    if periodic {
+                        loc - ((loc - coarse_loc) / coarse.width).round()
+                            * coarse.width
+                            * used_dimensions(coarse.dimensionality)
+                    } else {
+                        loc
+                    }
+                })
+                .collect::<Vec<_>>();
+            Some(Self::refine(&coarse, &coarse_cell, &locs))
+        };
+        #[cfg(feature = "rayon")]
+        let refinements = (0..coarse_generators.len())
+            .into_par_iter()
+            .map(refine_cell)
+            .collect();
+        #[cfg(not(feature = "rayon"))]
+        let refinements = (0..coarse_generators.len()).map(refine_cell).collect();
+
+        Self {
+            coarse,
+            coarse_generators: coarse_generators.to_vec(),
+            parents,
+            children,
+            refinements,
+        }
+    }
+
+    /// Build the Voronoi tesselation of `locs` within the given coarse cell.
+    fn refine(coarse: &Voronoi, coarse_cell: &ConvexCell, locs: &[DVec3]) -> Voronoi {
+        let dimensionality = coarse.dimensionality;
+        let simulation_volume = coarse_cell.as_simulation_volume();
+        let generators = locs
+            .iter()
+            .enumerate()
+            .map(|(idx, &loc)| Generator::new(idx, loc, dimensionality))
+            .collect::<Vec<_>>();
+        let rtree = build_rtree(&generators);
+
+        let mut cells = vec![];
+        let mut faces = vec![];
+        let mut face_vertices = vec![];
+        for generator in generators.iter() {
+            let mut convex_cell = ConvexCell::init(
+                generator.loc(),
+                generator.id(),
+                &simulation_volume,
+                dimensionality,
+            );
+            convex_cell.build(
+                &generators,
+                nn_iter(&rtree, generator.loc()),
+                SafetyCriterion::Strict,
+                dimensionality,
+            );
+            let mut cell_faces = vec![];
+            let mut cell_face_vertices = vec![];
+            let cell = VoronoiCell::from_convex_cell(
+                &convex_cell,
+                &mut cell_faces,
+                &mut cell_face_vertices,
+                &mut vec![],
+                &mut vec![],
+                &mut [],
+                CellSelection::All,
+                false,
+                &FaceIntegrators::default(),
+            );
+            cells.push(cell);
+            faces.push(cell_faces);
+            face_vertices.push(cell_face_vertices);
+        }
+
+        Voronoi::assemble(
+            coarse.anchor,
+            coarse.width,
+            false,
+            dimensionality,
+            cells,
+            faces,
+            face_vertices,
+            vec![],
+            vec![],
+            vec![],
+            0,
+            0,
+            &[],
+            None,
+        )
+    }
+
+    /// Get the coarse tesselation. Its cell `i` belongs to the generator `coarse_generators()[i]`.
+    pub fn coarse(&self) -> &Voronoi {
+        &self.coarse
+    }
+
+    /// Get the indices of the generators of the coarse cells.
+    pub fn coarse_generators(&self) -> &[usize] {
+        &self.coarse_generators
+    }
+
+    /// Get the index of the coarse cell containing every generator.
+    pub fn parents(&self) -> &[usize] {
+        &self.parents
+    }
+
+    /// Get the (sorted) indices of the generators contained in the coarse cell with the given index.
+    pub fn children(&self, coarse_idx: usize) -> &[usize] {
+        &self.children[coarse_idx]
+    }
+
+    /// Get the refinement of the coarse cell with the given index, if it was refined. Its cell `i` belongs to the
+    /// generator `children(coarse_idx)[i]`.
+    pub fn refinement(&self, coarse_idx: usize) -> Option<&Voronoi> {
+        self.refinements[coarse_idx].as_ref()
+    }
+
+    /// Get the finest cell of the generator with the given index, as the tesselation containing it and its index in
+    /// that tesselation: its cell in the refinement of its parent if it was refined, or the coarse cell if the
+    /// generator is a coarse generator of an unrefined cell. Returns `None` otherwise.
+    pub fn finest_cell(&self, idx: usize) -> Option<(&Voronoi, usize)> {
+        let parent = self.parents[idx];
+        match self.refinement(parent) {
+            Some(refinement) => {
+                let child_idx = self.children[parent]
+                    .binary_search(&idx)
+                    .expect("Generator must be a child of its parent!");
+                Some((refinement, child_idx))
+            }
+            None => (self.coarse_generators[parent] == idx).then_some((&self.coarse, parent)),
+        }
+    }
+}
+
+/// The dimensions used by a tesselation of the given dimensionality.
+fn used_dimensions(dimensionality: Dimensionality) -> DVec3 {
+    match dimensionality {
+        Dimensionality::Dimensionality1D => DVec3::X,
+        Dimensionality::Dimensionality2D => DVec3::new(1., 1., 0.),
+        Dimensionality::Dimensionality3D => DVec3::ONE,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+    use crate::voronoi::test::{perturbed_grid, perturbed_plane};
+
+    #[test]
+    fn test_hierarchical() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        for (generators, dimensionality) in [
+            (perturbed_grid(anchor, width, 6, 0.5), 3),
+            (perturbed_plane(anchor, width, 12, 0.5), 2),
+        ] {
+            for periodic in [false, true] {
+                let coarse_generators = (0..generators.len()).step_by(8).collect::<Vec<_>>();
+                let refine = (0..coarse_generators.len())
+                    .map(|i| i % 3 != 0)
+                    .collect::<Vec<_>>();
+                let hierarchy = HierarchicalVoronoi::build(
+                    &generators,
+                    &coarse_generators,
+                    &refine,
+                    anchor,
+                    width,
+                    dimensionality,
+                    periodic,
+                );
+                let coarse = hierarchy.coarse();
+                assert_eq!(
+                    (0..coarse_generators.len())
+                        .map(|i| hierarchy.children(i).len())
+                        .sum::<usize>(),
+                    generators.len()
+                );
+                for (coarse_idx, coarse_cell) in coarse.cells().iter().enumerate() {
+                    assert!(hierarchy
+                        .children(coarse_idx)
+                        .contains(&coarse_generators[coarse_idx]));
+                    let Some(refinement) = hierarchy.refinement(coarse_idx) else {
+                        assert!(!refine[coarse_idx]);
+                        continue;
+                    };
+                    assert_eq!(
+                        refinement.cells().len(),
+                        hierarchy.children(coarse_idx).len()
+                    );
+                    // The refinement partitions the coarse cell
+                    let volume = refinement.cells().iter().map(|c| c.volume()).sum::<f64>();
+                    assert_approx_eq!(f64, volume, coarse_cell.volume(), epsilon = 1e-10);
+                    let centroid = refinement
+                        .cells()
+                        .iter()
+                        .map(|c| c.volume() * c.centroid())
+                        .sum::<DVec3>()
+                        / volume;
+                    assert!(centroid.distance(coarse_cell.centroid()) < 1e-10);
+                    for (child_idx, &idx) in hierarchy.children(coarse_idx).iter().enumerate() {
+                        assert_eq!(hierarchy.parents()[idx], coarse_idx);
+                        let cell = &refinement.cells()[child_idx];
+                        assert!(coarse_cell.signed_distance(coarse, cell.centroid()) <= 1e-10);
+                        let (voronoi, cell_idx) = hierarchy.finest_cell(idx).unwrap();
+                        assert_eq!(voronoi.cells()[cell_idx].volume(), cell.volume());
+                    }
+                    // Faces on the boundary of the coarse cell have no neighbour
+                    let boundary_area = refinement
+                        .faces()
+                        .iter()
+                        .filter(|face| face.right().is_none())
+                        .map(|face| face.area())
+                        .sum::<f64>();
+                    let coarse_area = coarse_cell
+                        .faces(coarse)
+                        .map(|face| face.area())
+                        .sum::<f64>();
+                    assert_approx_eq!(f64, boundary_area, coarse_area, epsilon = 1e-10);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use glam::DVec3;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::rtree_nn::{nn_iter, wrapping_nn_iter};
+
+use super::{Dimensionality, Voronoi};
+
+/// The method used to interpolate cell-centred values, see [`Voronoi::sample`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interpolation {
+    /// The value of the cell containing the point (piecewise constant).
+    Nearest,
+    /// Inverse distance weighting of the values of the cell containing the point and its neighbours, with weights
+    /// `1 / d^power` (where `d` is the distance to the generators).
+    InverseDistance {
+        /// The exponent of the inverse distance weights, typically 2.
+        power: f64,
+    },
+    /// The value of the cell containing the point, linearly extrapolated from its generator with the least-squares
+    /// gradient of the field (see [`Voronoi::gradient_weights`]). Reproduces linear fields exactly.
+    Linear,
+}
+
+impl Voronoi {
+    /// Find the cell containing `point` and the position of its generator relative to `point`, i.e. taking the
+    /// periodic image of the generator closest to the (unwrapped) point for periodic tesselations.
+    fn locate(&self, point: DVec3) -> (usize, DVec3) {
+        if self.periodic {
+            let wraps = ((point - self.anchor) / self.width).floor() * self.width;
+            let (idx, shift) =
+                wrapping_nn_iter(&self.rtree, point - wraps, self.width, self.dimensionality)
+                    .next()
+                    .expect("R-tree cannot be empty!");
+            (
+                idx,
+                self.cells[idx].loc() + shift.unwrap_or(DVec3::ZERO) + wraps,
+            )
+        } else {
+            let (idx, _) = nn_iter(&self.rtree, point)
+                .next()
+                .expect("R-tree cannot be empty!");
+            (idx, self.cells[idx].loc())
+        }
+    }
+
+    /// Interpolate the cell-centred field with the given per-cell `values` at the given `points`, using the given
+    /// interpolation `method`. This method runs in parallel if the `"rayon"` feature is enabled.
+    ///
+    /// The points are located as in [`Voronoi::cells_at`]: for periodic tesselations, the periodic images of the
+    /// generators closest to the points are used, points outside the simulation volume of a non-periodic tesselation
+    /// are located in the nearest cell. The unused coordinates of 1D and 2D tesselations are ignored.
+    pub fn sample(&self, values: &[f64], points: &[DVec3], method: Interpolation) -> Vec<f64> {
+        assert_eq!(
+            values.len(),
+            self.cells.len(),
+            "Must have one value per cell!"
+        );
+        assert!(self.rtree.size() > 0, "Tesselation has no generators!");
+        let mask = match self.dimensionality {
+            Dimensionality::Dimensionality1D => DVec3::X,
+            Dimensionality::Dimensionality2D => DVec3::new(1., 1., 0.),
+            Dimensionality::Dimensionality3D => DVec3::ONE,
+        };
+        let gradients = match method {
+            Interpolation::Linear => self.gradient_weights().gradients(values),
+            _ => vec![],
+        };
+
+        let sample = |&point: &DVec3| {
+            let (cell_idx, loc) = self.locate(point);
+            match method {
+                Interpolation::Nearest => values[cell_idx],
+                Interpolation::Linear => {
+                    values[cell_idx] + gradients[cell_idx].dot((point - loc) * mask)
+                }
+                Interpolation::InverseDistance { power } => {
+                    // The neighbours of the cell, in the frame of the point
+                    let offset = loc - self.cells[cell_idx].loc();
+                    let neighbours = self.cells[cell_idx].faces(self).filter_map(|face| {
+                        if face.left() == cell_idx {
+                            let right = face.right()?;
+                            let shift = face.shift().unwrap_or(DVec3::ZERO);
+                            Some((right, self.cells[right].loc() + shift + offset))
+                        } else {
+                            Some((face.left(), self.cells[face.left()].loc() + offset))
+                        }
+                    });
+                    let (mut weighted_sum, mut total_weight) = (0., 0.);
+                    for (idx, loc) in [(cell_idx, loc)].into_iter().chain(neighbours) {
+                        let distance = ((point - loc) * mask).length();
+                        if distance == 0. {
+                            return values[idx];
+                        }
+                        let weight = distance.powf(-power);
+                        weighted_sum += weight * values[idx];
+                        total_weight += weight;
+                    }
+                    weighted_sum / total_weight
+                }
+            }
+        };
+
+        #[cfg(feature = "rayon")]
+        return points.par_iter().map(sample).collect();
+        #[cfg(not(feature = "rayon"))]
+        return points.iter().map(sample).collect();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+    use crate::voronoi::test::{perturbed_grid, perturbed_plane};
+
+    #[test]
+    fn test_sample() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 5, 0.5);
+        let points = perturbed_grid(anchor, width, 7, 0.9);
+        for periodic in [false, true] {
+            let voronoi = Voronoi::build(&generators, anchor, width, 3, periodic, None);
+            let field = |x: DVec3| 1. + 2. * x.x - x.y + 0.5 * x.z;
+            let values = generators.iter().map(|&g| field(g)).collect::<Vec<_>>();
+
+            let nearest = voronoi.sample(&values, &points, Interpolation::Nearest);
+            for (value, cell_idx) in nearest.iter().zip(voronoi.cells_at(&points)) {
+                assert_eq!(*value, values[cell_idx]);
+            }
+
+            // Inverse distance weighting interpolates the values at the generators and is bounded by the values
+            let method = Interpolation::InverseDistance { power: 2. };
+            let at_generators = voronoi.sample(&values, &generators, method);
+            for (value, expected) in at_generators.iter().zip(values.iter()) {
+                assert_approx_eq!(f64, *value, *expected, epsilon = 1e-12);
+            }
+            let (min, max) = values
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &v| {
+                    (min.min(v), max.max(v))
+                });
+            for value in voronoi.sample(&values, &points, method) {
+                assert!(value >= min && value <= max);
+            }
+
+            if !periodic {
+                // Linear interpolation reproduces linear fields
+                let linear = voronoi.sample(&values, &points, Interpolation::Linear);
+                for (value, point) in linear.iter().zip(points.iter()) {
+                    assert_approx_eq!(f64, *value, field(*point), epsilon = 1e-10);
+                }
+            } else {
+                // Periodic images of points are sampled consistently
+                let shifted = points.iter().map(|&p| p + width).collect::<Vec<_>>();
+                let sampled = voronoi.sample(&values, &points, method);
+                let shifted_values = voronoi.sample(&values, &shifted, method);
+                for (value, shifted_value) in sampled.iter().zip(shifted_values) {
+                    assert_approx_eq!(f64, *value, shifted_value, epsilon = 1e-12);
+                }
+            }
+        }
+
+        // 2D: the z coordinate is ignored
+        let generators = perturbed_plane(anchor, width, 5, 0.5);
+        let voronoi = Voronoi::build(&generators, anchor, width, 2, false, None);
+        let values = generators.iter().map(|g| g.x + g.y).collect::<Vec<_>>();
+        let point = DVec3::new(0.3, 0.6, 0.4);
+        let value = voronoi.sample(&values, &[point], Interpolation::Linear)[0];
+        assert_approx_eq!(f64, value, 0.9, epsilon = 1e-10);
+    }
+}
@@ -0,0 +1,481 @@
+use std::{collections::VecDeque, f64::consts::PI};
+
+use glam::DVec3;
+
+use super::{voronoi_cell::edge_endpoints, Dimensionality, Voronoi, VoronoiCell};
+
+/// The maximal recursion depth of the adaptive quadrature.
+const MAX_DEPTH: usize = 40;
+
+/// A smoothing kernel `W(r, h)` with compact support of radius `h` (i.e. `W(r, h) = 0` for `r >= h`), normalized to
+/// unit integral in the dimensionality of the tesselation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kernel {
+    /// The cubic spline (M4) kernel: `2 (1 - q)^3 - 8 (1/2 - q)^3` for `q = r / h < 1/2` and `2 (1 - q)^3` for
+    /// `1/2 <= q < 1` (up to normalization).
+    CubicSpline,
+    /// The Wendland C2 kernel: `(1 - q)^4 (1 + 4 q)` for `q = r / h < 1`, or `(1 - q)^3 (1 + 3 q)` in 1D (up to
+    /// normalization).
+    WendlandC2,
+    /// The Wendland C4 kernel: `(1 - q)^6 (1 + 6 q + 35/3 q^2)` for `q = r / h < 1`, or `(1 - q)^5 (1 + 5 q + 8 q^2)`
+    /// in 1D (up to normalization).
+    WendlandC4,
+}
+
+impl Kernel {
+    /// Evaluate the kernel at distance `r` for the support radius `h` in the given `dimensionality`.
+    pub fn evaluate(&self, r: f64, h: f64, dimensionality: usize) -> f64 {
+        let dimensionality = Dimensionality::from(dimensionality);
+        let q = r / h;
+        let Some(&(_, coefficients)) = self.pieces(dimensionality).iter().find(|(end, _)| q < *end)
+        else {
+            return 0.;
+        };
+        let w = coefficients.iter().rev().fold(0., |w, c| w * q + c);
+        self.norm(dimensionality) * w / h.powi(usize::from(dimensionality) as i32)
+    }
+
+    /// The normalization constant of the kernel for unit support radius.
+    fn norm(&self, dimensionality: Dimensionality) -> f64 {
+        use Dimensionality::*;
+        match (self, dimensionality) {
+            (Kernel::CubicSpline, Dimensionality1D) => 8. / 3.,
+            (Kernel::CubicSpline, Dimensionality2D) => 80. / (7. * PI),
+            (Kernel::CubicSpline, Dimensionality3D) => 16. / PI,
+            (Kernel::WendlandC2, Dimensionality1D) => 5. / 4.,
+            (Kernel::WendlandC2, Dimensionality2D) => 7. / PI,
+            (Kernel::WendlandC2, Dimensionality3D) => 21. / (2. * PI),
+            (Kernel::WendlandC4, Dimensionality1D) => 3. / 2.,
+            (Kernel::WendlandC4, Dimensionality2D) => 9. / PI,
+            (Kernel::WendlandC4, Dimensionality3D) => 495. / (32. * PI),
+        }
+    }
+
+    /// The (unnormalized) kernel as consecutive polynomial pieces in `q = r / h`, given by the end of the piece and the
+    /// coefficients of increasing powers of `q`. The last piece ends at 1.
+    fn pieces(&self, dimensionality: Dimensionality) -> &'static [(f64, &'static [f64])] {
+        match (self, dimensionality) {
+            (Kernel::CubicSpline, _) => &[(0.5, &[0.5, 0., -3., 3.]), (1., &[1., -3., 3., -1.])],
+            (Kernel::WendlandC2, Dimensionality::Dimensionality1D) => {
+                &[(1., &[1., 0., -6., 8., -3.])]
+            }
+            (Kernel::WendlandC2, _) => &[(1., &[1., 0., -10., 20., -15., 4.])],
+            (Kernel::WendlandC4, Dimensionality::Dimensionality1D) => {
+                &[(1., &[1., 0., -7., 0., 35., -56., 35., -8.])]
+            }
+            (Kernel::WendlandC4, _) => &[(
+                1.,
+                &[1., 0., -28. / 3., 0., 70., -448. / 3., 140., -64., 35. / 3.],
+            )],
+        }
+    }
+
+    /// The radial moment `int_0^min(u, 1) w(t) t^(n - 1) dt` of the (unnormalized) kernel `w`.
+    fn moment(&self, dimensionality: Dimensionality, n: i32, u: f64) -> f64 {
+        let mut moment = 0.;
+        let mut start = 0.;
+        for &(end, coefficients) in self.pieces(dimensionality) {
+            let upper = end.min(u);
+            if upper <= start {
+                break;
+            }
+            moment += power_integral(coefficients, n - 1, start, upper);
+            start = end;
+        }
+        moment
+    }
+
+    /// The antiderivative `int_0^u M(t) / t^2 dt` of the third radial moment `M` of the (unnormalized) kernel, see
+    /// [`Kernel::moment`].
+    fn cone_potential(&self, u: f64) -> f64 {
+        let dimensionality = Dimensionality::Dimensionality3D;
+        let mut potential = 0.;
+        let mut start = 0.;
+        for &(end, coefficients) in self.pieces(dimensionality) {
+            let upper = end.min(u);
+            if upper <= start {
+                break;
+            }
+            // On this piece, M(t) = offset + int_start^t w(s) s^2 ds
+            let offset = self.moment(dimensionality, 3, start);
+            if start > 0. {
+                potential += (offset - power_integral(coefficients, 2, 0., start))
+                    * (1. / start - 1. / upper);
+            }
+            // int t^-2 int_0^t c_k s^(k + 2) ds dt = c_k t^(k + 2) / ((k + 2) (k + 3))
+            potential += coefficients
+                .iter()
+                .enumerate()
+                .map(|(k, c)| {
+                    let k = k as i32;
+                    c * (upper.powi(k + 2) - start.powi(k + 2)) / ((k + 2) * (k + 3)) as f64
+                })
+                .sum::<f64>();
+            start = end;
+        }
+        if u > 1. {
+            potential += self.moment(dimensionality, 3, 1.) * (1. - 1. / u);
+        }
+        potential
+    }
+}
+
+/// The integral of `t^shift` times the polynomial with the given `coefficients` from `lower` to `upper`.
+fn power_integral(coefficients: &[f64], shift: i32, lower: f64, upper: f64) -> f64 {
+    coefficients
+        .iter()
+        .enumerate()
+        .map(|(k, c)| {
+            let p = k as i32 + shift + 1;
+            c * (upper.powi(p) - lower.powi(p)) / p as f64
+        })
+        .sum()
+}
+
+impl VoronoiCell {
+    /// Integrate the smoothing `kernel` with support radius `h` centered at `center` over this cell, i.e. compute the
+    /// integral of `W(|x - center|, h)` over the cell.
+    ///
+    /// The cell is decomposed into signed cones joining `center` with the (fan triangulated) faces of the cell, i.e.
+    /// tetrahedra in 3D and triangles in 2D. Along every ray from the center, the kernel is integrated exactly, the
+    /// remaining integral over the angle of every triangle is computed with adaptive quadrature up to an absolute error
+    /// of about `tolerance` for the whole cell (the kernel integrates to 1 over its support).
+    ///
+    /// This cell must be the cell of `voronoi` with index `cell_idx`. For periodic tesselations, `center` is first
+    /// moved to its periodic image nearest to the generator of this cell, the other periodic images of the kernel are
+    /// not taken into account. The unused coordinates of `center` are ignored for 1D and 2D tesselations.
+    pub fn kernel_integral(
+        &self,
+        voronoi: &Voronoi,
+        cell_idx: usize,
+        kernel: Kernel,
+        center: DVec3,
+        h: f64,
+        tolerance: f64,
+    ) -> f64 {
+        if self.volume() == 0. || h <= 0. {
+            return 0.;
+        }
+        let center = self.nearest_image(voronoi, center);
+        // Avoid round-off errors of the signed decomposition for cells outside the support
+        if self.signed_distance(voronoi, center) >= h {
+            return 0.;
+        }
+        let dimensionality = voronoi.dimensionality;
+        let norm = kernel.norm(dimensionality);
+        let breakpoints = kernel
+            .pieces(dimensionality)
+            .iter()
+            .map(|&(end, _)| end * h)
+            .collect::<Vec<_>>();
+        let tolerance = tolerance / (norm * self.face_count().max(1) as f64);
+        // The faces with their outward orientation and the center in their frame
+        let faces = self
+            .oriented_faces(voronoi, cell_idx)
+            .map(|(face, sign, offset)| (face, sign, center + offset));
+        match dimensionality {
+            Dimensionality::Dimensionality1D => {
+                let (lower, upper) = faces.fold(
+                    (f64::INFINITY, f64::NEG_INFINITY),
+                    |(lower, upper), (face, _, _)| {
+                        (lower.min(face.centroid().x), upper.max(face.centroid().x))
+                    },
+                );
+                let primitive =
+                    |x: f64| x.signum() * kernel.moment(dimensionality, 1, (x / h).abs());
+                norm * (primitive(upper - center.x) - primitive(lower - center.x))
+            }
+            Dimensionality::Dimensionality2D => {
+                // Triangles joining the center with the edges (counterclockwise around the cell)
+                let integral = faces
+                    .filter_map(|(face, sign, center)| {
+                        let (a, b) = edge_endpoints(face.vertices(voronoi), sign * face.normal())?;
+                        let (a, b) = (a.truncate().extend(center.z), b.truncate().extend(center.z));
+                        Some(polar_integral(
+                            center,
+                            a,
+                            b,
+                            DVec3::Z,
+                            &breakpoints,
+                            &|s| kernel.moment(dimensionality, 2, s / h),
+                            tolerance,
+                        ))
+                    })
+                    .sum::<f64>();
+                norm * integral
+            }
+            Dimensionality::Dimensionality3D => {
+                // Cones joining the center with the faces, integrated in the planes of the faces
+                let integral = faces
+                    .map(|(face, sign, center)| {
+                        let normal = face.normal();
+                        let vertices = face.vertices(voronoi);
+                        let Some(&v0) = vertices.first() else {
+                            return 0.;
+                        };
+                        let height = normal.dot(v0 - center);
+                        if height == 0. {
+                            return 0.;
+                        }
+                        // The breakpoints of the kernel in the distance to the center, in the plane of the face
+                        let foot = center + height * normal;
+                        let plane_breakpoints = breakpoints
+                            .iter()
+                            .filter(|&&r| r > height.abs())
+                            .map(|&r| (r * r - height * height).sqrt())
+                            .collect::<Vec<_>>();
+                        let base = kernel.cone_potential(height.abs() / h);
+                        let g = |s: f64| {
+                            kernel.cone_potential((height * height + s * s).sqrt() / h) - base
+                        };
+                        let n = vertices.len();
+                        let tolerance = tolerance * h / (height.abs() * n as f64);
+                        let face_integral = (0..n)
+                            .map(|i| {
+                                polar_integral(
+                                    foot,
+                                    vertices[i],
+                                    vertices[(i + 1) % n],
+                                    normal,
+                                    &plane_breakpoints,
+                                    &g,
+                                    tolerance,
+                                )
+                            })
+                            .sum::<f64>();
+                        sign * height / h * face_integral
+                    })
+                    .sum::<f64>();
+                norm * integral
+            }
+        }
+    }
+}
+
+impl Voronoi {
+    /// Integrate the smoothing `kernel` with support radius `h` centered at `center` over the cells it overlaps, see
+    /// [`VoronoiCell::kernel_integral`].
+    ///
+    /// The overlapping cells are found by walking the tesselation from the cell containing `center`. Cells that were
+    /// not constructed (in a partial tesselation) stop the walk. Returns the indices of the overlapping cells with their
+    /// (nonzero) integrals, which sum to 1 for kernels that lie entirely within the (constructed part of the) simulation
+    /// volume.
+    pub fn kernel_integrals(
+        &self,
+        kernel: Kernel,
+        center: DVec3,
+        h: f64,
+        tolerance: f64,
+    ) -> Vec<(usize, f64)> {
+        let start = self.cells_at(&[center])[0];
+        let mut visited = vec![false; self.cells.len()];
+        visited[start] = true;
+        let mut queue = VecDeque::from([start]);
+        let mut integrals = vec![];
+        while let Some(cell_idx) = queue.pop_front() {
+            let cell = &self.cells[cell_idx];
+            if cell.volume() == 0.
+                || cell.signed_distance(self, cell.nearest_image(self, center)) >= h
+            {
+                continue;
+            }
+            let integral = cell.kernel_integral(self, cell_idx, kernel, center, h, tolerance);
+            if integral > 0. {
+                integrals.push((cell_idx, integral));
+            }
+            for neighbour in cell.neighbour_ids(self, cell_idx).flatten() {
+                if !visited[neighbour] {
+                    visited[neighbour] = true;
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+        integrals.sort_unstable_by_key(|&(cell_idx, _)| cell_idx);
+        integrals
+    }
+}
+
+/// Integrate `g(S(phi))` over the polar angle `phi` of the triangle joining `origin` with the edge from `a` to `b` (in
+/// the plane through `origin` with the unit `normal`), where `S(phi)` is the distance from `origin` to the edge in the
+/// direction `phi`. The integral is positive if the triangle is oriented counterclockwise around `normal`.
+///
+/// The integration interval is split at the angles where `S` crosses one of the `breakpoints` (the kinks of `g`), and
+/// every part is integrated with adaptive Simpson quadrature.
+fn polar_integral(
+    origin: DVec3,
+    a: DVec3,
+    b: DVec3,
+    normal: DVec3,
+    breakpoints: &[f64],
+    g: &impl Fn(f64) -> f64,
+    tolerance: f64,
+) -> f64 {
+    let (a, b) = (a - origin, b - origin);
+    let edge = b - a;
+    let length_2 = edge.length_squared();
+    if length_2 == 0. {
+        return 0.;
+    }
+    // Angles are measured from the perpendicular from the origin to the edge
+    let perpendicular = a - a.dot(edge) / length_2 * edge;
+    let q = perpendicular.length();
+    if q <= 1e-14 * length_2.sqrt() {
+        return 0.;
+    }
+    let x_axis = perpendicular / q;
+    let y_axis = normal.cross(x_axis);
+    let angle = |p: DVec3| p.dot(y_axis).atan2(p.dot(x_axis));
+    let (phi_a, phi_b) = (angle(a), angle(b));
+    let (sign, lower, upper) = if phi_a <= phi_b {
+        (1., phi_a, phi_b)
+    } else {
+        (-1., phi_b, phi_a)
+    };
+
+    let mut points = vec![lower, upper];
+    for &r in breakpoints.iter().filter(|&&r| r > q) {
+        let phi = (q / r).acos();
+        points.extend(
+            [-phi, phi]
+                .into_iter()
+                .filter(|&phi| phi > lower && phi < upper),
+        );
+    }
+    points.sort_unstable_by(f64::total_cmp);
+    let f = |phi: f64| g(q / phi.cos());
+    let tolerance = tolerance / (points.len() - 1) as f64;
+    sign * points
+        .windows(2)
+        .map(|w| {
+            let (fa, fm, fb) = (f(w[0]), f(0.5 * (w[0] + w[1])), f(w[1]));
+            adaptive_simpson(
+                &f,
+                w[0],
+                w[1],
+                fa,
+                fm,
+                fb,
+                simpson(w[0], w[1], fa, fm, fb),
+                tolerance,
+                0,
+            )
+        })
+        .sum::<f64>()
+}
+
+/// Simpson's rule on `[a, b]` with the values `fa`, `fm` and `fb` at the endpoints and midpoint.
+fn simpson(a: f64, b: f64, fa: f64, fm: f64, fb: f64) -> f64 {
+    (b - a) / 6. * (fa + 4. * fm + fb)
+}
+
+/// Integrate `f` on `[a, b]` with adaptive Simpson quadrature, given the values at the endpoints and midpoint and the
+/// Simpson estimate `whole` of the integral.
+#[allow(clippy::too_many_arguments)]
+fn adaptive_simpson(
+    f: &impl Fn(f64) -> f64,
+    a: f64,
+    b: f64,
+    fa: f64,
+    fm: f64,
+    fb: f64,
+    whole: f64,
+    tolerance: f64,
+    depth: usize,
+) -> f64 {
+    let m = 0.5 * (a + b);
+    let (flm, frm) = (f(0.5 * (a + m)), f(0.5 * (m + b)));
+    let left = simpson(a, m, fa, flm, fm);
+    let right = simpson(m, b, fm, frm, fb);
+    let delta = left + right - whole;
+    if depth >= MAX_DEPTH || delta.abs() <= 15. * tolerance {
+        return left + right + delta / 15.;
+    }
+    adaptive_simpson(f, a, m, fa, flm, fm, left, 0.5 * tolerance, depth + 1)
+        + adaptive_simpson(f, m, b, fm, frm, fb, right, 0.5 * tolerance, depth + 1)
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+    use crate::voronoi::test::{perturbed_grid, perturbed_plane};
+
+    const KERNELS: [Kernel; 3] = [Kernel::CubicSpline, Kernel::WendlandC2, Kernel::WendlandC4];
+
+    #[test]
+    fn test_kernel_normalization() {
+        // Midpoint rule in spherical shells
+        let n = 100_000;
+        let h = 2.;
+        for kernel in KERNELS {
+            for (dimensionality, shell) in [(1, 2.), (2, 2. * PI), (3, 4. * PI)] {
+                let integral = (0..n)
+                    .map(|i| {
+                        let r = (i as f64 + 0.5) / n as f64 * h;
+                        shell
+                            * r.powi(dimensionality as i32 - 1)
+                            * kernel.evaluate(r, h, dimensionality)
+                            * h
+                            / n as f64
+                    })
+                    .sum::<f64>();
+                assert_approx_eq!(f64, integral, 1., epsilon = 1e-8);
+            }
+            assert_eq!(kernel.evaluate(h, h, 3), 0.);
+        }
+    }
+
+    #[test]
+    fn test_kernel_integrals() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let tolerance = 1e-8;
+        for (generators, dimensionality) in [
+            (perturbed_grid(anchor, width, 4, 0.5), 3),
+            (perturbed_plane(anchor, width, 6, 0.5), 2),
+            (
+                (0..10)
+                    .map(|i| DVec3::new((i as f64 + 0.5) / 10., 0., 0.))
+                    .collect(),
+                1,
+            ),
+        ] {
+            for periodic in [false, true] {
+                let voronoi =
+                    Voronoi::build(&generators, anchor, width, dimensionality, periodic, None);
+                // Periodic kernels may wrap around the simulation volume
+                let center = if periodic {
+                    DVec3::new(0.05, 0.9, 0.5)
+                } else {
+                    DVec3::new(0.45, 0.5, 0.55)
+                };
+                for kernel in KERNELS {
+                    let h = 0.35;
+                    let integrals = voronoi.kernel_integrals(kernel, center, h, tolerance);
+                    let total = integrals.iter().map(|&(_, integral)| integral).sum::<f64>();
+                    assert_approx_eq!(f64, total, 1., epsilon = 1e-6);
+                    // All overlapping cells are found
+                    for (cell_idx, cell) in voronoi.cells().iter().enumerate() {
+                        let integral =
+                            cell.kernel_integral(&voronoi, cell_idx, kernel, center, h, tolerance);
+                        match integrals.iter().find(|&&(idx, _)| idx == cell_idx) {
+                            Some(&(_, expected)) => assert_eq!(integral, expected),
+                            None => assert!(integral.abs() <= tolerance),
+                        }
+                    }
+                }
+            }
+        }
+
+        // A kernel centered on the corner of the simulation volume has a quarter of its mass inside it
+        let generators = perturbed_plane(anchor, width, 6, 0.5);
+        let voronoi = Voronoi::build(&generators, anchor, width, 2, false, None);
+        let total = voronoi
+            .kernel_integrals(Kernel::WendlandC2, DVec3::ZERO, 0.5, tolerance)
+            .iter()
+            .map(|&(_, integral)| integral)
+            .sum::<f64>();
+        assert_approx_eq!(f64, total, 0.25, epsilon = 1e-6);
+    }
+}
@@ -0,0 +1,73 @@
+use sprs::{CsMat, TriMat};
+
+use super::Voronoi;
+
+impl Voronoi {
+    /// Assemble the finite volume Laplacian of this tesselation as a sparse CSR matrix over the cells.
+    /// Requires the `sprs` feature to be enabled.
+    ///
+    /// The off-diagonal entry `(i, j)` is the sum of `A_ij / d_ij` over all faces between cells `i` and `j`, with
+    /// `A_ij` the face area and `d_ij` the distance between both generators (taking into account the periodic shift).
+    /// The diagonal entries are minus the sum of the off-diagonal entries of their row, such that `(L * phi)_i`
+    /// approximates the integral of the Laplacian of `phi` over cell `i`. Divide row `i` by the volume of cell `i`
+    /// to obtain the average Laplacian instead. Boundary faces do not contribute (zero flux boundary conditions).
+    pub fn laplacian(&self) -> CsMat<f64> {
+        let n = self.cells.len();
+        let mut triplets = TriMat::new((n, n));
+        for (cell_idx, cell) in self.cells.iter().enumerate() {
+            for face in cell.faces(self) {
+                let Some(right) = face.right() else {
+                    continue;
+                };
+                // Periodic faces are stored for both of their cells (as left cell)
+                let neighbour = if face.left() == cell_idx {
+                    right
+                } else {
+                    face.left()
+                };
+                if neighbour == cell_idx {
+                    // Face between a cell and its own periodic image
+                    continue;
+                }
+                let distance = face
+                    .generator_distance(self)
+                    .expect("Face between two cells must have a generator distance!");
+                let weight = face.area() / distance;
+                triplets.add_triplet(cell_idx, neighbour, weight);
+                triplets.add_triplet(cell_idx, cell_idx, -weight);
+            }
+        }
+        // Duplicate entries are summed
+        triplets.to_csr()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+    use glam::DVec3;
+
+    use super::*;
+    use crate::voronoi::test::perturbed_grid;
+
+    #[test]
+    fn test_laplacian() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        for periodic in [false, true] {
+            let voronoi = Voronoi::build(&generators, anchor, width, 3, periodic, None);
+            let laplacian = voronoi.laplacian();
+            assert_eq!(laplacian.shape(), (generators.len(), generators.len()));
+
+            // Symmetric with zero row sums
+            for (i, row) in laplacian.outer_iterator().enumerate() {
+                assert_approx_eq!(f64, row.data().iter().sum::<f64>(), 0., epsilon = 1e-10);
+                for (j, &value) in row.iter() {
+                    let transposed = *laplacian.get(j, i).expect("Laplacian must be symmetric!");
+                    assert_approx_eq!(f64, value, transposed, epsilon = 1e-10);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use glam::DVec3;
+
+use super::{Dimensionality, Generator};
+
+/// The generators binned into the cells of a regular grid matching a (perturbed) lattice, see
+/// [`crate::NeighbourSearch::Lattice`].
+pub(super) struct LatticeBins {
+    anchor: DVec3,
+    bin_width: DVec3,
+    shape: [usize; 3],
+    used: [bool; 3],
+    offsets: Vec<usize>,
+    generators: Vec<usize>,
+    periodic: Option<DVec3>,
+}
+
+impl LatticeBins {
+    /// The number of neighbouring bins (along every used dimension) searched for candidates around the bin of a
+    /// generator. For a perturbed lattice with one generator per bin, the cells are (almost always) complete within
+    /// this stencil.
+    const REACH: i64 = 2;
+
+    /// Bin the generators into a grid with the given `shape` (along the used dimensions) covering the simulation
+    /// volume. If no `shape` is given, it is estimated from the number of generators, and `None` is returned if the
+    /// generators do not form a (perturbed) lattice, i.e. if not every bin contains exactly one generator.
+    pub(super) fn new(
+        generators: &[Generator],
+        shape: Option<[usize; 3]>,
+        anchor: DVec3,
+        width: DVec3,
+        periodic: bool,
+        dimensionality: Dimensionality,
+    ) -> Option<Self> {
+        let used = match dimensionality {
+            Dimensionality::Dimensionality1D => [true, false, false],
+            Dimensionality::Dimensionality2D => [true, true, false],
+            Dimensionality::Dimensionality3D => [true, true, true],
+        };
+        let detect = shape.is_none();
+        let shape = match shape {
+            Some(shape) => shape,
+            None => {
+                // The shape of a lattice with cubic cells with the same number of generators
+                let dimension = usize::from(dimensionality) as i32;
+                let volume = (0..3)
+                    .filter(|&i| used[i])
+                    .map(|i| width[i])
+                    .product::<f64>();
+                let spacing = (volume / generators.len().max(1) as f64).powf(1. / dimension as f64);
+                [0, 1, 2].map(|i| (width[i] / spacing).round().max(1.) as usize)
+            }
+        };
+        let shape = [0, 1, 2].map(|i| if used[i] { shape[i].max(1) } else { 1 });
+        let bin_width = width / DVec3::from_array(shape.map(|n| n as f64));
+
+        let bin_count = shape.iter().product::<usize>();
+        if detect && bin_count != generators.len() {
+            return None;
+        }
+        let bins = generators
+            .iter()
+            .map(|g| {
+                let index = ((g.loc() - anchor) / bin_width).floor();
+                let index = [0, 1, 2].map(|i| (index[i].max(0.) as usize).min(shape[i] - 1));
+                (index[0] * shape[1] + index[1]) * shape[2] + index[2]
+            })
+            .collect::<Vec<_>>();
+        let mut offsets = vec![0; bin_count + 1];
+        for &bin in bins.iter() {
+            offsets[bin + 1] += 1;
+        }
+        if detect && offsets[1..].iter().any(|&count| count != 1) {
+            return None;
+        }
+        for i in 0..bin_count {
+            offsets[i + 1] += offsets[i];
+        }
+        let mut fill = offsets.clone();
+        let mut binned = vec![0; generators.len()];
+        for (idx, &bin) in bins.iter().enumerate() {
+            binned[fill[bin]] = idx;
+            fill[bin] += 1;
+        }
+
+        Some(Self {
+            anchor,
+            bin_width,
+            shape,
+            used,
+            offsets,
+            generators: binned,
+            periodic: periodic.then_some(width),
+        })
+    }
+
+    /// Get the candidate neighbours of a generator at `loc` in the stencil of bins around its own bin, as sorted
+    /// `(idx, shift, distance)` triplets (including the generator itself), together with the distance up to which
+    /// the candidates contain all neighbours.
+    pub(super) fn candidates(
+        &self,
+        generators: &[Generator],
+        loc: DVec3,
+    ) -> (Vec<(usize, Option<DVec3>, f64)>, f64) {
+        let index = ((loc - self.anchor) / self.bin_width).floor();
+        let index = [0, 1, 2].map(|i| (index[i].max(0.) as i64).min(self.shape[i] as i64 - 1));
+        let mut bound = f64::INFINITY;
+        let mut ranges = [0, 1, 2].map(|_| 0..=0);
+        for i in (0..3).filter(|&i| self.used[i]) {
+            let (mut start, mut end) = (index[i] - Self::REACH, index[i] + Self::REACH);
+            if self.periodic.is_none() {
+                start = start.max(0);
+                end = end.min(self.shape[i] as i64 - 1);
+            }
+            // Only the sides of the stencil within the simulation volume limit the bound
+            if self.periodic.is_some() || start > 0 {
+                bound = bound.min(loc[i] - (self.anchor[i] + start as f64 * self.bin_width[i]));
+            }
+            if self.periodic.is_some() || end < self.shape[i] as i64 - 1 {
+                bound = bound.min(self.anchor[i] + (end + 1) as f64 * self.bin_width[i] - loc[i]);
+            }
+            ranges[i] = start..=end;
+        }
+
+        let mut candidates = vec![];
+        for i in ranges[0].clone() {
+            for j in ranges[1].clone() {
+                for k in ranges[2].clone() {
+                    let (bin, shift) = self.wrap([i, j, k]);
+                    for &idx in &self.generators[self.offsets[bin]..self.offsets[bin + 1]] {
+                        let image = generators[idx].loc() + shift.unwrap_or(DVec3::ZERO);
+                        candidates.push((idx, shift, loc.distance(image)));
+                    }
+                }
+            }
+        }
+        candidates.sort_by(|a, b| a.2.partial_cmp(&b.2).expect("NaN distance encountered!"));
+        (candidates, bound)
+    }
+
+    /// Get the flat index of the bin with the given (possibly out of range) index, and the shift of the periodic image
+    /// of that bin.
+    fn wrap(&self, index: [i64; 3]) -> (usize, Option<DVec3>) {
+        let mut shift = DVec3::ZERO;
+        let index = [0, 1, 2].map(|i| {
+            let n = self.shape[i] as i64;
+            let wraps = index[i].div_euclid(n);
+            if let Some(width) = self.periodic {
+                shift[i] = wraps as f64 * width[i];
+            }
+            index[i].rem_euclid(n) as usize
+        });
+        (
+            (index[0] * self.shape[1] + index[1]) * self.shape[2] + index[2],
+            (shift != DVec3::ZERO).then_some(shift),
+        )
+    }
+}
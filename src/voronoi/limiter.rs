@@ -0,0 +1,156 @@
+use glam::DVec3;
+
+use super::Voronoi;
+
+/// Precomputed per-cell geometry for slope limiters, see [`Voronoi::limiter_geometry`].
+///
+/// For every cell, stores the offsets from its centroid to the centroids of its faces (the points at which a linear
+/// reconstruction is extrapolated) and the minimal and maximal extrapolation distances.
+#[derive(Default, Debug, Clone)]
+pub struct LimiterGeometry {
+    offsets: Vec<usize>,
+    faces: Vec<usize>,
+    face_offsets: Vec<DVec3>,
+    min_distances: Vec<f64>,
+    max_distances: Vec<f64>,
+}
+
+impl LimiterGeometry {
+    /// Get an `Iterator` over the `(face_idx, offset)` pairs of the cell with index `cell_idx`, where `offset` is the
+    /// vector from the centroid of the cell to the centroid of the face (in the frame of the cell for periodic faces).
+    pub fn cell_face_offsets(&self, cell_idx: usize) -> impl Iterator<Item = (usize, DVec3)> + '_ {
+        let range = self.offsets[cell_idx]..self.offsets[cell_idx + 1];
+        self.faces[range.clone()]
+            .iter()
+            .copied()
+            .zip(self.face_offsets[range].iter().copied())
+    }
+
+    /// Get the minimal distance from the centroid of every cell to the centroids of its faces (zero for cells without
+    /// faces).
+    pub fn min_distances(&self) -> &[f64] {
+        &self.min_distances
+    }
+
+    /// Get the maximal distance from the centroid of every cell to the centroids of its faces (zero for cells without
+    /// faces).
+    pub fn max_distances(&self) -> &[f64] {
+        &self.max_distances
+    }
+
+    /// Get the minimal and maximal extrapolated differences `gradient . offset` over the faces of every cell, for the
+    /// given per-cell `gradients` (e.g. from [`crate::GradientWeights::gradients`]).
+    ///
+    /// Comparing these with the range of the values of the neighbouring cells gives the limiting factors of e.g. the
+    /// Barth-Jespersen limiter.
+    pub fn extrapolation_ranges(&self, gradients: &[DVec3]) -> Vec<(f64, f64)> {
+        assert_eq!(
+            gradients.len() + 1,
+            self.offsets.len(),
+            "Must have one gradient per cell!"
+        );
+        gradients
+            .iter()
+            .enumerate()
+            .map(|(cell_idx, gradient)| {
+                self.cell_face_offsets(cell_idx)
+                    .map(|(_, offset)| gradient.dot(offset))
+                    .fold((0f64, 0f64), |(min, max), d| (min.min(d), max.max(d)))
+            })
+            .collect()
+    }
+}
+
+impl Voronoi {
+    /// Precompute the per-cell geometry needed for slope limiters in second order finite volume schemes: the offsets
+    /// from the cell centroids to the face centroids and the minimal and maximal extrapolation distances.
+    ///
+    /// All faces of a cell are included, also the faces on the boundary of the simulation volume.
+    pub fn limiter_geometry(&self) -> LimiterGeometry {
+        let mut offsets = vec![0];
+        let mut faces = vec![];
+        let mut face_offsets = vec![];
+        let mut min_distances = vec![];
+        let mut max_distances = vec![];
+
+        for cell in self.cells.iter() {
+            let (mut min, mut max) = (f64::INFINITY, 0f64);
+            for (&face_idx, face) in cell.face_indices(self).iter().zip(cell.faces(self)) {
+                let offset = face.centroid() - cell.centroid();
+                let distance = offset.length();
+                min = min.min(distance);
+                max = max.max(distance);
+                faces.push(face_idx);
+                face_offsets.push(offset);
+            }
+            offsets.push(faces.len());
+            min_distances.push(if min.is_finite() { min } else { 0. });
+            max_distances.push(max);
+        }
+
+        LimiterGeometry {
+            offsets,
+            faces,
+            face_offsets,
+            min_distances,
+            max_distances,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+    use crate::voronoi::test::perturbed_grid;
+
+    #[test]
+    fn test_limiter_geometry() {
+        // A single cube
+        let voronoi = Voronoi::build(
+            &[DVec3::new(0.3, 0.6, 0.5)],
+            DVec3::ZERO,
+            DVec3::ONE,
+            3,
+            false,
+            None,
+        );
+        let geometry = voronoi.limiter_geometry();
+        assert_approx_eq!(f64, geometry.min_distances()[0], 0.5, epsilon = 1e-12);
+        assert_approx_eq!(f64, geometry.max_distances()[0], 0.5, epsilon = 1e-12);
+        assert_eq!(geometry.cell_face_offsets(0).count(), 6);
+        let ranges = geometry.extrapolation_ranges(&[DVec3::new(2., 0., 0.)]);
+        assert_approx_eq!(f64, ranges[0].0, -1., epsilon = 1e-12);
+        assert_approx_eq!(f64, ranges[0].1, 1., epsilon = 1e-12);
+
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        for periodic in [false, true] {
+            let voronoi = Voronoi::build(&generators, anchor, width, 3, periodic, None);
+            let geometry = voronoi.limiter_geometry();
+            for (cell_idx, cell) in voronoi.cells().iter().enumerate() {
+                assert_eq!(
+                    geometry.cell_face_offsets(cell_idx).count(),
+                    cell.face_count()
+                );
+                // Divergence theorem: the integral of (x - c) n_x over the boundary of a cell is V e_x
+                let mut weighted_sum = DVec3::ZERO;
+                for (face_idx, offset) in geometry.cell_face_offsets(cell_idx) {
+                    let face = &voronoi.faces()[face_idx];
+                    let normal = if face.left() == cell_idx {
+                        face.normal()
+                    } else {
+                        -face.normal()
+                    };
+                    weighted_sum += face.area() * normal.dot(DVec3::X) * offset;
+                    let distance = offset.length();
+                    assert!(distance >= geometry.min_distances()[cell_idx]);
+                    assert!(distance <= geometry.max_distances()[cell_idx]);
+                }
+                assert!(weighted_sum.distance(cell.volume() * DVec3::X) < 1e-10);
+            }
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use glam::DVec3;
+
+use super::Voronoi;
+
+impl Voronoi {
+    /// Compute the velocities of the faces of this tesselation, given the velocities `w` of the generators.
+    ///
+    /// The velocity of a face is the mean velocity of its two generators, corrected for the rotation of the face
+    /// due to the offset of its centroid from the midpoint between both generators (Springel 2010, eq. 33).
+    /// The periodic shift is taken into account for the positions of the right generators.
+    /// Boundary faces belong to the static simulation volume and get zero velocity.
+    pub fn face_velocities(&self, w: &[DVec3]) -> Vec<DVec3> {
+        assert_eq!(
+            w.len(),
+            self.cells.len(),
+            "Must have one velocity per generator!"
+        );
+
+        self.faces
+            .iter()
+            .map(|face| {
+                let Some(right) = face.right() else {
+                    return DVec3::ZERO;
+                };
+                let left = face.left();
+                let left_loc = self.cells[left].loc();
+                let right_loc = self.cells[right].loc() + face.shift().unwrap_or(DVec3::ZERO);
+                let dx = right_loc - left_loc;
+                let midpoint = 0.5 * (left_loc + right_loc);
+                let correction =
+                    (w[left] - w[right]).dot(face.centroid() - midpoint) / dx.length_squared() * dx;
+                0.5 * (w[left] + w[right]) + correction
+            })
+            .collect()
+    }
+
+    /// Compute the mesh regularization ("steering") displacements of the generators towards the centroids of their
+    /// cells, following the scheme of Vogelsberger et al. (2012).
+    ///
+    /// Let `d` be the offset from the generator to the centroid of its cell and `R` the characteristic radius of
+    /// the cell. The displacement is `0` for `|d| < 0.9 eta R`, `d` for `|d| > 1.1 eta R` and ramps up linearly in
+    /// between. Finally, the displacements are clipped to a length of at most `max_fraction * R`.
+    /// Multiply the result by a characteristic speed (e.g. the sound speed) divided by `|d|` to obtain the steering
+    /// velocities of the original scheme instead. Cells that were not constructed get zero displacement.
+    pub fn steering_displacements(&self, eta: f64, max_fraction: f64) -> Vec<DVec3> {
+        self.cells
+            .iter()
+            .map(|cell| {
+                let radius = cell.characteristic_radius(self);
+                if cell.volume() == 0. || radius == 0. {
+                    return DVec3::ZERO;
+                }
+                let d = cell.centroid() - cell.loc();
+                if d == DVec3::ZERO {
+                    return d;
+                }
+                let x = d.length() / (eta * radius);
+                let factor = ((x - 0.9) / 0.2).clamp(0., 1.);
+                (factor * d).clamp_length_max(max_fraction * radius)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+    use crate::voronoi::test::perturbed_grid;
+
+    #[test]
+    fn test_face_velocities() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        let voronoi = Voronoi::build(&generators, anchor, width, 3, true, None);
+
+        // A uniform translation moves all faces with the same velocity
+        let velocity = DVec3::new(1., -2., 0.5);
+        let w = vec![velocity; generators.len()];
+        for face_velocity in voronoi.face_velocities(&w) {
+            assert!(face_velocity.distance(velocity) < 1e-12);
+        }
+
+        // For a uniform expansion w = x, the normal velocity of an interior face equals the normal component of its
+        // centroid (the face plane is scaled with the generators).
+        let voronoi = Voronoi::build(&generators, anchor, width, 3, false, None);
+        let face_velocities = voronoi.face_velocities(&generators);
+        for (face, face_velocity) in voronoi.faces().iter().zip(face_velocities) {
+            if face.right().is_none() {
+                assert_eq!(face_velocity, DVec3::ZERO);
+                continue;
+            }
+            assert_approx_eq!(
+                f64,
+                face_velocity.dot(face.normal()),
+                face.centroid().dot(face.normal()),
+                epsilon = 1e-10
+            );
+        }
+
+        // For a rotation and a shear, the face planes rotate. The normal velocity at the centroid of a face must match
+        // the motion of the bisector of the generators, moved along their velocities (central finite differences).
+        let rotation = |x: DVec3| DVec3::new(0.3, -1., 0.7).cross(x - DVec3::splat(0.5));
+        let shear = |x: DVec3| DVec3::new(x.y, 0., 0.);
+        for field in [&rotation as &dyn Fn(DVec3) -> DVec3, &shear] {
+            let w = generators.iter().map(|&x| field(x)).collect::<Vec<_>>();
+            let face_velocities = voronoi.face_velocities(&w);
+            for (face, face_velocity) in voronoi.faces().iter().zip(face_velocities) {
+                let Some(right) = face.right() else {
+                    continue;
+                };
+                let left = face.left();
+                // The signed distance of the centroid to the bisector at time `t`
+                let distance = |t: f64| {
+                    let left_loc = generators[left] + t * w[left];
+                    let right_loc = generators[right] + t * w[right];
+                    let normal = (right_loc - left_loc).normalize();
+                    normal.dot(face.centroid() - 0.5 * (left_loc + right_loc))
+                };
+                let dt = 1e-6;
+                let expected = (distance(-dt) - distance(dt)) / (2. * dt);
+                assert_approx_eq!(
+                    f64,
+                    face_velocity.dot(face.normal()),
+                    expected,
+                    epsilon = 1e-6
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_steering_displacements() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        let voronoi = Voronoi::build(&generators, anchor, width, 3, false, None);
+
+        // Without threshold, the generators are moved to the centroids (up to the clipping)
+        let displacements = voronoi.steering_displacements(0., 1.);
+        for (cell, displacement) in voronoi.cells().iter().zip(displacements) {
+            let d = cell.centroid() - cell.loc();
+            assert!(displacement.length() <= cell.characteristic_radius(&voronoi) + 1e-12);
+            if d.length() <= cell.characteristic_radius(&voronoi) {
+                assert!(displacement.distance(d) < 1e-12);
+            }
+        }
+
+        // With a large threshold, no generator is moved
+        let displacements = voronoi.steering_displacements(100., 1.);
+        assert!(displacements.iter().all(|&d| d == DVec3::ZERO));
+    }
+}
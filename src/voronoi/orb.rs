@@ -0,0 +1,265 @@
+use glam::DVec3;
+
+use crate::mask;
+
+/// An orthogonal recursive bisection (ORB) of a set of generators over a number of ranks, balancing the total cost of
+/// the generators per rank.
+///
+/// Every rank owns an axis aligned slab of the simulation volume containing its generators. The mask of a rank (see
+/// [`OrbDecomposition::mask`]) can be passed directly to [`crate::Voronoi::build_partial`] to construct the cells of
+/// that rank.
+#[derive(Debug, Clone)]
+pub struct OrbDecomposition {
+    ranks: Vec<usize>,
+    slabs: Vec<(DVec3, DVec3)>,
+    costs: Vec<f64>,
+}
+
+impl OrbDecomposition {
+    /// Decompose the simulation volume with the given `anchor` and `width` (containing the `generators`) over
+    /// `n_ranks` ranks.
+    ///
+    /// The volume is recursively split along the longest (used) axis of the current slab, such that the total cost of
+    /// the generators on both sides is proportional to the number of ranks on that side. The cost of every generator
+    /// is its (nonnegative) weight in `weights`, e.g. the [`crate::BuildProfile::cell_costs`] of a previous
+    /// construction, or 1 if no weights are given. Slabs without any cost are split by generator count instead.
+    ///
+    /// * `dimensionality` - The dimensionality of the tesselation, slabs are never split along the unused dimensions.
+    pub fn new(
+        generators: &[DVec3],
+        weights: Option<&[f64]>,
+        anchor: DVec3,
+        width: DVec3,
+        dimensionality: usize,
+        n_ranks: usize,
+    ) -> Self {
+        assert!(n_ranks > 0, "Must have at least one rank!");
+        if let Some(weights) = weights {
+            assert_eq!(
+                weights.len(),
+                generators.len(),
+                "Must have one weight per generator!"
+            );
+        }
+        assert!(
+            (1..=3).contains(&dimensionality),
+            "Invalid Voronoi dimensionality!"
+        );
+        let mut decomposition = Self {
+            ranks: vec![0; generators.len()],
+            slabs: vec![(anchor, width); n_ranks],
+            costs: vec![0.; n_ranks],
+        };
+        let weight = |idx: usize| weights.map_or(1., |weights| weights[idx]);
+        decomposition.bisect(
+            generators,
+            &weight,
+            dimensionality,
+            (0..generators.len()).collect(),
+            (anchor, width),
+            0,
+            n_ranks,
+        );
+        decomposition
+    }
+
+    /// Recursively split the slab with the given generators over `n_ranks` ranks starting at `first_rank`.
+    #[allow(clippy::too_many_arguments)]
+    fn bisect(
+        &mut self,
+        generators: &[DVec3],
+        weight: &impl Fn(usize) -> f64,
+        dimensionality: usize,
+        mut indices: Vec<usize>,
+        slab: (DVec3, DVec3),
+        first_rank: usize,
+        n_ranks: usize,
+    ) {
+        if n_ranks == 1 {
+            for &idx in indices.iter() {
+                self.ranks[idx] = first_rank;
+            }
+            self.slabs[first_rank] = slab;
+            self.costs[first_rank] = indices.iter().map(|&idx| weight(idx)).sum();
+            return;
+        }
+
+        let (anchor, width) = slab;
+        let axis = (0..dimensionality)
+            .max_by(|&a, &b| width[a].total_cmp(&width[b]))
+            .unwrap();
+        indices.sort_unstable_by(|&a, &b| generators[a][axis].total_cmp(&generators[b][axis]));
+
+        // Find the first generator of the right half
+        let left_ranks = n_ranks / 2;
+        let fraction = left_ranks as f64 / n_ranks as f64;
+        let total_cost = indices.iter().map(|&idx| weight(idx)).sum::<f64>();
+        let split = if total_cost > 0. {
+            let target = fraction * total_cost;
+            let mut cost = 0.;
+            let mut split = indices.len();
+            for (i, &idx) in indices.iter().enumerate() {
+                // Keep the generator on the side that best matches the target cost
+                if cost + 0.5 * weight(idx) > target {
+                    split = i;
+                    break;
+                }
+                cost += weight(idx);
+            }
+            split
+        } else {
+            (indices.len() * left_ranks + n_ranks / 2) / n_ranks
+        };
+
+        // Split the slab halfway between the last generator on the left and the first one on the right
+        let lower = anchor[axis];
+        let upper = anchor[axis] + width[axis];
+        let position = match (split.checked_sub(1), indices.get(split)) {
+            (Some(last), Some(&first)) => {
+                0.5 * (generators[indices[last]][axis] + generators[first][axis])
+            }
+            _ => lower + fraction * width[axis],
+        }
+        .clamp(lower, upper);
+        let mut left_width = width;
+        left_width[axis] = position - lower;
+        let mut right_anchor = anchor;
+        right_anchor[axis] = position;
+        let mut right_width = width;
+        right_width[axis] = upper - position;
+
+        let right = indices.split_off(split);
+        self.bisect(
+            generators,
+            weight,
+            dimensionality,
+            indices,
+            (anchor, left_width),
+            first_rank,
+            left_ranks,
+        );
+        self.bisect(
+            generators,
+            weight,
+            dimensionality,
+            right,
+            (right_anchor, right_width),
+            first_rank + left_ranks,
+            n_ranks - left_ranks,
+        );
+    }
+
+    /// Get the number of ranks.
+    pub fn n_ranks(&self) -> usize {
+        self.slabs.len()
+    }
+
+    /// Get the rank of every generator.
+    pub fn ranks(&self) -> &[usize] {
+        &self.ranks
+    }
+
+    /// Get the slab of the given rank as its anchor (lower left corner) and width. The slabs of all ranks tile the
+    /// simulation volume.
+    pub fn slab(&self, rank: usize) -> (DVec3, DVec3) {
+        self.slabs[rank]
+    }
+
+    /// Get the total cost of the generators of every rank.
+    pub fn costs(&self) -> &[f64] {
+        &self.costs
+    }
+
+    /// Get the ratio between the maximal and the average cost of the ranks (1 for a perfectly balanced
+    /// decomposition).
+    pub fn imbalance(&self) -> f64 {
+        let max = self.costs.iter().copied().fold(0., f64::max);
+        let average = self.costs.iter().sum::<f64>() / self.costs.len() as f64;
+        if average > 0. {
+            max / average
+        } else {
+            1.
+        }
+    }
+
+    /// Get the mask selecting the generators of the given rank, to be used with [`crate::Voronoi::build_partial`].
+    pub fn mask(&self, rank: usize) -> Vec<bool> {
+        mask::of_rank(&self.ranks, rank)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+    use crate::{
+        voronoi::test::{perturbed_grid, perturbed_plane},
+        Voronoi,
+    };
+
+    #[test]
+    fn test_orb() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        for (generators, dimensionality) in [
+            (perturbed_grid(anchor, width, 8, 0.5), 3),
+            (perturbed_plane(anchor, width, 20, 0.5), 2),
+        ] {
+            for n_ranks in [1, 3, 8] {
+                let orb = OrbDecomposition::new(
+                    &generators,
+                    None,
+                    anchor,
+                    width,
+                    dimensionality,
+                    n_ranks,
+                );
+                assert_eq!(orb.n_ranks(), n_ranks);
+                assert!(orb.imbalance() < 1. + 2. * n_ranks as f64 / generators.len() as f64);
+
+                // The slabs tile the simulation volume and contain their generators
+                let volume = (0..n_ranks)
+                    .map(|rank| {
+                        let (_, slab_width) = orb.slab(rank);
+                        slab_width.x * slab_width.y * slab_width.z
+                    })
+                    .sum::<f64>();
+                assert_approx_eq!(f64, volume, 1., epsilon = 1e-12);
+                for (&rank, &loc) in orb.ranks().iter().zip(generators.iter()) {
+                    let (slab_anchor, slab_width) = orb.slab(rank);
+                    assert!(loc.cmpge(slab_anchor).all());
+                    assert!(loc.cmple(slab_anchor + slab_width).all());
+                }
+            }
+        }
+
+        // Weighted by the cost of a previous construction
+        let generators = perturbed_grid(anchor, width, 8, 0.5);
+        let (voronoi, profile) =
+            Voronoi::build_profiled(&generators, None, anchor, width, 3, false, None);
+        let weights = profile
+            .cell_costs()
+            .into_iter()
+            .map(|cost| cost as f64)
+            .collect::<Vec<_>>();
+        let orb = OrbDecomposition::new(&generators, Some(&weights), anchor, width, 3, 4);
+        let max_weight = weights.iter().copied().fold(0., f64::max);
+        let average = weights.iter().sum::<f64>() / 4.;
+        assert!(orb.imbalance() <= 1. + 2. * max_weight / average);
+
+        // The partial tesselations of all ranks make up the full tesselation
+        let mut volume = 0.;
+        for rank in 0..orb.n_ranks() {
+            let mask = orb.mask(rank);
+            let partial = Voronoi::build_partial(&generators, &mask, anchor, width, 3, false, None);
+            for (i, cell) in partial.cells().iter().enumerate() {
+                if mask[i] {
+                    assert_approx_eq!(f64, cell.volume(), voronoi.cells()[i].volume());
+                    volume += cell.volume();
+                }
+            }
+        }
+        assert_approx_eq!(f64, volume, 1., epsilon = 1e-10);
+    }
+}
@@ -0,0 +1,242 @@
+use super::Voronoi;
+
+impl Voronoi {
+    /// Partition the cells of this tesselation into `n_parts` balanced, connected parts, while keeping the total area
+    /// of the faces between different parts (the edge cut) small.
+    ///
+    /// The parts are computed by recursive coordinate bisection of the generators: every bisection splits its cells
+    /// at the median coordinate along the axis for which the area of the faces between both halves is the smallest.
+    /// Afterwards, the pieces of a half that are not connected to the rest of it (through faces, including periodic
+    /// faces) are moved to the other half. Parts are balanced by cell count, up to these moved pieces.
+    ///
+    /// Returns the index of the part of every cell.
+    pub fn partition(&self, n_parts: usize) -> Vec<usize> {
+        assert!(n_parts > 0, "Must have at least one part!");
+        let mut neighbours = vec![vec![]; self.cells.len()];
+        for face in self.faces.iter() {
+            let Some(right) = face.right() else {
+                continue;
+            };
+            if right == face.left() {
+                continue;
+            }
+            neighbours[face.left()].push((right, face.area()));
+            if face.shift().is_none() {
+                neighbours[right].push((face.left(), face.area()));
+            }
+        }
+
+        let mut bisection = Bisection {
+            voronoi: self,
+            neighbours,
+            parts: vec![0; self.cells.len()],
+            visited: vec![false; self.cells.len()],
+        };
+        bisection.bisect((0..self.cells.len()).collect(), 0, n_parts);
+        bisection.parts
+    }
+}
+
+/// State of the recursive bisection of the cells, see [`Voronoi::partition`].
+struct Bisection<'a> {
+    voronoi: &'a Voronoi,
+    neighbours: Vec<Vec<(usize, f64)>>,
+    /// The part of every cell, or the first part of the subset it belongs to while that subset is being split.
+    parts: Vec<usize>,
+    visited: Vec<bool>,
+}
+
+impl Bisection<'_> {
+    /// Marks the cells of the left half of a bisection.
+    const LEFT: usize = usize::MAX;
+
+    /// Recursively split `cells` into `n_parts` parts with indices starting at `first_part`.
+    fn bisect(&mut self, mut cells: Vec<usize>, first_part: usize, n_parts: usize) {
+        for &cell in cells.iter() {
+            self.parts[cell] = first_part;
+        }
+        if n_parts == 1 {
+            return;
+        }
+        let left_parts = n_parts / 2;
+        let right_mark = first_part + left_parts;
+        let target = (cells.len() * left_parts + n_parts / 2) / n_parts;
+
+        // Split along the axis with the smallest cut
+        let mut best_axis = 0;
+        let mut best_cut = f64::INFINITY;
+        for axis in 0..usize::from(self.voronoi.dimensionality) {
+            self.split(&mut cells, axis, target, first_part);
+            let cut = self.cut(&cells[..target], first_part);
+            if cut < best_cut {
+                best_cut = cut;
+                best_axis = axis;
+            }
+        }
+        self.split(&mut cells, best_axis, target, first_part);
+        for &cell in cells[target..].iter() {
+            self.parts[cell] = right_mark;
+        }
+
+        // Move the pieces that are cut off from their half to the other half
+        self.connect(&cells[..target], Self::LEFT, right_mark);
+        let right = self.marked(&cells, right_mark);
+        self.connect(&right, right_mark, Self::LEFT);
+        let left = self.marked(&cells, Self::LEFT);
+        let right = self.marked(&cells, right_mark);
+
+        // The left half reuses the mark of this subset, so the right half must already be marked
+        self.bisect(left, first_part, left_parts);
+        self.bisect(right, right_mark, n_parts - left_parts);
+    }
+
+    /// Sort `cells` such that the first `target` cells have the smallest coordinates along `axis` and mark those cells
+    /// as the left half (and the others with `mark`).
+    fn split(&mut self, cells: &mut [usize], axis: usize, target: usize, mark: usize) {
+        let voronoi_cells = &self.voronoi.cells;
+        if target > 0 && target < cells.len() {
+            cells.select_nth_unstable_by(target, |&a, &b| {
+                voronoi_cells[a].loc()[axis].total_cmp(&voronoi_cells[b].loc()[axis])
+            });
+        }
+        for &cell in cells.iter() {
+            self.parts[cell] = mark;
+        }
+        for &cell in cells[..target].iter() {
+            self.parts[cell] = Self::LEFT;
+        }
+    }
+
+    /// The area of the faces between the given cells of the left half and the right half (marked with `mark`).
+    fn cut(&self, left: &[usize], mark: usize) -> f64 {
+        left.iter()
+            .flat_map(|&cell| self.neighbours[cell].iter())
+            .filter(|&&(neighbour, _)| self.parts[neighbour] == mark)
+            .map(|&(_, area)| area)
+            .sum()
+    }
+
+    /// Get the given cells marked with `mark`.
+    fn marked(&self, cells: &[usize], mark: usize) -> Vec<usize> {
+        cells
+            .iter()
+            .copied()
+            .filter(|&cell| self.parts[cell] == mark)
+            .collect()
+    }
+
+    /// Keep the largest connected component of the given `cells` (marked with `mark`) and remark the other components
+    /// touching cells marked with `other_mark` with that mark (components not connected to anything are kept).
+    fn connect(&mut self, cells: &[usize], mark: usize, other_mark: usize) {
+        for &cell in cells.iter() {
+            self.visited[cell] = false;
+        }
+        let mut components = vec![];
+        for &start in cells.iter() {
+            if self.visited[start] {
+                continue;
+            }
+            self.visited[start] = true;
+            let mut component = vec![start];
+            let mut touches_other = false;
+            let mut i = 0;
+            while i < component.len() {
+                for &(neighbour, _) in self.neighbours[component[i]].iter() {
+                    if self.parts[neighbour] == other_mark {
+                        touches_other = true;
+                    } else if self.parts[neighbour] == mark && !self.visited[neighbour] {
+                        self.visited[neighbour] = true;
+                        component.push(neighbour);
+                    }
+                }
+                i += 1;
+            }
+            components.push((component, touches_other));
+        }
+
+        let largest = (0..components.len()).max_by_key(|&i| components[i].0.len());
+        for (i, (component, touches_other)) in components.into_iter().enumerate() {
+            if Some(i) != largest && touches_other {
+                for cell in component {
+                    self.parts[cell] = other_mark;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use glam::DVec3;
+    use rand::prelude::*;
+
+    use super::*;
+    use crate::voronoi::test::{perturbed_grid, perturbed_plane};
+
+    fn edge_cut(voronoi: &Voronoi, parts: &[usize]) -> f64 {
+        voronoi
+            .faces()
+            .iter()
+            .filter(|face| {
+                face.right()
+                    .is_some_and(|right| parts[right] != parts[face.left()])
+            })
+            .map(|face| face.area())
+            .sum()
+    }
+
+    fn is_connected(voronoi: &Voronoi, cells: &[usize]) -> bool {
+        let mut visited = vec![cells[0]];
+        let mut i = 0;
+        while i < visited.len() {
+            for neighbour in voronoi.cells()[visited[i]]
+                .neighbour_ids(voronoi, visited[i])
+                .flatten()
+            {
+                if cells.contains(&neighbour) && !visited.contains(&neighbour) {
+                    visited.push(neighbour);
+                }
+            }
+            i += 1;
+        }
+        visited.len() == cells.len()
+    }
+
+    #[test]
+    fn test_partition() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        for (generators, dimensionality) in [
+            (perturbed_grid(anchor, width, 8, 0.5), 3),
+            (perturbed_plane(anchor, width, 20, 0.5), 2),
+        ] {
+            for periodic in [false, true] {
+                let voronoi =
+                    Voronoi::build(&generators, anchor, width, dimensionality, periodic, None);
+                assert!(voronoi.partition(1).iter().all(|&p| p == 0));
+                for n_parts in [2, 3, 8] {
+                    let parts = voronoi.partition(n_parts);
+                    let average = generators.len() as f64 / n_parts as f64;
+                    for part in 0..n_parts {
+                        let cells = (0..parts.len())
+                            .filter(|&i| parts[i] == part)
+                            .collect::<Vec<_>>();
+                        assert!(
+                            (cells.len() as f64 - average).abs() <= 0.1 * average,
+                            "{} {average}",
+                            cells.len()
+                        );
+                        assert!(is_connected(&voronoi, &cells));
+                    }
+
+                    // Much better than a random partition
+                    let mut rng = StdRng::seed_from_u64(4);
+                    let random = (0..parts.len())
+                        .map(|_| rng.gen_range(0..n_parts))
+                        .collect::<Vec<_>>();
+                    assert!(edge_cut(&voronoi, &parts) < 0.5 * edge_cut(&voronoi, &random));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use glam::DVec3;
+
+use super::{voronoi_cell::ConvexCell, Dimensionality, Voronoi};
+
+impl Voronoi {
+    /// Intersect the cell with the given index with a convex polytope and compute the volume and centroid of the
+    /// intersection.
+    ///
+    /// The polytope is the intersection of the half spaces `normal . (x - point) <= 0` for the given `(point, normal)`
+    /// pairs, i.e. the normals point outwards (as for [`Voronoi::fracture`]). For periodic tesselations, the cell is not
+    /// wrapped, i.e. the polytope must be given in the frame of the generator of the cell and its periodic images are
+    /// not taken into account. For 1D and 2D tesselations, the normals must lie in the used dimensions and the returned
+    /// volume is a length or area.
+    ///
+    /// Returns `None` if the intersection is empty or the cell was not constructed (in a partial tesselation).
+    pub fn intersect_cell(
+        &self,
+        cell_idx: usize,
+        half_spaces: &[(DVec3, DVec3)],
+    ) -> Option<(f64, DVec3)> {
+        if self.cells[cell_idx].volume() == 0. {
+            return None;
+        }
+        let simulation_volume = ConvexCell::init_simulation_volume(
+            self.anchor,
+            self.width,
+            self.periodic,
+            self.dimensionality,
+        );
+        let mut convex_cell =
+            ConvexCell::reconstruct(self, cell_idx, &simulation_volume, self.dimensionality)?;
+        for &(point, normal) in half_spaces {
+            convex_cell.clip_by_half_space(-normal, point, self.dimensionality);
+        }
+        let (volume, centroid) = convex_cell.volume_centroid();
+        (volume > 0.).then_some((volume, centroid))
+    }
+
+    /// Intersect the cell with the given index with the convex hull of the given `vertices`, see
+    /// [`Voronoi::intersect_cell`].
+    ///
+    /// The faces of the convex hull are found by brute force, so this is only meant for polytopes with few vertices
+    /// (e.g. the cells of an octree). Only the used coordinates of the vertices are taken into account for 1D and 2D
+    /// tesselations.
+    pub fn intersect_cell_hull(&self, cell_idx: usize, vertices: &[DVec3]) -> Option<(f64, DVec3)> {
+        let tolerance = 1e-10 * self.width.max_element();
+        let half_spaces = hull_half_spaces(vertices, self.dimensionality, tolerance);
+        if half_spaces.is_empty() {
+            return None;
+        }
+        self.intersect_cell(cell_idx, &half_spaces)
+    }
+}
+
+/// The supporting half spaces of the convex hull of `vertices`, as `(point, normal)` pairs with outward normals.
+///
+/// Every hyperplane through `dimensionality` of the vertices with all other vertices on one side of it is a
+/// supporting plane. Returns no half spaces if the vertices do not span a polytope of the given dimensionality.
+fn hull_half_spaces(
+    vertices: &[DVec3],
+    dimensionality: Dimensionality,
+    tolerance: f64,
+) -> Vec<(DVec3, DVec3)> {
+    let n = vertices.len();
+    let mut half_spaces = vec![];
+    let mut add_if_supporting = |point: DVec3, normal: DVec3| {
+        let Some(normal) = normal.try_normalize() else {
+            return;
+        };
+        let heights = vertices.iter().map(|&v| normal.dot(v - point));
+        let normal = if heights.clone().all(|h| h <= tolerance) {
+            normal
+        } else if heights.clone().all(|h| h >= -tolerance) {
+            -normal
+        } else {
+            return;
+        };
+        // Faces with more than `dimensionality` vertices are found multiple times
+        let duplicate = half_spaces
+            .iter()
+            .any(|&(other_point, other_normal): &(DVec3, DVec3)| {
+                normal.dot(other_normal) > 1. - 1e-12
+                    && normal.dot(point - other_point).abs() <= tolerance
+            });
+        if !duplicate {
+            half_spaces.push((point, normal));
+        }
+    };
+    match dimensionality {
+        Dimensionality::Dimensionality1D => {
+            let min = vertices.iter().map(|v| v.x).fold(f64::INFINITY, f64::min);
+            let max = vertices
+                .iter()
+                .map(|v| v.x)
+                .fold(f64::NEG_INFINITY, f64::max);
+            if max - min <= tolerance {
+                return vec![];
+            }
+            return vec![
+                (DVec3::new(min, 0., 0.), DVec3::NEG_X),
+                (DVec3::new(max, 0., 0.), DVec3::X),
+            ];
+        }
+        Dimensionality::Dimensionality2D => {
+            for i in 0..n {
+                for j in (i + 1)..n {
+                    let edge = vertices[j] - vertices[i];
+                    add_if_supporting(vertices[i], DVec3::new(edge.y, -edge.x, 0.));
+                }
+            }
+        }
+        Dimensionality::Dimensionality3D => {
+            for i in 0..n {
+                for j in (i + 1)..n {
+                    for k in (j + 1)..n {
+                        let normal = (vertices[j] - vertices[i]).cross(vertices[k] - vertices[i]);
+                        add_if_supporting(vertices[i], normal);
+                    }
+                }
+            }
+        }
+    }
+
+    // Degenerate vertices (e.g. coplanar in 3D) are supported from both sides by the same planes
+    let degenerate = half_spaces.iter().any(|&(point, normal)| {
+        vertices
+            .iter()
+            .all(|&v| normal.dot(v - point).abs() <= tolerance)
+    });
+    if degenerate {
+        return vec![];
+    }
+    half_spaces
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+    use crate::voronoi::test::{perturbed_grid, perturbed_plane};
+
+    /// The corners of the box with the given anchor and width.
+    fn corners(anchor: DVec3, width: DVec3) -> Vec<DVec3> {
+        (0..8)
+            .map(|i| {
+                anchor
+                    + width
+                        * DVec3::new((i & 1) as f64, ((i >> 1) & 1) as f64, ((i >> 2) & 1) as f64)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_intersect_cell() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let box_anchor = DVec3::new(0.2, 0.3, 0.1);
+        let box_width = DVec3::new(0.3, 0.4, 0.5);
+        let box_upper = box_anchor + box_width;
+        let half_spaces = [
+            (box_anchor, DVec3::NEG_X),
+            (box_upper, DVec3::X),
+            (box_anchor, DVec3::NEG_Y),
+            (box_upper, DVec3::Y),
+            (box_anchor, DVec3::NEG_Z),
+            (box_upper, DVec3::Z),
+        ];
+        for (generators, dimensionality, box_volume) in [
+            (perturbed_grid(anchor, width, 4, 0.5), 3, 0.06),
+            (perturbed_plane(anchor, width, 6, 0.5), 2, 0.12),
+        ] {
+            let voronoi = Voronoi::build(&generators, anchor, width, dimensionality, false, None);
+            let half_spaces = &half_spaces[..2 * dimensionality];
+
+            // The intersections with all cells partition the box
+            let mut volume = 0.;
+            let mut moment = DVec3::ZERO;
+            for cell_idx in 0..generators.len() {
+                let intersection = voronoi.intersect_cell(cell_idx, half_spaces);
+                let hull_intersection =
+                    voronoi.intersect_cell_hull(cell_idx, &corners(box_anchor, box_width));
+                assert_eq!(intersection.is_some(), hull_intersection.is_some());
+                let Some((cell_volume, centroid)) = intersection else {
+                    continue;
+                };
+                let (hull_volume, hull_centroid) = hull_intersection.unwrap();
+                assert_approx_eq!(f64, cell_volume, hull_volume, epsilon = 1e-10);
+                assert!(centroid.distance(hull_centroid) < 1e-10);
+                assert!(cell_volume <= voronoi.cells()[cell_idx].volume() + 1e-10);
+                volume += cell_volume;
+                moment += cell_volume * centroid;
+            }
+            assert_approx_eq!(f64, volume, box_volume, epsilon = 1e-10);
+            let centroid = moment / volume;
+            let expected = box_anchor + 0.5 * box_width;
+            assert_approx_eq!(f64, centroid.x, expected.x, epsilon = 1e-10);
+            assert_approx_eq!(f64, centroid.y, expected.y, epsilon = 1e-10);
+
+            // The whole simulation volume contains every cell
+            for (cell_idx, cell) in voronoi.cells().iter().enumerate() {
+                let (cell_volume, centroid) = voronoi
+                    .intersect_cell_hull(cell_idx, &corners(anchor, width))
+                    .unwrap();
+                assert_approx_eq!(f64, cell_volume, cell.volume(), epsilon = 1e-10);
+                assert!(centroid.distance(cell.centroid()) < 1e-10);
+            }
+        }
+
+        // A tetrahedron (corner of the unit cube)
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        let voronoi = Voronoi::build(&generators, anchor, width, 3, false, None);
+        let tetrahedron = [DVec3::ZERO, DVec3::X, DVec3::Y, DVec3::Z];
+        let volume = (0..generators.len())
+            .filter_map(|cell_idx| voronoi.intersect_cell_hull(cell_idx, &tetrahedron))
+            .map(|(volume, _)| volume)
+            .sum::<f64>();
+        assert_approx_eq!(f64, volume, 1. / 6., epsilon = 1e-10);
+
+        // Degenerate polytopes have no volume
+        let square = [DVec3::ZERO, DVec3::X, DVec3::Y, DVec3::new(1., 1., 0.)];
+        assert!((0..generators.len()).all(|i| voronoi.intersect_cell_hull(i, &square).is_none()));
+    }
+}
@@ -0,0 +1,207 @@
+use std::time::Duration;
+
+/// Performance counters of the construction of a single Voronoi cell.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct CellProfile {
+    pub(super) nn_candidates: usize,
+    pub(super) clipping_planes_tested: usize,
+    pub(super) clipping_planes_cut: usize,
+    pub(super) vertex_tests: usize,
+    pub(super) fallback: bool,
+    pub(super) complete: bool,
+    pub(super) confidence: f64,
+    pub(super) safety_radius: f64,
+}
+
+impl CellProfile {
+    /// Get the number of nearest neighbour candidates consumed (excluding the generator of the cell itself).
+    pub fn nn_candidates(&self) -> usize {
+        self.nn_candidates
+    }
+
+    /// Get the number of clipping planes the cell was tested against (i.e. before the safety criterion was met).
+    pub fn clipping_planes_tested(&self) -> usize {
+        self.clipping_planes_tested
+    }
+
+    /// Get the number of clipping planes that actually cut the cell.
+    pub fn clipping_planes_cut(&self) -> usize {
+        self.clipping_planes_cut
+    }
+
+    /// Get the number of vertex versus clipping plane tests performed.
+    pub fn vertex_tests(&self) -> usize {
+        self.vertex_tests
+    }
+
+    /// Get the final safety radius of the cell: twice the maximal distance from its generator to its vertices, beyond
+    /// which no other generator can clip it. Unusually large values (compared to the neighbouring cells) point to
+    /// regions where the input is poorly sampled (e.g. at huge density contrasts), which dominate the build time.
+    pub fn safety_radius(&self) -> f64 {
+        self.safety_radius
+    }
+
+    /// Whether the construction of this cell fell back to the incremental nearest neighbour search, because the
+    /// neighbours of the first pass of a [`crate::NeighbourSearch::BoundedK`] search did not satisfy the safety
+    /// criterion. Always `false` for the incremental search.
+    pub fn used_fallback(&self) -> bool {
+        self.fallback
+    }
+
+    /// Whether the construction of this cell met the (strict) safety criterion, i.e. whether the cell is guaranteed
+    /// to be exact. This can only be `false` for relaxed [`crate::SafetyCriterion`]s (and for cells that were not
+    /// constructed).
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Get the confidence of the construction of this cell: the fraction of the radius that needs to be searched for
+    /// neighbours to guarantee an exact cell (twice the maximal distance from the generator to the vertices of the
+    /// cell) that was actually searched. This is 1 for complete cells and 0 for cells that were not constructed.
+    pub fn confidence(&self) -> f64 {
+        if self.complete {
+            1.
+        } else {
+            self.confidence
+        }
+    }
+
+    /// Get an estimate of the construction cost of this cell (in arbitrary units), suitable for load balancing.
+    ///
+    /// This is the number of nearest neighbour candidates consumed plus the number of vertex versus clipping plane
+    /// tests, which dominate the construction time.
+    pub fn cost(&self) -> usize {
+        self.nn_candidates + self.vertex_tests
+    }
+}
+
+/// Performance counters of the construction of a Voronoi tesselation, see [`crate::Voronoi::build_profiled`].
+///
+/// Cells that were not constructed (in a partial tesselation) have all their counters set to 0.
+/// On `wasm32-unknown-unknown`, where no clock is available, all the timings are 0.
+#[derive(Default, Debug, Clone)]
+pub struct BuildProfile {
+    pub(super) cells: Vec<CellProfile>,
+    pub(super) rtree_time: Duration,
+    pub(super) cells_time: Duration,
+    pub(super) assemble_time: Duration,
+}
+
+impl BuildProfile {
+    /// Get the performance counters of the individual cells.
+    pub fn cells(&self) -> &[CellProfile] {
+        self.cells.as_ref()
+    }
+
+    /// Get the construction cost estimates of all cells (see [`CellProfile::cost`]).
+    pub fn cell_costs(&self) -> Vec<usize> {
+        self.cells.iter().map(|c| c.cost()).collect()
+    }
+
+    /// Get the indices of the (at most) `count` cells with the highest construction cost (see [`CellProfile::cost`]),
+    /// in order of decreasing cost.
+    pub fn most_expensive_cells(&self, count: usize) -> Vec<usize> {
+        let mut cells = (0..self.cells.len()).collect::<Vec<_>>();
+        cells.sort_by_key(|&i| std::cmp::Reverse(self.cells[i].cost()));
+        cells.truncate(count);
+        cells
+    }
+
+    /// Get the time spent building the r-tree of the generators.
+    pub fn rtree_time(&self) -> Duration {
+        self.rtree_time
+    }
+
+    /// Get the time spent constructing the Voronoi cells (and their faces).
+    pub fn cells_time(&self) -> Duration {
+        self.cells_time
+    }
+
+    /// Get the time spent flattening the faces and linking them to their cells.
+    pub fn assemble_time(&self) -> Duration {
+        self.assemble_time
+    }
+
+    /// Get the total construction time.
+    pub fn total_time(&self) -> Duration {
+        self.rtree_time + self.cells_time + self.assemble_time
+    }
+
+    /// Get the total number of clipping planes tested over all cells.
+    pub fn total_clipping_planes_tested(&self) -> usize {
+        self.cells.iter().map(|c| c.clipping_planes_tested).sum()
+    }
+
+    /// Get the total number of nearest neighbour candidates consumed over all cells.
+    pub fn total_nn_candidates(&self) -> usize {
+        self.cells.iter().map(|c| c.nn_candidates).sum()
+    }
+
+    /// Get the number of cells that fell back to the incremental nearest neighbour search (see
+    /// [`CellProfile::used_fallback`]).
+    pub fn fallback_count(&self) -> usize {
+        self.cells.iter().filter(|c| c.fallback).count()
+    }
+
+    /// Get the number of cells that did not meet the (strict) safety criterion (see [`CellProfile::is_complete`]),
+    /// including the cells that were not constructed.
+    pub fn incomplete_count(&self) -> usize {
+        self.cells.iter().filter(|c| !c.complete).count()
+    }
+
+    /// Get the maximal number of nearest neighbour candidates consumed by any cell.
+    pub fn max_nn_candidates(&self) -> usize {
+        self.cells
+            .iter()
+            .map(|c| c.nn_candidates)
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use glam::DVec3;
+
+    use crate::voronoi::test::perturbed_grid;
+    use crate::Voronoi;
+
+    #[test]
+    fn test_build_profiled() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        let mask = (0..generators.len())
+            .map(|i| i % 2 == 0)
+            .collect::<Vec<_>>();
+        let (voronoi, profile) =
+            Voronoi::build_profiled(&generators, Some(&mask), anchor, width, 3, false, None);
+        assert_eq!(profile.cells().len(), voronoi.cells().len());
+        for (cell, &built) in profile.cells().iter().zip(mask.iter()) {
+            if built {
+                assert!(cell.clipping_planes_tested() > 0);
+                assert!(cell.clipping_planes_cut() <= cell.clipping_planes_tested());
+                assert!(cell.cost() > cell.nn_candidates());
+                // At most one extra candidate is consumed to check the safety criterion
+                assert!(cell.nn_candidates() - cell.clipping_planes_tested() <= 1);
+            } else {
+                assert_eq!(cell.nn_candidates(), 0);
+                assert_eq!(cell.cost(), 0);
+            }
+        }
+        assert!(profile.max_nn_candidates() < generators.len());
+        let expensive = profile.most_expensive_cells(3);
+        assert_eq!(expensive.len(), 3);
+        assert!(profile.cells()[expensive[0]].cost() >= profile.cells()[expensive[2]].cost());
+        for (i, cell) in profile.cells().iter().enumerate() {
+            assert!(cell.cost() <= profile.cells()[expensive[0]].cost());
+            if cell.is_complete() {
+                // The safety radius is larger than the distance to any face
+                assert!(cell.safety_radius() > 2. * voronoi.cells()[i].min_face_distance(&voronoi));
+            } else {
+                assert_eq!(cell.safety_radius(), 0.);
+            }
+        }
+        assert!(profile.total_time() >= profile.cells_time());
+    }
+}
@@ -0,0 +1,206 @@
+use glam::DVec3;
+
+use crate::rtree_nn::{nn_iter, wrapping_nn_iter};
+
+use super::{Voronoi, VoronoiFace};
+
+impl VoronoiFace {
+    /// Whether the segment between the generators to the left and right of this face intersects the face, i.e.
+    /// whether the diametral sphere of both generators contains no other generator and the generators are connected
+    /// in the Gabriel graph. Always `false` for boundary faces.
+    ///
+    /// Generators on the boundary of the diametral sphere do not break the Gabriel property.
+    pub fn is_gabriel(&self, voronoi: &Voronoi) -> bool {
+        let Some(right) = self.right() else {
+            return false;
+        };
+        let left_loc = voronoi.cells[self.left()].loc();
+        let right_loc = voronoi.cells[right].loc() + self.shift().unwrap_or(DVec3::ZERO);
+        // The midpoint lies on the plane of the face, so we only need to check whether it lies inside the face.
+        let midpoint = 0.5 * (left_loc + right_loc);
+        let tolerance = 1e-10 * left_loc.distance(right_loc);
+
+        let vertices = self.vertices(voronoi);
+        match vertices.len() {
+            // 1D faces are points
+            0 | 1 => true,
+            // 2D faces are line segments
+            2 => {
+                let edge = vertices[1] - vertices[0];
+                let t = (midpoint - vertices[0]).dot(edge) / edge.length_squared();
+                let tolerance = tolerance / edge.length();
+                (-tolerance..=1. + tolerance).contains(&t)
+            }
+            // 3D faces are convex polygons, ordered counterclockwise around the normal
+            n => (0..n).all(|i| {
+                let (a, b) = (vertices[i], vertices[(i + 1) % n]);
+                (b - a).normalize().cross(midpoint - a).dot(self.normal()) >= -tolerance
+            }),
+        }
+    }
+}
+
+impl Voronoi {
+    /// Get the indices of the faces corresponding to the edges of the Gabriel graph of the generators (see
+    /// [`VoronoiFace::is_gabriel`]).
+    ///
+    /// Every pair of connected generators is returned once, for periodic tesselations one of both copies of a periodic
+    /// face is returned.
+    pub fn gabriel_faces(&self) -> Vec<usize> {
+        self.faces
+            .iter()
+            .enumerate()
+            .filter(|(_, face)| face.is_primary() && face.is_gabriel(self))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Get the indices of the faces corresponding to the edges of the (lune-based) β-skeleton of the generators, for
+    /// `beta >= 1`.
+    ///
+    /// Two generators `p` and `q` at a distance `d` are connected if no other generator lies strictly inside their
+    /// lune: the intersection of the two balls of radius `beta * d / 2` centered at `(1 - beta / 2) p + beta / 2 q`
+    /// and `beta / 2 p + (1 - beta / 2) q`. For `beta = 1` this is the Gabriel graph (see [`Voronoi::gabriel_faces`])
+    /// and for `beta = 2` the relative neighbourhood graph (see [`Voronoi::relative_neighbourhood_faces`]). Larger
+    /// values of `beta` give sparser subgraphs. Periodic images of the generators are taken into account for periodic
+    /// tesselations.
+    ///
+    /// Every pair of connected generators is returned once, as for [`Voronoi::gabriel_faces`].
+    pub fn beta_skeleton_faces(&self, beta: f64) -> Vec<usize> {
+        assert!(
+            beta >= 1.,
+            "Only beta-skeletons with beta >= 1 are supported!"
+        );
+        // For beta >= 1, the beta-skeleton is a subgraph of the Gabriel graph.
+        self.gabriel_faces()
+            .into_iter()
+            .filter(|&face_idx| {
+                let face = &self.faces[face_idx];
+                let p = self.cells[face.left()].loc();
+                let q = self.cells[face.right().expect("Gabriel faces have a right neighbour!")]
+                    .loc()
+                    + face.shift().unwrap_or(DVec3::ZERO);
+                let d = p.distance(q);
+                let midpoint = 0.5 * (p + q);
+                let centers = [
+                    (1. - 0.5 * beta) * p + 0.5 * beta * q,
+                    0.5 * beta * p + (1. - 0.5 * beta) * q,
+                ];
+                let radius_2 = 0.25 * beta * beta * d * d * (1. - 1e-10);
+                // The lune is contained in the ball of radius d / 2 * sqrt(2 beta - 1) around the midpoint.
+                let search_radius = 0.5 * d * (2. * beta - 1.).sqrt() * (1. + 1e-10);
+                let tolerance = 1e-10 * d;
+
+                let neighbours = if self.periodic {
+                    wrapping_nn_iter(&self.rtree, midpoint, self.width, self.dimensionality)
+                } else {
+                    nn_iter(&self.rtree, midpoint)
+                };
+                neighbours
+                    .map(|(idx, shift)| self.cells[idx].loc() + shift.unwrap_or(DVec3::ZERO))
+                    .take_while(|r| r.distance(midpoint) <= search_radius)
+                    .filter(|r| r.distance(p) > tolerance && r.distance(q) > tolerance)
+                    .all(|r| centers.iter().any(|c| r.distance_squared(*c) >= radius_2))
+            })
+            .collect()
+    }
+
+    /// Get the indices of the faces corresponding to the edges of the relative neighbourhood graph of the generators:
+    /// two generators are connected if no other generator is closer to both of them than they are to each other.
+    ///
+    /// This is the β-skeleton with `beta = 2`, see [`Voronoi::beta_skeleton_faces`].
+    pub fn relative_neighbourhood_faces(&self) -> Vec<usize> {
+        self.beta_skeleton_faces(2.)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::voronoi::test::{perturbed_grid, perturbed_plane};
+
+    fn image_shifts(voronoi: &Voronoi) -> Vec<DVec3> {
+        if voronoi.periodic() {
+            let mut shifts = vec![];
+            for i in -1..=1 {
+                for j in -1..=1 {
+                    for k in -1..=1 {
+                        shifts.push(DVec3::new(i as f64, j as f64, k as f64) * voronoi.width());
+                    }
+                }
+            }
+            shifts
+        } else {
+            vec![DVec3::ZERO]
+        }
+    }
+
+    /// Brute force check of the Gabriel property
+    fn diametral_sphere_is_empty(voronoi: &Voronoi, face: &VoronoiFace) -> bool {
+        let left_loc = voronoi.cells()[face.left()].loc();
+        let right_loc =
+            voronoi.cells()[face.right().unwrap()].loc() + face.shift().unwrap_or(DVec3::ZERO);
+        let center = 0.5 * (left_loc + right_loc);
+        let radius_2 = 0.25 * left_loc.distance_squared(right_loc);
+        let shifts = image_shifts(voronoi);
+        voronoi.cells().iter().all(|cell| {
+            shifts.iter().all(|&shift| {
+                (cell.loc() + shift).distance_squared(center) >= radius_2 * (1. - 1e-9)
+            })
+        })
+    }
+
+    /// Brute force check of the relative neighbourhood property
+    fn lune_is_empty(voronoi: &Voronoi, face: &VoronoiFace) -> bool {
+        let p = voronoi.cells()[face.left()].loc();
+        let q = voronoi.cells()[face.right().unwrap()].loc() + face.shift().unwrap_or(DVec3::ZERO);
+        let d_2 = p.distance_squared(q) * (1. - 1e-9);
+        image_shifts(voronoi).iter().all(|&shift| {
+            voronoi.cells().iter().all(|cell| {
+                let r = cell.loc() + shift;
+                r.distance_squared(p) >= d_2 || r.distance_squared(q) >= d_2
+            })
+        })
+    }
+
+    #[test]
+    fn test_proximity_graphs() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        for (generators, dimensionality) in [
+            (perturbed_grid(anchor, width, 5, 0.9), 3),
+            (perturbed_plane(anchor, width, 10, 0.9), 2),
+        ] {
+            for periodic in [false, true] {
+                let voronoi =
+                    Voronoi::build(&generators, anchor, width, dimensionality, periodic, None);
+                let gabriel_faces = voronoi.gabriel_faces();
+                assert!(!gabriel_faces.is_empty());
+                for (i, face) in voronoi.faces().iter().enumerate() {
+                    if !face.is_primary() {
+                        continue;
+                    }
+                    assert_eq!(
+                        gabriel_faces.contains(&i),
+                        diametral_sphere_is_empty(&voronoi, face)
+                    );
+                }
+
+                // beta-skeletons are nested
+                assert_eq!(voronoi.beta_skeleton_faces(1.), gabriel_faces);
+                let rng_faces = voronoi.relative_neighbourhood_faces();
+                assert!(!rng_faces.is_empty());
+                assert!(rng_faces.len() < gabriel_faces.len());
+                assert!(rng_faces.iter().all(|i| gabriel_faces.contains(i)));
+                let sparse_faces = voronoi.beta_skeleton_faces(3.);
+                assert!(sparse_faces.iter().all(|i| rng_faces.contains(i)));
+                for &i in gabriel_faces.iter() {
+                    assert_eq!(
+                        rng_faces.contains(&i),
+                        lune_is_empty(&voronoi, &voronoi.faces()[i])
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use glam::DVec3;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use super::{Dimensionality, Voronoi};
+
+/// Shape quality metrics of a Voronoi cell, see [`Voronoi::cell_qualities`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellQuality {
+    inscribed_radius: f64,
+    circumscribed_radius: f64,
+    min_dihedral_angle: f64,
+}
+
+impl CellQuality {
+    /// Get the minimal distance from the centroid of the cell to (the plane of) any of its faces, i.e. the radius of
+    /// the largest ball centered at the centroid that fits inside the cell.
+    pub fn inscribed_radius(&self) -> f64 {
+        self.inscribed_radius
+    }
+
+    /// Get the maximal distance from the centroid of the cell to any of its vertices, i.e. the radius of the smallest
+    /// ball centered at the centroid containing the cell.
+    pub fn circumscribed_radius(&self) -> f64 {
+        self.circumscribed_radius
+    }
+
+    /// Get the ratio of the inscribed and circumscribed radius (between `0` and `1`, e.g. `1 / sqrt(3)` for a cube).
+    /// This is `0` for cells that were not constructed.
+    pub fn radius_ratio(&self) -> f64 {
+        if self.circumscribed_radius > 0. {
+            self.inscribed_radius / self.circumscribed_radius
+        } else {
+            0.
+        }
+    }
+
+    /// Get the minimal interior angle (in radians) between two adjacent faces of the cell (e.g. `pi / 2` for a cube).
+    /// For 2D tesselations, this is the minimal interior angle of the polygon. This is `pi` for cells without
+    /// adjacent faces (i.e. in 1D).
+    pub fn min_dihedral_angle(&self) -> f64 {
+        self.min_dihedral_angle
+    }
+
+    /// Whether the radius ratio or the minimal dihedral angle of the cell fall below the given thresholds.
+    pub fn is_sliver(&self, min_radius_ratio: f64, min_dihedral_angle: f64) -> bool {
+        self.radius_ratio() < min_radius_ratio || self.min_dihedral_angle < min_dihedral_angle
+    }
+}
+
+impl Voronoi {
+    /// Compute the shape quality metrics of the cell with the given index (see [`CellQuality`]).
+    ///
+    /// For lower dimensional tesselations, the distances are measured in the used dimensions only.
+    /// Cells that were not constructed get all metrics equal to `0`.
+    pub fn cell_quality(&self, cell_idx: usize) -> CellQuality {
+        let cell = &self.cells[cell_idx];
+        if cell.volume() <= 0. {
+            return CellQuality {
+                inscribed_radius: 0.,
+                circumscribed_radius: 0.,
+                min_dihedral_angle: 0.,
+            };
+        }
+        let used_dimensions = match self.dimensionality {
+            Dimensionality::Dimensionality1D => DVec3::X,
+            Dimensionality::Dimensionality2D => DVec3::new(1., 1., 0.),
+            Dimensionality::Dimensionality3D => DVec3::ONE,
+        };
+        let centroid = cell.centroid();
+        let inscribed_radius = cell
+            .faces(self)
+            .map(|face| (face.centroid() - centroid).dot(face.normal()).abs())
+            .fold(f64::INFINITY, f64::min);
+        let circumscribed_radius = cell
+            .faces(self)
+            .flat_map(|face| face.vertices(self))
+            .map(|&v| ((v - centroid) * used_dimensions).length())
+            .fold(0., f64::max);
+
+        // The outward normals of the faces sharing every (welded) edge of the cell
+        let mut edge_normals: HashMap<(usize, usize), Vec<DVec3>> = HashMap::new();
+        for (face_idx, face_loop) in
+            self.cell_face_loops(cell_idx, 1e-10 * self.width.max_element())
+        {
+            let face = &self.faces[face_idx];
+            let normal = if face.left() == cell_idx {
+                face.normal()
+            } else {
+                -face.normal()
+            };
+            let n = face_loop.len();
+            for i in 0..n {
+                let (a, b) = (face_loop[i], face_loop[(i + 1) % n]);
+                edge_normals
+                    .entry((a.min(b), a.max(b)))
+                    .or_default()
+                    .push(normal);
+            }
+        }
+        let min_dihedral_angle = edge_normals
+            .values()
+            .filter(|normals| normals.len() == 2)
+            .map(|normals| PI - normals[0].dot(normals[1]).clamp(-1., 1.).acos())
+            .fold(PI, f64::min);
+
+        CellQuality {
+            inscribed_radius: inscribed_radius.min(circumscribed_radius),
+            circumscribed_radius,
+            min_dihedral_angle,
+        }
+    }
+
+    /// Compute the shape quality metrics of all cells (see [`Voronoi::cell_quality`]). This method runs in parallel
+    /// if the `"rayon"` feature is enabled.
+    pub fn cell_qualities(&self) -> Vec<CellQuality> {
+        #[cfg(feature = "rayon")]
+        return (0..self.cells.len())
+            .into_par_iter()
+            .map(|cell_idx| self.cell_quality(cell_idx))
+            .collect();
+        #[cfg(not(feature = "rayon"))]
+        return (0..self.cells.len())
+            .map(|cell_idx| self.cell_quality(cell_idx))
+            .collect();
+    }
+
+    /// Find the (constructed) sliver cells of this tesselation: the cells with a radius ratio below
+    /// `min_radius_ratio` or a minimal dihedral angle (in radians) below `min_dihedral_angle` (see
+    /// [`CellQuality`]).
+    ///
+    /// Returns the sorted indices of the sliver cells.
+    pub fn sliver_cells(&self, min_radius_ratio: f64, min_dihedral_angle: f64) -> Vec<usize> {
+        self.cell_qualities()
+            .into_iter()
+            .enumerate()
+            .filter(|&(cell_idx, quality)| {
+                self.cells[cell_idx].volume() > 0.
+                    && quality.is_sliver(min_radius_ratio, min_dihedral_angle)
+            })
+            .map(|(cell_idx, _)| cell_idx)
+            .collect()
+    }
+
+    /// Construct the Voronoi tesselation of `generators`, after selectively regularizing it: only the generators of
+    /// the sliver cells (see [`Voronoi::sliver_cells`]) are nudged towards the centroids of their cells (by the given
+    /// fraction `step` of the offset), until no slivers remain or `max_iterations` iterations were done.
+    ///
+    /// Unlike global Lloyd relaxation, this preserves deliberate density gradients of the generators. In every
+    /// iteration, only the cells affected by the moved generators (the moved cells and their old and new neighbours)
+    /// are rebuilt (see [`Voronoi::build_partial`]) to update their quality metrics. Generators of periodic
+    /// tesselations are wrapped back into the simulation volume.
+    ///
+    /// Returns the tesselation of the regularized generators (see [`VoronoiCell::loc`]) and the number of iterations
+    /// that moved any generator.
+    ///
+    /// [`VoronoiCell::loc`]: crate::VoronoiCell::loc
+    #[allow(clippy::too_many_arguments)]
+    pub fn build_regularized(
+        generators: &[DVec3],
+        anchor: DVec3,
+        width: DVec3,
+        dimensionality: usize,
+        periodic: bool,
+        min_radius_ratio: f64,
+        min_dihedral_angle: f64,
+        step: f64,
+        max_iterations: usize,
+    ) -> (Self, usize) {
+        let used_dimensions = match Dimensionality::from(dimensionality) {
+            Dimensionality::Dimensionality1D => DVec3::X,
+            Dimensionality::Dimensionality2D => DVec3::new(1., 1., 0.),
+            Dimensionality::Dimensionality3D => DVec3::ONE,
+        };
+        let mut generators = generators.to_vec();
+        let build = |generators: &[DVec3]| {
+            Voronoi::build(generators, anchor, width, dimensionality, periodic, None)
+        };
+        let neighbours = |voronoi: &Voronoi, cell_idx: usize| {
+            voronoi.cells[cell_idx]
+                .neighbour_ids(voronoi, cell_idx)
+                .flatten()
+                .collect::<Vec<_>>()
+        };
+
+        // The state of every cell, as of the last (partial) tesselation in which it was constructed
+        let voronoi = build(&generators);
+        let mut qualities = voronoi.cell_qualities();
+        let mut centroids = voronoi
+            .cells
+            .iter()
+            .map(|c| c.centroid())
+            .collect::<Vec<_>>();
+        let mut cell_neighbours = (0..generators.len())
+            .map(|cell_idx| neighbours(&voronoi, cell_idx))
+            .collect::<Vec<_>>();
+
+        let mut iterations = 0;
+        while iterations < max_iterations {
+            let slivers = (0..generators.len())
+                .filter(|&cell_idx| {
+                    qualities[cell_idx].is_sliver(min_radius_ratio, min_dihedral_angle)
+                })
+                .collect::<Vec<_>>();
+            if slivers.is_empty() {
+                break;
+            }
+            iterations += 1;
+
+            let mut mask = vec![false; generators.len()];
+            for &cell_idx in slivers.iter() {
+                let generator = &mut generators[cell_idx];
+                *generator += step * (centroids[cell_idx] - *generator) * used_dimensions;
+                if periodic {
+                    let wraps = ((*generator - anchor) / width).floor() * used_dimensions;
+                    *generator -= wraps * width;
+                }
+                mask[cell_idx] = true;
+                for &neighbour in cell_neighbours[cell_idx].iter() {
+                    mask[neighbour] = true;
+                }
+            }
+
+            // Rebuild the affected cells, including the cells that became neighbours of the moved cells
+            let voronoi = loop {
+                let voronoi = Voronoi::build_partial(
+                    &generators,
+                    &mask,
+                    anchor,
+                    width,
+                    dimensionality,
+                    periodic,
+                    None,
+                );
+                let mut complete = true;
+                for &cell_idx in slivers.iter() {
+                    for neighbour in neighbours(&voronoi, cell_idx) {
+                        complete &= mask[neighbour];
+                        mask[neighbour] = true;
+                    }
+                }
+                if complete {
+                    break voronoi;
+                }
+            };
+            for cell_idx in (0..generators.len()).filter(|&cell_idx| mask[cell_idx]) {
+                qualities[cell_idx] = voronoi.cell_quality(cell_idx);
+                centroids[cell_idx] = voronoi.cells[cell_idx].centroid();
+                cell_neighbours[cell_idx] = neighbours(&voronoi, cell_idx);
+            }
+        }
+
+        (build(&generators), iterations)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+    use crate::voronoi::test::{perturbed_grid, perturbed_plane};
+
+    #[test]
+    fn test_cell_quality() {
+        // A cube
+        let voronoi = Voronoi::build(
+            &[DVec3::splat(0.5)],
+            DVec3::ZERO,
+            DVec3::ONE,
+            3,
+            false,
+            None,
+        );
+        let quality = voronoi.cell_quality(0);
+        assert_approx_eq!(
+            f64,
+            quality.radius_ratio(),
+            1. / 3f64.sqrt(),
+            epsilon = 1e-12
+        );
+        assert_approx_eq!(f64, quality.min_dihedral_angle(), 0.5 * PI, epsilon = 1e-12);
+
+        // A flat box
+        let voronoi = Voronoi::build(
+            &[DVec3::new(0.5, 0.5, 0.05)],
+            DVec3::ZERO,
+            DVec3::new(1., 1., 0.1),
+            3,
+            false,
+            None,
+        );
+        assert_eq!(voronoi.sliver_cells(0.1, 0.1), vec![0]);
+
+        // A triangle in 2D (the bottom left corner of a square split along its diagonal)
+        let voronoi = Voronoi::build(
+            &[DVec3::new(0.2, 0.2, 0.), DVec3::new(0.8, 0.8, 0.)],
+            DVec3::ZERO,
+            DVec3::ONE,
+            2,
+            false,
+            None,
+        );
+        let quality = voronoi.cell_quality(0);
+        assert_approx_eq!(
+            f64,
+            quality.min_dihedral_angle(),
+            0.25 * PI,
+            epsilon = 1e-12
+        );
+
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        for (generators, dimensionality) in [
+            (perturbed_grid(anchor, width, 4, 0.), 3),
+            (perturbed_plane(anchor, width, 5, 0.), 2),
+        ] {
+            let voronoi = Voronoi::build(&generators, anchor, width, dimensionality, false, None);
+            // A regular grid has no slivers
+            assert!(voronoi.sliver_cells(0.5, 0.49 * PI).is_empty());
+            for quality in voronoi.cell_qualities() {
+                assert!(quality.inscribed_radius() <= quality.circumscribed_radius());
+            }
+        }
+    }
+
+    #[test]
+    fn test_build_regularized() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let (min_radius_ratio, min_dihedral_angle) = (0.3, 0.15 * PI);
+        for (generators, dimensionality) in [
+            (perturbed_grid(anchor, width, 5, 0.95), 3),
+            (perturbed_plane(anchor, width, 10, 0.95), 2),
+        ] {
+            for periodic in [false, true] {
+                let voronoi =
+                    Voronoi::build(&generators, anchor, width, dimensionality, periodic, None);
+                let slivers = voronoi.sliver_cells(min_radius_ratio, min_dihedral_angle);
+                let (regularized, iterations) = Voronoi::build_regularized(
+                    &generators,
+                    anchor,
+                    width,
+                    dimensionality,
+                    periodic,
+                    min_radius_ratio,
+                    min_dihedral_angle,
+                    0.5,
+                    50,
+                );
+                assert!(iterations > 0 || slivers.is_empty());
+                let remaining = regularized.sliver_cells(min_radius_ratio, min_dihedral_angle);
+                assert!(remaining.len() <= slivers.len());
+                if iterations < 50 {
+                    assert!(remaining.is_empty());
+                }
+                // Only the generators of (former) slivers move
+                let moved = regularized
+                    .cells()
+                    .iter()
+                    .zip(generators.iter())
+                    .filter(|(cell, &g)| cell.loc() != g)
+                    .count();
+                assert!(moved < generators.len() / 2);
+                assert!(moved >= slivers.len().min(1));
+            }
+        }
+
+        // Nothing to do for a regular grid
+        let generators = perturbed_grid(anchor, width, 4, 0.);
+        let (_, iterations) = Voronoi::build_regularized(
+            &generators,
+            anchor,
+            width,
+            3,
+            false,
+            min_radius_ratio,
+            min_dihedral_angle,
+            0.5,
+            10,
+        );
+        assert_eq!(iterations, 0);
+    }
+}
@@ -0,0 +1,123 @@
+use glam::{DVec2, DVec3};
+
+use super::{Voronoi, Voronoi2D};
+
+impl Voronoi2D {
+    /// Rasterize this tesselation to a cell-id map of `width` by `height` pixels covering the simulation volume.
+    ///
+    /// The pixels are returned in row-major order, starting from the top-left corner (i.e. the first row has the
+    /// highest y-coordinate), such that the buffer can be used directly as an image. Every pixel is assigned the
+    /// index of the cell containing its center (see [`crate::Voronoi::cells_at`]). Runs in parallel when the `rayon`
+    /// feature is enabled.
+    pub fn rasterize(&self, width: usize, height: usize) -> Vec<usize> {
+        let voronoi = self.as_voronoi();
+        let pixel_size = voronoi.width.truncate() / DVec2::new(width as f64, height as f64);
+        let top_left = voronoi.anchor + DVec3::new(0., voronoi.width.y, 0.);
+        let pixel_centers = (0..height)
+            .flat_map(|row| {
+                (0..width).map(move |col| {
+                    top_left
+                        + DVec3::new(
+                            (col as f64 + 0.5) * pixel_size.x,
+                            -(row as f64 + 0.5) * pixel_size.y,
+                            0.,
+                        )
+                })
+            })
+            .collect::<Vec<_>>();
+        voronoi.cells_at(&pixel_centers)
+    }
+
+    /// Rasterize this tesselation to an image of `width` by `height` pixels colored by the given per-cell `values`
+    /// (e.g. scalar values or RGBA colors).
+    ///
+    /// See [`Voronoi2D::rasterize`] for the layout of the returned buffer.
+    pub fn rasterize_values<T: Copy>(&self, values: &[T], width: usize, height: usize) -> Vec<T> {
+        assert_eq!(
+            values.len(),
+            self.cell_count(),
+            "Must have one value per cell!"
+        );
+        self.rasterize(width, height)
+            .into_iter()
+            .map(|cell_idx| values[cell_idx])
+            .collect()
+    }
+}
+
+impl Voronoi {
+    /// Voxelize this tesselation to a cell-id grid of `resolution[0]` by `resolution[1]` by `resolution[2]` voxels
+    /// covering the simulation volume.
+    ///
+    /// The voxels are returned in row-major (C) order, i.e. the voxel with indices `(i, j, k)` along the x-, y- and
+    /// z-axis is stored at index `(i * resolution[1] + j) * resolution[2] + k`, such that the buffer can be used
+    /// directly as a 3D array of shape `resolution`. Every voxel is assigned the index of the cell containing its
+    /// center (see [`Voronoi::cells_at`]). The resolution along the unused dimensions of 1D and 2D tesselations
+    /// should be 1. Runs in parallel when the `rayon` feature is enabled.
+    pub fn voxelize(&self, resolution: [usize; 3]) -> Vec<usize> {
+        let voxel_size = self.width / DVec3::from_array(resolution.map(|n| n as f64));
+        let voxel_centers = (0..resolution[0])
+            .flat_map(|i| {
+                (0..resolution[1]).flat_map(move |j| {
+                    (0..resolution[2]).map(move |k| {
+                        self.anchor + (DVec3::new(i as f64, j as f64, k as f64) + 0.5) * voxel_size
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+        self.cells_at(&voxel_centers)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::voronoi::test::perturbed_grid;
+
+    #[test]
+    fn test_rasterize() {
+        // Two cells splitting the domain at x = 0.5 and a third one in the top right corner
+        let generators = [
+            DVec2::new(0.25, 0.5),
+            DVec2::new(0.75, 0.25),
+            DVec2::new(0.75, 0.75),
+        ];
+        let voronoi = Voronoi2D::build(&generators, DVec2::ZERO, DVec2::ONE, false);
+        let image = voronoi.rasterize(4, 2);
+        assert_eq!(image, vec![0, 0, 2, 2, 0, 0, 1, 1]);
+
+        let colors = [[255u8, 0, 0], [0, 255, 0], [0, 0, 255]];
+        let image = voronoi.rasterize_values(&colors, 4, 2);
+        assert_eq!(image[3], colors[2]);
+        assert_eq!(image[7], colors[1]);
+
+        // The pixel counts are proportional to the cell areas
+        let image = voronoi.rasterize(100, 100);
+        for (i, cell) in voronoi.cells().enumerate() {
+            let count = image.iter().filter(|&&c| c == i).count();
+            assert!((count as f64 / 1e4 - cell.area()).abs() < 1e-2);
+        }
+    }
+
+    #[test]
+    fn test_voxelize() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 3, 0.5);
+        for periodic in [false, true] {
+            let voronoi = Voronoi::build(&generators, anchor, width, 3, periodic, None);
+            let resolution = [40, 30, 20];
+            let voxels = voronoi.voxelize(resolution);
+            assert_eq!(voxels.len(), 40 * 30 * 20);
+            let idx = (7 * 30 + 12) * 20 + 3;
+            let center = DVec3::new(7.5 / 40., 12.5 / 30., 3.5 / 20.);
+            assert_eq!(voxels[idx], voronoi.cells_at(&[center])[0]);
+
+            // The voxel counts are proportional to the cell volumes
+            for (i, cell) in voronoi.cells().iter().enumerate() {
+                let count = voxels.iter().filter(|&&c| c == i).count();
+                assert!((count as f64 / voxels.len() as f64 - cell.volume()).abs() < 1e-2);
+            }
+        }
+    }
+}
@@ -0,0 +1,374 @@
+use glam::DVec3;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::rtree_nn::{nn_iter, wrapping_nn_iter};
+
+use super::{voronoi_cell::ConvexCell, TriangleMesh, Voronoi};
+
+/// A cell of the intersection of two Voronoi tesselations, see [`Voronoi::supermesh`].
+#[derive(Debug, Clone)]
+pub struct SupermeshCell {
+    parents: (usize, usize),
+    shift: Option<DVec3>,
+    mesh: TriangleMesh,
+    volume: f64,
+    centroid: DVec3,
+}
+
+impl SupermeshCell {
+    /// Get the indices of the cells of both tesselations this cell is the intersection of.
+    pub fn parents(&self) -> (usize, usize) {
+        self.parents
+    }
+
+    /// Get the periodic shift applied to the parent cell of the second tesselation, if any (for periodic
+    /// tesselations).
+    pub fn shift(&self) -> Option<DVec3> {
+        self.shift
+    }
+
+    /// Get the closed, convex triangle mesh of this cell (oriented outwards).
+    pub fn mesh(&self) -> &TriangleMesh {
+        &self.mesh
+    }
+
+    /// Get the volume of this cell.
+    pub fn volume(&self) -> f64 {
+        self.volume
+    }
+
+    /// Get the centroid of this cell.
+    pub fn centroid(&self) -> DVec3 {
+        self.centroid
+    }
+}
+
+/// Whether the given `vertices` all lie outside (or on the boundary of) one of the half spaces `normal . (x - point) >=
+/// 0`, i.e. whether the convex hull of the vertices and the intersection of the half spaces have disjoint interiors.
+/// Disjoint shapes that are not separated by one of the half spaces are not detected.
+fn separated(
+    mut half_spaces: impl Iterator<Item = (DVec3, DVec3)>,
+    vertices: &[DVec3],
+    tolerance: f64,
+) -> bool {
+    half_spaces.any(|(normal, point)| vertices.iter().all(|v| normal.dot(*v - point) <= tolerance))
+}
+
+impl Voronoi {
+    /// Intersect every cell of this tesselation with the overlapping cells of `other` (and their periodic images).
+    ///
+    /// Calls `f(other_cell_idx, shift, intersection)` for every non-empty intersection of a cell of this tesselation,
+    /// and returns the results per cell of this tesselation.
+    fn intersect_cells<T: Send>(
+        &self,
+        other: &Voronoi,
+        f: impl Fn(usize, Option<DVec3>, &ConvexCell) -> T + Sync,
+    ) -> Vec<Vec<T>> {
+        assert_eq!(
+            usize::from(self.dimensionality),
+            usize::from(other.dimensionality),
+            "Can only intersect tesselations of the same dimensionality!"
+        );
+        assert_eq!(
+            self.periodic, other.periodic,
+            "Can only intersect tesselations with the same periodicity!"
+        );
+        let reconstruct = |voronoi: &Voronoi| {
+            let simulation_volume = ConvexCell::init_simulation_volume(
+                voronoi.anchor,
+                voronoi.width,
+                voronoi.periodic,
+                voronoi.dimensionality,
+            );
+            (0..voronoi.cells.len())
+                .map(|cell_idx| {
+                    (voronoi.cells[cell_idx].volume() > 0.)
+                        .then(|| {
+                            ConvexCell::reconstruct(
+                                voronoi,
+                                cell_idx,
+                                &simulation_volume,
+                                voronoi.dimensionality,
+                            )
+                        })
+                        .flatten()
+                })
+                .collect::<Vec<_>>()
+        };
+        let cells = reconstruct(self);
+        let other_cells = reconstruct(other);
+        // The safety radius is twice the distance to the furthest vertex of a cell
+        let max_other_radius = other_cells
+            .iter()
+            .flatten()
+            .map(|cell| 0.5 * cell.safety_radius())
+            .fold(0., f64::max);
+
+        let tolerance = 1e-10 * self.width.max_element();
+
+        let intersect = |cell_idx: usize| {
+            let Some(cell) = &cells[cell_idx] else {
+                return vec![];
+            };
+            let planes = cell
+                .clipping_planes
+                .iter()
+                .map(|plane| (plane.normal(), plane.project_onto(cell.loc)))
+                .collect::<Vec<_>>();
+            let vertices = cell.vertices.iter().map(|v| v.loc).collect::<Vec<_>>();
+            let search_radius = (0.5 * cell.safety_radius() + max_other_radius) * (1. + 1e-10);
+            let candidates = if self.periodic {
+                wrapping_nn_iter(&other.rtree, cell.loc, other.width, other.dimensionality)
+            } else {
+                nn_iter(&other.rtree, cell.loc)
+            };
+            candidates
+                .take_while(|&(idx, shift)| {
+                    cell.loc
+                        .distance(other.cells[idx].loc() + shift.unwrap_or(DVec3::ZERO))
+                        <= search_radius
+                })
+                .filter_map(|(idx, shift)| {
+                    let other_cell = other_cells[idx].as_ref()?;
+                    let offset = shift.unwrap_or(DVec3::ZERO);
+                    let other_planes = other_cell
+                        .clipping_planes
+                        .iter()
+                        .map(|plane| (plane.normal(), plane.project_onto(other_cell.loc) + offset))
+                        .collect::<Vec<_>>();
+                    let other_vertices = other_cell
+                        .vertices
+                        .iter()
+                        .map(|v| v.loc + offset)
+                        .collect::<Vec<_>>();
+                    if separated(other_planes.iter().copied(), &vertices, tolerance)
+                        || separated(planes.iter().copied(), &other_vertices, tolerance)
+                    {
+                        return None;
+                    }
+
+                    let mut intersection = cell.clone();
+                    for (normal, point) in other_planes {
+                        // Only clip by planes cutting off a vertex, (nearly) coinciding planes would create
+                        // degenerate slivers
+                        if intersection
+                            .vertices
+                            .iter()
+                            .any(|v| normal.dot(v.loc - point) < -tolerance)
+                        {
+                            intersection.clip_by_half_space(normal, point, self.dimensionality);
+                        }
+                    }
+                    (!intersection.vertices.is_empty()).then(|| f(idx, shift, &intersection))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        #[cfg(feature = "rayon")]
+        return (0..self.cells.len())
+            .into_par_iter()
+            .map(intersect)
+            .collect();
+        #[cfg(not(feature = "rayon"))]
+        return (0..self.cells.len()).map(intersect).collect();
+    }
+
+    /// Compute the conservative remapping weights from the cells of this tesselation to the cells of `other`.
+    ///
+    /// For every cell of this tesselation, returns the `(other_cell_idx, volume)` pairs of the cells of `other` it
+    /// overlaps with, sorted by index, where `volume` is the volume of the intersection of both cells (computed by
+    /// clipping the cells with each other). For periodic tesselations, the intersections with all periodic images of
+    /// the cells of `other` are summed.
+    ///
+    /// Since the cells of both tesselations partition the same simulation volume, the weights of every cell sum to its
+    /// volume, so a quantity `q` can be transferred conservatively by setting `q_other[j] = Σ_i q[i] * w_ij /
+    /// volume_i`. Both tesselations must have the same dimensionality, simulation volume and periodicity. Cells that
+    /// were not constructed (in a partial tesselation) have no weights.
+    pub fn remap_weights(&self, other: &Voronoi) -> Vec<Vec<(usize, f64)>> {
+        let mut weights = self.intersect_cells(other, |idx, _shift, intersection| {
+            (idx, intersection.volume_centroid().0)
+        });
+        for weights in weights.iter_mut() {
+            weights.retain(|&(_, volume)| volume > 0.);
+            weights.sort_by_key(|&(idx, _)| idx);
+            weights.dedup_by(|(idx, volume), (prev_idx, prev_volume)| {
+                let duplicate = idx == prev_idx;
+                if duplicate {
+                    *prev_volume += *volume;
+                }
+                duplicate
+            });
+        }
+        weights
+    }
+
+    /// Construct the intersection of this tesselation with `other` (the "supermesh"), i.e. the convex cells formed by
+    /// intersecting every cell of this tesselation with every overlapping cell of `other`.
+    ///
+    /// The supermesh cells are ordered by their parent cell in this tesselation, and by their parent cell in `other`
+    /// for each of those. For periodic tesselations, the intersections with different periodic images of a cell of
+    /// `other` are separate supermesh cells (with different [`SupermeshCell::shift`]s). Their volumes are the weights
+    /// of [`Voronoi::remap_weights`], and their centroids allow reconstructing linear fields for higher order
+    /// conservative remapping. Both tesselations must have the same dimensionality, simulation volume and periodicity.
+    pub fn supermesh(&self, other: &Voronoi) -> Vec<SupermeshCell> {
+        let tolerance = 1e-10 * self.width.max_element();
+        let mut cells = self.intersect_cells(other, |idx, shift, intersection| {
+            let (volume, centroid) = intersection.volume_centroid();
+            let vertex_loops = intersection
+                .face_vertex_loops()
+                .into_iter()
+                .filter(|vertex_loop| !vertex_loop.is_empty())
+                .map(|vertex_loop| {
+                    vertex_loop
+                        .into_iter()
+                        .map(|v| intersection.vertices[v].loc)
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            let mesh = TriangleMesh::from_polygons(
+                vertex_loops
+                    .iter()
+                    .map(|polygon| (polygon.as_slice(), false)),
+                tolerance,
+            );
+            SupermeshCell {
+                parents: (intersection.idx, idx),
+                shift,
+                mesh,
+                volume,
+                centroid,
+            }
+        });
+        for cells in cells.iter_mut() {
+            cells.retain(|cell| cell.volume > 0.);
+            cells.sort_by_key(|cell| cell.parents.1);
+        }
+        cells.into_iter().flatten().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+    use crate::voronoi::test::{perturbed_grid, perturbed_plane};
+
+    #[test]
+    fn test_remap_weights() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        for periodic in [false, true] {
+            let voronoi = Voronoi::build(
+                &perturbed_grid(anchor, width, 4, 0.5),
+                anchor,
+                width,
+                3,
+                periodic,
+                None,
+            );
+            let other = Voronoi::build(
+                &perturbed_grid(anchor, width, 3, 0.5),
+                anchor,
+                width,
+                3,
+                periodic,
+                None,
+            );
+
+            // The weights of every cell sum to its volume, and to the volumes of the cells of the other tesselation
+            let weights = voronoi.remap_weights(&other);
+            let mut other_volumes = vec![0.; other.cells().len()];
+            for (cell, weights) in voronoi.cells().iter().zip(weights.iter()) {
+                let volume = weights.iter().map(|(_, w)| w).sum::<f64>();
+                assert_approx_eq!(f64, volume, cell.volume(), epsilon = 1e-10);
+                for &(j, w) in weights {
+                    other_volumes[j] += w;
+                }
+            }
+            for (volume, cell) in other_volumes.iter().zip(other.cells()) {
+                assert_approx_eq!(f64, *volume, cell.volume(), epsilon = 1e-10);
+            }
+
+            // Remapping onto the same tesselation is the identity
+            for (i, weights) in voronoi.remap_weights(&voronoi).iter().enumerate() {
+                assert_eq!(weights.len(), 1);
+                assert_eq!(weights[0].0, i);
+                assert_approx_eq!(
+                    f64,
+                    weights[0].1,
+                    voronoi.cells()[i].volume(),
+                    epsilon = 1e-10
+                );
+            }
+        }
+
+        // 2D
+        let voronoi = Voronoi::build(
+            &perturbed_plane(anchor, width, 5, 0.5),
+            anchor,
+            width,
+            2,
+            true,
+            None,
+        );
+        let other = Voronoi::build(
+            &perturbed_plane(anchor, width, 4, 0.5),
+            anchor,
+            width,
+            2,
+            true,
+            None,
+        );
+        let total = voronoi
+            .remap_weights(&other)
+            .iter()
+            .flatten()
+            .map(|(_, w)| w)
+            .sum::<f64>();
+        assert_approx_eq!(f64, total, 1., epsilon = 1e-10);
+    }
+
+    #[test]
+    fn test_supermesh() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let voronoi = Voronoi::build(
+            &perturbed_grid(anchor, width, 4, 0.5),
+            anchor,
+            width,
+            3,
+            false,
+            None,
+        );
+        let other = Voronoi::build(
+            &perturbed_grid(anchor, width, 3, 0.5),
+            anchor,
+            width,
+            3,
+            false,
+            None,
+        );
+        let supermesh = voronoi.supermesh(&other);
+        let weights = voronoi.remap_weights(&other);
+        assert_eq!(supermesh.len(), weights.iter().map(Vec::len).sum::<usize>());
+
+        // The centroids of the supermesh cells of a parent cell average to its centroid
+        let mut centroids = vec![DVec3::ZERO; voronoi.cells().len()];
+        for (cell, (i, j)) in supermesh.iter().zip(
+            weights
+                .iter()
+                .enumerate()
+                .flat_map(|(i, w)| w.iter().map(move |(j, _)| (i, *j))),
+        ) {
+            assert_eq!(cell.parents(), (i, j));
+            assert!(cell.shift().is_none());
+            assert_approx_eq!(f64, cell.mesh().volume(), cell.volume(), epsilon = 1e-10);
+            centroids[i] += cell.volume() * cell.centroid();
+        }
+        for (centroid, cell) in centroids.iter().zip(voronoi.cells()) {
+            assert!((*centroid / cell.volume()).distance(cell.centroid()) < 1e-10);
+        }
+    }
+}
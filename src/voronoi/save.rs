@@ -0,0 +1,401 @@
+use std::{collections::HashSet, error::Error, path::Path};
+
+use glam::DVec3;
+use hdf5::{Group, H5Type};
+
+use super::{Dimensionality, Voronoi};
+
+/// The compression filter applied to the datasets written by [`Voronoi::save_with_options`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    /// Do not compress the datasets.
+    #[default]
+    None,
+    /// Gzip (deflate) compression with the given level (0-9).
+    Gzip(u8),
+    /// LZF compression (fast, with a lower compression ratio). Requires the `hdf5-lzf` feature to be enabled.
+    #[cfg(feature = "hdf5-lzf")]
+    Lzf,
+}
+
+/// The (groups of) datasets that can be written by [`Voronoi::save_with_options`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OutputDataset {
+    /// `Cells/Volume`.
+    CellVolume,
+    /// `Cells/FaceConnectionsOffset`.
+    CellFaceConnectionsOffset,
+    /// `Cells/FaceCount`.
+    CellFaceCount,
+    /// `Cells/Centroid`.
+    CellCentroid,
+    /// `Cells/Generator`.
+    CellGenerator,
+    /// `Faces/Area`.
+    FaceArea,
+    /// `Faces/Centroid`.
+    FaceCentroid,
+    /// `Faces/Normal`.
+    FaceNormal,
+    /// `Faces/Start` and `Faces/End`, only written for 2D tesselations.
+    FaceEndpoints,
+    /// `Faces/VertexOffset`, `Faces/VertexCount` and `FaceVertices` (the vertices of all faces). Not written by
+    /// default.
+    FaceVertices,
+    /// `Faces/VectorIntegral{i}` and `Faces/ScalarIntegral{i}` for every extra vector resp. scalar face integral
+    /// (see [`Voronoi::face_integrals`]). Not written by default.
+    FaceIntegrals,
+    /// `CellFaceConnections`.
+    CellFaceConnections,
+}
+
+impl OutputDataset {
+    /// The datasets written by [`Voronoi::save`].
+    const DEFAULT: [OutputDataset; 10] = [
+        Self::CellVolume,
+        Self::CellFaceConnectionsOffset,
+        Self::CellFaceCount,
+        Self::CellCentroid,
+        Self::CellGenerator,
+        Self::FaceArea,
+        Self::FaceCentroid,
+        Self::FaceNormal,
+        Self::FaceEndpoints,
+        Self::CellFaceConnections,
+    ];
+}
+
+/// A custom per-cell array written alongside the datasets of the tesselation.
+#[derive(Debug, Clone)]
+enum CellArray {
+    Scalar(Vec<f64>),
+    Vector(Vec<DVec3>),
+}
+
+impl CellArray {
+    fn len(&self) -> usize {
+        match self {
+            CellArray::Scalar(values) => values.len(),
+            CellArray::Vector(values) => values.len(),
+        }
+    }
+}
+
+/// Options for writing a Voronoi tesselation to a hdf5 file, see [`Voronoi::save_with_options`].
+///
+/// The default options write the same datasets as [`Voronoi::save`], contiguous and uncompressed.
+#[derive(Debug, Clone)]
+pub struct SaveOptions {
+    chunk_size: Option<usize>,
+    compression: Compression,
+    slab_size: usize,
+    datasets: HashSet<OutputDataset>,
+    cell_arrays: Vec<(String, CellArray)>,
+}
+
+impl Default for SaveOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: None,
+            compression: Compression::None,
+            slab_size: 1 << 20,
+            datasets: OutputDataset::DEFAULT.into_iter().collect(),
+            cell_arrays: vec![],
+        }
+    }
+}
+
+impl SaveOptions {
+    /// Store the datasets in chunks of (at most) `chunk_size` elements. Required for compression, defaults to
+    /// `65536` when compression is enabled without setting a chunk size.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "Chunk size must be positive!");
+        self.chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Compress the datasets with the given filter.
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Write the datasets in slabs of (at most) `slab_size` elements, such that only one slab of every dataset is
+    /// materialized in memory at a time. Defaults to `2^20`.
+    pub fn slab_size(mut self, slab_size: usize) -> Self {
+        assert!(slab_size > 0, "Slab size must be positive!");
+        self.slab_size = slab_size;
+        self
+    }
+
+    /// Also write the given dataset.
+    pub fn with(mut self, dataset: OutputDataset) -> Self {
+        self.datasets.insert(dataset);
+        self
+    }
+
+    /// Do not write the given dataset.
+    pub fn without(mut self, dataset: OutputDataset) -> Self {
+        self.datasets.remove(&dataset);
+        self
+    }
+
+    /// Only write the given datasets (and the registered custom cell arrays).
+    pub fn only(mut self, datasets: &[OutputDataset]) -> Self {
+        self.datasets = datasets.iter().copied().collect();
+        self
+    }
+
+    /// Write a custom scalar array with one value per cell as `Cells/{name}`.
+    pub fn cell_values(mut self, name: &str, values: Vec<f64>) -> Self {
+        self.cell_arrays
+            .push((name.to_string(), CellArray::Scalar(values)));
+        self
+    }
+
+    /// Write a custom vector array with one value per cell as `Cells/{name}`.
+    pub fn cell_vectors(mut self, name: &str, values: Vec<DVec3>) -> Self {
+        self.cell_arrays
+            .push((name.to_string(), CellArray::Vector(values)));
+        self
+    }
+
+    fn writes(&self, dataset: OutputDataset) -> bool {
+        self.datasets.contains(&dataset)
+    }
+
+    /// Create a dataset of `len` elements in `group` and write it slab by slab, computing the element with index `i`
+    /// as `value(i)`.
+    fn write_dataset<T: H5Type>(
+        &self,
+        group: &Group,
+        name: &str,
+        len: usize,
+        value: impl Fn(usize) -> T,
+    ) -> hdf5::Result<()> {
+        let mut builder = group.new_dataset::<T>();
+        let chunk_size = match self.compression {
+            Compression::None => self.chunk_size,
+            _ => Some(self.chunk_size.unwrap_or(1 << 16)),
+        };
+        if let Some(chunk_size) = chunk_size {
+            builder = builder.chunk(chunk_size.min(len).max(1));
+        }
+        match self.compression {
+            Compression::None => (),
+            Compression::Gzip(level) => builder = builder.deflate(level),
+            #[cfg(feature = "hdf5-lzf")]
+            Compression::Lzf => builder = builder.lzf(),
+        }
+        let dataset = builder.shape(len).create(name)?;
+
+        let mut slab = Vec::with_capacity(self.slab_size.min(len));
+        for start in (0..len).step_by(self.slab_size) {
+            let end = (start + self.slab_size).min(len);
+            slab.clear();
+            slab.extend((start..end).map(&value));
+            dataset.write_slice(&slab, start..end)?;
+        }
+        Ok(())
+    }
+}
+
+impl Voronoi {
+    /// Save the Voronoi tesselation to a hdf5 file. Requires the `hdf5` feature to be enabled.
+    pub fn save<P: AsRef<Path>>(&self, filename: P) -> Result<(), Box<dyn Error>> {
+        self.save_with_options(filename, &SaveOptions::default())
+    }
+
+    /// Save the Voronoi tesselation to a hdf5 file, with the given dataset selection, custom cell arrays, chunking,
+    /// compression and slab size (see [`SaveOptions`]). Requires the `hdf5` feature to be enabled.
+    ///
+    /// The datasets are written slab by slab directly from the tesselation, so saving does not duplicate the memory
+    /// used by the cells and faces. Returns an error if a custom cell array does not have one value per cell.
+    pub fn save_with_options<P: AsRef<Path>>(
+        &self,
+        filename: P,
+        options: &SaveOptions,
+    ) -> Result<(), Box<dyn Error>> {
+        if let Some((name, _)) = options
+            .cell_arrays
+            .iter()
+            .find(|(_, array)| array.len() != self.cells.len())
+        {
+            return Err(format!("Cell array {name} must have one value per cell!").into());
+        }
+
+        // Create the file to write the data to
+        let file = hdf5::File::create(filename)?;
+
+        // Write cell info
+        let group = file.create_group("Cells")?;
+        let cells = &self.cells;
+        if options.writes(OutputDataset::CellVolume) {
+            options.write_dataset(&group, "Volume", cells.len(), |i| cells[i].volume())?;
+        }
+        if options.writes(OutputDataset::CellFaceConnectionsOffset) {
+            options.write_dataset(&group, "FaceConnectionsOffset", cells.len(), |i| {
+                cells[i].face_connections_offset()
+            })?;
+        }
+        if options.writes(OutputDataset::CellFaceCount) {
+            options.write_dataset(&group, "FaceCount", cells.len(), |i| cells[i].face_count())?;
+        }
+        if options.writes(OutputDataset::CellCentroid) {
+            options.write_dataset(&group, "Centroid", cells.len(), |i| {
+                cells[i].centroid().to_array()
+            })?;
+        }
+        if options.writes(OutputDataset::CellGenerator) {
+            options.write_dataset(&group, "Generator", cells.len(), |i| {
+                cells[i].loc().to_array()
+            })?;
+        }
+        for (name, array) in options.cell_arrays.iter() {
+            match array {
+                CellArray::Scalar(values) => {
+                    options.write_dataset(&group, name, values.len(), |i| values[i])?
+                }
+                CellArray::Vector(values) => {
+                    options.write_dataset(&group, name, values.len(), |i| values[i].to_array())?
+                }
+            }
+        }
+
+        // Write face info
+        let group = file.create_group("Faces")?;
+        let faces = &self.faces;
+        if options.writes(OutputDataset::FaceArea) {
+            options.write_dataset(&group, "Area", faces.len(), |i| faces[i].area())?;
+        }
+        if options.writes(OutputDataset::FaceCentroid) {
+            options.write_dataset(&group, "Centroid", faces.len(), |i| {
+                faces[i].centroid().to_array()
+            })?;
+        }
+        if options.writes(OutputDataset::FaceNormal) {
+            options.write_dataset(&group, "Normal", faces.len(), |i| {
+                faces[i].normal().to_array()
+            })?;
+        }
+        if let Dimensionality::Dimensionality2D = self.dimensionality {
+            if options.writes(OutputDataset::FaceEndpoints) {
+                // Also write face start and end points
+                let face_direction = |i: usize| faces[i].area() * faces[i].normal().cross(DVec3::Z);
+                options.write_dataset(&group, "Start", faces.len(), |i| {
+                    (faces[i].centroid() - 0.5 * face_direction(i)).to_array()
+                })?;
+                options.write_dataset(&group, "End", faces.len(), |i| {
+                    (faces[i].centroid() + 0.5 * face_direction(i)).to_array()
+                })?;
+            }
+        }
+        if options.writes(OutputDataset::FaceVertices) {
+            options.write_dataset(&group, "VertexOffset", faces.len(), |i| {
+                faces[i].vertex_offset()
+            })?;
+            options.write_dataset(&group, "VertexCount", faces.len(), |i| {
+                faces[i].vertex_count()
+            })?;
+            let vertices = self.face_vertices();
+            options.write_dataset(&file, "FaceVertices", vertices.len(), |i| {
+                vertices[i].to_array()
+            })?;
+        }
+        if options.writes(OutputDataset::FaceIntegrals) {
+            let (vector_integrals, scalar_integrals) = self.face_integrals();
+            for (id, integrals) in vector_integrals.iter().enumerate() {
+                options.write_dataset(
+                    &group,
+                    &format!("VectorIntegral{id}"),
+                    integrals.len(),
+                    |i| integrals[i].to_array(),
+                )?;
+            }
+            for (id, integrals) in scalar_integrals.iter().enumerate() {
+                options.write_dataset(
+                    &group,
+                    &format!("ScalarIntegral{id}"),
+                    integrals.len(),
+                    |i| integrals[i],
+                )?;
+            }
+        }
+
+        // Write cell face connections
+        if options.writes(OutputDataset::CellFaceConnections) {
+            let connections = self.cell_face_connections();
+            options.write_dataset(&file, "CellFaceConnections", connections.len(), |i| {
+                connections[i]
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::voronoi::test::perturbed_grid;
+
+    #[test]
+    fn test_save_with_options() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 5, 0.5);
+        let voronoi = Voronoi::build(&generators, anchor, width, 3, true, None);
+        let options = SaveOptions::default()
+            .chunk_size(16)
+            .compression(Compression::Gzip(4))
+            .slab_size(7);
+        voronoi
+            .save_with_options("test_save_with_options.hdf5", &options)
+            .unwrap();
+
+        let file = hdf5::File::open("test_save_with_options.hdf5").unwrap();
+        let volumes = file
+            .dataset("Cells/Volume")
+            .unwrap()
+            .read_raw::<f64>()
+            .unwrap();
+        assert_eq!(volumes.len(), generators.len());
+        for (volume, cell) in volumes.iter().zip(voronoi.cells()) {
+            assert_eq!(*volume, cell.volume());
+        }
+        let connections = file
+            .dataset("CellFaceConnections")
+            .unwrap()
+            .read_raw::<usize>()
+            .unwrap();
+        assert_eq!(connections, voronoi.cell_face_connections());
+
+        // Dataset selection and custom arrays
+        let options = SaveOptions::default()
+            .without(OutputDataset::FaceNormal)
+            .with(OutputDataset::FaceVertices)
+            .cell_values("Index", (0..generators.len()).map(|i| i as f64).collect());
+        voronoi
+            .save_with_options("test_save_with_options.hdf5", &options)
+            .unwrap();
+        let file = hdf5::File::open("test_save_with_options.hdf5").unwrap();
+        assert!(file.dataset("Faces/Normal").is_err());
+        let vertices = file
+            .dataset("FaceVertices")
+            .unwrap()
+            .read_raw::<[f64; 3]>()
+            .unwrap();
+        assert_eq!(vertices.len(), voronoi.face_vertices().len());
+        let index = file
+            .dataset("Cells/Index")
+            .unwrap()
+            .read_raw::<f64>()
+            .unwrap();
+        assert_eq!(index[3], 3.);
+
+        let options = SaveOptions::default().cell_values("Index", vec![0.]);
+        assert!(voronoi
+            .save_with_options("test_save_with_options.hdf5", &options)
+            .is_err());
+    }
+}
@@ -0,0 +1,181 @@
+use glam::DVec3;
+
+use crate::rtree_nn::{nn_iter, wrapping_nn_iter};
+
+use super::{Dimensionality, Voronoi};
+
+/// Clip the segment `a + t * d` with `t` in `[0, 1]` to the box with the given `anchor` and `width`.
+///
+/// Returns the clipped parameter range, if the segment intersects the box.
+fn clip_to_box(a: DVec3, d: DVec3, anchor: DVec3, width: DVec3) -> Option<(f64, f64)> {
+    let (mut t_min, mut t_max) = (0f64, 1f64);
+    for i in 0..3 {
+        if d[i] == 0. {
+            if a[i] < anchor[i] || a[i] > anchor[i] + width[i] {
+                return None;
+            }
+            continue;
+        }
+        let t0 = (anchor[i] - a[i]) / d[i];
+        let t1 = (anchor[i] + width[i] - a[i]) / d[i];
+        t_min = t_min.max(t0.min(t1));
+        t_max = t_max.min(t0.max(t1));
+    }
+    (t_min < t_max).then_some((t_min, t_max))
+}
+
+impl Voronoi {
+    /// Trace the line segment from `a` to `b` through this tesselation.
+    ///
+    /// Returns the ordered list of `(cell_idx, length)` pairs of the cells traversed by the segment and the length of
+    /// the part of the segment inside each of them. For periodic tesselations, the segment wraps around the
+    /// simulation volume (and may be longer than it, in which case cells can appear several times). For non-periodic
+    /// tesselations, the parts of the segment outside of the simulation volume are ignored. The unused coordinates of
+    /// `a` and `b` are ignored for 1D and 2D tesselations.
+    ///
+    /// The traversal stops at cells that were not constructed (in a partial tesselation).
+    pub fn segment_lengths(&self, mut a: DVec3, mut b: DVec3) -> Vec<(usize, f64)> {
+        // Move the segment to the middle of the unused dimensions
+        let center = self.anchor + 0.5 * self.width;
+        if let Dimensionality::Dimensionality1D = self.dimensionality {
+            (a.y, b.y) = (center.y, center.y);
+        }
+        if let Dimensionality::Dimensionality1D | Dimensionality::Dimensionality2D =
+            self.dimensionality
+        {
+            (a.z, b.z) = (center.z, center.z);
+        }
+        let d = b - a;
+        let length = d.length();
+        if length == 0. || self.cells.is_empty() {
+            return vec![];
+        }
+
+        // Find the starting cell and the shift of its generator in the frame of the segment
+        let (mut t, t_end, mut cell_idx, mut offset) = if self.periodic {
+            let wraps = ((a - self.anchor) / self.width).floor() * self.width;
+            let (idx, shift) =
+                wrapping_nn_iter(&self.rtree, a - wraps, self.width, self.dimensionality)
+                    .next()
+                    .expect("R-tree cannot be empty!");
+            (0., 1., idx, wraps + shift.unwrap_or(DVec3::ZERO))
+        } else {
+            let Some((t_min, t_max)) = clip_to_box(a, d, self.anchor, self.width) else {
+                return vec![];
+            };
+            let (idx, _) = nn_iter(&self.rtree, a + t_min * d)
+                .next()
+                .expect("R-tree cannot be empty!");
+            (t_min, t_max, idx, DVec3::ZERO)
+        };
+
+        let mut lengths = vec![];
+        loop {
+            // Find the face through which the segment leaves the current cell
+            let mut exit: Option<(f64, Option<(usize, DVec3)>)> = None;
+            for face in self.cells[cell_idx].faces(self) {
+                let (normal, neighbour) = if face.left() == cell_idx {
+                    let neighbour = face
+                        .right()
+                        .map(|right| (right, offset + face.shift().unwrap_or(DVec3::ZERO)));
+                    (face.normal(), neighbour)
+                } else {
+                    (-face.normal(), Some((face.left(), offset)))
+                };
+                let dn = normal.dot(d);
+                if dn <= 0. {
+                    continue;
+                }
+                let t_face = normal.dot(face.centroid() + offset - a) / dn;
+                if exit.is_none_or(|(t_exit, _)| t_face < t_exit) {
+                    exit = Some((t_face, neighbour));
+                }
+            }
+            let Some((t_exit, neighbour)) = exit else {
+                break;
+            };
+            let t_next = t_exit.clamp(t, t_end);
+            if t_next > t {
+                lengths.push((cell_idx, (t_next - t) * length));
+            }
+            t = t_next;
+            match neighbour {
+                Some((idx, shift)) if t < t_end => {
+                    cell_idx = idx;
+                    offset = shift;
+                }
+                _ => break,
+            }
+        }
+        lengths
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+    use crate::voronoi::test::{perturbed_grid, perturbed_plane};
+
+    #[test]
+    fn test_segment_lengths() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        for periodic in [false, true] {
+            let voronoi = Voronoi::build(&generators, anchor, width, 3, periodic, None);
+            let a = DVec3::new(0.1, 0.2, 0.3);
+            let b = DVec3::new(0.9, 0.7, 0.6);
+            let lengths = voronoi.segment_lengths(a, b);
+            let total = lengths.iter().map(|(_, l)| l).sum::<f64>();
+            assert_approx_eq!(f64, total, a.distance(b), epsilon = 1e-10);
+
+            // The middle of every piece lies in the cell it was assigned to
+            let d = (b - a).normalize();
+            let mut s = 0.;
+            for &(cell_idx, length) in lengths.iter() {
+                let middle = a + (s + 0.5 * length) * d;
+                assert_eq!(voronoi.cells_at(&[middle])[0], cell_idx);
+                s += length;
+            }
+        }
+
+        // Parts outside of the simulation volume are ignored for non-periodic tesselations
+        let voronoi = Voronoi::build(&generators, anchor, width, 3, false, None);
+        let lengths = voronoi.segment_lengths(DVec3::new(-1., 0.5, 0.5), DVec3::new(2., 0.5, 0.5));
+        let total = lengths.iter().map(|(_, l)| l).sum::<f64>();
+        assert_approx_eq!(f64, total, 1., epsilon = 1e-10);
+        assert!(voronoi
+            .segment_lengths(DVec3::splat(2.), DVec3::splat(3.))
+            .is_empty());
+
+        // Segments wrap around periodic tesselations
+        let voronoi = Voronoi::build(&generators, anchor, width, 3, true, None);
+        let a = DVec3::new(0.9, 0.5, 0.1);
+        let b = a + DVec3::new(2.3, 0.4, -1.7);
+        let lengths = voronoi.segment_lengths(a, b);
+        let total = lengths.iter().map(|(_, l)| l).sum::<f64>();
+        assert_approx_eq!(f64, total, a.distance(b), epsilon = 1e-10);
+        let d = (b - a).normalize();
+        let mut s = 0.;
+        for &(cell_idx, length) in lengths.iter() {
+            let middle = a + (s + 0.5 * length) * d;
+            assert_eq!(voronoi.cells_at(&[middle])[0], cell_idx);
+            s += length;
+        }
+
+        // 2D: the z coordinates are ignored
+        let voronoi = Voronoi::build(
+            &perturbed_plane(anchor, width, 5, 0.5),
+            anchor,
+            width,
+            2,
+            false,
+            None,
+        );
+        let lengths = voronoi.segment_lengths(DVec3::new(0., 0., 5.), DVec3::new(1., 1., -3.));
+        let total = lengths.iter().map(|(_, l)| l).sum::<f64>();
+        assert_approx_eq!(f64, total, 2f64.sqrt(), epsilon = 1e-10);
+    }
+}
@@ -0,0 +1,297 @@
+use std::collections::HashSet;
+
+use glam::DVec3;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use rstar::{PointDistance, RTree, RTreeObject, AABB};
+
+use crate::util::{distance_to_polygon, distance_to_segment, weld};
+
+use super::{Dimensionality, Voronoi};
+
+/// The elements of the face network of a tesselation to measure distances to, see [`Voronoi::skeleton`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkeletonElements {
+    /// The faces between neighbouring cells (the edges between neighbouring cells for 2D tesselations and the points
+    /// between neighbouring cells for 1D tesselations).
+    Faces,
+    /// The edges of the faces between neighbouring cells (the vertices of 2D tesselations). Not available for 1D
+    /// tesselations.
+    Edges,
+}
+
+/// A face (polygon) or edge (segment) of the face network, stored in the bounding volume hierarchy of a
+/// [`Skeleton`].
+struct SkeletonElement {
+    vertices: Vec<DVec3>,
+    normal: DVec3,
+    envelope: AABB<[f64; 3]>,
+}
+
+impl SkeletonElement {
+    fn new(vertices: Vec<DVec3>, normal: DVec3) -> Self {
+        let (min, max) = vertices.iter().fold(
+            (DVec3::splat(f64::INFINITY), DVec3::splat(f64::NEG_INFINITY)),
+            |(min, max), &v| (min.min(v), max.max(v)),
+        );
+        Self {
+            vertices,
+            normal,
+            envelope: AABB::from_corners(min.to_array(), max.to_array()),
+        }
+    }
+
+    fn distance(&self, point: DVec3) -> f64 {
+        match self.vertices[..] {
+            [a, b] => distance_to_segment(point, a, b),
+            _ => distance_to_polygon(point, &self.vertices, self.normal),
+        }
+    }
+}
+
+impl RTreeObject for SkeletonElement {
+    type Envelope = AABB<[f64; 3]>;
+
+    fn envelope(&self) -> Self::Envelope {
+        self.envelope
+    }
+}
+
+impl PointDistance for SkeletonElement {
+    fn distance_2(&self, point: &[f64; 3]) -> f64 {
+        self.distance(DVec3::from_array(*point)).powi(2)
+    }
+}
+
+/// A bounding volume hierarchy over the faces or edges of the face network of a tesselation, to query the (unsigned)
+/// distance from arbitrary points to the network (see [`Voronoi::skeleton`]).
+pub struct Skeleton {
+    rtree: RTree<SkeletonElement>,
+    anchor: DVec3,
+    width: DVec3,
+    periodic: bool,
+    dimensionality: Dimensionality,
+}
+
+impl Skeleton {
+    /// Get the distance from `point` to the nearest element of this skeleton.
+    ///
+    /// For periodic tesselations, the distance to the nearest periodic image of the network is returned. The unused
+    /// coordinates of `point` are ignored for 1D and 2D tesselations. Returns infinity if the skeleton is empty (e.g.
+    /// for a tesselation with a single non-periodic cell).
+    pub fn distance(&self, mut point: DVec3) -> f64 {
+        if self.rtree.size() == 0 {
+            return f64::INFINITY;
+        }
+        let center = self.anchor + 0.5 * self.width;
+        let mut shifts = vec![DVec3::ZERO];
+        if self.periodic {
+            point -= ((point - self.anchor) / self.width).floor() * self.width;
+        }
+        let images: &[i32] = if self.periodic { &[-1, 0, 1] } else { &[0] };
+        match self.dimensionality {
+            Dimensionality::Dimensionality1D => {
+                (point.y, point.z) = (center.y, center.z);
+                shifts = images
+                    .iter()
+                    .map(|&i| DVec3::new(i as f64, 0., 0.))
+                    .collect();
+            }
+            Dimensionality::Dimensionality2D => {
+                point.z = center.z;
+                shifts = images
+                    .iter()
+                    .flat_map(|&i| {
+                        images
+                            .iter()
+                            .map(move |&j| DVec3::new(i as f64, j as f64, 0.))
+                    })
+                    .collect();
+            }
+            Dimensionality::Dimensionality3D if self.periodic => {
+                shifts = images
+                    .iter()
+                    .flat_map(|&i| {
+                        images.iter().flat_map(move |&j| {
+                            images
+                                .iter()
+                                .map(move |&k| DVec3::new(i as f64, j as f64, k as f64))
+                        })
+                    })
+                    .collect();
+            }
+            Dimensionality::Dimensionality3D => (),
+        }
+
+        // The images of the point that can be closer to the network than the best distance found so far
+        let root_envelope = self.rtree.root().envelope();
+        let mut distance = f64::INFINITY;
+        for shift in shifts {
+            let image = (point + shift * self.width).to_array();
+            if root_envelope.distance_2(&image) >= distance * distance {
+                continue;
+            }
+            if let Some(element) = self.rtree.nearest_neighbor(&image) {
+                distance = distance.min(element.distance(DVec3::from_array(image)));
+            }
+        }
+        distance
+    }
+
+    /// Get the distances from the given `points` to the nearest element of this skeleton (see
+    /// [`Skeleton::distance`]). This method runs in parallel if the `"rayon"` feature is enabled.
+    pub fn distances(&self, points: &[DVec3]) -> Vec<f64> {
+        #[cfg(feature = "rayon")]
+        return points.par_iter().map(|&p| self.distance(p)).collect();
+        #[cfg(not(feature = "rayon"))]
+        return points.iter().map(|&p| self.distance(p)).collect();
+    }
+}
+
+impl Voronoi {
+    /// Build a bounding volume hierarchy over the faces (or face edges) between neighbouring cells of this
+    /// tesselation, to query the distance from arbitrary points to the face network (e.g. for procedural cellular
+    /// textures or to measure the wall thickness of foams).
+    ///
+    /// The faces on the boundary of the simulation volume are not part of the network. For periodic tesselations, the
+    /// faces with a periodic neighbour are included at both sides of the simulation volume.
+    pub fn skeleton(&self, elements: SkeletonElements) -> Skeleton {
+        let internal_faces = self.faces.iter().filter(|face| face.right().is_some());
+        let elements = match elements {
+            SkeletonElements::Faces => internal_faces
+                .map(|face| SkeletonElement::new(face.vertices(self).to_vec(), face.normal()))
+                .collect(),
+            SkeletonElements::Edges => {
+                assert!(
+                    !matches!(self.dimensionality, Dimensionality::Dimensionality1D),
+                    "1D tesselations have no edges!"
+                );
+                let tolerance = 1e-10 * self.width.max_element();
+                let (points, indices) = weld(&self.face_vertices, tolerance);
+                let mut edges = HashSet::new();
+                for face in internal_faces {
+                    let (offset, n) = (face.vertex_offset(), face.vertices(self).len());
+                    for i in 0..n {
+                        let a = indices[offset + i];
+                        let b = indices[offset + (i + 1) % n];
+                        // The edges between the top and bottom of the (prismatic) faces of 2D tesselations are the
+                        // vertices of the tesselation, the other edges are artifacts of the 3D representation.
+                        let is_edge = match self.dimensionality {
+                            Dimensionality::Dimensionality2D => {
+                                points[a].truncate().distance(points[b].truncate()) <= tolerance
+                            }
+                            _ => true,
+                        };
+                        if a != b && is_edge {
+                            edges.insert((a.min(b), a.max(b)));
+                        }
+                    }
+                }
+                edges
+                    .into_iter()
+                    .map(|(a, b)| SkeletonElement::new(vec![points[a], points[b]], DVec3::ZERO))
+                    .collect()
+            }
+        };
+
+        Skeleton {
+            rtree: RTree::bulk_load(elements),
+            anchor: self.anchor,
+            width: self.width,
+            periodic: self.periodic,
+            dimensionality: self.dimensionality,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+    use crate::voronoi::test::perturbed_grid;
+
+    fn grid(n: usize, dimensionality: usize) -> Vec<DVec3> {
+        let k = |i: usize| if dimensionality > i { n } else { 1 };
+        let mut generators = vec![];
+        for i in 0..k(0) {
+            for j in 0..k(1) {
+                for l in 0..k(2) {
+                    let index = DVec3::new(i as f64, j as f64, l as f64);
+                    let mut loc = (index + 0.5) / n as f64;
+                    if dimensionality < 3 {
+                        loc.z = 0.;
+                    }
+                    if dimensionality < 2 {
+                        loc.y = 0.;
+                    }
+                    generators.push(loc);
+                }
+            }
+        }
+        generators
+    }
+
+    #[test]
+    fn test_skeleton() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+
+        // Regular grid: the faces lie in the planes x, y, z = k / 4
+        let voronoi = Voronoi::build(&grid(4, 3), anchor, width, 3, false, None);
+        let faces = voronoi.skeleton(SkeletonElements::Faces);
+        let edges = voronoi.skeleton(SkeletonElements::Edges);
+        let point = DVec3::new(0.3, 0.6, 0.9);
+        assert_approx_eq!(f64, faces.distance(point), 0.05, epsilon = 1e-12);
+        assert_approx_eq!(
+            f64,
+            edges.distance(point),
+            0.0125f64.sqrt(),
+            epsilon = 1e-12
+        );
+        // The boundary of the simulation volume is not part of the network
+        let point = DVec3::new(0.3, 0.6, 0.99);
+        assert_approx_eq!(f64, faces.distance(point), 0.05, epsilon = 1e-12);
+        let voronoi = Voronoi::build(&grid(4, 3), anchor, width, 3, true, None);
+        let faces = voronoi.skeleton(SkeletonElements::Faces);
+        assert_approx_eq!(f64, faces.distance(point), 0.01, epsilon = 1e-12);
+        assert_approx_eq!(
+            f64,
+            faces.distance(point + DVec3::new(-2., 3., 1.)),
+            0.01,
+            epsilon = 1e-12
+        );
+
+        // 2D and 1D
+        let voronoi = Voronoi::build(&grid(4, 2), anchor, width, 2, false, None);
+        let point = DVec3::new(0.3, 0.6, 0.3);
+        let distances = [SkeletonElements::Faces, SkeletonElements::Edges]
+            .map(|elements| voronoi.skeleton(elements).distance(point));
+        assert_approx_eq!(f64, distances[0], 0.05, epsilon = 1e-12);
+        assert_approx_eq!(f64, distances[1], 0.0125f64.sqrt(), epsilon = 1e-12);
+        let voronoi = Voronoi::build(&grid(4, 1), anchor, width, 1, true, None);
+        let faces = voronoi.skeleton(SkeletonElements::Faces);
+        assert_approx_eq!(
+            f64,
+            faces.distance(DVec3::new(0.98, 0.3, 0.)),
+            0.02,
+            epsilon = 1e-12
+        );
+
+        // Irregular: the nearest face of a point is one of the faces of the cell containing it
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        let points = perturbed_grid(anchor, width, 6, 0.9);
+        let voronoi = Voronoi::build(&generators, anchor, width, 3, false, None);
+        let distances = voronoi.skeleton(SkeletonElements::Faces).distances(&points);
+        for ((point, distance), cell_idx) in
+            points.iter().zip(distances).zip(voronoi.cells_at(&points))
+        {
+            let expected = voronoi.cells()[cell_idx]
+                .faces(&voronoi)
+                .filter(|face| face.right().is_some())
+                .map(|face| distance_to_polygon(*point, face.vertices(&voronoi), face.normal()))
+                .fold(f64::INFINITY, f64::min);
+            assert_approx_eq!(f64, distance, expected, epsilon = 1e-12);
+        }
+    }
+}
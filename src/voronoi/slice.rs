@@ -0,0 +1,204 @@
+use glam::{DVec2, DVec3};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use super::{voronoi_cell::ConvexCell, Dimensionality, Voronoi};
+
+/// A plane with an orthonormal coordinate frame, used to slice a 3D tesselation (see [`Voronoi::slice`]).
+#[derive(Debug, Clone, Copy)]
+pub struct SlicePlane {
+    origin: DVec3,
+    u: DVec3,
+    v: DVec3,
+}
+
+impl SlicePlane {
+    /// Create the plane through `point` with the given `normal`, with an arbitrary (right handed) in-plane coordinate
+    /// frame.
+    pub fn new(point: DVec3, normal: DVec3) -> Self {
+        let (u, v) = normal.normalize().any_orthonormal_pair();
+        Self {
+            origin: point,
+            u,
+            v,
+        }
+    }
+
+    /// Create the plane through `origin` spanned by the orthogonal directions `u` and `v`, which become the (normalized)
+    /// coordinate axes of the plane. The normal of the plane is `u x v`.
+    pub fn from_axes(origin: DVec3, u: DVec3, v: DVec3) -> Self {
+        let u = u.normalize();
+        let v = v.normalize();
+        assert!(
+            u.dot(v).abs() < 1e-10,
+            "The axes of a slice plane must be orthogonal!"
+        );
+        Self { origin, u, v }
+    }
+
+    /// The plane through `origin` perpendicular to the z-axis, with the x- and y-axis as coordinate axes.
+    pub fn xy(origin: DVec3) -> Self {
+        Self::from_axes(origin, DVec3::X, DVec3::Y)
+    }
+
+    /// Get the origin of the coordinate frame of this plane.
+    pub fn origin(&self) -> DVec3 {
+        self.origin
+    }
+
+    /// Get the (unit) normal of this plane.
+    pub fn normal(&self) -> DVec3 {
+        self.u.cross(self.v)
+    }
+
+    /// Get the coordinates in this plane of the projection of a point onto it.
+    pub fn to_plane(&self, point: DVec3) -> DVec2 {
+        let dx = point - self.origin;
+        DVec2::new(dx.dot(self.u), dx.dot(self.v))
+    }
+
+    /// Get the position in 3D of the point with the given coordinates in this plane.
+    pub fn to_world(&self, point: DVec2) -> DVec3 {
+        self.origin + point.x * self.u + point.y * self.v
+    }
+}
+
+/// A convex polygon in the coordinate frame of a [`SlicePlane`].
+#[derive(Debug, Clone)]
+pub struct Polygon2D {
+    vertices: Vec<DVec2>,
+}
+
+impl Polygon2D {
+    /// Get the vertices of this polygon, ordered counterclockwise.
+    pub fn vertices(&self) -> &[DVec2] {
+        &self.vertices
+    }
+
+    /// Get the area of this polygon.
+    pub fn area(&self) -> f64 {
+        0.5 * self.edges().map(|(a, b)| a.perp_dot(b)).sum::<f64>()
+    }
+
+    /// Get the centroid of this polygon.
+    pub fn centroid(&self) -> DVec2 {
+        let moment = self
+            .edges()
+            .map(|(a, b)| a.perp_dot(b) * (a + b))
+            .sum::<DVec2>();
+        moment / (6. * self.area())
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (DVec2, DVec2)> + '_ {
+        let n = self.vertices.len();
+        (0..n).map(move |i| (self.vertices[i], self.vertices[(i + 1) % n]))
+    }
+}
+
+impl Voronoi {
+    /// Intersect the cells of this (3D) tesselation with the given plane.
+    ///
+    /// Returns the indices of the cells intersecting the plane, together with their cross-sections as polygons in the
+    /// coordinate frame of the plane, ordered by cell index. Cells that were not constructed (in a partial
+    /// tesselation) are skipped. Cells of periodic tesselations are not wrapped, so cross-sections may extend beyond
+    /// the simulation volume, and the periodic images of a cell are not sliced.
+    pub fn slice(&self, plane: &SlicePlane) -> Vec<(usize, Polygon2D)> {
+        assert!(
+            matches!(self.dimensionality, Dimensionality::Dimensionality3D),
+            "Slicing is only supported for 3D tesselations!"
+        );
+        let simulation_volume = ConvexCell::init_simulation_volume(
+            self.anchor,
+            self.width,
+            self.periodic,
+            self.dimensionality,
+        );
+        let normal = plane.normal();
+
+        let slice = |cell_idx: usize| {
+            let cell = &self.cells[cell_idx];
+            if cell.volume() == 0. {
+                return None;
+            }
+            // Only reconstruct the cells with vertices on both sides of the plane
+            let (min, max) = cell
+                .faces(self)
+                .flat_map(|face| face.vertices(self))
+                .map(|v| normal.dot(*v - plane.origin))
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), d| {
+                    (min.min(d), max.max(d))
+                });
+            if min >= 0. || max <= 0. {
+                return None;
+            }
+            let mut convex_cell =
+                ConvexCell::reconstruct(self, cell_idx, &simulation_volume, self.dimensionality)?;
+            let plane_count = convex_cell.clipping_planes.len();
+            convex_cell.clip_by_half_space(normal, plane.origin, self.dimensionality);
+            if convex_cell.vertices.is_empty() || convex_cell.clipping_planes.len() == plane_count {
+                return None;
+            }
+            // The new face is oriented counterclockwise when seen from outside of the clipped cell, i.e. from below
+            // the plane.
+            let mut vertices = convex_cell.face_vertex_loops()[plane_count]
+                .iter()
+                .map(|&v| plane.to_plane(convex_cell.vertices[v].loc))
+                .collect::<Vec<_>>();
+            vertices.reverse();
+            (vertices.len() >= 3).then_some((cell_idx, Polygon2D { vertices }))
+        };
+
+        #[cfg(feature = "rayon")]
+        return (0..self.cells.len())
+            .into_par_iter()
+            .filter_map(slice)
+            .collect();
+        #[cfg(not(feature = "rayon"))]
+        return (0..self.cells.len()).filter_map(slice).collect();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+    use crate::voronoi::test::perturbed_grid;
+
+    #[test]
+    fn test_slice() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        let voronoi = Voronoi::build(&generators, anchor, width, 3, false, None);
+
+        // Axis aligned
+        let plane = SlicePlane::xy(DVec3::new(0., 0., 0.4));
+        let slices = voronoi.slice(&plane);
+        let area = slices.iter().map(|(_, p)| p.area()).sum::<f64>();
+        assert_approx_eq!(f64, area, 1., epsilon = 1e-10);
+        for (cell_idx, polygon) in slices.iter() {
+            assert!(polygon.area() > 0.);
+            let centroid = plane.to_world(polygon.centroid());
+            assert_eq!(voronoi.cells_at(&[centroid])[0], *cell_idx);
+        }
+
+        // Oblique: the cross-section of the unit cube through its center perpendicular to a diagonal is a regular
+        // hexagon
+        let plane = SlicePlane::new(DVec3::splat(0.5), DVec3::ONE);
+        assert!(plane.normal().distance(DVec3::ONE.normalize()) < 1e-12);
+        let point = DVec3::new(0.3, 0.2, 0.1);
+        assert!(
+            plane
+                .to_world(plane.to_plane(point))
+                .distance(point - plane.normal().dot(point - plane.origin()) * plane.normal())
+                < 1e-12
+        );
+        let slices = voronoi.slice(&plane);
+        let area = slices.iter().map(|(_, p)| p.area()).sum::<f64>();
+        assert_approx_eq!(f64, area, 0.75 * 3f64.sqrt(), epsilon = 1e-10);
+
+        // Outside of the tesselation
+        assert!(voronoi.slice(&SlicePlane::xy(DVec3::splat(2.))).is_empty());
+    }
+}
@@ -0,0 +1,481 @@
+use std::{collections::HashMap, f64::consts::PI};
+
+use glam::DVec3;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use super::Generator;
+use crate::{
+    rtree_nn,
+    spatial::{build_rtree, RTree},
+};
+
+/// A cell of a [`SphericalVoronoi`] tesselation.
+#[derive(Debug, Clone)]
+pub struct SphericalVoronoiCell {
+    loc: DVec3,
+    centroid: DVec3,
+    area: f64,
+    edge_connections_offset: usize,
+    edge_count: usize,
+}
+
+impl SphericalVoronoiCell {
+    /// Get the position of the generator of this cell (on the unit sphere).
+    pub fn loc(&self) -> DVec3 {
+        self.loc
+    }
+
+    /// Get the spherical centroid of this cell, i.e. the (area weighted) mean position of the cell projected back
+    /// onto the unit sphere.
+    pub fn centroid(&self) -> DVec3 {
+        self.centroid
+    }
+
+    /// Get the area of this cell on the unit sphere (i.e. the solid angle it subtends).
+    pub fn area(&self) -> f64 {
+        self.area
+    }
+
+    /// Get the indices of the edges of this cell, ordered counterclockwise (seen from outside the sphere).
+    pub fn edge_indices<'a>(&'a self, voronoi: &'a SphericalVoronoi) -> &'a [usize] {
+        &voronoi.cell_edge_connections
+            [self.edge_connections_offset..(self.edge_connections_offset + self.edge_count)]
+    }
+
+    /// Get an `Iterator` over the edges of this cell.
+    pub fn edges<'a>(
+        &'a self,
+        voronoi: &'a SphericalVoronoi,
+    ) -> impl Iterator<Item = &'a SphericalVoronoiEdge> + 'a {
+        self.edge_indices(voronoi)
+            .iter()
+            .map(|&i| &voronoi.edges[i])
+    }
+
+    /// Get the number of edges of this cell.
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+}
+
+/// An edge of a [`SphericalVoronoi`] tesselation: a great circle arc between the cells on its left and right.
+#[derive(Debug, Clone)]
+pub struct SphericalVoronoiEdge {
+    left: usize,
+    right: usize,
+    start: DVec3,
+    end: DVec3,
+    length: f64,
+}
+
+impl SphericalVoronoiEdge {
+    /// Get the index of the cell on the _left_ of this edge.
+    pub fn left(&self) -> usize {
+        self.left
+    }
+
+    /// Get the index of the cell on the _right_ of this edge.
+    pub fn right(&self) -> usize {
+        self.right
+    }
+
+    /// Get the start point of this edge. Edges run counterclockwise around their left cell (seen from outside the
+    /// sphere).
+    pub fn start(&self) -> DVec3 {
+        self.start
+    }
+
+    /// Get the end point of this edge (equal to the start point for an edge spanning a full great circle).
+    pub fn end(&self) -> DVec3 {
+        self.end
+    }
+
+    /// Get the length of this edge on the unit sphere (i.e. the angle it subtends).
+    pub fn length(&self) -> f64 {
+        self.length
+    }
+
+    /// Get the unit normal of the great circle of this edge, pointing towards the left cell.
+    pub fn normal(&self, voronoi: &SphericalVoronoi) -> DVec3 {
+        (voronoi.cells[self.left].loc - voronoi.cells[self.right].loc).normalize()
+    }
+}
+
+/// A Voronoi tesselation of the unit sphere (using the geodesic distance).
+///
+/// The cells are constructed by clipping a spherical polygon with the great circles bisecting the generator and its
+/// nearest neighbours (i.e. with planes through the origin), until the same safety criterion as for the (flat)
+/// [`crate::Voronoi`] tesselation is met.
+pub struct SphericalVoronoi {
+    cells: Vec<SphericalVoronoiCell>,
+    edges: Vec<SphericalVoronoiEdge>,
+    cell_edge_connections: Vec<usize>,
+}
+
+/// A part of the boundary of a spherical cell along the great circle shared with a single neighbour.
+struct SphericalEdgeRun {
+    neighbour: usize,
+    start: DVec3,
+    end: DVec3,
+    length: f64,
+}
+
+/// A convex spherical polygon, stored as its vertices (ordered counterclockwise seen from outside the sphere) and
+/// the neighbour whose bisector contains the arc starting at every vertex.
+struct SphericalPolygon {
+    loc: DVec3,
+    vertices: Vec<DVec3>,
+    neighbours: Vec<usize>,
+    normals: Vec<DVec3>,
+}
+
+fn angle(a: DVec3, b: DVec3) -> f64 {
+    a.cross(b).length().atan2(a.dot(b))
+}
+
+impl SphericalPolygon {
+    /// Initialize the spherical polygon as the hemisphere `normal . x >= 0`, bounded by the bisector with the
+    /// neighbour with index `neighbour`.
+    fn hemisphere(loc: DVec3, normal: DVec3, neighbour: usize) -> Self {
+        let u = normal.any_orthonormal_vector();
+        let w = normal.cross(u);
+        Self {
+            loc,
+            vertices: vec![u, w, -u, -w],
+            neighbours: vec![neighbour; 4],
+            normals: vec![normal; 4],
+        }
+    }
+
+    /// Clip this polygon with the hemisphere `normal . x >= 0`.
+    fn clip(&mut self, normal: DVec3, neighbour: usize) {
+        let signs = self
+            .vertices
+            .iter()
+            .map(|v| v.dot(normal))
+            .collect::<Vec<_>>();
+        if signs.iter().all(|&s| s >= 0.) {
+            return;
+        }
+
+        let count = self.vertices.len();
+        let mut vertices = Vec::with_capacity(count + 1);
+        let mut neighbours = Vec::with_capacity(count + 1);
+        let mut normals = Vec::with_capacity(count + 1);
+        let mut push = |vertex: DVec3, ngb: usize, n: DVec3| {
+            // Do not create degenerate (zero length) arcs when a vertex lies on the clipping plane
+            if vertices
+                .last()
+                .is_some_and(|&last: &DVec3| last.distance_squared(vertex) < 1e-28)
+            {
+                vertices.pop();
+                neighbours.pop();
+                normals.pop();
+            }
+            vertices.push(vertex);
+            neighbours.push(ngb);
+            normals.push(n);
+        };
+        for i in 0..count {
+            let j = (i + 1) % count;
+            let v_i = self.vertices[i];
+            let (s_i, s_j) = (signs[i], signs[j]);
+            if s_i >= 0. {
+                push(v_i, self.neighbours[i], self.normals[i]);
+            }
+            if (s_i >= 0.) != (s_j >= 0.) {
+                // Arcs can span up to half a great circle (e.g. the arcs of a lune), so we cannot simply
+                // interpolate between v_i and v_j: parametrize the arc by its angle from v_i instead.
+                let direction = self.normals[i].cross(v_i);
+                let theta = (-s_i).atan2(normal.dot(direction)).rem_euclid(PI);
+                let intersection = self.point_on_arc(i, theta);
+                if s_i >= 0. {
+                    push(intersection, neighbour, normal);
+                } else {
+                    push(intersection, self.neighbours[i], self.normals[i]);
+                }
+            }
+        }
+        if vertices.len() > 1 && vertices[0].distance_squared(vertices[vertices.len() - 1]) < 1e-28
+        {
+            vertices.pop();
+            neighbours.pop();
+            normals.pop();
+        }
+
+        self.vertices = vertices;
+        self.neighbours = neighbours;
+        self.normals = normals;
+    }
+
+    /// Get the point at the given angle along the arc starting at the vertex with index `i`.
+    fn point_on_arc(&self, i: usize, theta: f64) -> DVec3 {
+        let v = self.vertices[i];
+        let direction = self.normals[i].cross(v);
+        (theta.cos() * v + theta.sin() * direction).normalize()
+    }
+
+    fn safety_radius(&self) -> f64 {
+        2. * self
+            .vertices
+            .iter()
+            .map(|v| v.distance_squared(self.loc))
+            .fold(0., f64::max)
+            .sqrt()
+    }
+
+    /// Build the Spherical Voronoi cell of the generator with index `idx`.
+    fn build(idx: usize, generators: &[DVec3], rtree: &RTree<Generator>) -> Self {
+        let loc = generators[idx];
+        let mut nearest_neighbours = rtree_nn::nn_iter(rtree, loc);
+        assert_eq!(
+            nearest_neighbours
+                .next()
+                .expect("Nearest neighbours cannot be empty!")
+                .0,
+            idx,
+            "First nearest neighbour should be the generator itself!"
+        );
+        let mut polygon: Option<Self> = None;
+        for (ngb_idx, _) in nearest_neighbours {
+            let dx = loc - generators[ngb_idx];
+            let dist = dx.length();
+            assert!(dist.is_finite() && dist > 0.0, "Degenerate point set!");
+            let normal = dx / dist;
+            match polygon.as_mut() {
+                None => polygon = Some(Self::hemisphere(loc, normal, ngb_idx)),
+                Some(polygon) => {
+                    if polygon.safety_radius() < dist {
+                        break;
+                    }
+                    polygon.clip(normal, ngb_idx);
+                }
+            }
+        }
+
+        polygon.expect("A spherical Voronoi tesselation needs at least 2 generators!")
+    }
+
+    /// Compute the area and centroid of this polygon and merge the consecutive arcs shared with the same neighbour.
+    fn finalize(&self) -> (f64, DVec3, Vec<SphericalEdgeRun>) {
+        let count = self.vertices.len();
+        let mut area = 0.;
+        let mut moment = DVec3::ZERO;
+        for i in 0..count {
+            let (a, b) = (self.vertices[i], self.vertices[(i + 1) % count]);
+            let length = angle(a, b);
+            // Solid angle of the spherical triangles (p, a, m) and (p, m, b), see Van Oosterom & Strackee (1983).
+            // The arc is split at its midpoint m, since the formula breaks down for arcs spanning half a great
+            // circle.
+            let m = self.point_on_arc(i, 0.5 * length);
+            let p = self.loc;
+            let solid_angle = |a: DVec3, b: DVec3| {
+                2. * p.dot(a.cross(b)).atan2(1. + p.dot(a) + a.dot(b) + b.dot(p))
+            };
+            area += solid_angle(a, m) + solid_angle(m, b);
+            // The first moment of a spherical polygon is half the sum of its edges' lengths times their inward
+            // normals.
+            moment += 0.5 * length * self.normals[i];
+        }
+
+        // Find the start of a run of arcs shared with the same neighbour
+        let first = (0..count)
+            .find(|&i| self.neighbours[i] != self.neighbours[(i + count - 1) % count])
+            .unwrap_or(0);
+        let mut runs: Vec<SphericalEdgeRun> = vec![];
+        for k in 0..count {
+            let i = (first + k) % count;
+            let (a, b) = (self.vertices[i], self.vertices[(i + 1) % count]);
+            match runs.last_mut() {
+                Some(run) if run.neighbour == self.neighbours[i] => {
+                    run.end = b;
+                    run.length += angle(a, b);
+                }
+                _ => runs.push(SphericalEdgeRun {
+                    neighbour: self.neighbours[i],
+                    start: a,
+                    end: b,
+                    length: angle(a, b),
+                }),
+            }
+        }
+
+        let centroid = if moment.length_squared() > 0. {
+            moment.normalize()
+        } else {
+            self.loc
+        };
+        (area, centroid, runs)
+    }
+}
+
+impl SphericalVoronoi {
+    /// Construct the spherical Voronoi tesselation of the given generators.
+    ///
+    /// The generators are projected onto the unit sphere. At least 2 (distinct) generators are needed.
+    pub fn build(generators: &[DVec3]) -> Self {
+        span!("build_spherical_voronoi");
+        let generators = generators
+            .iter()
+            .map(|g| {
+                let g = g.normalize();
+                assert!(g.is_finite(), "Generators cannot be located at the origin!");
+                g
+            })
+            .collect::<Vec<_>>();
+        let rtree = build_rtree(&generators, 3);
+
+        let build = |idx: usize| SphericalPolygon::build(idx, &generators, &rtree).finalize();
+        #[cfg(feature = "rayon")]
+        let polygons = (0..generators.len())
+            .into_par_iter()
+            .map(build)
+            .collect::<Vec<_>>();
+        #[cfg(not(feature = "rayon"))]
+        let polygons = (0..generators.len()).map(build).collect::<Vec<_>>();
+
+        // Every edge is constructed by the cell with the lower index, and linked to the cell with the higher index.
+        let mut cells = Vec::with_capacity(generators.len());
+        let mut edges = vec![];
+        let mut cell_edge_connections = vec![];
+        let mut edge_lookup = HashMap::new();
+        for (idx, (area, centroid, runs)) in polygons.into_iter().enumerate() {
+            let edge_connections_offset = cell_edge_connections.len();
+            for run in runs {
+                let key = (idx.min(run.neighbour), idx.max(run.neighbour));
+                let existing = if run.neighbour < idx {
+                    edge_lookup.get(&key).copied()
+                } else {
+                    None
+                };
+                let edge_idx = existing.unwrap_or_else(|| {
+                    edges.push(SphericalVoronoiEdge {
+                        left: idx,
+                        right: run.neighbour,
+                        start: run.start,
+                        end: run.end,
+                        length: run.length,
+                    });
+                    edge_lookup.insert(key, edges.len() - 1);
+                    edges.len() - 1
+                });
+                cell_edge_connections.push(edge_idx);
+            }
+            cells.push(SphericalVoronoiCell {
+                loc: generators[idx],
+                centroid,
+                area,
+                edge_connections_offset,
+                edge_count: cell_edge_connections.len() - edge_connections_offset,
+            });
+        }
+
+        Self {
+            cells,
+            edges,
+            cell_edge_connections,
+        }
+    }
+
+    /// Get the cells of this tesselation.
+    pub fn cells(&self) -> &[SphericalVoronoiCell] {
+        self.cells.as_ref()
+    }
+
+    /// Get the edges of this tesselation.
+    pub fn edges(&self) -> &[SphericalVoronoiEdge] {
+        self.edges.as_ref()
+    }
+
+    /// Get the edge indices of all cells (see [`SphericalVoronoiCell::edge_indices`]).
+    pub fn cell_edge_connections(&self) -> &[usize] {
+        self.cell_edge_connections.as_ref()
+    }
+
+    /// Get the vertices of the cell with the given index, ordered counterclockwise (seen from outside the sphere).
+    pub fn cell_vertices(&self, cell_idx: usize) -> Vec<DVec3> {
+        self.cells[cell_idx]
+            .edges(self)
+            .map(|edge| {
+                if edge.left == cell_idx {
+                    edge.start
+                } else {
+                    edge.end
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+    use rand::{distributions::Uniform, prelude::*};
+
+    use super::*;
+
+    #[test]
+    fn test_octahedron() {
+        let generators = [
+            DVec3::X,
+            DVec3::NEG_X,
+            DVec3::Y,
+            DVec3::NEG_Y,
+            DVec3::Z,
+            DVec3::NEG_Z,
+        ];
+        let voronoi = SphericalVoronoi::build(&generators);
+        assert_eq!(voronoi.edges().len(), 12);
+        for cell in voronoi.cells() {
+            assert_approx_eq!(f64, cell.area(), 4. * PI / 6., epsilon = 1e-12);
+            assert!(cell.centroid().distance(cell.loc()) < 1e-12);
+            assert_eq!(cell.edge_count(), 4);
+        }
+        for edge in voronoi.edges() {
+            assert_approx_eq!(f64, edge.length(), (1. / 3f64).acos(), epsilon = 1e-12);
+        }
+
+        // Two generators split the sphere in two hemispheres along a single edge
+        let voronoi = SphericalVoronoi::build(&[DVec3::Z, DVec3::NEG_Z]);
+        assert_eq!(voronoi.edges().len(), 1);
+        assert_approx_eq!(f64, voronoi.edges()[0].length(), 2. * PI, epsilon = 1e-12);
+        assert_approx_eq!(f64, voronoi.cells()[0].area(), 2. * PI, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_spherical_voronoi() {
+        let mut rng = thread_rng();
+        let distr = Uniform::new(-1., 1.);
+        let generators = (0..200)
+            .map(|_| DVec3::new(rng.sample(distr), rng.sample(distr), rng.sample(distr)))
+            .collect::<Vec<_>>();
+        let voronoi = SphericalVoronoi::build(&generators);
+
+        let total_area = voronoi.cells().iter().map(|c| c.area()).sum::<f64>();
+        assert_approx_eq!(f64, total_area, 4. * PI, epsilon = 1e-10);
+        // Euler characteristic of the sphere for a tesselation with 3 edges meeting at every vertex
+        assert_eq!(voronoi.edges().len(), 3 * generators.len() - 6);
+
+        for edge in voronoi.edges() {
+            let left = voronoi.cells()[edge.left()].loc();
+            let right = voronoi.cells()[edge.right()].loc();
+            for vertex in [edge.start(), edge.end()] {
+                assert_approx_eq!(f64, vertex.length(), 1., epsilon = 1e-12);
+                assert_approx_eq!(
+                    f64,
+                    vertex.distance(left),
+                    vertex.distance(right),
+                    epsilon = 1e-10
+                );
+            }
+            // Edges run counterclockwise around their left cell
+            assert!(left.dot(edge.start().cross(edge.end())) > 0.);
+            assert!(edge.normal(&voronoi).dot(left) > 0.);
+        }
+        for (i, cell) in voronoi.cells().iter().enumerate() {
+            assert_eq!(voronoi.cell_vertices(i).len(), cell.edge_count());
+            assert!(cell.centroid().distance(cell.loc()) < 1.);
+        }
+    }
+}
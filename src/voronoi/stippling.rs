@@ -0,0 +1,120 @@
+use glam::{DVec2, DVec3};
+use rand::Rng;
+
+use crate::sampling::density_sampling;
+
+use super::Voronoi2D;
+
+impl Voronoi2D {
+    /// Stipple an image: distribute `count` generators according to the darkness of the image and relax them with
+    /// `iterations` density-weighted Lloyd iterations.
+    ///
+    /// `image` contains the (non-negative) density of `image_width` by `image_height` pixels in row-major order,
+    /// starting from the top-left corner (the same layout as [`Voronoi2D::rasterize`]); typically the darkness of a
+    /// grayscale image. The tesselation covers the image with one unit of length per pixel, i.e. its simulation volume
+    /// has anchor `(0, 0)` and width `(image_width, image_height)`.
+    ///
+    /// The initial generators are drawn from the density (see [`density_sampling`]). In every iteration, the
+    /// generators are moved to the density-weighted centroids of their cells, which are estimated by rasterizing the
+    /// tesselation at the resolution of the image. Generators of cells without any density are left in place.
+    ///
+    /// Returns the relaxed tesselation: the cell generators are the final stipple positions, and
+    /// [`Voronoi2D::cell_polygon`] gives the cell polygons.
+    pub fn stipple<R: Rng + ?Sized>(
+        image: &[f64],
+        image_width: usize,
+        image_height: usize,
+        count: usize,
+        iterations: usize,
+        rng: &mut R,
+    ) -> Self {
+        assert_eq!(
+            image.len(),
+            image_width * image_height,
+            "Must have one density value per pixel!"
+        );
+        let max_density = image.iter().copied().fold(0., f64::max);
+        assert!(max_density > 0., "The image must have a positive density!");
+
+        let width = DVec2::new(image_width as f64, image_height as f64);
+        let pixel = |loc: DVec3| {
+            let col = (loc.x as usize).min(image_width - 1);
+            let row = image_height - 1 - (loc.y as usize).min(image_height - 1);
+            row * image_width + col
+        };
+        let mut generators = density_sampling(
+            DVec3::ZERO,
+            width.extend(1.),
+            2,
+            count,
+            |loc| image[pixel(loc)],
+            max_density,
+            rng,
+        )
+        .into_iter()
+        .map(|g| g.truncate())
+        .collect::<Vec<_>>();
+
+        for _ in 0..iterations {
+            let voronoi = Self::build(&generators, DVec2::ZERO, width, false);
+            let mut weights = vec![0.; count];
+            let mut moments = vec![DVec2::ZERO; count];
+            for (i, cell_idx) in voronoi
+                .rasterize(image_width, image_height)
+                .into_iter()
+                .enumerate()
+            {
+                let center = DVec2::new(
+                    (i % image_width) as f64 + 0.5,
+                    (image_height - i / image_width) as f64 - 0.5,
+                );
+                weights[cell_idx] += image[i];
+                moments[cell_idx] += image[i] * center;
+            }
+            for (generator, (weight, moment)) in
+                generators.iter_mut().zip(weights.into_iter().zip(moments))
+            {
+                if weight > 0. {
+                    *generator = moment / weight;
+                }
+            }
+        }
+
+        Self::build(&generators, DVec2::ZERO, width, false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn test_stipple() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let (width, height) = (40, 20);
+
+        // Only the left half of the image is dark
+        let image = (0..(width * height))
+            .map(|i| if i % width < width / 2 { 1. } else { 0. })
+            .collect::<Vec<_>>();
+        let voronoi = Voronoi2D::stipple(&image, width, height, 50, 10, &mut rng);
+        assert_eq!(voronoi.cell_count(), 50);
+        for cell in voronoi.cells() {
+            assert!(cell.loc().x < width as f64 / 2.);
+        }
+
+        // For a uniform density, Lloyd iterations bring the generators closer to their cell centroids
+        let image = vec![1.; width * height];
+        let offset = |voronoi: &Voronoi2D| {
+            voronoi
+                .cells()
+                .map(|cell| cell.loc().distance(cell.centroid()))
+                .sum::<f64>()
+        };
+        let initial = Voronoi2D::stipple(&image, width, height, 50, 0, &mut rng);
+        let relaxed = Voronoi2D::stipple(&image, width, height, 50, 20, &mut rng);
+        assert!(offset(&relaxed) < 0.5 * offset(&initial));
+    }
+}
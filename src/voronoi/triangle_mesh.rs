@@ -0,0 +1,197 @@
+use glam::DVec3;
+
+use crate::util::weld;
+
+use super::{Voronoi, VoronoiCell};
+
+/// An indexed triangle mesh.
+///
+/// Triangles are oriented counterclockwise when seen from outside the enclosed volume.
+#[derive(Default, Debug, Clone)]
+pub struct TriangleMesh {
+    vertices: Vec<DVec3>,
+    triangles: Vec<[usize; 3]>,
+}
+
+impl TriangleMesh {
+    /// Build a mesh by welding the vertices of the given convex, counterclockwise polygons and
+    /// fan-triangulating them. Polygons flagged as `reversed` are flipped.
+    pub(super) fn from_polygons<'a>(
+        polygons: impl Iterator<Item = (&'a [DVec3], bool)>,
+        tolerance: f64,
+    ) -> Self {
+        let mut points = vec![];
+        let mut polygon_offsets = vec![0];
+        let mut polygon_reversed = vec![];
+        for (polygon, reversed) in polygons {
+            points.extend_from_slice(polygon);
+            polygon_offsets.push(points.len());
+            polygon_reversed.push(reversed);
+        }
+        let (vertices, welded) = weld(&points, tolerance);
+
+        let mut triangles = vec![];
+        for (i, &reversed) in polygon_reversed.iter().enumerate() {
+            let mut polygon = welded[polygon_offsets[i]..polygon_offsets[i + 1]].to_vec();
+            polygon.dedup();
+            while polygon.len() > 1 && polygon.first() == polygon.last() {
+                polygon.pop();
+            }
+            if reversed {
+                polygon.reverse();
+            }
+            for j in 1..polygon.len().saturating_sub(1) {
+                triangles.push([polygon[0], polygon[j], polygon[j + 1]]);
+            }
+        }
+
+        Self {
+            vertices,
+            triangles,
+        }
+    }
+
+    /// Get the vertices of this mesh.
+    pub fn vertices(&self) -> &[DVec3] {
+        self.vertices.as_ref()
+    }
+
+    /// Get the triangles of this mesh as triplets of indices into [`TriangleMesh::vertices`].
+    pub fn triangles(&self) -> &[[usize; 3]] {
+        self.triangles.as_ref()
+    }
+
+    /// Get the total area of this mesh.
+    pub fn area(&self) -> f64 {
+        self.triangles
+            .iter()
+            .map(|&[a, b, c]| {
+                let (a, b, c) = (self.vertices[a], self.vertices[b], self.vertices[c]);
+                0.5 * (b - a).cross(c - a).length()
+            })
+            .sum()
+    }
+
+    /// Get the volume enclosed by this mesh (only meaningful for closed meshes).
+    pub fn volume(&self) -> f64 {
+        self.triangles
+            .iter()
+            .map(|&[a, b, c]| self.vertices[a].dot(self.vertices[b].cross(self.vertices[c])) / 6.)
+            .sum()
+    }
+}
+
+impl Voronoi {
+    /// Extract the boundary surface of the union of the cells with the given indices as a closed triangle mesh.
+    ///
+    /// Only the faces between selected cells and unselected cells (or the boundary of the simulation volume) are kept.
+    /// The vertices are welded and the triangles are oriented outwards.
+    /// For periodic tesselations, faces between two selected cells across a periodic boundary are also kept, such that
+    /// the surface remains closed.
+    pub fn boundary_surface(&self, cells: &[usize]) -> TriangleMesh {
+        let mut selected = vec![false; self.cells.len()];
+        for &idx in cells {
+            selected[idx] = true;
+        }
+
+        // Every face is oriented outwards w.r.t. its left cell.
+        let polygons = self.faces.iter().filter_map(|face| {
+            let left_selected = selected[face.left()];
+            let right_selected = face.right().is_some_and(|right| selected[right]);
+            if face.shift().is_some() {
+                // Periodic faces are stored for both cells separately
+                left_selected.then(|| (face.vertices(self), false))
+            } else {
+                (left_selected != right_selected).then(|| (face.vertices(self), right_selected))
+            }
+        });
+
+        TriangleMesh::from_polygons(polygons, 1e-10 * self.width.max_element())
+    }
+}
+
+impl VoronoiCell {
+    /// Get the surface of this cell as a closed triangle mesh.
+    ///
+    /// The faces of the cell are fan-triangulated, the vertices are welded and the triangles are oriented outwards.
+    /// For periodic tesselations, the cell is not wrapped, i.e. the mesh is given in the frame of the generator of this
+    /// cell. The mesh is empty for cells that were not constructed (in a partial tesselation).
+    pub fn triangle_mesh(&self, voronoi: &Voronoi) -> TriangleMesh {
+        // Faces are oriented outwards w.r.t. their left cell, so faces with this cell on their right are flipped.
+        let polygons = self.faces(voronoi).map(|face| {
+            let reversed = face.normal().dot(face.centroid() - self.loc()) < 0.;
+            (face.vertices(voronoi), reversed)
+        });
+        TriangleMesh::from_polygons(polygons, 1e-10 * voronoi.width.max_element())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+    use crate::voronoi::test::perturbed_grid;
+
+    fn check_closed(mesh: &TriangleMesh) {
+        // Every directed edge should have exactly one oppositely directed counterpart
+        let mut edges = vec![];
+        for &[a, b, c] in mesh.triangles() {
+            edges.extend([(a, b), (b, c), (c, a)]);
+        }
+        edges.sort();
+        for &(a, b) in edges.iter() {
+            assert!(edges.binary_search(&(b, a)).is_ok());
+        }
+        let len = edges.len();
+        edges.dedup();
+        assert_eq!(edges.len(), len);
+    }
+
+    #[test]
+    fn test_boundary_surface() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        for periodic in [false, true] {
+            let voronoi = Voronoi::build(&generators, anchor, width, 3, periodic, None);
+            let cells = (0..generators.len())
+                .filter(|&i| generators[i].x < 0.5)
+                .collect::<Vec<_>>();
+            let mesh = voronoi.boundary_surface(&cells);
+            check_closed(&mesh);
+            let volume = cells
+                .iter()
+                .map(|&i| voronoi.cells()[i].volume())
+                .sum::<f64>();
+            assert_approx_eq!(f64, mesh.volume(), volume, epsilon = 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_boundary_surface_all() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 3, 0.5);
+        let voronoi = Voronoi::build(&generators, anchor, width, 3, false, None);
+        let mesh = voronoi.boundary_surface(&(0..generators.len()).collect::<Vec<_>>());
+        check_closed(&mesh);
+        assert_approx_eq!(f64, mesh.volume(), 1., epsilon = 1e-10);
+        assert_approx_eq!(f64, mesh.area(), 6., epsilon = 1e-10);
+    }
+
+    #[test]
+    fn test_cell_triangle_mesh() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 3, 0.5);
+        for periodic in [false, true] {
+            let voronoi = Voronoi::build(&generators, anchor, width, 3, periodic, None);
+            for cell in voronoi.cells() {
+                let mesh = cell.triangle_mesh(&voronoi);
+                check_closed(&mesh);
+                assert_approx_eq!(f64, mesh.volume(), cell.volume(), epsilon = 1e-10);
+            }
+        }
+    }
+}
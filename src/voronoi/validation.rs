@@ -0,0 +1,248 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::util::weld;
+
+use super::{Dimensionality, Voronoi};
+
+/// A topological or geometric defect of a Voronoi cell, see [`Voronoi::validate`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TopologyDefect {
+    /// The (welded) vertices, edges and faces of the cell violate Euler's formula `V - E + F = 2`.
+    EulerCharacteristic {
+        vertex_count: usize,
+        edge_count: usize,
+        face_count: usize,
+    },
+    /// The vertex loop of the face with the given index is not closed off by the other faces of the cell, i.e. one of
+    /// its edges is not shared by exactly one other face of the cell.
+    OpenFace { face_idx: usize },
+    /// The vertices of the face with the given index lie up to `max_distance` away from its plane.
+    NonPlanarFace { face_idx: usize, max_distance: f64 },
+}
+
+/// A defect of a single Voronoi cell.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellDefect {
+    cell: usize,
+    defect: TopologyDefect,
+}
+
+impl CellDefect {
+    /// Get the index of the offending cell.
+    pub fn cell(&self) -> usize {
+        self.cell
+    }
+
+    /// Get the defect of the cell.
+    pub fn defect(&self) -> TopologyDefect {
+        self.defect
+    }
+}
+
+/// A report of the topological validation of a Voronoi tesselation, see [`Voronoi::validate`].
+#[derive(Debug, Clone)]
+pub struct ValidationReport {
+    checked_cells: usize,
+    defects: Vec<CellDefect>,
+}
+
+impl ValidationReport {
+    /// Whether no defects were found.
+    pub fn is_valid(&self) -> bool {
+        self.defects.is_empty()
+    }
+
+    /// Get the number of (constructed) cells that were checked.
+    pub fn checked_cells(&self) -> usize {
+        self.checked_cells
+    }
+
+    /// Get all defects, ordered by cell.
+    pub fn defects(&self) -> &[CellDefect] {
+        self.defects.as_ref()
+    }
+
+    /// Get the (sorted) indices of the cells with at least one defect.
+    pub fn invalid_cells(&self) -> Vec<usize> {
+        let mut cells = self.defects.iter().map(|d| d.cell).collect::<Vec<_>>();
+        cells.dedup();
+        cells
+    }
+}
+
+impl Voronoi {
+    /// Get the welded vertex loops (with consecutive duplicates removed) of the faces of the cell with the given
+    /// index, together with the indices of the faces. Faces that collapse to less than three vertices are skipped.
+    pub(super) fn cell_face_loops(
+        &self,
+        cell_idx: usize,
+        tolerance: f64,
+    ) -> Vec<(usize, Vec<usize>)> {
+        let face_indices = self.cells[cell_idx].face_indices(self);
+        let points = face_indices
+            .iter()
+            .flat_map(|&face_idx| self.faces[face_idx].vertices(self).iter().copied())
+            .collect::<Vec<_>>();
+        let (_, welded) = weld(&points, tolerance);
+        let mut offset = 0;
+        let mut face_loops = vec![];
+        for &face_idx in face_indices {
+            let n = self.faces[face_idx].vertex_count();
+            let mut face_loop = welded[offset..offset + n].to_vec();
+            offset += n;
+            face_loop.dedup();
+            while face_loop.len() > 1 && face_loop.first() == face_loop.last() {
+                face_loop.pop();
+            }
+            if face_loop.len() >= 3 {
+                face_loops.push((face_idx, face_loop));
+            }
+        }
+        face_loops
+    }
+
+    /// Validate the topology of the cells of this tesselation.
+    ///
+    /// For every constructed cell (i.e. with nonzero volume), the vertices of its faces are welded within
+    /// `tolerance` and the cell is checked to be a closed polyhedron satisfying `V - E + F = 2`, with every face
+    /// planar within `tolerance`. Faces that collapse to less than three vertices after welding are ignored. For 1D
+    /// and 2D tesselations, the faces orthogonal to the unused dimensions are not stored, so only planarity is
+    /// checked.
+    ///
+    /// This catches clipping bugs for degenerate inputs far earlier than wrong volumes do.
+    pub fn validate(&self, tolerance: f64) -> ValidationReport {
+        let mut checked_cells = 0;
+        let mut defects = vec![];
+        for (cell_idx, cell) in self.cells.iter().enumerate() {
+            if cell.volume() <= 0. {
+                continue;
+            }
+            checked_cells += 1;
+            let face_indices = cell.face_indices(self);
+
+            // Planarity
+            for &face_idx in face_indices {
+                let face = &self.faces[face_idx];
+                let max_distance = face
+                    .vertices(self)
+                    .iter()
+                    .map(|v| (*v - face.centroid()).dot(face.normal()).abs())
+                    .fold(0., f64::max);
+                if max_distance > tolerance {
+                    defects.push(CellDefect {
+                        cell: cell_idx,
+                        defect: TopologyDefect::NonPlanarFace {
+                            face_idx,
+                            max_distance,
+                        },
+                    });
+                }
+            }
+
+            if !matches!(self.dimensionality, Dimensionality::Dimensionality3D) {
+                continue;
+            }
+
+            let face_loops = self.cell_face_loops(cell_idx, tolerance);
+
+            let mut edges = HashMap::new();
+            let mut vertices = HashSet::new();
+            for (_, face_loop) in face_loops.iter() {
+                let n = face_loop.len();
+                for i in 0..n {
+                    let (a, b) = (face_loop[i], face_loop[(i + 1) % n]);
+                    *edges.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+                    vertices.insert(a);
+                }
+            }
+
+            let characteristic =
+                vertices.len() as i64 - edges.len() as i64 + face_loops.len() as i64;
+            if characteristic != 2 {
+                defects.push(CellDefect {
+                    cell: cell_idx,
+                    defect: TopologyDefect::EulerCharacteristic {
+                        vertex_count: vertices.len(),
+                        edge_count: edges.len(),
+                        face_count: face_loops.len(),
+                    },
+                });
+            }
+
+            for (face_idx, face_loop) in face_loops.iter() {
+                let n = face_loop.len();
+                let is_closed = (0..n).all(|i| {
+                    let (a, b) = (face_loop[i], face_loop[(i + 1) % n]);
+                    edges[&(a.min(b), a.max(b))] == 2
+                });
+                if !is_closed {
+                    defects.push(CellDefect {
+                        cell: cell_idx,
+                        defect: TopologyDefect::OpenFace {
+                            face_idx: *face_idx,
+                        },
+                    });
+                }
+            }
+        }
+
+        ValidationReport {
+            checked_cells,
+            defects,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use glam::DVec3;
+
+    use super::*;
+    use crate::voronoi::test::{perturbed_grid, perturbed_plane};
+
+    #[test]
+    fn test_validate() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        for pert in [0.5, 0.] {
+            let generators = perturbed_grid(anchor, width, 4, pert);
+            for periodic in [false, true] {
+                let voronoi = Voronoi::build(&generators, anchor, width, 3, periodic, None);
+                let report = voronoi.validate(1e-10);
+                assert!(report.is_valid(), "{:?}", report.defects());
+                assert_eq!(report.checked_cells(), generators.len());
+            }
+        }
+
+        let generators = perturbed_plane(anchor, width, 5, 0.5);
+        let voronoi = Voronoi::build(&generators, anchor, width, 2, false, None);
+        assert!(voronoi.validate(1e-10).is_valid());
+
+        // A partial tesselation only checks the constructed cells
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        let mask = (0..generators.len())
+            .map(|i| i % 3 == 0)
+            .collect::<Vec<_>>();
+        let voronoi = Voronoi::build_partial(&generators, &mask, anchor, width, 3, false, None);
+        let report = voronoi.validate(1e-10);
+        assert!(report.is_valid());
+        assert_eq!(report.checked_cells(), mask.iter().filter(|&&m| m).count());
+
+        // A corrupted face is detected
+        let mut voronoi = Voronoi::build(&generators, anchor, width, 3, false, None);
+        let face = &voronoi.faces[0];
+        let (left, offset) = (face.left(), face.vertex_offset());
+        voronoi.face_vertices[offset] += 0.01 * face.normal();
+        let report = voronoi.validate(1e-10);
+        assert!(!report.is_valid());
+        assert!(report.invalid_cells().contains(&left));
+        assert!(report.defects().iter().any(|d| matches!(
+            d.defect(),
+            TopologyDefect::NonPlanarFace { face_idx: 0, .. }
+        )));
+        assert!(report
+            .defects()
+            .iter()
+            .any(|d| matches!(d.defect(), TopologyDefect::OpenFace { face_idx: 0 })));
+    }
+}
@@ -0,0 +1,296 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use glam::DVec3;
+
+use crate::util::weld;
+
+use super::{Boundary, Voronoi};
+
+/// A face of a cell, as seen from that cell.
+struct CellFace {
+    neighbour: i64,
+    normal: DVec3,
+    area: f64,
+    /// Indices into the vertices of the cell, ordered counterclockwise when seen from outside the cell.
+    vertices: Vec<usize>,
+}
+
+/// The per-cell geometry needed for the voro++ format codes.
+struct CellGeometry {
+    faces: Vec<CellFace>,
+    vertices: Vec<DVec3>,
+}
+
+impl CellGeometry {
+    fn new(voronoi: &Voronoi, cell_idx: usize, welded: &[usize], points: &[DVec3]) -> Self {
+        let mut local_indices = HashMap::new();
+        let mut vertices = vec![];
+        let faces = voronoi.cells[cell_idx]
+            .faces(voronoi)
+            .map(|face| {
+                let offset = face.vertex_offset();
+                let mut face_vertices = (offset..(offset + face.vertex_count()))
+                    .map(|i| {
+                        *local_indices.entry(welded[i]).or_insert_with(|| {
+                            vertices.push(points[welded[i]]);
+                            vertices.len() - 1
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                let (neighbour, normal) = if face.left() == cell_idx {
+                    let neighbour = match face.right() {
+                        Some(right) => right as i64,
+                        None => wall_id(face.boundary().unwrap_or(Boundary::Cut)),
+                    };
+                    (neighbour, face.normal())
+                } else {
+                    face_vertices.reverse();
+                    (face.left() as i64, -face.normal())
+                };
+                CellFace {
+                    neighbour,
+                    normal,
+                    area: face.area(),
+                    vertices: face_vertices,
+                }
+            })
+            .collect();
+        Self { faces, vertices }
+    }
+
+    fn perimeter(&self, face: &CellFace) -> f64 {
+        let n = face.vertices.len();
+        (0..n)
+            .map(|i| {
+                self.vertices[face.vertices[i]].distance(self.vertices[face.vertices[(i + 1) % n]])
+            })
+            .sum()
+    }
+}
+
+/// The voro++ ids of the walls of the container (negative, to distinguish them from particle ids).
+fn wall_id(boundary: Boundary) -> i64 {
+    match boundary {
+        Boundary::XMin => -1,
+        Boundary::XMax => -2,
+        Boundary::YMin => -3,
+        Boundary::YMax => -4,
+        Boundary::ZMin => -5,
+        Boundary::ZMax => -6,
+        Boundary::Cut => -7,
+    }
+}
+
+fn join<T>(items: impl Iterator<Item = T>, f: impl Fn(T) -> String) -> String {
+    items.map(f).collect::<Vec<_>>().join(" ")
+}
+
+fn bracket(v: DVec3) -> String {
+    format!("({},{},{})", v.x, v.y, v.z)
+}
+
+impl Voronoi {
+    /// Write one line per cell to `writer`, formatted with a voro++ custom output format string (as used by
+    /// `container::print_custom` or the `-c` command line option of voro++).
+    ///
+    /// The supported format codes are:
+    /// * `%i`: the index of the cell, `%x`, `%y`, `%z` and `%q`: the position of its generator.
+    /// * `%w`: the number of vertices, `%p` and `%P`: the vertices relative to the generator and in global coordinates,
+    ///   `%o`: the number of faces meeting at every vertex.
+    /// * `%g`: the number of edges, `%E`: the total edge length, `%e`: the perimeter of every face.
+    /// * `%s`: the number of faces, `%F`: the total face area, `%A`: the frequency table of the face orders, `%a`: the
+    ///   order (number of vertices) of every face, `%f`: the area of every face, `%l`: the outward normal of every
+    ///   face, `%t`: the vertex indices of every face (counterclockwise when seen from outside the cell) and `%n`: the
+    ///   neighbour of every face.
+    /// * `%v`: the volume, `%c` and `%C`: the centroid relative to the generator and in global coordinates.
+    /// * `%%`: a literal `%`.
+    ///
+    /// As in voro++, faces on the boundary of the simulation volume have negative neighbour ids: `-1` to `-6` for the
+    /// lower and upper x, y and z boundaries and `-7` for [`Boundary::Cut`] faces. Unknown format codes (e.g. `%r`,
+    /// since generators have no radius) are copied verbatim. Cells that were not constructed (in a partial
+    /// tesselation) are skipped.
+    pub fn write_custom<W: Write>(&self, format: &str, mut writer: W) -> io::Result<()> {
+        let (points, welded) = weld(&self.face_vertices, 1e-10 * self.width.max_element());
+        for (cell_idx, cell) in self.cells.iter().enumerate() {
+            if cell.volume() == 0. {
+                continue;
+            }
+            let geometry = CellGeometry::new(self, cell_idx, &welded, &points);
+            let loc = cell.loc();
+
+            let mut line = String::new();
+            let mut chars = format.chars();
+            while let Some(c) = chars.next() {
+                if c != '%' {
+                    line.push(c);
+                    continue;
+                }
+                let Some(code) = chars.next() else {
+                    line.push(c);
+                    break;
+                };
+                let field = match code {
+                    'i' => cell_idx.to_string(),
+                    'x' => loc.x.to_string(),
+                    'y' => loc.y.to_string(),
+                    'z' => loc.z.to_string(),
+                    'q' => format!("{} {} {}", loc.x, loc.y, loc.z),
+                    'w' => geometry.vertices.len().to_string(),
+                    'p' => join(geometry.vertices.iter(), |&v| bracket(v - loc)),
+                    'P' => join(geometry.vertices.iter(), |&v| bracket(v)),
+                    'o' => {
+                        let mut orders = vec![0; geometry.vertices.len()];
+                        for v in geometry.faces.iter().flat_map(|f| f.vertices.iter()) {
+                            orders[*v] += 1;
+                        }
+                        join(orders.iter(), |o| o.to_string())
+                    }
+                    'g' => (geometry
+                        .faces
+                        .iter()
+                        .map(|f| f.vertices.len())
+                        .sum::<usize>()
+                        / 2)
+                    .to_string(),
+                    'E' => (0.5
+                        * geometry
+                            .faces
+                            .iter()
+                            .map(|f| geometry.perimeter(f))
+                            .sum::<f64>())
+                    .to_string(),
+                    'e' => join(geometry.faces.iter(), |f| geometry.perimeter(f).to_string()),
+                    's' => geometry.faces.len().to_string(),
+                    'F' => geometry
+                        .faces
+                        .iter()
+                        .map(|f| f.area)
+                        .sum::<f64>()
+                        .to_string(),
+                    'A' => {
+                        let max_order = geometry
+                            .faces
+                            .iter()
+                            .map(|f| f.vertices.len())
+                            .max()
+                            .unwrap_or(0);
+                        let mut frequencies = vec![0; max_order + 1];
+                        for f in geometry.faces.iter() {
+                            frequencies[f.vertices.len()] += 1;
+                        }
+                        join(frequencies.iter(), |f| f.to_string())
+                    }
+                    'a' => join(geometry.faces.iter(), |f| f.vertices.len().to_string()),
+                    'f' => join(geometry.faces.iter(), |f| f.area.to_string()),
+                    'l' => join(geometry.faces.iter(), |f| bracket(f.normal)),
+                    't' => join(geometry.faces.iter(), |f| {
+                        format!(
+                            "({})",
+                            join(f.vertices.iter(), |v| v.to_string()).replace(' ', ",")
+                        )
+                    }),
+                    'n' => join(geometry.faces.iter(), |f| f.neighbour.to_string()),
+                    'v' => cell.volume().to_string(),
+                    'c' => {
+                        let c = cell.centroid() - loc;
+                        format!("{} {} {}", c.x, c.y, c.z)
+                    }
+                    'C' => {
+                        let c = cell.centroid();
+                        format!("{} {} {}", c.x, c.y, c.z)
+                    }
+                    '%' => "%".to_string(),
+                    _ => format!("%{code}"),
+                };
+                line.push_str(&field);
+            }
+            writeln!(writer, "{line}")?;
+        }
+        Ok(())
+    }
+
+    /// Save this tesselation to a text file using a voro++ custom output format string, see
+    /// [`Voronoi::write_custom`].
+    pub fn save_custom<P: AsRef<Path>>(
+        &self,
+        format: &str,
+        filename: P,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut writer = BufWriter::new(File::create(filename)?);
+        self.write_custom(format, &mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+    use crate::voronoi::test::perturbed_grid;
+
+    #[test]
+    fn test_write_custom() {
+        // A single cube
+        let voronoi = Voronoi::build(
+            &[DVec3::splat(0.5)],
+            DVec3::ZERO,
+            DVec3::ONE,
+            3,
+            false,
+            None,
+        );
+        let mut output = vec![];
+        voronoi
+            .write_custom("%i %q %v %w %g %s %A %F %E %C %n 100%% %r", &mut output)
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.ends_with(" 100% %r\n"));
+        let fields = output
+            .split(' ')
+            .take(24)
+            .map(|f| f.parse::<f64>().unwrap())
+            .collect::<Vec<_>>();
+        let expected = [
+            0., 0.5, 0.5, 0.5, 1., 8., 12., 6., 0., 0., 0., 0., 6., 6., 12., 0.5, 0.5, 0.5,
+        ];
+        for (field, expected) in fields.iter().zip(expected) {
+            assert_approx_eq!(f64, *field, expected, epsilon = 1e-12);
+        }
+        let mut neighbours = fields[18..].iter().map(|&n| n as i64).collect::<Vec<_>>();
+        neighbours.sort();
+        assert_eq!(neighbours, [-6, -5, -4, -3, -2, -1]);
+
+        // Values are consistent with the faces of the tesselation
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 3, 0.5);
+        let voronoi = Voronoi::build(&generators, anchor, width, 3, true, None);
+        let mut output = vec![];
+        voronoi
+            .write_custom("%i %v %s %F %f %n %a %t", &mut output)
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.lines().count(), generators.len());
+        for (line, cell) in output.lines().zip(voronoi.cells()) {
+            let fields = line.split(' ').collect::<Vec<_>>();
+            let face_count = cell.face_count();
+            assert_eq!(fields[2].parse::<usize>().unwrap(), face_count);
+            let areas = fields[4..(4 + face_count)]
+                .iter()
+                .map(|a| a.parse::<f64>().unwrap())
+                .sum::<f64>();
+            assert_approx_eq!(f64, areas, fields[3].parse().unwrap(), epsilon = 1e-12);
+            let neighbours = &fields[(4 + face_count)..(4 + 2 * face_count)];
+            assert!(neighbours.iter().all(|n| n.parse::<usize>().is_ok()));
+            assert_eq!(fields.len(), 4 + 4 * face_count);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use glam::DVec3;
+
+use super::Voronoi;
+
+/// A cell of a [`Voronoi1D`] tesselation: the interval `[left, right]` around its generator.
+#[derive(Debug, Clone, Copy)]
+pub struct VoronoiCell1D {
+    loc: f64,
+    left: f64,
+    right: f64,
+}
+
+impl VoronoiCell1D {
+    /// Get the position of the generator of this cell.
+    pub fn loc(&self) -> f64 {
+        self.loc
+    }
+
+    /// Get the left endpoint of this cell.
+    ///
+    /// For periodic tesselations, the interval is not wrapped: the endpoints may lie (slightly) outside of the
+    /// simulation volume.
+    pub fn left(&self) -> f64 {
+        self.left
+    }
+
+    /// Get the right endpoint of this cell (see [`VoronoiCell1D::left`]).
+    pub fn right(&self) -> f64 {
+        self.right
+    }
+
+    /// Get the length of this cell.
+    pub fn length(&self) -> f64 {
+        self.right - self.left
+    }
+
+    /// Get the midpoint (centroid) of this cell.
+    pub fn midpoint(&self) -> f64 {
+        0.5 * (self.left + self.right)
+    }
+}
+
+/// A 1D Voronoi tesselation, taking scalar coordinates and returning the cells as intervals.
+///
+/// This is a thin front-end over a 1D [`Voronoi`] tesselation (which can be accessed with [`Voronoi1D::as_voronoi`]).
+pub struct Voronoi1D {
+    voronoi: Voronoi,
+}
+
+impl Voronoi1D {
+    /// Construct the 1D Voronoi tesselation, see [`Voronoi::build`].
+    pub fn build(generators: &[f64], anchor: f64, width: f64, periodic: bool) -> Self {
+        Self {
+            voronoi: Voronoi::build(
+                &Self::extend(generators),
+                DVec3::new(anchor, 0., 0.),
+                DVec3::new(width, 1., 1.),
+                1,
+                periodic,
+                None,
+            ),
+        }
+    }
+
+    /// Construct only the cells of the 1D Voronoi tesselation for which `mask` is `true`, see
+    /// [`Voronoi::build_partial`].
+    pub fn build_partial(
+        generators: &[f64],
+        mask: &[bool],
+        anchor: f64,
+        width: f64,
+        periodic: bool,
+    ) -> Self {
+        Self {
+            voronoi: Voronoi::build_partial(
+                &Self::extend(generators),
+                mask,
+                DVec3::new(anchor, 0., 0.),
+                DVec3::new(width, 1., 1.),
+                1,
+                periodic,
+                None,
+            ),
+        }
+    }
+
+    fn extend(generators: &[f64]) -> Vec<DVec3> {
+        generators.iter().map(|&g| DVec3::new(g, 0., 0.)).collect()
+    }
+
+    /// Get the underlying [`Voronoi`] tesselation.
+    pub fn as_voronoi(&self) -> &Voronoi {
+        &self.voronoi
+    }
+
+    /// Get the number of cells.
+    pub fn cell_count(&self) -> usize {
+        self.voronoi.cells.len()
+    }
+
+    /// Get the cell with the given index.
+    ///
+    /// Cells that were not constructed (in a partial tesselation) are empty intervals at their generator.
+    pub fn cell(&self, cell_idx: usize) -> VoronoiCell1D {
+        let cell = &self.voronoi.cells[cell_idx];
+        let loc = cell.loc().x;
+        if cell.volume() == 0. {
+            return VoronoiCell1D {
+                loc,
+                left: loc,
+                right: loc,
+            };
+        }
+        // The unused dimensions have unit width, so the volume of the cell is its length.
+        let half_length = 0.5 * cell.volume();
+        let midpoint = cell.centroid().x;
+        VoronoiCell1D {
+            loc,
+            left: midpoint - half_length,
+            right: midpoint + half_length,
+        }
+    }
+
+    /// Get an `Iterator` over the cells.
+    pub fn cells(&self) -> impl ExactSizeIterator<Item = VoronoiCell1D> + '_ {
+        (0..self.cell_count()).map(|i| self.cell(i))
+    }
+}
+
+impl From<Voronoi1D> for Voronoi {
+    fn from(voronoi: Voronoi1D) -> Self {
+        voronoi.voronoi
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_voronoi_1d() {
+        let generators = [0.1, 0.3, 0.4, 0.8];
+        let voronoi = Voronoi1D::build(&generators, 0., 1., false);
+        let expected = [(0., 0.2), (0.2, 0.35), (0.35, 0.6), (0.6, 1.)];
+        for (cell, (left, right)) in voronoi.cells().zip(expected) {
+            assert_approx_eq!(f64, cell.left(), left, epsilon = 1e-12);
+            assert_approx_eq!(f64, cell.right(), right, epsilon = 1e-12);
+            assert_approx_eq!(f64, cell.midpoint(), 0.5 * (left + right), epsilon = 1e-12);
+        }
+
+        // With periodic boundaries, the first and last cells extend across the boundary
+        let voronoi = Voronoi1D::build(&generators, 0., 1., true);
+        let expected = [(-0.05, 0.2), (0.2, 0.35), (0.35, 0.6), (0.6, 0.95)];
+        for (cell, (left, right)) in voronoi.cells().zip(expected) {
+            assert_approx_eq!(f64, cell.left(), left, epsilon = 1e-12);
+            assert_approx_eq!(f64, cell.right(), right, epsilon = 1e-12);
+            assert!(cell.left() < cell.loc() && cell.loc() < cell.right());
+        }
+        let total_length = voronoi.cells().map(|c| c.length()).sum::<f64>();
+        assert_approx_eq!(f64, total_length, 1., epsilon = 1e-12);
+    }
+}
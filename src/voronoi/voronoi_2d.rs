@@ -0,0 +1,275 @@
+use glam::DVec2;
+
+use super::{Voronoi, VoronoiFace};
+
+/// A cell of a [`Voronoi2D`] tesselation.
+#[derive(Debug, Clone, Copy)]
+pub struct VoronoiCell2D {
+    loc: DVec2,
+    centroid: DVec2,
+    area: f64,
+}
+
+impl VoronoiCell2D {
+    /// Get the position of the generator of this cell.
+    pub fn loc(&self) -> DVec2 {
+        self.loc
+    }
+
+    /// Get the centroid of this cell.
+    pub fn centroid(&self) -> DVec2 {
+        self.centroid
+    }
+
+    /// Get the area of this cell.
+    pub fn area(&self) -> f64 {
+        self.area
+    }
+}
+
+/// An edge (face) of a [`Voronoi2D`] tesselation.
+#[derive(Debug, Clone, Copy)]
+pub struct VoronoiEdge2D {
+    left: usize,
+    right: Option<usize>,
+    start: DVec2,
+    end: DVec2,
+    normal: DVec2,
+    shift: Option<DVec2>,
+}
+
+impl VoronoiEdge2D {
+    fn from_face(face: &VoronoiFace, voronoi: &Voronoi) -> Self {
+        let normal = face.normal().truncate();
+        // The vertices of 2D faces are the endpoints of the edge at both ends of the (unit) z-extent.
+        let vertices = face.vertices(voronoi);
+        let mut start = vertices[0].truncate();
+        let mut end = vertices
+            .iter()
+            .map(|v| v.truncate())
+            .max_by(|a, b| {
+                a.distance_squared(start)
+                    .total_cmp(&b.distance_squared(start))
+            })
+            .expect("Face must have vertices!");
+        // Orient the edge counterclockwise around its left cell
+        if (end - start).perp().dot(normal) > 0. {
+            std::mem::swap(&mut start, &mut end);
+        }
+        Self {
+            left: face.left(),
+            right: face.right(),
+            start,
+            end,
+            normal,
+            shift: face.shift().map(|shift| shift.truncate()),
+        }
+    }
+
+    /// Get the index of the cell on the _left_ of this edge.
+    pub fn left(&self) -> usize {
+        self.left
+    }
+
+    /// Get the index of the cell on the _right_ of this edge (`None` for boundary edges).
+    pub fn right(&self) -> Option<usize> {
+        self.right
+    }
+
+    /// Get the start point of this edge. Edges run counterclockwise around their left cell.
+    pub fn start(&self) -> DVec2 {
+        self.start
+    }
+
+    /// Get the end point of this edge. Edges run counterclockwise around their left cell.
+    pub fn end(&self) -> DVec2 {
+        self.end
+    }
+
+    /// Get the length of this edge.
+    pub fn length(&self) -> f64 {
+        self.start.distance(self.end)
+    }
+
+    /// Get the midpoint of this edge.
+    pub fn midpoint(&self) -> DVec2 {
+        0.5 * (self.start + self.end)
+    }
+
+    /// Get the normal of this edge, pointing away from the left cell.
+    pub fn normal(&self) -> DVec2 {
+        self.normal
+    }
+
+    /// Get the shift vector (if any) to apply to the cell to the right of this edge to bring it to the reference
+    /// frame of this edge. Can only be `Some` for periodic tesselations.
+    pub fn shift(&self) -> Option<DVec2> {
+        self.shift
+    }
+}
+
+/// A 2D Voronoi tesselation, taking and returning 2D coordinates.
+///
+/// This is a thin front-end over a 2D [`Voronoi`] tesselation (which can be accessed with [`Voronoi2D::as_voronoi`])
+/// whose faces are the edges of the cells.
+pub struct Voronoi2D {
+    voronoi: Voronoi,
+}
+
+impl Voronoi2D {
+    /// Construct the 2D Voronoi tesselation, see [`Voronoi::build`].
+    pub fn build(generators: &[DVec2], anchor: DVec2, width: DVec2, periodic: bool) -> Self {
+        let generators = generators.iter().map(|g| g.extend(0.)).collect::<Vec<_>>();
+        Self {
+            voronoi: Voronoi::build(
+                &generators,
+                anchor.extend(0.),
+                width.extend(1.),
+                2,
+                periodic,
+                None,
+            ),
+        }
+    }
+
+    /// Construct only the cells of the 2D Voronoi tesselation for which `mask` is `true`, see
+    /// [`Voronoi::build_partial`].
+    pub fn build_partial(
+        generators: &[DVec2],
+        mask: &[bool],
+        anchor: DVec2,
+        width: DVec2,
+        periodic: bool,
+    ) -> Self {
+        let generators = generators.iter().map(|g| g.extend(0.)).collect::<Vec<_>>();
+        Self {
+            voronoi: Voronoi::build_partial(
+                &generators,
+                mask,
+                anchor.extend(0.),
+                width.extend(1.),
+                2,
+                periodic,
+                None,
+            ),
+        }
+    }
+
+    /// Get the underlying [`Voronoi`] tesselation.
+    pub fn as_voronoi(&self) -> &Voronoi {
+        &self.voronoi
+    }
+
+    /// Get the number of cells.
+    pub fn cell_count(&self) -> usize {
+        self.voronoi.cells.len()
+    }
+
+    /// Get the cell with the given index.
+    pub fn cell(&self, cell_idx: usize) -> VoronoiCell2D {
+        let cell = &self.voronoi.cells[cell_idx];
+        VoronoiCell2D {
+            loc: cell.loc().truncate(),
+            centroid: cell.centroid().truncate(),
+            area: cell.volume(),
+        }
+    }
+
+    /// Get an `Iterator` over the cells.
+    pub fn cells(&self) -> impl ExactSizeIterator<Item = VoronoiCell2D> + '_ {
+        (0..self.cell_count()).map(|i| self.cell(i))
+    }
+
+    /// Get the number of edges.
+    pub fn edge_count(&self) -> usize {
+        self.voronoi.faces.len()
+    }
+
+    /// Get the edge with the given index.
+    pub fn edge(&self, edge_idx: usize) -> VoronoiEdge2D {
+        VoronoiEdge2D::from_face(&self.voronoi.faces[edge_idx], &self.voronoi)
+    }
+
+    /// Get an `Iterator` over the edges.
+    pub fn edges(&self) -> impl ExactSizeIterator<Item = VoronoiEdge2D> + '_ {
+        (0..self.edge_count()).map(|i| self.edge(i))
+    }
+
+    /// Get the indices of the edges of the cell with the given index.
+    pub fn cell_edge_indices(&self, cell_idx: usize) -> &[usize] {
+        self.voronoi.cells[cell_idx].face_indices(&self.voronoi)
+    }
+
+    /// Get the vertices of the cell with the given index, ordered counterclockwise.
+    pub fn cell_polygon(&self, cell_idx: usize) -> Vec<DVec2> {
+        let mut vertices = self
+            .cell_edge_indices(cell_idx)
+            .iter()
+            .map(|&i| {
+                let edge = self.edge(i);
+                if edge.left() == cell_idx {
+                    edge.start()
+                } else {
+                    edge.end()
+                }
+            })
+            .collect::<Vec<_>>();
+        let centroid = self.voronoi.cells[cell_idx].centroid().truncate();
+        let angle = |v: &DVec2| (*v - centroid).y.atan2((*v - centroid).x);
+        vertices.sort_by(|a, b| angle(a).total_cmp(&angle(b)));
+        vertices
+    }
+
+    /// Get the signed area of the cell with the given index, computed from its polygon (positive for a correctly
+    /// constructed cell).
+    pub fn cell_signed_area(&self, cell_idx: usize) -> f64 {
+        let polygon = self.cell_polygon(cell_idx);
+        0.5 * (0..polygon.len())
+            .map(|i| polygon[i].perp_dot(polygon[(i + 1) % polygon.len()]))
+            .sum::<f64>()
+    }
+}
+
+impl From<Voronoi2D> for Voronoi {
+    fn from(voronoi: Voronoi2D) -> Self {
+        voronoi.voronoi
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use float_cmp::assert_approx_eq;
+    use rand::{distributions::Uniform, prelude::*};
+
+    use super::*;
+
+    #[test]
+    fn test_voronoi_2d() {
+        let mut rng = thread_rng();
+        let distr = Uniform::new(0., 1.);
+        let generators = (0..50)
+            .map(|_| DVec2::new(rng.sample(distr), rng.sample(distr)))
+            .collect::<Vec<_>>();
+        for periodic in [false, true] {
+            let voronoi = Voronoi2D::build(&generators, DVec2::ZERO, DVec2::ONE, periodic);
+            assert_eq!(voronoi.cell_count(), generators.len());
+            let total_area = voronoi.cells().map(|c| c.area()).sum::<f64>();
+            assert_approx_eq!(f64, total_area, 1., epsilon = 1e-10);
+            for (i, cell) in voronoi.cells().enumerate() {
+                assert_eq!(cell.loc(), generators[i]);
+                assert_approx_eq!(
+                    f64,
+                    voronoi.cell_signed_area(i),
+                    cell.area(),
+                    epsilon = 1e-10
+                );
+            }
+            for edge in voronoi.edges() {
+                // The edge runs counterclockwise around the left generator
+                let left_loc = generators[edge.left()];
+                assert!((edge.start() - left_loc).perp_dot(edge.end() - left_loc) > 0.);
+                assert!(edge.normal().dot(edge.midpoint() - left_loc) > 0.);
+            }
+        }
+    }
+}
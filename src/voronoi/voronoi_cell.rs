@@ -1,18 +1,22 @@
-use glam::DVec3;
+use glam::{DMat3, DVec3, Vec3A};
+use rand::Rng;
+use smallvec::{smallvec, SmallVec};
 
 use crate::{
     geometry::{intersect_planes, Plane},
     integrators::{
-        ScalarVoronoiFaceIntegrator, VectorVoronoiFaceIntegrator, VolumeCentroidIntegrator,
-        VoronoiCellIntegrator,
+        FaceIntegralStore, FaceIntegrators, VolumeCentroidIntegrator, VoronoiCellIntegrator,
     },
     simple_cycle::SimpleCycle,
-    util::GetMutMultiple,
+    util::{
+        ball_cone_volume, disk_triangle_area, distance_to_polygon, signed_volume_tet,
+        GetMutMultiple,
+    },
     voronoi::voronoi_face::VoronoiFaceBuilder,
     Voronoi, VoronoiFace,
 };
 
-use super::{Dimensionality, Generator};
+use super::{Boundary, CellProfile, CellSelection, Dimensionality, Generator, SafetyCriterion};
 
 #[derive(Clone)]
 pub struct HalfSpace {
@@ -20,6 +24,7 @@ pub struct HalfSpace {
     d: f64,
     pub right_idx: Option<usize>,
     pub shift: Option<DVec3>,
+    pub boundary: Option<Boundary>,
 }
 
 impl HalfSpace {
@@ -29,6 +34,15 @@ impl HalfSpace {
             d: n.dot(p),
             right_idx,
             shift,
+            boundary: None,
+        }
+    }
+
+    /// A half space bounding the simulation volume.
+    fn wall(n: DVec3, p: DVec3, boundary: Boundary) -> Self {
+        HalfSpace {
+            boundary: Some(boundary),
+            ..Self::new(n, p, None, None)
         }
     }
 
@@ -37,6 +51,23 @@ impl HalfSpace {
         self.plane.n.dot(vertex) < self.d
     }
 
+    /// Whether a vertex is clipped by this half space, first tested in single precision with the `offset` of the vertex
+    /// from the generator of its cell and this half space relative to that generator (`normal . offset < d`). Only if
+    /// the vertex lies too close to the plane for that test to be conclusive, the test is repeated in double precision,
+    /// so the result is the same as for [`HalfSpace::clips`].
+    fn clips_prechecked(&self, vertex: DVec3, offset: Vec3A, normal: Vec3A, d: f32) -> bool {
+        let distance = normal.dot(offset) - d;
+        // Conservative bound on the round-off error of the single precision test
+        let tolerance = SINGLE_PRECISION_TOLERANCE * (3. * offset.abs().max_element() + d.abs());
+        if distance < -tolerance {
+            true
+        } else if distance > tolerance {
+            false
+        } else {
+            self.clips(vertex)
+        }
+    }
+
     pub fn normal(&self) -> DVec3 {
         self.plane.n
     }
@@ -46,6 +77,10 @@ impl HalfSpace {
     }
 }
 
+/// The relative tolerance of the clipping tests in single precision (see [`HalfSpace::clips_prechecked`]), well above
+/// the round-off error of the conversion to single precision and the dot product.
+const SINGLE_PRECISION_TOLERANCE: f32 = 1e-5;
+
 #[derive(Clone)]
 pub struct Vertex {
     pub loc: DVec3,
@@ -65,14 +100,24 @@ impl Vertex {
     }
 }
 
+/// The number of clipping planes stored inline in a [`ConvexCell`] before spilling to the heap. Typical 3D cells have
+/// around 15 faces (plus the walls of the simulation volume).
+const INLINE_PLANES: usize = 32;
+/// The number of vertices stored inline in a [`ConvexCell`] before spilling to the heap.
+const INLINE_VERTICES: usize = 64;
+
 #[derive(Clone)]
 pub struct ConvexCell {
     pub loc: DVec3,
-    pub clipping_planes: Vec<HalfSpace>,
-    pub vertices: Vec<Vertex>,
+    pub clipping_planes: SmallVec<[HalfSpace; INLINE_PLANES]>,
+    pub vertices: SmallVec<[Vertex; INLINE_VERTICES]>,
     boundary: SimpleCycle,
     safety_radius: f64,
     pub idx: usize,
+    single_precision_prechecks: bool,
+    /// The positions of the vertices relative to the generator in single precision, indexed like `vertices`. Only
+    /// kept up to date with single precision prechecks, empty otherwise.
+    offsets: Vec<Vec3A>,
 }
 
 impl ConvexCell {
@@ -86,6 +131,13 @@ impl ConvexCell {
         let mut cell = simulation_volume.clone();
         cell.idx = idx;
         cell.loc = loc;
+        if cell.single_precision_prechecks {
+            cell.offsets = cell
+                .vertices
+                .iter()
+                .map(|v| (v.loc - loc).as_vec3a())
+                .collect();
+        }
         cell.update_safety_radius(dimensionality);
         cell
     }
@@ -110,15 +162,15 @@ impl ConvexCell {
                 width.z *= 3.;
             }
         }
-        let clipping_planes = vec![
-            HalfSpace::new(DVec3::X, anchor, None, None),
-            HalfSpace::new(DVec3::NEG_X, anchor + width, None, None),
-            HalfSpace::new(DVec3::Y, anchor, None, None),
-            HalfSpace::new(DVec3::NEG_Y, anchor + width, None, None),
-            HalfSpace::new(DVec3::Z, anchor, None, None),
-            HalfSpace::new(DVec3::NEG_Z, anchor + width, None, None),
+        let clipping_planes: SmallVec<[HalfSpace; INLINE_PLANES]> = smallvec![
+            HalfSpace::wall(DVec3::X, anchor, Boundary::XMin),
+            HalfSpace::wall(DVec3::NEG_X, anchor + width, Boundary::XMax),
+            HalfSpace::wall(DVec3::Y, anchor, Boundary::YMin),
+            HalfSpace::wall(DVec3::NEG_Y, anchor + width, Boundary::YMax),
+            HalfSpace::wall(DVec3::Z, anchor, Boundary::ZMin),
+            HalfSpace::wall(DVec3::NEG_Z, anchor + width, Boundary::ZMax),
         ];
-        let vertices = vec![
+        let vertices = smallvec![
             Vertex::from_dual(2, 5, 0, &clipping_planes),
             Vertex::from_dual(5, 3, 0, &clipping_planes),
             Vertex::from_dual(1, 5, 2, &clipping_planes),
@@ -135,16 +187,82 @@ impl ConvexCell {
             vertices,
             safety_radius: 0.,
             idx: 0,
+            single_precision_prechecks: false,
+            offsets: Vec::new(),
+        }
+    }
+
+    /// Whether the cells initialized from this simulation volume test which vertices are clipped by a new plane in
+    /// single precision first, see [`crate::BuildOptions::single_precision_prechecks`].
+    pub(super) fn with_single_precision_prechecks(
+        mut self,
+        single_precision_prechecks: bool,
+    ) -> Self {
+        self.single_precision_prechecks = single_precision_prechecks;
+        self
+    }
+
+    /// Reconstruct the convex cell of a fully constructed Voronoi cell by clipping the given
+    /// `simulation_volume` with the half spaces of the cell's faces.
+    ///
+    /// Returns `None` if the intersection of the cell with the simulation volume is empty.
+    pub(super) fn reconstruct(
+        voronoi: &Voronoi,
+        cell_idx: usize,
+        simulation_volume: &ConvexCell,
+        dimensionality: Dimensionality,
+    ) -> Option<Self> {
+        let voronoi_cell = &voronoi.cells()[cell_idx];
+        let loc = voronoi_cell.loc();
+        let mut cell = ConvexCell::init(loc, cell_idx, simulation_volume, dimensionality);
+        for face in voronoi_cell.faces(voronoi) {
+            let Some(right_idx) = face.right() else {
+                continue;
+            };
+            let (ngb_idx, ngb_loc, shift) = if face.left() == cell_idx {
+                let shift = face.shift();
+                (
+                    right_idx,
+                    voronoi.cells()[right_idx].loc() + shift.unwrap_or(DVec3::ZERO),
+                    shift,
+                )
+            } else {
+                (face.left(), voronoi.cells()[face.left()].loc(), None)
+            };
+            let dx = loc - ngb_loc;
+            let dist = dx.length();
+            let n = dx / dist;
+            let p = 0.5 * (loc + ngb_loc);
+            cell.clip_by_plane(HalfSpace::new(n, p, Some(ngb_idx), shift), dimensionality);
+            if cell.vertices.is_empty() {
+                return None;
+            }
         }
+
+        Some(cell)
     }
 
-    /// Build the Convex cell by repeatedly intersecting it with the appropriate half spaces
+    /// Use this cell as the simulation volume of a nested tesselation: its faces become boundary faces (the walls of
+    /// the original simulation volume keep their [`Boundary`]).
+    pub(super) fn as_simulation_volume(&self) -> Self {
+        let mut volume = self.clone();
+        for half_space in volume.clipping_planes.iter_mut() {
+            half_space.right_idx = None;
+            half_space.shift = None;
+        }
+        volume
+    }
+
+    /// Build the Convex cell by repeatedly intersecting it with the appropriate half spaces, until the given
+    /// `criterion` is met.
     pub(super) fn build(
         &mut self,
         generators: &[Generator],
         mut nearest_neighbours: Box<dyn Iterator<Item = (usize, Option<DVec3>)> + '_>,
+        criterion: SafetyCriterion,
         dimensionality: Dimensionality,
-    ) {
+    ) -> CellProfile {
+        let mut profile = CellProfile::default();
         // skip the first nearest neighbour (will be this cell)
         assert_eq!(
             nearest_neighbours
@@ -156,22 +274,149 @@ impl ConvexCell {
         );
         // now loop over the nearest neighbours and clip this cell until the safety radius is reached
         for (idx, shift) in nearest_neighbours {
-            let generator = generators[idx];
-            let ngb_loc;
-            if let Some(shift) = shift {
-                ngb_loc = generator.loc() + shift;
-            } else {
-                ngb_loc = generator.loc();
+            if !self.clip_by_neighbour(
+                generators,
+                idx,
+                shift,
+                criterion,
+                dimensionality,
+                &mut profile,
+            ) {
+                return profile;
             }
-            let dx = self.loc - ngb_loc;
-            let dist = dx.length();
-            assert!(dist.is_finite() && dist > 0.0, "Degenerate point set!");
-            if self.safety_radius < dist {
-                return;
+        }
+
+        // All the generators have been considered
+        profile.complete = true;
+        profile
+    }
+
+    /// Build the Voronoi cell by first clipping it with the given `candidates` (sorted `(idx, shift, distance)`
+    /// triplets, possibly including the generator of this cell itself), which must contain all the neighbours closer
+    /// than `bound`. Candidates farther away than `bound` are ignored. If the safety criterion is not met afterwards,
+    /// the construction continues with the remaining neighbours from the full `nearest_neighbours` iterator.
+    pub(super) fn build_bounded<'a>(
+        &mut self,
+        generators: &[Generator],
+        candidates: &[(usize, Option<DVec3>, f64)],
+        bound: f64,
+        nearest_neighbours: impl FnOnce() -> Box<dyn Iterator<Item = (usize, Option<DVec3>)> + 'a>,
+        criterion: SafetyCriterion,
+        dimensionality: Dimensionality,
+    ) -> CellProfile {
+        let mut profile = CellProfile::default();
+        let self_idx = self.idx;
+        let is_self = move |idx: usize, shift: Option<DVec3>| idx == self_idx && shift.is_none();
+        // Only the candidates closer than the bound are guaranteed to be the nearest neighbours
+        let candidates = &candidates[..candidates.partition_point(|&(_, _, dist)| dist < bound)];
+        for &(idx, shift, _) in candidates {
+            if is_self(idx, shift) {
+                continue;
             }
-            let n = dx / dist;
-            let p = 0.5 * (self.loc + ngb_loc);
-            self.clip_by_plane(HalfSpace::new(n, p, Some(idx), shift), dimensionality);
+            if !self.clip_by_neighbour(
+                generators,
+                idx,
+                shift,
+                criterion,
+                dimensionality,
+                &mut profile,
+            ) {
+                return profile;
+            }
+        }
+        // All the neighbours closer than the bound have been considered.
+        if self.safety_radius < bound {
+            profile.complete = true;
+            return profile;
+        }
+
+        profile.fallback = true;
+        let remaining = nearest_neighbours().filter(|&(idx, shift)| {
+            !is_self(idx, shift)
+                && !candidates
+                    .iter()
+                    .any(|&(c_idx, c_shift, _)| c_idx == idx && c_shift == shift)
+        });
+        for (idx, shift) in remaining {
+            if !self.clip_by_neighbour(
+                generators,
+                idx,
+                shift,
+                criterion,
+                dimensionality,
+                &mut profile,
+            ) {
+                return profile;
+            }
+        }
+
+        profile.complete = true;
+        profile
+    }
+
+    /// Clip this cell by the bisector with the (periodic image of the) neighbouring generator with index `idx`.
+    ///
+    /// Returns `false` if the construction of the cell is finished according to the given `criterion`, i.e. if the
+    /// neighbour lies beyond the safety radius (in which case the cell is complete), or beyond the maximal search
+    /// radius or neighbour budget (in which case it might not be).
+    fn clip_by_neighbour(
+        &mut self,
+        generators: &[Generator],
+        idx: usize,
+        shift: Option<DVec3>,
+        criterion: SafetyCriterion,
+        dimensionality: Dimensionality,
+        profile: &mut CellProfile,
+    ) -> bool {
+        profile.nn_candidates += 1;
+        let generator = generators[idx];
+        let ngb_loc;
+        if let Some(shift) = shift {
+            ngb_loc = generator.loc() + shift;
+        } else {
+            ngb_loc = generator.loc();
+        }
+        let dx = self.loc - ngb_loc;
+        let dist = dx.length();
+        assert!(dist.is_finite() && dist > 0.0, "Degenerate point set!");
+        if self.safety_radius < dist {
+            profile.complete = true;
+            return false;
+        }
+        let exhausted = match criterion {
+            SafetyCriterion::Strict => false,
+            SafetyCriterion::MaxSearchRadius { radius } => dist > radius,
+            SafetyCriterion::NeighbourBudget { max_neighbours } => {
+                profile.clipping_planes_tested >= max_neighbours
+            }
+        };
+        if exhausted {
+            // All the neighbours closer than this one have been considered
+            profile.confidence = dist / self.safety_radius;
+            return false;
+        }
+        let n = dx / dist;
+        let p = 0.5 * (self.loc + ngb_loc);
+        profile.clipping_planes_tested += 1;
+        profile.vertex_tests += self.vertices.len();
+        let plane_count = self.clipping_planes.len();
+        self.clip_by_plane(HalfSpace::new(n, p, Some(idx), shift), dimensionality);
+        if self.clipping_planes.len() > plane_count {
+            profile.clipping_planes_cut += 1;
+        }
+        true
+    }
+
+    /// Clip this cell by the half space `normal . (x - point) >= 0`, which is not the bisector with a neighbour.
+    /// The cell becomes empty (i.e. has no vertices) if it lies entirely outside of the half space.
+    pub(super) fn clip_by_half_space(
+        &mut self,
+        normal: DVec3,
+        point: DVec3,
+        dimensionality: Dimensionality,
+    ) {
+        if !self.vertices.is_empty() {
+            self.clip_by_plane(HalfSpace::new(normal, point, None, None), dimensionality);
         }
     }
 
@@ -180,16 +425,34 @@ impl ConvexCell {
         let mut i = 0;
         let mut num_v = self.vertices.len();
         let mut num_r = 0;
+        // The plane relative to the generator, for the single precision tests
+        let normal = p.plane.n.as_vec3a();
+        let d = (p.d - p.plane.n.dot(self.loc)) as f32;
         while i < num_v {
-            if p.clips(self.vertices[i].loc) {
+            let clipped = if self.single_precision_prechecks {
+                p.clips_prechecked(self.vertices[i].loc, self.offsets[i], normal, d)
+            } else {
+                p.clips(self.vertices[i].loc)
+            };
+            if clipped {
                 num_v -= 1;
                 num_r += 1;
                 self.vertices.swap(i, num_v);
+                if self.single_precision_prechecks {
+                    self.offsets.swap(i, num_v);
+                }
             } else {
                 i += 1;
             }
         }
 
+        if num_v == 0 {
+            // The cell is empty (can only happen when clipping an existing cell by a new volume).
+            self.vertices.clear();
+            self.offsets.clear();
+            return;
+        }
+
         // Were any vertices clipped?
         if num_r > 0 {
             // Add the new clipping plane
@@ -210,6 +473,15 @@ impl ConvexCell {
                     .push(Vertex::from_dual(cur, next, p_idx, &self.clipping_planes));
                 cur = next;
             }
+            if self.single_precision_prechecks {
+                self.offsets.truncate(num_v);
+                let loc = self.loc;
+                self.offsets.extend(
+                    self.vertices[num_v..]
+                        .iter()
+                        .map(|v| (v.loc - loc).as_vec3a()),
+                );
+            }
             self.update_safety_radius(dimensionality);
         }
     }
@@ -239,6 +511,11 @@ impl ConvexCell {
         }
     }
 
+    /// Get the radius around the generator beyond which no other generator can clip this cell.
+    pub(super) fn safety_radius(&self) -> f64 {
+        self.safety_radius
+    }
+
     fn update_safety_radius(&mut self, dimensionality: Dimensionality) {
         let max_dist_2 = self
             .vertices
@@ -262,6 +539,117 @@ impl ConvexCell {
         // Update safety radius
         self.safety_radius = 2. * max_dist_2.sqrt();
     }
+
+    /// Compute the volume and centroid of this cell, by decomposing it into tetrahedra (joining one of its vertices
+    /// with its fan-triangulated faces).
+    pub(super) fn volume_centroid(&self) -> (f64, DVec3) {
+        let Some(origin) = self.vertices.first().map(|v| v.loc) else {
+            return (0., DVec3::ZERO);
+        };
+        let mut volume = 0.;
+        let mut moment = DVec3::ZERO;
+        for vertex_loop in self.face_vertex_loops() {
+            let Some(&first) = vertex_loop.first() else {
+                continue;
+            };
+            let v0 = self.vertices[first].loc;
+            for w in vertex_loop.windows(2).skip(1) {
+                let (v1, v2) = (self.vertices[w[0]].loc, self.vertices[w[1]].loc);
+                let tet_volume = signed_volume_tet(origin, v0, v1, v2);
+                volume += tet_volume;
+                moment += tet_volume * (origin + v0 + v1 + v2) / 4.;
+            }
+        }
+        if volume > 0. {
+            (volume, moment / volume)
+        } else {
+            (0., origin)
+        }
+    }
+
+    /// Compute the ordered vertex loops of the faces of this cell (indexed by clipping plane).
+    ///
+    /// The vertices of each loop are ordered counterclockwise when seen from outside the cell.
+    /// Clipping planes that do not contribute a face to this cell get an empty loop.
+    pub(super) fn face_vertex_loops(&self) -> Vec<Vec<usize>> {
+        // For every plane, collect the (incoming plane, vertex, outgoing plane) triples of the
+        // vertices on that plane. Around plane `a`, the vertex with dual `(a, b, c)` is followed by
+        // the vertex with dual `(a, c, x)`.
+        let mut fans: Vec<Vec<(usize, usize, usize)>> =
+            (0..self.clipping_planes.len()).map(|_| vec![]).collect();
+        for (v_idx, vertex) in self.vertices.iter().enumerate() {
+            let (a, b, c) = vertex.dual;
+            fans[a].push((b, v_idx, c));
+            fans[b].push((c, v_idx, a));
+            fans[c].push((a, v_idx, b));
+        }
+
+        fans.iter()
+            .enumerate()
+            .map(|(p_idx, fan)| {
+                if fan.len() < 3 {
+                    return vec![];
+                }
+                let mut vertex_loop = Vec::with_capacity(fan.len());
+                let (start, mut cur_v, mut next) = fan[0];
+                vertex_loop.push(cur_v);
+                while next != start && vertex_loop.len() < fan.len() {
+                    match fan.iter().find(|(incoming, _, _)| *incoming == next) {
+                        Some(&(_, v, outgoing)) => {
+                            cur_v = v;
+                            next = outgoing;
+                            vertex_loop.push(cur_v);
+                        }
+                        None => break,
+                    }
+                }
+                if vertex_loop.len() != fan.len() {
+                    // Inconsistent topology (should not happen), fall back to sorting the vertices
+                    // by angle around their center.
+                    vertex_loop = self.sort_face_vertices(p_idx, fan.iter().map(|(_, v, _)| *v));
+                }
+
+                // Make sure the loop is oriented counterclockwise w.r.t. the outward normal.
+                let outward = -self.clipping_planes[p_idx].normal();
+                if self.newell_normal(&vertex_loop).dot(outward) < 0. {
+                    vertex_loop.reverse();
+                }
+                vertex_loop
+            })
+            .collect()
+    }
+
+    fn sort_face_vertices(
+        &self,
+        p_idx: usize,
+        vertices: impl Iterator<Item = usize>,
+    ) -> Vec<usize> {
+        let mut vertices = vertices.collect::<Vec<_>>();
+        let n = self.clipping_planes[p_idx].normal();
+        let center = vertices
+            .iter()
+            .map(|&v| self.vertices[v].loc)
+            .sum::<DVec3>()
+            / vertices.len() as f64;
+        let u = n.any_orthonormal_vector();
+        let w = n.cross(u);
+        let angle = |v: usize| {
+            let d = self.vertices[v].loc - center;
+            d.dot(w).atan2(d.dot(u))
+        };
+        vertices.sort_by(|&a, &b| angle(a).total_cmp(&angle(b)));
+        vertices
+    }
+
+    fn newell_normal(&self, vertex_loop: &[usize]) -> DVec3 {
+        let mut normal = DVec3::ZERO;
+        for (i, &v) in vertex_loop.iter().enumerate() {
+            let cur = self.vertices[v].loc;
+            let next = self.vertices[vertex_loop[(i + 1) % vertex_loop.len()]].loc;
+            normal += cur.cross(next);
+        }
+        normal
+    }
 }
 
 struct VoronoiCellBuilder {
@@ -311,18 +699,17 @@ impl VoronoiCell {
     /// Build a Voronoi cell from a ConvexCell by computing the relevant integrals.
     ///
     /// Any Voronoi faces that are created by the construction of this cell are stored in the `faces` vector.
-    pub fn from_convex_cell(
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn from_convex_cell(
         convex_cell: &ConvexCell,
         faces: &mut Vec<VoronoiFace>,
+        face_vertices: &mut Vec<DVec3>,
         vector_face_integrals: &mut Vec<DVec3>,
         scalar_face_integrals: &mut Vec<f64>,
-        mask: Option<&[bool]>,
-        vector_face_integrators: &[Box<
-            dyn Fn() -> Box<dyn VectorVoronoiFaceIntegrator> + Send + Sync,
-        >],
-        scalar_face_integrators: &[Box<
-            dyn Fn() -> Box<dyn ScalarVoronoiFaceIntegrator> + Send + Sync,
-        >],
+        generic_face_integrals: &mut [Box<dyn FaceIntegralStore>],
+        mask: CellSelection,
+        deduplicate_periodic_faces: bool,
+        face_integrators: &FaceIntegrators,
     ) -> Self {
         let idx = convex_cell.idx;
         let loc = convex_cell.loc;
@@ -338,13 +725,9 @@ impl VoronoiCell {
             half_space: &'a HalfSpace,
             left_idx: usize,
             left_loc: DVec3,
-            mask: Option<&[bool]>,
-            vector_face_integrators: &[Box<
-                dyn Fn() -> Box<dyn VectorVoronoiFaceIntegrator> + Send + Sync,
-            >],
-            scalar_face_integrators: &[Box<
-                dyn Fn() -> Box<dyn ScalarVoronoiFaceIntegrator> + Send + Sync,
-            >],
+            mask: CellSelection,
+            deduplicate_periodic_faces: bool,
+            face_integrators: &FaceIntegrators,
         ) {
             match half_space {
                 // Don't construct faces twice in case the voronoi cell of right_idx is also being constructed.
@@ -352,15 +735,20 @@ impl VoronoiCell {
                     right_idx: Some(right_idx),
                     shift: None,
                     ..
-                } if *right_idx <= left_idx && mask.map_or(true, |mask| mask[*right_idx]) => (),
+                } if *right_idx <= left_idx && mask.contains(*right_idx) => (),
+                // Periodic faces are constructed by the cell for which they are primary and mirrored afterwards.
+                HalfSpace {
+                    right_idx: Some(right_idx),
+                    shift: Some(shift),
+                    ..
+                } if deduplicate_periodic_faces
+                    && (*right_idx < left_idx
+                        || (*right_idx == left_idx && shift.to_array() < (-*shift).to_array()))
+                    && mask.contains(*right_idx) => {}
                 _ => {
-                    maybe_face.get_or_insert(VoronoiFaceBuilder::new(
-                        left_idx,
-                        left_loc,
-                        half_space,
-                        vector_face_integrators,
-                        scalar_face_integrators,
-                    ));
+                    maybe_face.get_or_insert_with(|| {
+                        VoronoiFaceBuilder::new(left_idx, left_loc, half_space, face_integrators)
+                    });
                 }
             }
         }
@@ -382,8 +770,8 @@ impl VoronoiCell {
                 idx,
                 loc,
                 mask,
-                vector_face_integrators,
-                scalar_face_integrators,
+                deduplicate_periodic_faces,
+                face_integrators,
             );
             maybe_init_face(
                 maybe_face_1,
@@ -391,8 +779,8 @@ impl VoronoiCell {
                 idx,
                 loc,
                 mask,
-                vector_face_integrators,
-                scalar_face_integrators,
+                deduplicate_periodic_faces,
+                face_integrators,
             );
             maybe_init_face(
                 maybe_face_2,
@@ -400,8 +788,8 @@ impl VoronoiCell {
                 idx,
                 loc,
                 mask,
-                vector_face_integrators,
-                scalar_face_integrators,
+                deduplicate_periodic_faces,
+                face_integrators,
             );
 
             // Project generator on planes
@@ -413,9 +801,9 @@ impl VoronoiCell {
             let g_on_p2 = plane_2.project_onto(loc);
 
             // Project generator on edges between planes
-            let g_on_p01 = plane_0.project_onto_intersection(&plane_1, loc);
-            let g_on_p02 = plane_0.project_onto_intersection(&plane_2, loc);
-            let g_on_p12 = plane_1.project_onto_intersection(&plane_2, loc);
+            let g_on_p01 = plane_0.project_onto_intersection(plane_1, loc);
+            let g_on_p02 = plane_0.project_onto_intersection(plane_2, loc);
+            let g_on_p12 = plane_1.project_onto_intersection(plane_2, loc);
 
             // Project generator on vertex determined by planes
             let g_on_p012 = vertex.loc;
@@ -429,33 +817,49 @@ impl VoronoiCell {
             cell.extend(g_on_p012, g_on_p2, g_on_p12);
 
             // Calculate the signed areas of the triangles on the faces and update their barycenters
-            maybe_face_0.as_mut().map(|f| {
+            if let Some(f) = maybe_face_0.as_mut() {
                 f.extend(g_on_p012, g_on_p01, g_on_p0);
                 f.extend(g_on_p012, g_on_p0, g_on_p02);
-            });
-            maybe_face_1.as_mut().map(|f| {
+            }
+            if let Some(f) = maybe_face_1.as_mut() {
                 f.extend(g_on_p012, g_on_p1, g_on_p01);
                 f.extend(g_on_p012, g_on_p12, g_on_p1);
-            });
-            maybe_face_2.as_mut().map(|f| {
+            }
+            if let Some(f) = maybe_face_2.as_mut() {
                 f.extend(g_on_p012, g_on_p02, g_on_p2);
                 f.extend(g_on_p012, g_on_p2, g_on_p12);
-            });
+            }
         }
 
         // Filter out uninitialized faces and finalize the rest
-        for maybe_face in maybe_faces {
-            if let Some(face) = maybe_face {
-                let (face, vector_integrals, scalar_integrals) = face.build();
-                faces.push(face);
-                vector_face_integrals.extend(vector_integrals);
-                scalar_face_integrals.extend(scalar_integrals);
-            }
+        let vertex_loops = convex_cell.face_vertex_loops();
+        for (face, vertex_loop) in maybe_faces.into_iter().zip(vertex_loops) {
+            let Some(face) = face else {
+                continue;
+            };
+            face_vertices.extend(vertex_loop.iter().map(|&v| convex_cell.vertices[v].loc));
+            let (face, vector_integrals, scalar_integrals) =
+                face.build(vertex_loop.len(), generic_face_integrals);
+            faces.push(face);
+            vector_face_integrals.extend(vector_integrals);
+            scalar_face_integrals.extend(scalar_integrals);
         }
 
         cell.build()
     }
 
+    /// Apply the transformation `x -> x * scale + translation` to this cell.
+    /// `jacobian` is the determinant of the linear part of the transformation.
+    pub(super) fn transform(&mut self, translation: DVec3, scale: DVec3, jacobian: f64) {
+        self.loc = self.loc * scale + translation;
+        self.centroid = self.centroid * scale + translation;
+        self.volume *= jacobian;
+    }
+
+    pub(super) fn with_loc(self, loc: DVec3) -> Self {
+        Self { loc, ..self }
+    }
+
     pub(super) fn finalize(&mut self, face_connections_offset: usize, face_count: usize) {
         self.face_connections_offset = face_connections_offset;
         self.face_count = face_count;
@@ -476,19 +880,60 @@ impl VoronoiCell {
         self.volume
     }
 
+    /// Get the radius of the sphere (3D), circle (2D) or half the length of the interval (1D) with the same volume as
+    /// this cell, i.e. `(3V / 4pi)^(1/3)` in 3D, `(V / pi)^(1/2)` in 2D and `V / 2` in 1D.
+    pub fn characteristic_radius(&self, voronoi: &Voronoi) -> f64 {
+        match voronoi.dimensionality {
+            Dimensionality::Dimensionality1D => 0.5 * self.volume,
+            Dimensionality::Dimensionality2D => (self.volume / std::f64::consts::PI).sqrt(),
+            Dimensionality::Dimensionality3D => {
+                (0.75 * self.volume / std::f64::consts::PI).powf(1. / 3.)
+            }
+        }
+    }
+
+    /// Get the minimal distance from the generator of this cell to (the plane of) any of its faces, computed from its
+    /// faces on every call. This is 0 for cells without faces.
+    pub fn min_face_distance(&self, voronoi: &Voronoi) -> f64 {
+        self.faces(voronoi)
+            .map(|face| (face.centroid() - self.loc).dot(face.normal()).abs())
+            .reduce(f64::min)
+            .unwrap_or(0.)
+    }
+
     /// Get the indices of the faces that have this cell as its left or right neighbour.
-    pub fn face_indices<'a>(&'a self, voronoi: &'a Voronoi) -> &[usize] {
+    pub fn face_indices<'a>(&'a self, voronoi: &'a Voronoi) -> &'a [usize] {
         &voronoi.cell_face_connections
             [self.face_connections_offset..(self.face_connections_offset + self.face_count)]
     }
 
     /// Get an `Iterator` over the Voronoi faces that have this cell as their left _or_ right generator.
-    pub fn faces<'a>(&'a self, voronoi: &'a Voronoi) -> impl Iterator<Item = &VoronoiFace> + 'a {
+    pub fn faces<'a>(&'a self, voronoi: &'a Voronoi) -> impl Iterator<Item = &'a VoronoiFace> + 'a {
         self.face_indices(voronoi)
             .iter()
             .map(|&i| &voronoi.faces[i])
     }
 
+    /// Get an `Iterator` over the indices of the neighbouring cells across the faces of this cell (in the order of
+    /// [`VoronoiCell::faces`]), or `None` for boundary faces.
+    ///
+    /// This cell must be the cell of `voronoi` with index `cell_idx`. Neighbours are reported once per face, so a cell
+    /// may appear multiple times in periodic tesselations (e.g. a neighbour that is both a direct and a periodic
+    /// neighbour, or this cell itself for a face with its own periodic image).
+    pub fn neighbour_ids<'a>(
+        &'a self,
+        voronoi: &'a Voronoi,
+        cell_idx: usize,
+    ) -> impl Iterator<Item = Option<usize>> + 'a {
+        self.faces(voronoi).map(move |face| {
+            if face.left() == cell_idx {
+                face.right()
+            } else {
+                Some(face.left())
+            }
+        })
+    }
+
     /// Get an `Iterator` over the extra vector face integral with given `id` of the faces that have this cell as neighbours.
     pub fn vector_face_integrals<'a>(
         &'a self,
@@ -505,10 +950,285 @@ impl VoronoiCell {
         &'a self,
         id: usize,
         voronoi: &'a Voronoi,
-    ) -> impl Iterator<Item = DVec3> + 'a {
+    ) -> impl Iterator<Item = f64> + 'a {
         self.face_indices(voronoi)
             .iter()
-            .map(move |i| voronoi.vector_face_integrals[id][*i])
+            .map(move |i| voronoi.scalar_face_integrals[id][*i])
+    }
+
+    /// Get the signed (Euclidean) distance from `point` to the boundary of this cell: negative inside the cell and
+    /// positive outside of it.
+    ///
+    /// For periodic tesselations, the cell is not wrapped, i.e. `point` must be given in the frame of the generator of
+    /// this cell. The unused coordinates of `point` are ignored for 1D and 2D tesselations.
+    pub fn signed_distance(&self, voronoi: &Voronoi, mut point: DVec3) -> f64 {
+        match voronoi.dimensionality {
+            Dimensionality::Dimensionality1D => {
+                point.y = self.loc.y;
+                point.z = self.loc.z;
+            }
+            Dimensionality::Dimensionality2D => point.z = self.loc.z,
+            Dimensionality::Dimensionality3D => (),
+        }
+        // The faces with their normals pointing away from this cell
+        let faces = || {
+            self.faces(voronoi).map(|face| {
+                let normal = face.normal();
+                if normal.dot(face.centroid() - self.loc) < 0. {
+                    (face, -normal)
+                } else {
+                    (face, normal)
+                }
+            })
+        };
+
+        // Inside the cell, the closest point of the boundary lies on the closest face plane
+        let max_plane_distance = faces()
+            .map(|(face, normal)| normal.dot(point - face.centroid()))
+            .fold(f64::NEG_INFINITY, f64::max);
+        if max_plane_distance <= 0. {
+            return max_plane_distance;
+        }
+
+        // Outside the cell, the closest point of the boundary lies on the closest face
+        faces()
+            .map(|(face, normal)| distance_to_polygon(point, face.vertices(voronoi), normal))
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    /// Whether `point` lies inside this cell (or on its boundary), i.e. on the inner side of the planes of all faces
+    /// of this cell.
+    ///
+    /// This cell must be the cell of `voronoi` with index `cell_idx`. For periodic tesselations, `point` may be given
+    /// in any periodic frame: it is first moved to its periodic image nearest to the generator of this cell. The
+    /// unused coordinates of `point` are ignored for 1D and 2D tesselations.
+    pub fn contains(&self, voronoi: &Voronoi, cell_idx: usize, point: DVec3) -> bool {
+        let point = self.nearest_image(voronoi, point);
+        self.oriented_faces(voronoi, cell_idx)
+            .all(|(face, sign, offset)| {
+                sign * face.normal().dot(point + offset - face.centroid()) <= 0.
+            })
+    }
+
+    /// Get the (exact) volume of the intersection of this cell with the sphere (ball) with given `center` and `radius`,
+    /// i.e. the length or area of the intersection with a line segment or disk for 1D and 2D tesselations.
+    ///
+    /// This cell must be the cell of `voronoi` with index `cell_idx`. For periodic tesselations, `center` is first
+    /// moved to its periodic image nearest to the generator of this cell, the other periodic images of the sphere are
+    /// not taken into account. The unused coordinates of `center` are ignored for 1D and 2D tesselations.
+    pub fn sphere_overlap(
+        &self,
+        voronoi: &Voronoi,
+        cell_idx: usize,
+        center: DVec3,
+        radius: f64,
+    ) -> f64 {
+        if self.volume == 0. || radius <= 0. {
+            return 0.;
+        }
+        let center = self.nearest_image(voronoi, center);
+        // The faces with the center in their frame
+        let faces = self
+            .oriented_faces(voronoi, cell_idx)
+            .map(|(face, sign, offset)| (face, sign, center + offset));
+        match voronoi.dimensionality {
+            Dimensionality::Dimensionality1D => {
+                let (lower, upper) = faces.fold(
+                    (f64::INFINITY, f64::NEG_INFINITY),
+                    |(lower, upper), (face, _, _)| {
+                        (lower.min(face.centroid().x), upper.max(face.centroid().x))
+                    },
+                );
+                ((center.x + radius).min(upper) - (center.x - radius).max(lower)).max(0.)
+            }
+            Dimensionality::Dimensionality2D => faces
+                .filter_map(|(face, sign, center)| {
+                    let (a, b) = edge_endpoints(face.vertices(voronoi), sign * face.normal())?;
+                    Some(disk_triangle_area(center, radius, a, b))
+                })
+                .sum(),
+            Dimensionality::Dimensionality3D => faces
+                .map(|(face, sign, center)| {
+                    sign * ball_cone_volume(center, radius, face.vertices(voronoi), face.normal())
+                })
+                .sum(),
+        }
+    }
+
+    /// Generate `n` points distributed uniformly inside this cell.
+    ///
+    /// The cell is decomposed into simplices (tetrahedra, triangles or a line segment joining its centroid with its
+    /// faces), of which one is picked with probability proportional to its volume for every point, which is then
+    /// sampled uniformly in barycentric coordinates. The points are given in the frame of the generator of this cell
+    /// (also for periodic tesselations) and their unused coordinates are those of the generator for 1D and 2D
+    /// tesselations. Cells that were not constructed (in a partial tesselation) produce no points.
+    ///
+    /// This cell must be the cell of `voronoi` with index `cell_idx`.
+    pub fn sample_uniform<R: Rng + ?Sized>(
+        &self,
+        voronoi: &Voronoi,
+        cell_idx: usize,
+        rng: &mut R,
+        n: usize,
+    ) -> Vec<DVec3> {
+        if self.volume == 0. {
+            return vec![];
+        }
+        let simplices = self.simplices(voronoi, cell_idx);
+        let dimensionality = voronoi.dimensionality;
+        let mut cumulative = simplices
+            .iter()
+            .scan(0., |total, simplex| {
+                *total += simplex_measure(simplex, dimensionality);
+                Some(*total)
+            })
+            .collect::<Vec<_>>();
+        let total = cumulative.last().copied().unwrap_or(0.);
+        if total <= 0. {
+            return vec![];
+        }
+        cumulative.pop();
+
+        (0..n)
+            .map(|_| {
+                let target = rng.gen::<f64>() * total;
+                let simplex = &simplices[cumulative.partition_point(|&c| c <= target)];
+                // Uniform barycentric coordinates are the spacings of sorted uniform numbers
+                let mut u = [rng.gen::<f64>(), rng.gen::<f64>(), rng.gen::<f64>()];
+                match voronoi.dimensionality {
+                    Dimensionality::Dimensionality1D => u[1..].fill(1.),
+                    Dimensionality::Dimensionality2D => u[2] = 1.,
+                    Dimensionality::Dimensionality3D => (),
+                }
+                u.sort_unstable_by(f64::total_cmp);
+                simplex[0] * u[0]
+                    + simplex[1] * (u[1] - u[0])
+                    + simplex[2] * (u[2] - u[1])
+                    + simplex[3] * (1. - u[2])
+            })
+            .collect()
+    }
+
+    /// Get the (exact) covariance matrix of the positions inside this cell about its centroid, i.e. the integral of
+    /// `(x - centroid)⊗(x - centroid)` over the cell divided by its volume.
+    ///
+    /// This is the shape tensor of the cell: its eigenvectors are the principal axes of the cell and the ratios of its
+    /// eigenvalues measure the anisotropy of the cell (a cube with side `a` has covariance `a² / 12` times the
+    /// identity). It is computed from the decomposition of the cell into simplices joining its centroid with its faces.
+    /// The rows and columns of the unused coordinates are zero for 1D and 2D tesselations. Cells that were not
+    /// constructed (in a partial tesselation) have zero covariance.
+    ///
+    /// This cell must be the cell of `voronoi` with index `cell_idx`.
+    pub fn covariance(&self, voronoi: &Voronoi, cell_idx: usize) -> DMat3 {
+        if self.volume == 0. {
+            return DMat3::ZERO;
+        }
+        let apex = self.nearest_image(voronoi, self.centroid);
+        let dimensionality = voronoi.dimensionality;
+        let n = usize::from(dimensionality) as f64;
+        let outer = |a: DVec3| DMat3::from_cols(a * a.x, a * a.y, a * a.z);
+        let mut volume = 0.;
+        let mut first_moment = DVec3::ZERO;
+        let mut second_moment = DMat3::ZERO;
+        for simplex in self.simplices(voronoi, cell_idx) {
+            let simplex_volume = simplex_measure(&simplex, dimensionality);
+            // The second moment of a simplex about the origin is `V / ((n + 1)(n + 2)) (Σ v_i⊗v_i + s⊗s)`, with `s`
+            // the sum of its vertices (the unused vertices are equal to the apex, i.e. zero relative to it)
+            let relative = simplex.map(|v| v - apex);
+            let sum = relative.iter().sum::<DVec3>();
+            second_moment += (relative.iter().map(|&v| outer(v)).sum::<DMat3>() + outer(sum))
+                * (simplex_volume / ((n + 1.) * (n + 2.)));
+            first_moment += sum * (simplex_volume / (n + 1.));
+            volume += simplex_volume;
+        }
+        if volume <= 0. {
+            return DMat3::ZERO;
+        }
+        // Correct for the (round off) offset of the apex from the centroid
+        let mean = first_moment / volume;
+        second_moment * (1. / volume) - outer(mean)
+    }
+
+    /// Decompose this cell into simplices (tetrahedra, triangles or a line segment) joining its centroid with its
+    /// faces, in the frame of the generator of this cell. The simplices always have 4 vertices, the unused vertices
+    /// (and coordinates) are equal to those of the centroid, which is the first vertex (except in 1D).
+    fn simplices(&self, voronoi: &Voronoi, cell_idx: usize) -> Vec<[DVec3; 4]> {
+        let apex = self.nearest_image(voronoi, self.centroid);
+        // The vertices of the simplices, the unused vertices are equal to the apex
+        match voronoi.dimensionality {
+            Dimensionality::Dimensionality1D => {
+                let (lower, upper) = self.faces(voronoi).fold(
+                    (f64::INFINITY, f64::NEG_INFINITY),
+                    |(lower, upper), face| {
+                        (lower.min(face.centroid().x), upper.max(face.centroid().x))
+                    },
+                );
+                vec![[
+                    DVec3::new(lower, apex.y, apex.z),
+                    DVec3::new(upper, apex.y, apex.z),
+                    apex,
+                    apex,
+                ]]
+            }
+            Dimensionality::Dimensionality2D => self
+                .oriented_faces(voronoi, cell_idx)
+                .filter_map(|(face, sign, offset)| {
+                    let (a, b) = edge_endpoints(face.vertices(voronoi), sign * face.normal())?;
+                    let (a, b) = (a - offset, b - offset);
+                    Some([
+                        apex,
+                        a.truncate().extend(apex.z),
+                        b.truncate().extend(apex.z),
+                        apex,
+                    ])
+                })
+                .collect::<Vec<_>>(),
+            Dimensionality::Dimensionality3D => self
+                .oriented_faces(voronoi, cell_idx)
+                .flat_map(|(face, _, offset)| {
+                    let vertices = face.vertices(voronoi);
+                    (2..vertices.len()).map(move |i| {
+                        let [v0, v1, v2] =
+                            [vertices[0], vertices[i - 1], vertices[i]].map(|v| v - offset);
+                        [apex, v0, v1, v2]
+                    })
+                })
+                .collect::<Vec<_>>(),
+        }
+    }
+
+    /// Move `point` to its periodic image nearest to the generator of this cell (for periodic tesselations) and replace
+    /// its unused coordinates by those of the generator (for 1D and 2D tesselations).
+    pub(super) fn nearest_image(&self, voronoi: &Voronoi, mut point: DVec3) -> DVec3 {
+        match voronoi.dimensionality {
+            Dimensionality::Dimensionality1D => {
+                point.y = self.loc.y;
+                point.z = self.loc.z;
+            }
+            Dimensionality::Dimensionality2D => point.z = self.loc.z,
+            Dimensionality::Dimensionality3D => (),
+        }
+        if voronoi.periodic {
+            point -= ((point - self.loc) / voronoi.width).round() * voronoi.width;
+        }
+        point
+    }
+
+    /// Get an `Iterator` over the faces of this cell with their orientation (1 if their normal points away from this
+    /// cell, -1 otherwise) and the offset from the frame of this cell to the frame of the face (i.e. the periodic shift
+    /// of faces with this cell on their right). This cell must be the cell of `voronoi` with index `cell_idx`.
+    pub(super) fn oriented_faces<'a>(
+        &'a self,
+        voronoi: &'a Voronoi,
+        cell_idx: usize,
+    ) -> impl Iterator<Item = (&'a VoronoiFace, f64, DVec3)> + 'a {
+        self.faces(voronoi).map(move |face| {
+            if face.left() == cell_idx {
+                (face, 1., DVec3::ZERO)
+            } else {
+                (face, -1., face.shift().unwrap_or(DVec3::ZERO))
+            }
+        })
     }
 
     /// Get the offset of the slice of the indices of this cell's faces in the `Voronoi::cell_face_connections` array.
@@ -522,9 +1242,35 @@ impl VoronoiCell {
     }
 }
 
+/// The volume (length or area for 1D and 2D tesselations) of a simplex of [`VoronoiCell::simplices`].
+fn simplex_measure([v0, v1, v2, v3]: &[DVec3; 4], dimensionality: Dimensionality) -> f64 {
+    match dimensionality {
+        Dimensionality::Dimensionality1D => (v1.x - v0.x).abs(),
+        Dimensionality::Dimensionality2D => 0.5 * (*v1 - *v0).cross(*v2 - *v0).length(),
+        Dimensionality::Dimensionality3D => signed_volume_tet(*v0, *v1, *v2, *v3).abs(),
+    }
+}
+
+/// Get the endpoints of the edge of a face of a 2D tesselation with the given (`vertices` and) outward `normal`,
+/// ordered counterclockwise around the cell.
+pub(super) fn edge_endpoints(vertices: &[DVec3], normal: DVec3) -> Option<(DVec3, DVec3)> {
+    let tangent = DVec3::new(-normal.y, normal.x, 0.);
+    let position = |v: &&DVec3| tangent.dot(**v);
+    let a = vertices
+        .iter()
+        .min_by(|u, v| position(u).total_cmp(&position(v)))?;
+    let b = vertices
+        .iter()
+        .max_by(|u, v| position(u).total_cmp(&position(v)))?;
+    Some((*a, *b))
+}
+
 #[cfg(test)]
 mod test {
+    use float_cmp::assert_approx_eq;
+
     use super::*;
+    use crate::voronoi::test::perturbed_grid;
 
     const DIM3D: usize = 3;
 
@@ -558,4 +1304,389 @@ mod test {
 
         assert_eq!(cell.clipping_planes.len(), 7)
     }
+
+    #[test]
+    fn test_signed_distance() {
+        let voronoi = Voronoi::build(
+            &[DVec3::splat(0.5)],
+            DVec3::ZERO,
+            DVec3::ONE,
+            DIM3D,
+            false,
+            None,
+        );
+        let cell = &voronoi.cells()[0];
+        for (point, distance) in [
+            (DVec3::new(0.5, 0.5, 0.5), -0.5),
+            (DVec3::new(0.2, 0.6, 0.5), -0.2),
+            (DVec3::new(0.5, 0.5, 1.5), 0.5),
+            (DVec3::new(1.5, 1.5, 0.5), 0.5f64.sqrt()),
+            (DVec3::splat(2.), 3f64.sqrt()),
+        ] {
+            assert_approx_eq!(
+                f64,
+                cell.signed_distance(&voronoi, point),
+                distance,
+                epsilon = 1e-12
+            );
+        }
+
+        // The sign agrees with point location
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let voronoi = Voronoi::build(
+            &perturbed_grid(anchor, width, 4, 0.5),
+            anchor,
+            width,
+            DIM3D,
+            false,
+            None,
+        );
+        let points = perturbed_grid(anchor, width, 6, 0.9);
+        for (point, cell_idx) in points.iter().zip(voronoi.cells_at(&points)) {
+            for (idx, cell) in voronoi.cells().iter().enumerate() {
+                let distance = cell.signed_distance(&voronoi, *point);
+                if idx == cell_idx {
+                    assert!(distance <= 0.);
+                } else {
+                    assert!(distance >= -1e-12);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_neighbour_ids() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        for periodic in [false, true] {
+            let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, periodic, None);
+            for (idx, cell) in voronoi.cells().iter().enumerate() {
+                let neighbour_ids = cell.neighbour_ids(&voronoi, idx).collect::<Vec<_>>();
+                assert_eq!(neighbour_ids.len(), cell.face_count());
+                for (face, neighbour_id) in cell.faces(&voronoi).zip(neighbour_ids) {
+                    match neighbour_id {
+                        Some(neighbour_idx) if face.left() == idx => {
+                            assert_eq!(face.right(), Some(neighbour_idx))
+                        }
+                        Some(neighbour_idx) => {
+                            assert_eq!(face.left(), neighbour_idx);
+                            assert_eq!(face.right(), Some(idx));
+                        }
+                        None => {
+                            assert!(face.right().is_none());
+                            assert_eq!(face.left(), idx);
+                        }
+                    }
+                    // Neighbours are mutual
+                    if let Some(neighbour_idx) = neighbour_id {
+                        assert!(voronoi.cells()[neighbour_idx]
+                            .neighbour_ids(&voronoi, neighbour_idx)
+                            .any(|id| id == Some(idx)));
+                    }
+                }
+            }
+            let boundary_faces = voronoi
+                .faces()
+                .iter()
+                .filter(|f| f.right().is_none())
+                .count();
+            let none_count = voronoi
+                .cells()
+                .iter()
+                .enumerate()
+                .flat_map(|(idx, cell)| cell.neighbour_ids(&voronoi, idx))
+                .filter(Option::is_none)
+                .count();
+            assert_eq!(none_count, boundary_faces);
+            if periodic {
+                assert_eq!(none_count, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_neighbour_ids_copied_cell() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 3, 0.5);
+        let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, false, None);
+        for (idx, cell) in voronoi.cells().iter().enumerate() {
+            let copy = *cell;
+            assert!(copy
+                .neighbour_ids(&voronoi, idx)
+                .eq(cell.neighbour_ids(&voronoi, idx)));
+            // Without periodic faces, a cell is never its own neighbour
+            assert!(copy.neighbour_ids(&voronoi, idx).all(|id| id != Some(idx)));
+        }
+    }
+
+    #[test]
+    fn test_contains() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        let points = perturbed_grid(anchor, width, 5, 0.9);
+        for periodic in [false, true] {
+            let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, periodic, None);
+            for (point, cell_idx) in points.iter().zip(voronoi.cells_at(&points)) {
+                for (idx, cell) in voronoi.cells().iter().enumerate() {
+                    assert_eq!(cell.contains(&voronoi, idx, *point), idx == cell_idx);
+                    if periodic {
+                        // Any periodic image of the point is accepted
+                        for shift in [DVec3::X, -DVec3::Y, DVec3::new(1., -2., 1.)] {
+                            assert_eq!(
+                                cell.contains(&voronoi, idx, *point + shift),
+                                idx == cell_idx
+                            );
+                        }
+                    }
+                }
+            }
+            // The generators lie inside their own cell
+            for (idx, cell) in voronoi.cells().iter().enumerate() {
+                assert!(cell.contains(&voronoi, idx, cell.loc()));
+            }
+            if !periodic {
+                assert!(!voronoi
+                    .cells()
+                    .iter()
+                    .enumerate()
+                    .any(|(idx, cell)| cell.contains(&voronoi, idx, DVec3::new(0.5, 0.5, 1.1))));
+            }
+        }
+    }
+
+    #[test]
+    fn test_sphere_overlap() {
+        use std::f64::consts::PI;
+
+        // A single unit cube
+        let voronoi = Voronoi::build(
+            &[DVec3::splat(0.5)],
+            DVec3::ZERO,
+            DVec3::ONE,
+            DIM3D,
+            false,
+            None,
+        );
+        let cell = &voronoi.cells()[0];
+        let ball_volume = |radius: f64| 4. / 3. * PI * radius.powi(3);
+        for (center, radius, volume) in [
+            (DVec3::splat(0.5), 0.3, ball_volume(0.3)),
+            (DVec3::new(0.2, 0.6, 0.7), 0.15, ball_volume(0.15)),
+            // Octant, quarter and half of a ball
+            (DVec3::ZERO, 0.7, 0.125 * ball_volume(0.7)),
+            (DVec3::new(1., 0.5, 1.), 0.4, 0.25 * ball_volume(0.4)),
+            (DVec3::new(0.5, 0., 0.5), 0.4, 0.5 * ball_volume(0.4)),
+            (DVec3::splat(0.5), 1., 1.),
+            (DVec3::new(0.5, 0.5, 1.8), 0.7, 0.),
+            (DVec3::splat(0.5), 0., 0.),
+        ] {
+            assert_approx_eq!(
+                f64,
+                cell.sphere_overlap(&voronoi, 0, center, radius),
+                volume,
+                epsilon = 1e-12
+            );
+        }
+
+        // The overlaps with all cells make up the ball
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        for periodic in [false, true] {
+            let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, periodic, None);
+            // Periodic balls may wrap around the simulation volume
+            let center = if periodic {
+                DVec3::new(0.1, 0.95, 0.5)
+            } else {
+                DVec3::new(0.4, 0.55, 0.5)
+            };
+            let radius = 0.3;
+            let overlaps = voronoi
+                .cells()
+                .iter()
+                .enumerate()
+                .map(|(idx, cell)| cell.sphere_overlap(&voronoi, idx, center, radius))
+                .collect::<Vec<_>>();
+            assert_approx_eq!(
+                f64,
+                overlaps.iter().sum::<f64>(),
+                ball_volume(radius),
+                epsilon = 1e-10
+            );
+            for (cell, overlap) in voronoi.cells().iter().zip(overlaps) {
+                assert!(overlap >= -1e-12 && overlap <= cell.volume() + 1e-12);
+            }
+        }
+
+        // Disks in 2D
+        let generators = crate::voronoi::test::perturbed_plane(anchor, width, 5, 0.5);
+        let voronoi = Voronoi::build(&generators, anchor, width, 2, false, None);
+        for (center, radius, area) in [
+            (DVec3::new(0.5, 0.4, 3.), 0.3, PI * 0.09),
+            (DVec3::new(0., 0.5, 0.), 0.2, 0.5 * PI * 0.04),
+            (DVec3::new(0.5, 0.5, 0.), 1., 1.),
+        ] {
+            let total = voronoi
+                .cells()
+                .iter()
+                .enumerate()
+                .map(|(idx, cell)| cell.sphere_overlap(&voronoi, idx, center, radius))
+                .sum::<f64>();
+            assert_approx_eq!(f64, total, area, epsilon = 1e-10);
+        }
+
+        // Line segments in 1D
+        let generators = [0.1, 0.3, 0.7].map(|x| DVec3::new(x, 0., 0.));
+        let voronoi = Voronoi::build(&generators, anchor, width, 1, false, None);
+        let center = DVec3::new(0.4, 2., 0.);
+        let overlaps = voronoi
+            .cells()
+            .iter()
+            .enumerate()
+            .map(|(idx, cell)| cell.sphere_overlap(&voronoi, idx, center, 0.25))
+            .collect::<Vec<_>>();
+        for (overlap, expected) in overlaps.into_iter().zip([0.05, 0.3, 0.15]) {
+            assert_approx_eq!(f64, overlap, expected, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_sample_uniform() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(3);
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 3, 0.5);
+        let planar = generators
+            .iter()
+            .map(|g| DVec3::new(g.x, g.y, 0.3))
+            .collect::<Vec<_>>();
+        for (generators, dimensionality) in [(generators, DIM3D), (planar, 2)] {
+            for periodic in [false, true] {
+                let voronoi =
+                    Voronoi::build(&generators, anchor, width, dimensionality, periodic, None);
+                let n = 4000;
+                for (idx, cell) in voronoi.cells().iter().enumerate() {
+                    let points = cell.sample_uniform(&voronoi, idx, &mut rng, n);
+                    assert_eq!(points.len(), n);
+                    for point in points.iter() {
+                        assert!(cell.signed_distance(&voronoi, *point) <= 1e-12);
+                    }
+                    // The sample mean converges to the centroid
+                    let mean = points.iter().sum::<DVec3>() / n as f64;
+                    let spread = cell.volume().powf(1. / dimensionality as f64);
+                    assert!(mean.distance(cell.centroid()) < 0.1 * spread);
+                }
+            }
+
+            // The cells of all points agree with the sampled cells
+            let voronoi = Voronoi::build(&generators, anchor, width, dimensionality, false, None);
+            for (idx, cell) in voronoi.cells().iter().enumerate() {
+                let points = cell.sample_uniform(&voronoi, idx, &mut rng, 100);
+                assert!(voronoi.cells_at(&points).into_iter().all(|i| i == idx));
+            }
+        }
+
+        // Line segments in 1D
+        let generators = [0.1, 0.3, 0.7].map(|x| DVec3::new(x, 0., 0.));
+        let voronoi = Voronoi::build(&generators, anchor, width, 1, false, None);
+        let points = voronoi.cells()[2].sample_uniform(&voronoi, 2, &mut rng, 1000);
+        assert!(points.iter().all(|p| p.x >= 0.5 && p.x <= 1. && p.y == 0.));
+        let mean = points.iter().map(|p| p.x).sum::<f64>() / 1000.;
+        assert!((mean - 0.75).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_covariance() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let assert_mat_eq = |a: DMat3, b: DMat3, epsilon: f64| {
+            for (a, b) in a.to_cols_array().into_iter().zip(b.to_cols_array()) {
+                assert_approx_eq!(f64, a, b, epsilon = epsilon);
+            }
+        };
+
+        // A single box shaped cell
+        let anchor = DVec3::ZERO;
+        let width = DVec3::new(1., 2., 3.);
+        let generators = [DVec3::new(0.3, 1.2, 1.1)];
+        for (dimensionality, diagonal) in [
+            (1, DVec3::new(1., 0., 0.)),
+            (2, DVec3::new(1., 4., 0.)),
+            (DIM3D, DVec3::new(1., 4., 9.)),
+        ] {
+            let voronoi = Voronoi::build(&generators, anchor, width, dimensionality, false, None);
+            let covariance = voronoi.cells()[0].covariance(&voronoi, 0);
+            assert_mat_eq(covariance, DMat3::from_diagonal(diagonal / 12.), 1e-12);
+        }
+
+        // The covariance agrees with the sample covariance
+        let mut rng = StdRng::seed_from_u64(5);
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 3, 0.5);
+        for periodic in [false, true] {
+            let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, periodic, None);
+            for (idx, cell) in voronoi.cells().iter().enumerate() {
+                let covariance = cell.covariance(&voronoi, idx);
+                assert_mat_eq(covariance, covariance.transpose(), 1e-14);
+                let n = 20000;
+                let points = cell.sample_uniform(&voronoi, idx, &mut rng, n);
+                let sample = points
+                    .iter()
+                    .map(|&p| {
+                        let d = p - cell.centroid();
+                        DMat3::from_cols(d * d.x, d * d.y, d * d.z)
+                    })
+                    .sum::<DMat3>()
+                    * (1. / n as f64);
+                let scale = cell.volume().powf(2. / 3.);
+                assert_mat_eq(covariance * (1. / scale), sample * (1. / scale), 5e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_copied_cell() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        use crate::voronoi::Kernel;
+
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 3, 0.5);
+        for periodic in [false, true] {
+            let voronoi = Voronoi::build(&generators, anchor, width, DIM3D, periodic, None);
+            for (idx, cell) in voronoi.cells().iter().enumerate() {
+                // A copy of a cell is oriented like the cell in the tesselation
+                let copy = *cell;
+                assert!(copy.contains(&voronoi, idx, copy.centroid()));
+                assert_approx_eq!(
+                    f64,
+                    copy.sphere_overlap(&voronoi, idx, copy.centroid(), 2.),
+                    cell.volume(),
+                    epsilon = 1e-12
+                );
+                assert_eq!(
+                    copy.covariance(&voronoi, idx),
+                    cell.covariance(&voronoi, idx)
+                );
+                let center = copy.centroid();
+                assert_eq!(
+                    copy.kernel_integral(&voronoi, idx, Kernel::WendlandC2, center, 0.3, 1e-8),
+                    cell.kernel_integral(&voronoi, idx, Kernel::WendlandC2, center, 0.3, 1e-8)
+                );
+                let points = copy.sample_uniform(&voronoi, idx, &mut StdRng::seed_from_u64(7), 20);
+                assert_eq!(
+                    points,
+                    cell.sample_uniform(&voronoi, idx, &mut StdRng::seed_from_u64(7), 20)
+                );
+                assert!(points.iter().all(|&p| cell.contains(&voronoi, idx, p)));
+            }
+        }
+    }
 }
@@ -1,11 +1,11 @@
 use glam::DVec3;
 
 use crate::integrators::{
-    AreaCentroidIntegrator, ScalarVoronoiFaceIntegrator, VectorVoronoiFaceIntegrator,
-    VoronoiFaceIntegrator,
+    AreaCentroidIntegrator, FaceIntegralStore, FaceIntegrators, GenericFaceIntegrator,
+    ScalarVoronoiFaceIntegrator, VectorVoronoiFaceIntegrator, VoronoiFaceIntegrator,
 };
 
-use super::{voronoi_cell::HalfSpace, Dimensionality};
+use super::{voronoi_cell::HalfSpace, Dimensionality, Voronoi};
 
 pub struct VoronoiFaceBuilder<'a> {
     left_idx: usize,
@@ -15,6 +15,7 @@ pub struct VoronoiFaceBuilder<'a> {
     area_centroid: AreaCentroidIntegrator,
     vector_face_integrators: Vec<Box<dyn VectorVoronoiFaceIntegrator<Output = DVec3>>>,
     scalar_face_integrators: Vec<Box<dyn ScalarVoronoiFaceIntegrator<Output = f64>>>,
+    generic_face_integrators: Vec<Box<dyn GenericFaceIntegrator>>,
 }
 
 impl<'a> VoronoiFaceBuilder<'a> {
@@ -22,21 +23,36 @@ impl<'a> VoronoiFaceBuilder<'a> {
         left_idx: usize,
         left_loc: DVec3,
         half_space: &'a HalfSpace,
-        vector_face_integrals: &[Box<
-            dyn Fn() -> Box<dyn VectorVoronoiFaceIntegrator> + Send + Sync,
-        >],
-        scalar_face_integrals: &[Box<
-            dyn Fn() -> Box<dyn ScalarVoronoiFaceIntegrator> + Send + Sync,
-        >],
+        face_integrators: &FaceIntegrators,
     ) -> Self {
         let half_loc = half_space.project_onto(left_loc);
-        let vector_face_integrators = vector_face_integrals
+        let right_idx = half_space.right_idx;
+        let vector_face_integrators = face_integrators
+            .vector_integrators()
             .iter()
-            .map(|get_integrator| get_integrator())
+            .map(|get_integrator| {
+                let mut integrator = get_integrator();
+                integrator.init(left_idx, right_idx);
+                integrator
+            })
             .collect();
-        let scalar_face_integrators = scalar_face_integrals
+        let scalar_face_integrators = face_integrators
+            .scalar_integrators()
             .iter()
-            .map(|get_integrator| get_integrator())
+            .map(|get_integrator| {
+                let mut integrator = get_integrator();
+                integrator.init(left_idx, right_idx);
+                integrator
+            })
+            .collect();
+        let generic_face_integrators = face_integrators
+            .generic_integrators()
+            .iter()
+            .map(|factory| {
+                let mut integrator = factory.integrator();
+                integrator.init(left_idx, right_idx);
+                integrator
+            })
             .collect();
         Self {
             left_idx,
@@ -46,6 +62,7 @@ impl<'a> VoronoiFaceBuilder<'a> {
             area_centroid: AreaCentroidIntegrator::init(),
             vector_face_integrators,
             scalar_face_integrators,
+            generic_face_integrators,
         }
     }
 
@@ -58,9 +75,18 @@ impl<'a> VoronoiFaceBuilder<'a> {
         for integrator in self.scalar_face_integrators.iter_mut() {
             integrator.collect(v0, v1, v2, self.left_loc, self.right_loc)
         }
+        for integrator in self.generic_face_integrators.iter_mut() {
+            integrator.collect(v0, v1, v2, self.left_loc, self.right_loc)
+        }
     }
 
-    pub fn build(self) -> (VoronoiFace, Vec<DVec3>, Vec<f64>) {
+    /// Finalize the face and its extra integrals. The integrals with arbitrary output types are appended to the
+    /// given `generic_face_integrals` (one store per integrator).
+    pub fn build(
+        self,
+        vertex_count: usize,
+        generic_face_integrals: &mut [Box<dyn FaceIntegralStore>],
+    ) -> (VoronoiFace, Vec<DVec3>, Vec<f64>) {
         let (area, centroid) = self.area_centroid.finalize();
         let vector_integrals = self
             .vector_face_integrators
@@ -72,6 +98,13 @@ impl<'a> VoronoiFaceBuilder<'a> {
             .iter()
             .map(|integrator| integrator.finalize())
             .collect();
+        for (integrator, store) in self
+            .generic_face_integrators
+            .iter()
+            .zip(generic_face_integrals.iter_mut())
+        {
+            integrator.finalize_into(store.as_mut());
+        }
         (
             VoronoiFace::new(
                 self.left_idx,
@@ -80,6 +113,8 @@ impl<'a> VoronoiFaceBuilder<'a> {
                 centroid,
                 -self.half_space.normal(),
                 self.half_space.shift,
+                self.half_space.boundary,
+                vertex_count,
             ),
             vector_integrals,
             scalar_integrals,
@@ -87,7 +122,28 @@ impl<'a> VoronoiFaceBuilder<'a> {
     }
 }
 
+/// The boundary of the simulation volume that created a boundary face, see [`VoronoiFace::boundary`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Boundary {
+    /// The lower boundary of the simulation volume along the x-axis.
+    XMin,
+    /// The upper boundary of the simulation volume along the x-axis.
+    XMax,
+    /// The lower boundary of the simulation volume along the y-axis.
+    YMin,
+    /// The upper boundary of the simulation volume along the y-axis.
+    YMax,
+    /// The lower boundary of the simulation volume along the z-axis.
+    ZMin,
+    /// The upper boundary of the simulation volume along the z-axis.
+    ZMax,
+    /// Not a boundary of the simulation volume: the face used to be shared with a neighbouring cell that was removed
+    /// from the tesselation (e.g. by [`Voronoi::extract`]).
+    Cut,
+}
+
 /// A Voronoi face between two neighbouring generators.
+#[derive(Debug, Clone, Copy)]
 pub struct VoronoiFace {
     left: usize,
     right: Option<usize>,
@@ -95,9 +151,13 @@ pub struct VoronoiFace {
     centroid: DVec3,
     normal: DVec3,
     shift: Option<DVec3>,
+    boundary: Option<Boundary>,
+    vertex_offset: usize,
+    vertex_count: usize,
 }
 
 impl VoronoiFace {
+    #[allow(clippy::too_many_arguments)]
     pub(super) fn new(
         left: usize,
         right: Option<usize>,
@@ -105,6 +165,8 @@ impl VoronoiFace {
         centroid: DVec3,
         normal: DVec3,
         shift: Option<DVec3>,
+        boundary: Option<Boundary>,
+        vertex_count: usize,
     ) -> Self {
         VoronoiFace {
             left,
@@ -113,6 +175,79 @@ impl VoronoiFace {
             centroid,
             normal,
             shift,
+            boundary,
+            vertex_offset: 0,
+            vertex_count,
+        }
+    }
+
+    /// Copy of this face with new neighbours. If `flip` is true, the orientation of the face is reversed.
+    /// Faces without a right neighbour cannot have a shift. Faces that lose their right neighbour become
+    /// [`Boundary::Cut`] boundary faces.
+    pub(super) fn remapped(&self, left: usize, right: Option<usize>, flip: bool) -> Self {
+        VoronoiFace {
+            left,
+            right,
+            normal: if flip { -self.normal } else { self.normal },
+            shift: right.and(self.shift),
+            boundary: match right {
+                Some(_) => None,
+                None => self.boundary.or(Some(Boundary::Cut)),
+            },
+            ..*self
+        }
+    }
+
+    pub(super) fn with_shift(self, shift: Option<DVec3>) -> Self {
+        Self { shift, ..self }
+    }
+
+    /// The copy of this periodic face stored for its right cell: translated to the frame of the right cell and with
+    /// reversed orientation.
+    pub(super) fn periodic_twin(&self) -> Self {
+        let (Some(right), Some(shift)) = (self.right, self.shift) else {
+            panic!("Only periodic faces have a twin!");
+        };
+        VoronoiFace {
+            left: right,
+            right: Some(self.left),
+            centroid: self.centroid - shift,
+            normal: -self.normal,
+            shift: Some(-shift),
+            ..*self
+        }
+    }
+
+    /// Apply the transformation `x -> x * scale + translation` to this face.
+    /// `jacobian` is the determinant of the linear part of the transformation.
+    pub(super) fn transform(&mut self, translation: DVec3, scale: DVec3, jacobian: f64) {
+        // Area vectors transform with the cofactor matrix of the transformation
+        let area_vector = jacobian * self.area * self.normal / scale;
+        self.area = area_vector.length();
+        self.normal = area_vector / self.area;
+        self.centroid = self.centroid * scale + translation;
+        self.shift = self.shift.map(|shift| shift * scale);
+    }
+
+    pub(super) fn finalize(&mut self, vertex_offset: usize) {
+        self.vertex_offset = vertex_offset;
+    }
+
+    pub(super) fn vertex_offset(&self) -> usize {
+        self.vertex_offset
+    }
+
+    /// Whether this face represents the pair of generators it separates. Faces between two cells are stored once,
+    /// except for periodic faces, which are stored for both of their cells: only one of both copies is primary.
+    pub(super) fn is_primary(&self) -> bool {
+        let Some(right) = self.right else {
+            return false;
+        };
+        match self.shift {
+            Some(shift) => {
+                self.left < right || (self.left == right && shift.to_array() > (-shift).to_array())
+            }
+            None => true,
         }
     }
 
@@ -135,6 +270,12 @@ impl VoronoiFace {
         self.right
     }
 
+    /// Get the boundary of the simulation volume that created this face.
+    /// Returns `None` for interior faces (i.e. faces with a right neighbour).
+    pub fn boundary(&self) -> Option<Boundary> {
+        self.boundary
+    }
+
     /// Get the area of this face.
     pub fn area(&self) -> f64 {
         self.area
@@ -150,9 +291,260 @@ impl VoronoiFace {
         self.normal
     }
 
+    /// Get the area vector of this face (i.e. its area times its normal).
+    pub fn area_vector(&self) -> DVec3 {
+        self.area * self.normal
+    }
+
+    /// Get the signed distance between the left and right generator of this face, measured along the face normal
+    /// and taking into account the periodic shift if necessary.
+    /// Returns `None` for boundary faces.
+    pub fn generator_distance(&self, voronoi: &Voronoi) -> Option<f64> {
+        let right_loc = self.right_loc(voronoi)?;
+        Some((right_loc - voronoi.cells[self.left].loc()).dot(self.normal))
+    }
+
+    /// Get the position of the generator to the right of this face in the reference frame of this face (i.e. of the
+    /// left generator), with the periodic shift applied if necessary.
+    /// Returns `None` for boundary faces.
+    pub fn right_loc(&self, voronoi: &Voronoi) -> Option<DVec3> {
+        let right = self.right?;
+        Some(voronoi.cells[right].loc() + self.shift.unwrap_or(DVec3::ZERO))
+    }
+
     /// Get the shift vector (if any) to apply to the generator to the right of this face to bring it to the reference frame of this face.
     /// Can only be `Some` for periodic Voronoi tesselations.
     pub fn shift(&self) -> Option<DVec3> {
         self.shift
     }
+
+    /// Get the vertices of this face, ordered counterclockwise when looking at the face from the
+    /// side its normal points to (i.e. from the _right_ generator).
+    pub fn vertices<'a>(&self, voronoi: &'a Voronoi) -> &'a [DVec3] {
+        &voronoi.face_vertices[self.vertex_offset..(self.vertex_offset + self.vertex_count)]
+    }
+
+    /// Get the number of vertices (and edges) of this face.
+    pub fn vertex_count(&self) -> usize {
+        self.vertex_count
+    }
+
+    /// Whether `point` lies on this face, i.e. within distance `tolerance` of the plane of this face, with its
+    /// projection onto that plane inside the loop of [`VoronoiFace::vertices`] (or within distance `tolerance` of it).
+    ///
+    /// For periodic faces, `point` must be given in the reference frame of this face (i.e. of the left generator).
+    /// The unused coordinates of `point` are ignored for 1D and 2D tesselations.
+    pub fn contains(&self, voronoi: &Voronoi, mut point: DVec3, tolerance: f64) -> bool {
+        match voronoi.dimensionality {
+            Dimensionality::Dimensionality1D => {
+                point.y = self.centroid.y;
+                point.z = self.centroid.z;
+            }
+            Dimensionality::Dimensionality2D => point.z = self.centroid.z,
+            Dimensionality::Dimensionality3D => (),
+        }
+        let height = self.normal.dot(point - self.centroid);
+        if height.abs() > tolerance {
+            return false;
+        }
+        let projected = point - height * self.normal;
+        // The vertices are ordered counterclockwise around the normal, so the inside lies to the left of every edge
+        let vertices = self.vertices(voronoi);
+        (0..vertices.len()).all(|i| {
+            let (a, b) = (vertices[i], vertices[(i + 1) % vertices.len()]);
+            let edge = b - a;
+            edge.cross(projected - a).dot(self.normal) >= -tolerance * edge.length()
+        })
+    }
+
+    /// Get an `Iterator` over the lengths of the edges of this face, in the same order as [`VoronoiFace::vertices`]
+    /// (the first edge runs from the first to the second vertex, the last one closes the loop).
+    ///
+    /// For 2D tesselations, the area of a face is the length of the Voronoi edge dual to its Delaunay edge, these are
+    /// the edges of the face when extruded along the z-axis.
+    pub fn edge_lengths<'a>(&self, voronoi: &'a Voronoi) -> impl Iterator<Item = f64> + 'a {
+        let vertices = self.vertices(voronoi);
+        (0..vertices.len()).map(move |i| vertices[i].distance(vertices[(i + 1) % vertices.len()]))
+    }
+
+    /// Get the perimeter of this face (i.e. the sum of its [`VoronoiFace::edge_lengths`]).
+    pub fn perimeter(&self, voronoi: &Voronoi) -> f64 {
+        self.edge_lengths(voronoi).sum()
+    }
+
+    /// Get the solid angle subtended by this face at the _left_ generator. Only defined for 3D tesselations.
+    pub fn solid_angle_left(&self, voronoi: &Voronoi) -> f64 {
+        self.solid_angle(voronoi, voronoi.cells[self.left].loc())
+    }
+
+    /// Get the solid angle subtended by this face at the (periodic image of the) _right_ generator. Only defined for
+    /// 3D tesselations.
+    /// Returns `None` for boundary faces.
+    pub fn solid_angle_right(&self, voronoi: &Voronoi) -> Option<f64> {
+        let right_loc = voronoi.cells[self.right?].loc() + self.shift.unwrap_or(DVec3::ZERO);
+        Some(self.solid_angle(voronoi, right_loc))
+    }
+
+    /// The solid angle subtended by this face at `point`, summed over the triangles of a fan triangulation
+    /// (using the formula of Van Oosterom and Strackee).
+    fn solid_angle(&self, voronoi: &Voronoi, point: DVec3) -> f64 {
+        assert!(
+            matches!(voronoi.dimensionality, Dimensionality::Dimensionality3D),
+            "Solid angles are only defined for 3D tesselations!"
+        );
+        let vertices = self.vertices(voronoi);
+        let Some(&v0) = vertices.first() else {
+            return 0.;
+        };
+        let a = v0 - point;
+        let a_length = a.length();
+        vertices
+            .windows(2)
+            .skip(1)
+            .map(|w| {
+                let (b, c) = (w[0] - point, w[1] - point);
+                let (b_length, c_length) = (b.length(), c.length());
+                let numerator = a.dot(b.cross(c));
+                let denominator = a_length * b_length * c_length
+                    + a.dot(b) * c_length
+                    + a.dot(c) * b_length
+                    + b.dot(c) * a_length;
+                2. * numerator.atan2(denominator)
+            })
+            .sum::<f64>()
+            .abs()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f64::consts::PI;
+
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+    use crate::voronoi::test::{perturbed_grid, perturbed_plane};
+
+    #[test]
+    fn test_solid_angles() {
+        // The faces of a cube subtend 4pi / 6 at its center
+        let voronoi = Voronoi::build(
+            &[DVec3::splat(0.5)],
+            DVec3::ZERO,
+            DVec3::ONE,
+            3,
+            false,
+            None,
+        );
+        for face in voronoi.faces() {
+            assert_approx_eq!(
+                f64,
+                face.solid_angle_left(&voronoi),
+                2. * PI / 3.,
+                epsilon = 1e-12
+            );
+            assert!(face.solid_angle_right(&voronoi).is_none());
+        }
+
+        // The faces of every cell subtend the full sphere at its generator
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        for periodic in [false, true] {
+            let voronoi = Voronoi::build(&generators, anchor, width, 3, periodic, None);
+            for (cell_idx, cell) in voronoi.cells().iter().enumerate() {
+                let total = cell
+                    .faces(&voronoi)
+                    .map(|face| {
+                        if face.left() == cell_idx {
+                            face.solid_angle_left(&voronoi)
+                        } else {
+                            face.solid_angle_right(&voronoi).unwrap()
+                        }
+                    })
+                    .sum::<f64>();
+                assert_approx_eq!(f64, total, 4. * PI, epsilon = 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn test_edge_lengths() {
+        // The faces of the unit cube
+        let voronoi = Voronoi::build(
+            &[DVec3::splat(0.5)],
+            DVec3::ZERO,
+            DVec3::ONE,
+            3,
+            false,
+            None,
+        );
+        for face in voronoi.faces() {
+            assert_eq!(face.vertex_count(), 4);
+            for length in face.edge_lengths(&voronoi) {
+                assert_approx_eq!(f64, length, 1., epsilon = 1e-12);
+            }
+            assert_approx_eq!(f64, face.perimeter(&voronoi), 4., epsilon = 1e-12);
+        }
+
+        // In 2D, the faces are rectangles of unit height with the Voronoi edges as width
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_plane(anchor, width, 5, 0.5);
+        let voronoi = Voronoi::build(&generators, anchor, width, 2, false, None);
+        for face in voronoi.faces() {
+            assert_eq!(face.vertex_count(), face.edge_lengths(&voronoi).count());
+            assert_approx_eq!(
+                f64,
+                face.perimeter(&voronoi),
+                2. * (face.area() + 1.),
+                epsilon = 1e-10
+            );
+        }
+    }
+
+    #[test]
+    fn test_contains() {
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let tolerance = 1e-8;
+        for (generators, dimensionality) in [
+            (perturbed_grid(anchor, width, 4, 0.5), 3),
+            (perturbed_plane(anchor, width, 5, 0.5), 2),
+        ] {
+            for periodic in [false, true] {
+                let voronoi =
+                    Voronoi::build(&generators, anchor, width, dimensionality, periodic, None);
+                for face in voronoi.faces() {
+                    let centroid = face.centroid();
+                    assert!(face.contains(&voronoi, centroid, tolerance));
+                    // Slightly off the plane of the face
+                    let normal = face.normal();
+                    assert!(face.contains(
+                        &voronoi,
+                        centroid + 0.5 * tolerance * normal,
+                        tolerance
+                    ));
+                    assert!(!face.contains(
+                        &voronoi,
+                        centroid - 2. * tolerance * normal,
+                        tolerance
+                    ));
+                    for &vertex in face.vertices(&voronoi) {
+                        assert!(face.contains(&voronoi, vertex, tolerance));
+                        let midpoint = 0.5 * (centroid + vertex);
+                        assert!(face.contains(&voronoi, midpoint, tolerance));
+                        // Beyond the vertices of the (convex) face
+                        if vertex.distance(centroid) > 100. * tolerance {
+                            let outside = centroid + 1.1 * (vertex - centroid);
+                            assert!(!face.contains(&voronoi, outside, tolerance));
+                        }
+                    }
+                    if dimensionality == 2 {
+                        assert!(face.contains(&voronoi, centroid + 5. * DVec3::Z, tolerance));
+                    }
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+
+use glam::DVec3;
+
+use crate::util::weld;
+
+use super::Voronoi;
+
+/// A unique edge of a Voronoi tesselation, see [`Voronoi::edges`].
+#[derive(Debug, Clone)]
+pub struct VoronoiEdge {
+    vertices: [usize; 2],
+    faces: Vec<usize>,
+    cells: Vec<usize>,
+}
+
+impl VoronoiEdge {
+    /// Get the indices of the end points of this edge into [`Wireframe::vertices`] (the smallest index first).
+    pub fn vertices(&self) -> [usize; 2] {
+        self.vertices
+    }
+
+    /// Get the (sorted) indices of the faces (into [`Voronoi::faces`]) incident to this edge.
+    pub fn faces(&self) -> &[usize] {
+        &self.faces
+    }
+
+    /// Get the (sorted) indices of the cells incident to this edge, i.e. the cells on both sides of its faces.
+    pub fn cells(&self) -> &[usize] {
+        &self.cells
+    }
+}
+
+/// The edges of a Voronoi tesselation with welded vertices.
+#[derive(Debug, Clone)]
+pub struct Wireframe {
+    vertices: Vec<DVec3>,
+    edges: Vec<VoronoiEdge>,
+}
+
+impl Wireframe {
+    fn from_voronoi(voronoi: &Voronoi) -> Self {
+        let (vertices, welded) = weld(&voronoi.face_vertices, 1e-10 * voronoi.width.max_element());
+        let mut edge_lookup = HashMap::new();
+        let mut edges: Vec<VoronoiEdge> = vec![];
+        for (face_idx, face) in voronoi.faces.iter().enumerate() {
+            let offset = face.vertex_offset();
+            let n = face.vertex_count();
+            for i in 0..n {
+                let (a, b) = (welded[offset + i], welded[offset + (i + 1) % n]);
+                if a == b {
+                    continue;
+                }
+                let edge_idx = *edge_lookup.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    edges.push(VoronoiEdge {
+                        vertices: [a.min(b), a.max(b)],
+                        faces: vec![],
+                        cells: vec![],
+                    });
+                    edges.len() - 1
+                });
+                let edge = &mut edges[edge_idx];
+                // Degenerate faces might run along the same edge more than once
+                if edge.faces.last() != Some(&face_idx) {
+                    edge.faces.push(face_idx);
+                }
+                edge.cells.push(face.left());
+                edge.cells.extend(face.right());
+            }
+        }
+        for edge in edges.iter_mut() {
+            edge.cells.sort_unstable();
+            edge.cells.dedup();
+        }
+        edges.sort_unstable_by_key(|edge| edge.vertices);
+
+        Self { vertices, edges }
+    }
+
+    /// Get the (welded) vertices of the edges.
+    pub fn vertices(&self) -> &[DVec3] {
+        &self.vertices
+    }
+
+    /// Get the unique edges, sorted by their vertex indices.
+    pub fn edges(&self) -> &[VoronoiEdge] {
+        &self.edges
+    }
+
+    /// Get the positions of the end points of the edge with the given index.
+    pub fn edge_points(&self, edge_idx: usize) -> (DVec3, DVec3) {
+        let [a, b] = self.edges[edge_idx].vertices;
+        (self.vertices[a], self.vertices[b])
+    }
+}
+
+impl Voronoi {
+    /// Get the unique edges of the faces of this tesselation, welded across faces and cells, together with the faces
+    /// and cells incident to every edge.
+    ///
+    /// For periodic tesselations, the edges of faces with a periodic neighbour are returned at both sides of the
+    /// simulation volume (see also [`Voronoi::unique_face_edges`]).
+    pub fn edges(&self) -> Wireframe {
+        Wireframe::from_voronoi(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::voronoi::test::perturbed_grid;
+
+    #[test]
+    fn test_edges() {
+        // The edges of the unit cube
+        let voronoi = Voronoi::build(
+            &[DVec3::splat(0.5)],
+            DVec3::ZERO,
+            DVec3::ONE,
+            3,
+            false,
+            None,
+        );
+        let wireframe = voronoi.edges();
+        assert_eq!(wireframe.vertices().len(), 8);
+        assert_eq!(wireframe.edges().len(), 12);
+        for (edge_idx, edge) in wireframe.edges().iter().enumerate() {
+            assert_eq!(edge.faces().len(), 2);
+            assert_eq!(edge.cells(), &[0]);
+            let (a, b) = wireframe.edge_points(edge_idx);
+            assert!((a.distance(b) - 1.).abs() < 1e-12);
+        }
+
+        let anchor = DVec3::ZERO;
+        let width = DVec3::splat(1.);
+        let generators = perturbed_grid(anchor, width, 4, 0.5);
+        for periodic in [false, true] {
+            let voronoi = Voronoi::build(&generators, anchor, width, 3, periodic, None);
+            let wireframe = voronoi.edges();
+            assert_eq!(
+                wireframe.vertices().len(),
+                voronoi.to_halfedge().vertices().len()
+            );
+            for edge in wireframe.edges() {
+                // Every edge is shared by at least two faces, which all touch its end points
+                assert!(edge.faces().len() >= 2);
+                for &face_idx in edge.faces() {
+                    let face = &voronoi.faces()[face_idx];
+                    assert!(edge.cells().contains(&face.left()));
+                    for vertex in edge.vertices() {
+                        let point = wireframe.vertices()[vertex];
+                        assert!(face
+                            .vertices(&voronoi)
+                            .iter()
+                            .any(|v| v.distance(point) < 1e-9));
+                    }
+                }
+            }
+        }
+    }
+}